@@ -1,26 +1,29 @@
+use crate::models::{ChatRequest, ChatTurnOptions, SamplingParams};
 use crate::services::orchestrator::ChatOrchestrator;
 use crate::state::AppState;
 use serde_json::json;
-use tauri::{ipc::Channel, State};
+use tauri::{ipc::Channel, AppHandle, State};
 
 #[tauri::command]
 pub async fn send_message(
+    app: AppHandle,
     state: State<'_, AppState>,
     session_id: String,
     message: String,
-    temperature: f32,
-    max_tokens: i32,
+    options: ChatTurnOptions,
     on_event: Channel<serde_json::Value>,
 ) -> Result<(), String> {
-    send_message_with_orchestrator(
-        &state.orchestrator,
-        session_id,
-        message,
-        temperature,
-        max_tokens,
-        on_event,
-    )
-    .await
+    let mut options = options;
+    // Without a per-message policy the one from the settings applies.
+    if options.tool_loop_policy.is_none() {
+        options.tool_loop_policy = Some(
+            crate::commands::config::get_config(&app)
+                .unwrap_or_default()
+                .tool_loop_policy,
+        );
+    }
+    send_message_with_orchestrator(&state.orchestrator, session_id, message, options, on_event)
+        .await
 }
 
 #[tauri::command]
@@ -28,7 +31,7 @@ pub async fn clear_chat(state: State<'_, AppState>, session_id: String) -> Resul
     clear_chat_with_orchestrator(&state.orchestrator, session_id).await
 }
 
-use crate::models::ChatMessage;
+use crate::models::{ChatMessage, ToolApprovalDecision};
 
 #[tauri::command]
 pub async fn load_history_context(
@@ -39,11 +42,22 @@ pub async fn load_history_context(
     load_history_context_with_orchestrator(&state.orchestrator, session_id, messages).await
 }
 
+/// Answers an `approval_request` event sent while a message is processed.
+#[tauri::command]
+pub async fn respond_tool_approval(
+    state: State<'_, AppState>,
+    request_id: String,
+    decision: ToolApprovalDecision,
+) -> Result<(), String> {
+    respond_tool_approval_with_orchestrator(&state.orchestrator, request_id, decision).await
+}
+
 #[tauri::command]
 pub async fn generate_chat_title(
     state: tauri::State<'_, AppState>,
     first_user_message: String,
     first_assistant_message: String,
+    model: Option<String>,
 ) -> Result<String, String> {
     let system_prompt = "\
 You are a title generation assistant. \
@@ -63,6 +77,7 @@ Do NOT provide any explanations or thinking.";
             name: None,
             tool_call_id: None,
             tool_calls: None,
+            tool_approvals: None,
         },
         ChatMessage {
             role: "user".to_string(),
@@ -70,20 +85,28 @@ Do NOT provide any explanations or thinking.";
             name: None,
             tool_call_id: None,
             tool_calls: None,
+            tool_approvals: None,
         },
     ];
 
     let response = state
         .orchestrator
         .complete_chat_once(
-            messages,
-            0.3,
-            0.9,
-            40,
-            64,
-            Some("none".to_string()),
-            Some(0),
-            Some(json!({ "enable_thinking": false })),
+            model,
+            ChatRequest {
+                messages,
+                max_tokens: 64,
+                reasoning_format: Some("none".to_string()),
+                reasoning_budget: Some(0),
+                chat_template_kwargs: Some(json!({ "enable_thinking": false })),
+                ..Default::default()
+            },
+            &SamplingParams {
+                temperature: Some(0.3),
+                top_p: Some(0.9),
+                top_k: Some(40),
+                ..Default::default()
+            },
         )
         .await?;
 
@@ -106,12 +129,15 @@ pub async fn send_message_with_orchestrator(
     orchestrator: &ChatOrchestrator,
     session_id: String,
     message: String,
-    temperature: f32,
-    max_tokens: i32,
+    options: ChatTurnOptions,
     on_event: Channel<serde_json::Value>,
 ) -> Result<(), String> {
+    options.sampling.validate()?;
+    if let Some(policy) = &options.tool_loop_policy {
+        policy.validate()?;
+    }
     orchestrator
-        .process(&session_id, message, temperature, max_tokens, on_event)
+        .process(&session_id, message, &options, on_event)
         .await
 }
 
@@ -133,3 +159,13 @@ pub async fn load_history_context_with_orchestrator(
         .await;
     Ok(())
 }
+
+pub async fn respond_tool_approval_with_orchestrator(
+    orchestrator: &ChatOrchestrator,
+    request_id: String,
+    decision: ToolApprovalDecision,
+) -> Result<(), String> {
+    orchestrator
+        .respond_to_approval(&request_id, decision)
+        .await
+}
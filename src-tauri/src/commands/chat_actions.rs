@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{ipc::Channel, AppHandle, Manager, State};
 
-use crate::models::ChatMessage;
+use crate::models::{ChatMessage, ChatTurnOptions};
 use crate::state::AppState;
 
 #[derive(Debug, Serialize)]
@@ -173,19 +173,13 @@ pub async fn chat_action_regenerate(
     state: State<'_, AppState>,
     session_id: String,
     message_index: usize,
-    temperature: f32,
-    max_tokens: i32,
+    options: ChatTurnOptions,
     on_event: Channel<serde_json::Value>,
 ) -> Result<(), String> {
+    options.sampling.validate()?;
     state
         .orchestrator
-        .regenerate_at(
-            &session_id,
-            message_index,
-            temperature,
-            max_tokens,
-            on_event,
-        )
+        .regenerate_at(&session_id, message_index, &options, on_event)
         .await?;
     let base_dir = app_config_dir(&app)?;
     log_action_to_dir(
@@ -2,7 +2,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::{command, AppHandle, Manager};
 
-use crate::models::{AppConfig, ConfigError};
+use crate::models::{AppConfig, ConfigError, RegistryCredential};
 
 fn get_config_path(app: &AppHandle) -> Result<PathBuf, ConfigError> {
     let app_dir = app
@@ -28,13 +28,32 @@ pub fn get_config(app: &AppHandle) -> Result<AppConfig, String> {
 
 #[command]
 pub async fn load_config(app: AppHandle) -> Result<AppConfig, String> {
-    get_config(&app)
+    get_config(&app).map(AppConfig::without_secrets)
 }
 
 #[command]
 pub async fn save_config(app: AppHandle, config: AppConfig) -> Result<(), String> {
+    config.sampling.validate()?;
     let config_path = build_config_file_path(&app).map_err(|e| e.to_string())?;
-    save_config_to_path(&config_path, &config)
+    save_config_keeping_secrets(&config_path, config)
+}
+
+/// Stores the credentials for one registry host; `None` removes them.
+#[command]
+pub async fn set_registry_credential(
+    app: AppHandle,
+    host: String,
+    credential: Option<RegistryCredential>,
+) -> Result<(), String> {
+    let config_path = build_config_file_path(&app).map_err(|e| e.to_string())?;
+    set_registry_credential_at_path(&config_path, &host, credential)
+}
+
+/// Stores the Hugging Face token; `None` or a blank token removes it.
+#[command]
+pub async fn set_hf_token(app: AppHandle, token: Option<String>) -> Result<(), String> {
+    let config_path = build_config_file_path(&app).map_err(|e| e.to_string())?;
+    set_hf_token_at_path(&config_path, token)
 }
 
 #[command]
@@ -68,6 +87,49 @@ pub fn save_config_to_path(config_path: &Path, config: &AppConfig) -> Result<(),
     crate::utils::save_json(config_path, config)
 }
 
+/// Saves a config coming from the frontend. It never holds the secrets, so
+/// the stored ones are carried over.
+pub fn save_config_keeping_secrets(
+    config_path: &Path,
+    mut config: AppConfig,
+) -> Result<(), String> {
+    let stored = get_config_from_path(config_path).unwrap_or_default();
+    config.registry_credentials = stored.registry_credentials;
+    config.hf_token = stored.hf_token;
+    save_config_to_path(config_path, &config)
+}
+
+pub fn set_registry_credential_at_path(
+    config_path: &Path,
+    host: &str,
+    credential: Option<RegistryCredential>,
+) -> Result<(), String> {
+    let host = host.trim();
+    if host.is_empty() {
+        return Err("Registry host must not be empty".to_string());
+    }
+    let mut config = get_config_from_path(config_path)?;
+    match credential {
+        Some(credential) => {
+            config
+                .registry_credentials
+                .insert(host.to_string(), credential);
+        }
+        None => {
+            config.registry_credentials.remove(host);
+        }
+    }
+    save_config_to_path(config_path, &config)
+}
+
+pub fn set_hf_token_at_path(config_path: &Path, token: Option<String>) -> Result<(), String> {
+    let mut config = get_config_from_path(config_path)?;
+    config.hf_token = token
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty());
+    save_config_to_path(config_path, &config)
+}
+
 pub fn reset_config_at_path(config_path: &Path) -> Result<AppConfig, String> {
     let config = AppConfig::default();
     save_config_to_path(config_path, &config)?;
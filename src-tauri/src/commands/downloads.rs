@@ -0,0 +1,97 @@
+use futures::FutureExt;
+use std::sync::Arc;
+use tauri::ipc::Channel;
+use tauri::{command, AppHandle, State};
+
+use crate::commands::llama_cpp::forward_broadcast;
+use crate::models::DownloadJob;
+use crate::services::downloads::{DownloadManager, DownloadRunner};
+use crate::state::AppState;
+
+/// Hooks the manager up to the real pull and resumes the persisted queue.
+pub fn start_download_manager(app: &AppHandle, manager: &DownloadManager) {
+    let app = app.clone();
+    let runner: DownloadRunner = Arc::new(move |job, progress| {
+        let app = app.clone();
+        async move {
+            crate::commands::models::pull_model(
+                &app,
+                job.model_reference,
+                job.models_root,
+                progress,
+            )
+            .await
+        }
+        .boxed()
+    });
+    manager.start(runner);
+}
+
+/// Queues a pull and returns its job; progress arrives via `subscribe_download_events`.
+#[command]
+pub async fn start_download(
+    state: State<'_, AppState>,
+    model_reference: String,
+    models_root: String,
+) -> Result<DownloadJob, String> {
+    start_download_with_manager(&state.downloads, model_reference, models_root)
+}
+
+#[command]
+pub async fn pause_download(
+    state: State<'_, AppState>,
+    job_id: String,
+) -> Result<DownloadJob, String> {
+    state.downloads.pause(&job_id)
+}
+
+#[command]
+pub async fn resume_download(
+    state: State<'_, AppState>,
+    job_id: String,
+) -> Result<DownloadJob, String> {
+    state.downloads.resume(&job_id)
+}
+
+/// Stops the job and deletes its partial files.
+#[command]
+pub async fn cancel_download(
+    state: State<'_, AppState>,
+    job_id: String,
+) -> Result<DownloadJob, String> {
+    state.downloads.cancel(&job_id)
+}
+
+#[command]
+pub async fn list_downloads(state: State<'_, AppState>) -> Result<Vec<DownloadJob>, String> {
+    Ok(state.downloads.list())
+}
+
+/// Streams a job snapshot to `on_event` on every status or progress change.
+#[command]
+pub async fn subscribe_download_events(
+    state: State<'_, AppState>,
+    on_event: Channel<DownloadJob>,
+) -> Result<(), String> {
+    subscribe_download_events_with_manager(&state.downloads, move |job| on_event.send(job).is_ok());
+    Ok(())
+}
+
+pub fn start_download_with_manager(
+    manager: &DownloadManager,
+    model_reference: String,
+    models_root: String,
+) -> Result<DownloadJob, String> {
+    if models_root.trim().is_empty() {
+        return Err("Models directory is not configured".to_string());
+    }
+    manager.enqueue(model_reference, models_root)
+}
+
+/// `sink` returns false once the receiving side is gone, which ends the subscription.
+pub fn subscribe_download_events_with_manager<F>(manager: &DownloadManager, sink: F)
+where
+    F: Fn(DownloadJob) -> bool + Send + 'static,
+{
+    forward_broadcast(manager.subscribe_events(), sink);
+}
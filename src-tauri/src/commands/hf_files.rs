@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use sysinfo::System;
+use tauri::{command, AppHandle};
+
+use crate::commands::models::{hf_base_url, hf_client, HfApiModel};
+use crate::infrastructure::nvidia_smi::NvidiaSmi;
+use crate::models::{AppConfig, HfGgufFile};
+use crate::services::memory_estimate::estimate_fit_from_size;
+
+const DEFAULT_REVISION: &str = "main";
+
+// ---------------------------------------------------------------------------
+// Tauri command — Hugging Face repository browser
+// ---------------------------------------------------------------------------
+
+/// Lists the GGUF models of `repo_id` (`org/repo`) with size, quant label and
+/// whether they would fit this machine, so the user can pick one before
+/// downloading. Uses the mirror and token from the settings.
+#[command]
+pub async fn hf_list_gguf_files(
+    app: AppHandle,
+    repo_id: String,
+    revision: Option<String>,
+) -> Result<Vec<HfGgufFile>, String> {
+    let app_config = crate::commands::config::get_config(&app).unwrap_or_default();
+    let (vram_bytes, ram_bytes) = tauri::async_runtime::spawn_blocking(|| {
+        let mut sys = System::new();
+        sys.refresh_memory();
+        (NvidiaSmi::free_vram_bytes(), sys.available_memory())
+    })
+    .await
+    .map_err(|e| format!("Memory probe task failed: {}", e))?;
+
+    hf_list_gguf_files_with_config(
+        &app_config,
+        &repo_id,
+        revision.as_deref(),
+        &vram_bytes,
+        ram_bytes,
+    )
+    .await
+}
+
+pub async fn hf_list_gguf_files_with_config(
+    app_config: &AppConfig,
+    repo_id: &str,
+    revision: Option<&str>,
+    vram_bytes: &[u64],
+    ram_bytes: u64,
+) -> Result<Vec<HfGgufFile>, String> {
+    let repo_id = repo_id.trim().trim_matches('/');
+    let segments: Vec<&str> = repo_id.split('/').collect();
+    if segments.len() != 2 || segments.iter().any(|s| s.is_empty() || *s == "..") {
+        return Err("Hugging Face repo must be in the format <org>/<repo>".to_string());
+    }
+    let revision = revision
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .unwrap_or(DEFAULT_REVISION);
+
+    // `blobs=true` adds file sizes to the sibling list.
+    let url = format!(
+        "{}/api/models/{}/revision/{}?blobs=true",
+        hf_base_url(app_config),
+        repo_id,
+        revision
+    );
+    let response = hf_client(app_config)?
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to query Hugging Face API: {}", e))?;
+    match response.status().as_u16() {
+        401 | 403 => {
+            return Err(format!(
+                "{} is gated or private; add a Hugging Face token in the settings",
+                repo_id
+            ))
+        }
+        404 => return Err(format!("Repository {} not found", repo_id)),
+        _ if !response.status().is_success() => {
+            return Err(format!(
+                "Failed to query Hugging Face API: HTTP {}",
+                response.status()
+            ))
+        }
+        _ => {}
+    }
+    let api_model: HfApiModel = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Hugging Face response: {}", e))?;
+
+    let files: Vec<(String, Option<u64>)> = api_model
+        .siblings
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| (s.rfilename, s.size))
+        .collect();
+    let mut listed = group_gguf_files(&files);
+    for file in &mut listed {
+        if file.size_bytes > 0 {
+            file.fit = Some(estimate_fit_from_size(
+                file.size_bytes,
+                vram_bytes,
+                ram_bytes,
+            ));
+        }
+    }
+    Ok(listed)
+}
+
+/// Collapses split parts into one entry and drops vision projectors
+/// (`mmproj`), which cannot be loaded as a model on their own.
+pub fn group_gguf_files(files: &[(String, Option<u64>)]) -> Vec<HfGgufFile> {
+    let mut groups: BTreeMap<String, Vec<(u32, String, u64)>> = BTreeMap::new();
+    for (path, size) in files {
+        let lower = path.to_lowercase();
+        let file_name = lower.rsplit('/').next().unwrap_or(&lower);
+        if !lower.ends_with(".gguf") || file_name.contains("mmproj") {
+            continue;
+        }
+        let (key, index) = match split_part(path) {
+            Some((prefix, index, total)) => (format!("{}-of-{:05}", prefix, total), index),
+            None => (path.clone(), 1),
+        };
+        groups
+            .entry(key)
+            .or_default()
+            .push((index, path.clone(), size.unwrap_or(0)));
+    }
+
+    groups
+        .into_values()
+        .map(|mut parts| {
+            parts.sort();
+            let filename = parts[0].1.clone();
+            HfGgufFile {
+                quantization: quant_label_from_filename(&filename),
+                size_bytes: parts.iter().map(|(_, _, size)| size).sum(),
+                parts: parts.into_iter().map(|(_, path, _)| path).collect(),
+                filename,
+                fit: None,
+            }
+        })
+        .collect()
+}
+
+/// `model-Q4_K_M-00002-of-00003.gguf` -> (`model-Q4_K_M`, 2, 3).
+fn split_part(path: &str) -> Option<(&str, u32, u32)> {
+    let stem = path
+        .strip_suffix(".gguf")
+        .or_else(|| path.strip_suffix(".GGUF"))?;
+    let tail = stem.get(stem.len().checked_sub(15)?..)?;
+    let digits = |s: &str| s.len() == 5 && s.bytes().all(|b| b.is_ascii_digit());
+    // The byte slices below would panic inside a multi-byte character.
+    if !tail.is_ascii()
+        || !tail.starts_with('-')
+        || &tail[6..10] != "-of-"
+        || !digits(&tail[1..6])
+        || !digits(&tail[10..])
+    {
+        return None;
+    }
+    Some((
+        &stem[..stem.len() - 15],
+        tail[1..6].parse().ok()?,
+        tail[10..].parse().ok()?,
+    ))
+}
+
+/// Quant suffixes that follow `Q4`/`IQ3`/`TQ1` in llama.cpp file names.
+const QUANT_SUFFIXES: &[&str] = &["K", "S", "M", "L", "XS", "XXS", "XL", "NL"];
+const FLOAT_LABELS: &[&str] = &["BF16", "F16", "F32", "MXFP4"];
+
+/// Finds the last quant label in a GGUF file name, e.g. `Q4_K_M`, `IQ3_XS`,
+/// `Q8_0` or `BF16`.
+pub fn quant_label_from_filename(path: &str) -> Option<String> {
+    let name = path.rsplit('/').next().unwrap_or(path).to_uppercase();
+    let name = name.strip_suffix(".GGUF").unwrap_or(&name);
+    let name = split_part(&format!("{}.gguf", name))
+        .map(|(prefix, _, _)| prefix.to_string())
+        .unwrap_or_else(|| name.to_string());
+
+    let is_separator = |c: char| matches!(c, '-' | '.' | '_');
+    let mut found = None;
+    for (start, _) in name.char_indices() {
+        if start > 0 && !name[..start].ends_with(is_separator) {
+            continue;
+        }
+        let rest = &name[start..];
+        if let Some(label) = FLOAT_LABELS.iter().find(|label| {
+            rest.starts_with(*label) && rest[label.len()..].chars().next().is_none_or(is_separator)
+        }) {
+            found = Some(label.to_string());
+            continue;
+        }
+        let head_len = ["IQ", "TQ", "Q"]
+            .iter()
+            .find(|p| rest.starts_with(*p))
+            .map(|p| p.len())
+            .filter(|&len| rest[len..].starts_with(|c: char| c.is_ascii_digit()));
+        let Some(head_len) = head_len else {
+            continue;
+        };
+        let digits = rest[head_len..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len() - head_len);
+        let mut end = head_len + digits;
+        if !rest[end..].chars().next().is_none_or(is_separator) {
+            continue;
+        }
+        while let Some(after) = rest[end..].strip_prefix('_') {
+            let segment = after.split(is_separator).next().unwrap_or("");
+            let known = QUANT_SUFFIXES.contains(&segment)
+                || (!segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()));
+            if !known {
+                break;
+            }
+            end += 1 + segment.len();
+        }
+        found = Some(rest[..end].to_string());
+    }
+    found
+}
@@ -1,9 +1,14 @@
-use crate::models::{LlamaCppConfig, ServerMetrics};
+use crate::models::{
+    LaunchOverrides, LaunchParams, LlamaCppConfig, LlamaServerError, LoadProgress,
+    ModelChatDefaults, ModelEvent, ModelId, RunningModel, ServerLogLine, ServerMetrics,
+};
+use crate::infrastructure::llama::progress::ProgressSender;
 use crate::services::llama::LlamaCppService;
 use crate::state::AppState;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use tauri::command;
+use tauri::ipc::Channel;
 use tauri::AppHandle;
 use tauri::Manager;
 use tauri::State;
@@ -22,39 +27,151 @@ pub async fn ensure_chat_template(app: AppHandle, hf_repo: String) -> Result<Str
 }
 
 // ─── Comando: start_llama_server ──────────────────────────────────────────────
+//
+// Com `model_id` (full_identifier da biblioteca), o restante vem do preset
+// nomeado ou do preset padrão do modelo; os campos de `overrides` têm prioridade.
 
 #[command]
 pub async fn start_llama_server(
     app: AppHandle,
-    binary_path: String,
-    model_path: String,
-    port: u16,
-    ctx_size: u32,
-    n_gpu_layers: i32,
-    parallel: Option<u32>,
-    chat_template: Option<String>,
-    chat_template_file: Option<String>,
+    model_id: Option<String>,
+    preset: Option<String>,
+    overrides: Option<LaunchOverrides>,
+    on_progress: Channel<LoadProgress>,
+    state: State<'_, AppState>,
+) -> Result<String, LlamaServerError> {
+    let app_config = crate::commands::config::get_config(&app).unwrap_or_default();
+    let overrides = overrides.unwrap_or_default();
+    let mut chat_defaults = None;
+    let mut config = match model_id {
+        Some(id) => {
+            let (model, preset) =
+                crate::commands::presets::resolve_model_launch(&app, &id, preset.as_deref())?;
+            let sampling = crate::commands::presets::model_sampling(&app, &id)?;
+            chat_defaults = ModelChatDefaults::from_model(&model, sampling.as_ref());
+            let mut config =
+                crate::commands::presets::config_from_preset(&model, preset.as_ref(), &app_config)?;
+            // Modelos Ollama sem tokenizer.chat_template no GGUF: converte o template Go
+            if config.chat_template.is_none() && config.chat_template_file.is_none() {
+                if let Some(go_template) = &model.template {
+                    match crate::services::templates::ensure_converted_go_template(&app, go_template) {
+                        Ok(path) => {
+                            config.chat_template_file = Some(path.to_string_lossy().to_string())
+                        }
+                        Err(err) => eprintln!(
+                            "[llama-desktop] Could not convert the chat template of {}: {}",
+                            id, err
+                        ),
+                    }
+                }
+            }
+            config
+        }
+        None => {
+            if preset.is_some() {
+                return Err("A launch preset requires model_id".to_string().into());
+            }
+            let (Some(binary_path), Some(model_path), Some(port), Some(ctx_size), Some(n_gpu_layers)) =
+                (
+                    overrides.binary_path.clone(),
+                    overrides.model_path.clone(),
+                    overrides.port,
+                    overrides.ctx_size,
+                    overrides.n_gpu_layers,
+                )
+            else {
+                return Err(
+                    "binary_path, model_path, port, ctx_size and n_gpu_layers are required without model_id"
+                        .to_string()
+                        .into(),
+                );
+            };
+            LlamaCppConfig {
+                llama_cpp_path: binary_path,
+                model_path,
+                port,
+                ctx_size,
+                parallel: 1,
+                n_gpu_layers,
+                chat_template: None,
+                chat_template_file: None,
+                port_range: app_config.server_port_range,
+                restart_policy: None,
+                launch_params: LaunchParams::default(),
+            }
+        }
+    };
+    overrides.apply_to(&mut config);
+    let (chat_template, chat_template_file) =
+        resolve_chat_template(&app, config.chat_template, config.chat_template_file)?;
+    config.chat_template = chat_template;
+    config.chat_template_file = chat_template_file;
+    // The sender is dropped once the server is up or has failed, which ends this forwarder.
+    let (progress, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = progress_rx.recv().await {
+            if on_progress.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    state
+        .llama_service
+        .set_chat_defaults(&config.model_path, chat_defaults);
+    start_llama_server_with_service(&state.llama_service, config, Some(progress)).await
+}
+
+/// Lets the settings screen report conflicting options before a launch.
+#[command]
+pub fn validate_launch_params(params: LaunchParams) -> Result<(), Vec<String>> {
+    params.validate()
+}
+
+#[command]
+pub async fn stop_llama_server(
     state: State<'_, AppState>,
+    model_id: Option<String>,
 ) -> Result<String, String> {
-    let (resolved_template, resolved_template_file) =
-        resolve_chat_template(&app, chat_template, chat_template_file)?;
-    start_llama_server_with_service(
-        &state.llama_service,
-        binary_path,
-        model_path,
-        port,
-        ctx_size,
-        n_gpu_layers,
-        parallel,
-        resolved_template,
-        resolved_template_file,
-    )
+    stop_llama_server_with_service(&state.llama_service, model_id).await
+}
+
+#[command]
+pub async fn list_running_models(state: State<'_, AppState>) -> Result<Vec<RunningModel>, String> {
+    list_running_models_with_service(&state.llama_service).await
+}
+
+#[command]
+pub async fn get_server_logs(
+    state: State<'_, AppState>,
+    model_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ServerLogLine>, String> {
+    get_server_logs_with_service(&state.llama_service, model_id, limit).await
+}
+
+/// Streams new llama-server output lines to `on_event` until the channel closes.
+#[command]
+pub async fn subscribe_server_logs(
+    state: State<'_, AppState>,
+    model_id: Option<String>,
+    on_event: Channel<ServerLogLine>,
+) -> Result<(), String> {
+    subscribe_server_logs_with_service(&state.llama_service, model_id, move |line| {
+        on_event.send(line).is_ok()
+    })
     .await
 }
 
+/// Streams crash and restart notifications for all models to `on_event`.
 #[command]
-pub async fn stop_llama_server(state: State<'_, AppState>) -> Result<String, String> {
-    stop_llama_server_with_service(&state.llama_service).await
+pub async fn subscribe_model_events(
+    state: State<'_, AppState>,
+    on_event: Channel<ModelEvent>,
+) -> Result<(), String> {
+    subscribe_model_events_with_service(&state.llama_service, move |event| {
+        on_event.send(event).is_ok()
+    })
+    .await
 }
 
 #[command]
@@ -97,27 +214,10 @@ pub async fn get_server_metrics(
 
 pub async fn start_llama_server_with_service(
     service: &LlamaCppService,
-    binary_path: String,
-    model_path: String,
-    port: u16,
-    ctx_size: u32,
-    n_gpu_layers: i32,
-    parallel: Option<u32>,
-    chat_template: Option<String>,
-    chat_template_file: Option<String>,
-) -> Result<String, String> {
-    let config = LlamaCppConfig {
-        llama_cpp_path: binary_path,
-        model_path,
-        port,
-        ctx_size,
-        parallel: parallel.unwrap_or(1),
-        n_gpu_layers,
-        chat_template,
-        chat_template_file,
-    };
-
-    let pid = service.start(config).await?;
+    config: LlamaCppConfig,
+    progress: Option<ProgressSender>,
+) -> Result<String, LlamaServerError> {
+    let pid = service.start_with_progress(config, progress).await?;
     Ok(pid.to_string())
 }
 
@@ -157,11 +257,85 @@ fn resolve_chat_template(
     Ok((None, Some(file_path.to_string_lossy().to_string())))
 }
 
-pub async fn stop_llama_server_with_service(service: &LlamaCppService) -> Result<String, String> {
-    service.stop().await?;
+/// Stops the named model, or the default model when `model_id` is `None`.
+pub async fn stop_llama_server_with_service(
+    service: &LlamaCppService,
+    model_id: Option<String>,
+) -> Result<String, String> {
+    match model_id {
+        Some(id) => service.stop_model(ModelId(id)).await?,
+        None => service.stop().await?,
+    }
     Ok("Server stopped".to_string())
 }
 
+pub async fn list_running_models_with_service(
+    service: &LlamaCppService,
+) -> Result<Vec<RunningModel>, String> {
+    Ok(service.list_models().await)
+}
+
+pub async fn get_server_logs_with_service(
+    service: &LlamaCppService,
+    model_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ServerLogLine>, String> {
+    service.server_logs(model_id.as_deref(), limit).await
+}
+
+/// `sink` returns false once the receiving side is gone, which ends the subscription.
+pub async fn subscribe_server_logs_with_service<F>(
+    service: &LlamaCppService,
+    model_id: Option<String>,
+    sink: F,
+) -> Result<(), String>
+where
+    F: Fn(ServerLogLine) -> bool + Send + 'static,
+{
+    let filter = model_id.map(ModelId);
+    forward_broadcast(service.logs().subscribe(), move |line: ServerLogLine| {
+        if filter.as_ref().is_some_and(|id| *id != line.model_id) {
+            return true;
+        }
+        sink(line)
+    });
+    Ok(())
+}
+
+pub async fn subscribe_model_events_with_service<F>(
+    service: &LlamaCppService,
+    sink: F,
+) -> Result<(), String>
+where
+    F: Fn(ModelEvent) -> bool + Send + 'static,
+{
+    forward_broadcast(service.subscribe_events(), sink);
+    Ok(())
+}
+
+/// Pumps `receiver` into `sink` on a background task until either side goes away.
+pub(crate) fn forward_broadcast<T, F>(mut receiver: tokio::sync::broadcast::Receiver<T>, sink: F)
+where
+    T: Clone + Send + 'static,
+    F: Fn(T) -> bool + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        use tokio::sync::broadcast::error::RecvError;
+        loop {
+            match receiver.recv().await {
+                Ok(item) => {
+                    if !sink(item) {
+                        break;
+                    }
+                }
+                // A slow consumer misses items but keeps following the stream.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
 pub async fn is_server_running_with_service(service: &LlamaCppService) -> Result<bool, String> {
     Ok(service.is_running().await)
 }
@@ -283,6 +283,8 @@ fn build_server_from_object(obj: &serde_json::Map<String, Value>) -> Option<McpS
         headers: parse_string_map(obj.get("headers")),
         tool_allowlist: parse_string_list(obj.get("tool_allowlist")),
         resource_allowlist: parse_string_list(obj.get("resource_allowlist")),
+        tool_approval: None,
+        tool_approval_overrides: None,
     })
 }
 
@@ -321,6 +323,8 @@ fn build_server_from_legacy_map(
         headers: parse_string_map(obj.get("headers")),
         tool_allowlist: parse_string_list(obj.get("tool_allowlist")),
         resource_allowlist: parse_string_list(obj.get("resource_allowlist")),
+        tool_approval: None,
+        tool_approval_overrides: None,
     }
 }
 
@@ -109,7 +109,10 @@ fn default_tavily_config() -> McpConfig {
             headers: None,
             tool_allowlist: None,
             resource_allowlist: None,
+            tool_approval: None,
+            tool_approval_overrides: None,
         }],
+        ..Default::default()
     }
 }
 
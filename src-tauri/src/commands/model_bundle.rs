@@ -0,0 +1,340 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+
+use crate::commands::model_maintenance::{find_manifest_for_model, manifest_blobs};
+use crate::commands::models::{
+    digest_to_blob_filename, ensure_clean_segment, ensure_sha256_digest, find_model_blob_path,
+    parse_model_manifest_sync, parse_model_path,
+};
+use crate::models::{BundleBlob, BundleIndex, ModelInfo, ModelManifest, BUNDLE_FORMAT_VERSION};
+
+const INDEX_ENTRY: &str = "index.json";
+const MANIFEST_ENTRY: &str = "manifest.json";
+const BLOBS_PREFIX: &str = "blobs/";
+
+// ---------------------------------------------------------------------------
+// Tauri commands — offline model bundles
+// ---------------------------------------------------------------------------
+
+/// Writes `model_id` (`provider:name:version`) to `destination` as a tar of
+/// `index.json`, `manifest.json` and every referenced blob.
+#[command]
+pub async fn export_model(
+    models_root: String,
+    model_id: String,
+    destination: String,
+) -> Result<BundleIndex, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        export_model_at_root(Path::new(&models_root), &model_id, Path::new(&destination))
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+/// Installs a bundle written by `export_model` into `models_root`, checking
+/// every blob against its digest. Needs no network access.
+#[command]
+pub async fn import_model_bundle(
+    app: AppHandle,
+    models_root: String,
+    bundle_path: String,
+) -> Result<ModelInfo, String> {
+    let metadata_root = crate::commands::models::get_metadata_root(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        import_model_bundle_at_root(
+            Path::new(&models_root),
+            &metadata_root,
+            Path::new(&bundle_path),
+        )
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+pub fn export_model_at_root(
+    models_root: &Path,
+    model_id: &str,
+    destination: &Path,
+) -> Result<BundleIndex, String> {
+    let manifest_path = find_manifest_for_model(models_root, model_id)?;
+    let (provider, library, name, version) = parse_model_path(&manifest_path.to_string_lossy())?;
+    // The manifest is copied byte for byte so the bundle reproduces it exactly.
+    let manifest_bytes = fs::read(&manifest_path)
+        .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+    let manifest: ModelManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| format!("Failed to parse {}: {}", manifest_path.display(), e))?;
+
+    let media_types = std::iter::once(manifest.config.media_type.clone())
+        .chain(manifest.layers.iter().map(|l| l.media_type.clone()));
+    let mut seen = HashSet::new();
+    let mut blobs = Vec::new();
+    for ((digest, size), media_type) in manifest_blobs(&manifest).into_iter().zip(media_types) {
+        if !seen.insert(digest.clone()) {
+            continue;
+        }
+        let path = find_model_blob_path(models_root, &digest)
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("Blob {} of {} is missing", digest, model_id))?;
+        let actual = fs::metadata(&path)
+            .map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?
+            .len();
+        if actual != size {
+            return Err(format!(
+                "Blob {} of {} is {} bytes, expected {}",
+                digest, model_id, actual, size
+            ));
+        }
+        blobs.push((
+            BundleBlob {
+                digest,
+                size,
+                media_type,
+            },
+            path,
+        ));
+    }
+
+    let index = BundleIndex {
+        format_version: BUNDLE_FORMAT_VERSION,
+        provider,
+        library,
+        name,
+        version,
+        total_bytes: blobs.iter().map(|(b, _)| b.size).sum(),
+        blobs: blobs.iter().map(|(b, _)| b.clone()).collect(),
+    };
+    let index_bytes = serde_json::to_vec_pretty(&index)
+        .map_err(|e| format!("Failed to serialise bundle index: {}", e))?;
+
+    // Written next to the destination and renamed, so a failed export never
+    // leaves something that looks like a complete bundle.
+    let tmp_path = PathBuf::from(format!("{}.partial", destination.display()));
+    let result = (|| {
+        let file = File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create {}: {}", tmp_path.display(), e))?;
+        let mut builder = tar::Builder::new(file);
+        append_bytes(&mut builder, INDEX_ENTRY, &index_bytes)?;
+        append_bytes(&mut builder, MANIFEST_ENTRY, &manifest_bytes)?;
+        for (blob, path) in &blobs {
+            let entry_name = format!("{}{}", BLOBS_PREFIX, digest_to_blob_filename(&blob.digest));
+            builder
+                .append_path_with_name(path, &entry_name)
+                .map_err(|e| format!("Failed to add {} to bundle: {}", blob.digest, e))?;
+        }
+        builder
+            .into_inner()
+            .and_then(|mut file| file.flush())
+            .map_err(|e| format!("Failed to write bundle: {}", e))
+    })();
+    if let Err(err) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(err);
+    }
+    fs::rename(&tmp_path, destination)
+        .map_err(|e| format!("Failed to finalise {}: {}", destination.display(), e))?;
+
+    Ok(index)
+}
+
+/// Blobs are verified and renamed into `blobs/` one by one; the manifest is
+/// written last, so an interrupted import never registers an incomplete model.
+/// Re-importing an installed bundle is a no-op apart from restoring missing blobs.
+pub fn import_model_bundle_at_root(
+    models_root: &Path,
+    metadata_root: &str,
+    bundle_path: &Path,
+) -> Result<ModelInfo, String> {
+    let file = File::open(bundle_path)
+        .map_err(|e| format!("Failed to open {}: {}", bundle_path.display(), e))?;
+    let mut archive = tar::Archive::new(file);
+    let mut entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read bundle: {}", e))?;
+    let mut next_entry = |expected: &str| -> Result<Vec<u8>, String> {
+        let mut entry = entries
+            .next()
+            .ok_or_else(|| format!("Bundle has no {}", expected))?
+            .map_err(|e| format!("Failed to read bundle: {}", e))?;
+        let path = entry_path(&entry)?;
+        if path != expected {
+            return Err(format!("Expected {} in bundle, found {}", expected, path));
+        }
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read {}: {}", expected, e))?;
+        Ok(bytes)
+    };
+
+    let index: BundleIndex = serde_json::from_slice(&next_entry(INDEX_ENTRY)?)
+        .map_err(|e| format!("Invalid bundle index: {}", e))?;
+    if index.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "Bundle format {} is newer than this app supports ({})",
+            index.format_version, BUNDLE_FORMAT_VERSION
+        ));
+    }
+    ensure_clean_segment(&index.provider, "Provider")?;
+    ensure_clean_segment(&index.library, "Library")?;
+    ensure_clean_segment(&index.name, "Name")?;
+    ensure_clean_segment(&index.version, "Version")?;
+    for blob in &index.blobs {
+        ensure_sha256_digest(&blob.digest)?;
+    }
+
+    let manifest_bytes = next_entry(MANIFEST_ENTRY)?;
+    let manifest: ModelManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| format!("Invalid bundle manifest: {}", e))?;
+    for (digest, size) in manifest_blobs(&manifest) {
+        if !index
+            .blobs
+            .iter()
+            .any(|b| b.digest == digest && b.size == size)
+        {
+            return Err(format!("Bundle index does not list blob {}", digest));
+        }
+    }
+
+    let manifest_path = models_root
+        .join("manifests")
+        .join(&index.provider)
+        .join(&index.library)
+        .join(&index.name)
+        .join(&index.version)
+        .join("manifest.json");
+    if let Ok(existing) = fs::read(&manifest_path) {
+        if existing != manifest_bytes {
+            return Err(format!(
+                "{}:{}:{} is already installed with a different manifest",
+                index.provider, index.name, index.version
+            ));
+        }
+    }
+
+    let blobs_dir = models_root.join("blobs");
+    fs::create_dir_all(&blobs_dir)
+        .map_err(|e| format!("Failed to create blobs directory: {}", e))?;
+    let mut installed = HashSet::new();
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read bundle: {}", e))?;
+        let path = entry_path(&entry)?;
+        let blob = path
+            .strip_prefix(BLOBS_PREFIX)
+            .and_then(|filename| {
+                index
+                    .blobs
+                    .iter()
+                    .find(|b| digest_to_blob_filename(&b.digest) == filename)
+            })
+            .ok_or_else(|| format!("Unexpected entry {} in bundle", path))?;
+        install_blob(&mut entry, blob, &blobs_dir)?;
+        installed.insert(blob.digest.clone());
+    }
+    if let Some(missing) = index
+        .blobs
+        .iter()
+        .find(|b| !installed.contains(&b.digest) && !blob_present(&blobs_dir, b))
+    {
+        return Err(format!("Bundle is missing blob {}", missing.digest));
+    }
+
+    if let Some(parent) = manifest_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&manifest_path, &manifest_bytes)
+        .map_err(|e| format!("Failed to write {}: {}", manifest_path.display(), e))?;
+
+    parse_model_manifest_sync(
+        manifest_path.to_string_lossy().to_string(),
+        models_root.to_string_lossy().to_string(),
+        metadata_root,
+    )
+}
+
+fn append_bytes(builder: &mut tar::Builder<File>, name: &str, bytes: &[u8]) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, bytes)
+        .map_err(|e| format!("Failed to add {} to bundle: {}", name, e))
+}
+
+fn entry_path(entry: &tar::Entry<'_, File>) -> Result<String, String> {
+    entry
+        .path()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .map_err(|e| format!("Invalid entry name in bundle: {}", e))
+}
+
+fn blob_present(blobs_dir: &Path, blob: &BundleBlob) -> bool {
+    fs::metadata(blobs_dir.join(digest_to_blob_filename(&blob.digest)))
+        .map(|m| m.len() == blob.size)
+        .unwrap_or(false)
+}
+
+/// Streams one bundle entry to `<blob>.partial` while hashing it, then renames
+/// it into place. Blobs already in the library are skipped.
+fn install_blob(entry: &mut impl Read, blob: &BundleBlob, blobs_dir: &Path) -> Result<(), String> {
+    if blob_present(blobs_dir, blob) {
+        return Ok(());
+    }
+    let blob_path = blobs_dir.join(digest_to_blob_filename(&blob.digest));
+    let tmp_path = crate::infrastructure::blob_download::partial_path(&blob_path);
+    let mut writer = HashingWriter {
+        inner: File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create {}: {}", tmp_path.display(), e))?,
+        hasher: Sha256::new(),
+        written: 0,
+    };
+    let copied = io::copy(entry, &mut writer).and_then(|_| writer.inner.flush());
+    let actual: String = writer
+        .hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let actual = format!("sha256:{}", actual);
+    let error = match copied {
+        Err(e) => Some(format!("Failed to extract {}: {}", blob.digest, e)),
+        Ok(()) if writer.written != blob.size => Some(format!(
+            "Blob {} is {} bytes, expected {}",
+            blob.digest, writer.written, blob.size
+        )),
+        Ok(()) if actual != blob.digest => Some(format!(
+            "Blob {} failed verification: digest mismatch (got {})",
+            blob.digest, actual
+        )),
+        Ok(()) => None,
+    };
+    if let Some(error) = error {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(error);
+    }
+    fs::rename(&tmp_path, &blob_path).map_err(|e| format!("Failed to finalise blob: {}", e))
+}
+
+struct HashingWriter {
+    inner: File,
+    hasher: Sha256,
+    written: u64,
+}
+
+impl Write for HashingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,241 @@
+use serde::Serialize;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tokio::fs as tokio_fs;
+
+use crate::commands::models::{
+    digest_to_blob_filename, ensure_clean_segment, parse_model_manifest_sync,
+    write_blob_from_bytes, HF_CONFIG_MEDIA_TYPE, HF_MANIFEST_MEDIA_TYPE, HF_MODEL_MEDIA_TYPE,
+};
+use crate::infrastructure::blob_download::{partial_path, sha256_file};
+use crate::infrastructure::gguf;
+use crate::models::{
+    GgufInfo, ImportMode, ManifestConfig, ManifestLayer, ModelInfo, ModelManifest,
+};
+
+/// Provider directory for models that did not come from a registry.
+pub const LOCAL_PROVIDER: &str = "local";
+const LOCAL_LIBRARY: &str = "library";
+
+/// Provenance recorded in the config blob, like the HF config metadata.
+#[derive(Serialize)]
+struct LocalConfigMetadata {
+    source: String,
+    filename: String,
+    original_path: String,
+    architecture: Option<String>,
+    quantization: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command — local GGUF import
+// ---------------------------------------------------------------------------
+
+/// Adds a GGUF file on disk to the library as `local:<name>:<version>`.
+///
+/// `name` defaults to the GGUF `general.name` (or the file name) and `version`
+/// to the quantization label. Importing the same file again is a no-op.
+#[command]
+pub async fn import_gguf(
+    app: AppHandle,
+    models_root: String,
+    source_path: String,
+    name: Option<String>,
+    version: Option<String>,
+    mode: Option<ImportMode>,
+) -> Result<ModelInfo, String> {
+    let metadata_root = crate::commands::models::get_metadata_root(&app)?;
+    import_gguf_at_root(
+        Path::new(&models_root),
+        &metadata_root,
+        Path::new(&source_path),
+        name,
+        version,
+        mode.unwrap_or_default(),
+    )
+    .await
+}
+
+pub async fn import_gguf_at_root(
+    models_root: &Path,
+    metadata_root: &str,
+    source: &Path,
+    name: Option<String>,
+    version: Option<String>,
+    mode: ImportMode,
+) -> Result<ModelInfo, String> {
+    if !source.is_file() {
+        return Err(format!("{} is not a file", source.display()));
+    }
+    // Also rejects anything that is not a GGUF before we hash gigabytes.
+    let info = gguf::read_gguf_info(source)?;
+
+    let name = match name.map(|n| n.trim().to_string()) {
+        Some(name) => name,
+        None => default_name(&info, source),
+    };
+    let version = match version.map(|v| v.trim().to_string()) {
+        Some(version) => version,
+        None => default_version(&info),
+    };
+    ensure_clean_segment(&name, "Name")?;
+    ensure_clean_segment(&version, "Version")?;
+
+    let digest = format!("sha256:{}", sha256_file(source).await?);
+    let size = tokio_fs::metadata(source)
+        .await
+        .map_err(|e| format!("Failed to stat {}: {}", source.display(), e))?
+        .len();
+
+    let manifest_path = models_root
+        .join("manifests")
+        .join(LOCAL_PROVIDER)
+        .join(LOCAL_LIBRARY)
+        .join(&name)
+        .join(&version)
+        .join("manifest.json");
+    if manifest_path.exists() {
+        let existing: ModelManifest = crate::utils::read_json(&manifest_path)?;
+        if !existing.layers.iter().any(|l| l.digest == digest) {
+            return Err(format!(
+                "A local model {}:{} already exists; choose another name or version",
+                name, version
+            ));
+        }
+    }
+
+    let blobs_dir = models_root.join("blobs");
+    tokio_fs::create_dir_all(&blobs_dir)
+        .await
+        .map_err(|e| format!("Failed to create blobs directory: {}", e))?;
+    let blob_path = blobs_dir.join(digest_to_blob_filename(&digest));
+    let blob_present = tokio_fs::metadata(&blob_path)
+        .await
+        .map(|m| m.len() == size)
+        .unwrap_or(false);
+    if blob_present {
+        // Same content is already in the library; a move only has to drop the original.
+        if mode == ImportMode::Move && !same_file(source, &blob_path) {
+            tokio_fs::remove_file(source)
+                .await
+                .map_err(|e| format!("Failed to remove {}: {}", source.display(), e))?;
+        }
+    } else {
+        place_blob(source, &blob_path, mode).await?;
+    }
+
+    let config = LocalConfigMetadata {
+        source: LOCAL_PROVIDER.to_string(),
+        filename: source
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        original_path: source.to_string_lossy().to_string(),
+        architecture: info.architecture.clone(),
+        quantization: info.quantization.clone(),
+    };
+    let config_bytes = serde_json::to_vec(&config)
+        .map_err(|e| format!("Failed to serialise config metadata: {}", e))?;
+    let (config_digest, config_size) = write_blob_from_bytes(&blobs_dir, &config_bytes).await?;
+
+    let manifest = ModelManifest {
+        schema_version: 2,
+        media_type: HF_MANIFEST_MEDIA_TYPE.to_string(),
+        config: ManifestConfig {
+            media_type: HF_CONFIG_MEDIA_TYPE.to_string(),
+            digest: config_digest,
+            size: config_size,
+        },
+        layers: vec![ManifestLayer {
+            media_type: HF_MODEL_MEDIA_TYPE.to_string(),
+            digest,
+            size,
+        }],
+    };
+    crate::utils::save_json(&manifest_path, &manifest)?;
+
+    parse_model_manifest_sync(
+        manifest_path.to_string_lossy().to_string(),
+        models_root.to_string_lossy().to_string(),
+        metadata_root,
+    )
+}
+
+/// Puts `source` at `blob_path` according to `mode`. Copies go through a
+/// `.partial` file so an interrupted import never leaves a truncated blob.
+async fn place_blob(source: &Path, blob_path: &Path, mode: ImportMode) -> Result<(), String> {
+    match mode {
+        ImportMode::HardLink => {
+            if tokio_fs::hard_link(source, blob_path).await.is_ok() {
+                return Ok(());
+            }
+            copy_blob(source, blob_path).await
+        }
+        ImportMode::Copy => copy_blob(source, blob_path).await,
+        ImportMode::Move => {
+            if tokio_fs::rename(source, blob_path).await.is_ok() {
+                return Ok(());
+            }
+            copy_blob(source, blob_path).await?;
+            tokio_fs::remove_file(source)
+                .await
+                .map_err(|e| format!("Failed to remove {}: {}", source.display(), e))
+        }
+    }
+}
+
+async fn copy_blob(source: &Path, blob_path: &Path) -> Result<(), String> {
+    let tmp_path = partial_path(blob_path);
+    tokio_fs::copy(source, &tmp_path)
+        .await
+        .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+    tokio_fs::rename(&tmp_path, blob_path)
+        .await
+        .map_err(|e| format!("Failed to finalise blob: {}", e))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// `general.name`, else the file stem, as a lowercase path segment.
+fn default_name(info: &GgufInfo, source: &Path) -> String {
+    let raw = info
+        .name
+        .clone()
+        .filter(|n| !n.trim().is_empty())
+        .or_else(|| source.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    let name = slug(&raw);
+    if name.is_empty() {
+        "model".to_string()
+    } else {
+        name
+    }
+}
+
+fn default_version(info: &GgufInfo) -> String {
+    info.quantization
+        .as_deref()
+        .map(slug)
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "latest".to_string())
+}
+
+/// Lowercases and replaces anything outside `[a-z0-9._-]` with single dashes.
+fn slug(value: &str) -> String {
+    let mut out = String::new();
+    for c in value.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+            out.push(c);
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let out = out.trim_matches('-').to_string();
+    // ".." would be rejected as a path segment.
+    out.replace("..", ".")
+}
@@ -0,0 +1,452 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, State};
+
+use crate::commands::models::{digest_to_blob_filename, find_model_blob_path, parse_model_path};
+use crate::commands::presets::{build_presets_path, load_presets_from_path, save_presets_to_path};
+use crate::infrastructure::blob_download::sha256_file;
+use crate::models::{
+    BlobCheck, BlobFile, BlobStatus, DeleteModelReport, ManifestCheck, ModelLibrary, ModelManifest,
+    ModelsDirectoryReport,
+};
+use crate::state::AppState;
+
+// ---------------------------------------------------------------------------
+// Tauri command — model deletion
+// ---------------------------------------------------------------------------
+
+/// Removes a model's manifest, then deletes those of its blobs no other
+/// manifest references plus stale `.partial` files. `dry_run` reports without
+/// deleting.
+#[command]
+pub async fn delete_model(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    models_root: String,
+    model_id: String,
+    dry_run: Option<bool>,
+) -> Result<DeleteModelReport, String> {
+    let running_blobs: Vec<PathBuf> = state
+        .llama_service
+        .list_models()
+        .await
+        .into_iter()
+        .map(|m| PathBuf::from(m.config.model_path))
+        .collect();
+    let active_partials: Vec<PathBuf> = state
+        .downloads
+        .list()
+        .into_iter()
+        .filter(|job| job.status.is_active())
+        .flat_map(|job| job.partial_files)
+        .map(PathBuf::from)
+        .collect();
+    let metadata_root = crate::commands::models::get_metadata_root(&app)?;
+
+    delete_model_at_root(
+        Path::new(&models_root),
+        &metadata_root,
+        &model_id,
+        dry_run.unwrap_or(false),
+        &running_blobs,
+        &active_partials,
+    )
+}
+
+/// `running_blobs` may not be deleted; `active_partials` (and their `.N`
+/// chunk files) belong to downloads in progress and are left alone.
+pub fn delete_model_at_root(
+    models_root: &Path,
+    metadata_root: &str,
+    model_id: &str,
+    dry_run: bool,
+    running_blobs: &[PathBuf],
+    active_partials: &[PathBuf],
+) -> Result<DeleteModelReport, String> {
+    let manifest_path = find_manifest_for_model(models_root, model_id)?;
+    let manifest: Option<ModelManifest> = crate::utils::read_json(&manifest_path).ok();
+    let mut warnings = Vec::new();
+
+    // Blob filenames the other manifests still point at.
+    let mut referenced = HashSet::new();
+    let mut references_complete = true;
+    for path in list_manifest_files(models_root) {
+        if path == manifest_path {
+            continue;
+        }
+        match crate::utils::read_json::<ModelManifest>(&path) {
+            Ok(other) => referenced.extend(
+                manifest_blobs(&other)
+                    .iter()
+                    .map(|(digest, _)| digest_to_blob_filename(digest)),
+            ),
+            Err(e) => {
+                references_complete = false;
+                warnings.push(format!(
+                    "Kept all blobs because a manifest is unreadable: {}",
+                    e
+                ));
+            }
+        }
+    }
+
+    // Only this model's own blobs are candidates: an unreferenced blob may be
+    // a finished layer of a pull or import that has not written its manifest.
+    let own_blobs: HashSet<String> = manifest
+        .as_ref()
+        .map(manifest_blobs)
+        .unwrap_or_default()
+        .iter()
+        .map(|(digest, _)| digest_to_blob_filename(digest))
+        .collect();
+
+    let shared_blobs: Vec<String> = manifest
+        .as_ref()
+        .map(manifest_blobs)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(digest, _)| digest)
+        .filter(|digest| referenced.contains(&digest_to_blob_filename(digest)))
+        .collect();
+
+    let mut removed_blobs = Vec::new();
+    let mut removed_partials = Vec::new();
+    let blobs_dir = models_root.join("blobs");
+    if let Ok(entries) = fs::read_dir(&blobs_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let removed = BlobFile {
+                path: path.to_string_lossy().to_string(),
+                bytes,
+            };
+            if name.contains(".partial") {
+                if !is_active_partial(&path, active_partials) {
+                    removed_partials.push(removed);
+                }
+            } else if references_complete
+                && own_blobs.contains(&name)
+                && !referenced.contains(&name)
+            {
+                if running_blobs.iter().any(|p| same_file(p, &path)) {
+                    return Err(format!(
+                        "Model {} is running; stop it before deleting",
+                        model_id
+                    ));
+                }
+                removed_blobs.push(removed);
+            }
+        }
+    }
+    removed_blobs.sort_by(|a, b| a.path.cmp(&b.path));
+    removed_partials.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let bytes_reclaimed = removed_blobs
+        .iter()
+        .chain(&removed_partials)
+        .map(|f| f.bytes)
+        .sum();
+    let report = DeleteModelReport {
+        model_id: model_id.to_string(),
+        dry_run,
+        manifest_path: manifest_path.to_string_lossy().to_string(),
+        removed_blobs,
+        removed_partials,
+        shared_blobs,
+        bytes_reclaimed,
+        warnings,
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    remove_manifest(models_root, &manifest_path)?;
+    for file in report.removed_blobs.iter().chain(&report.removed_partials) {
+        fs::remove_file(&file.path)
+            .map_err(|e| format!("Failed to delete {}: {}", file.path, e))?;
+    }
+    remove_from_library(models_root, model_id)?;
+    crate::commands::models::remove_from_metadata_cache(metadata_root, model_id)?;
+    let presets_path = build_presets_path(models_root);
+    let mut presets = load_presets_from_path(&presets_path)?;
+    if presets.models.remove(model_id).is_some() {
+        save_presets_to_path(&presets_path, &presets)?;
+    }
+
+    Ok(report)
+}
+
+/// The library entry's manifest when it still exists, otherwise the single
+/// manifest under `manifests/` whose `provider:name:version` matches.
+pub(crate) fn find_manifest_for_model(models_root: &Path, model_id: &str) -> Result<PathBuf, String> {
+    let library_path = models_root.join("modelLibrary.json");
+    if library_path.exists() {
+        let library: ModelLibrary = crate::utils::read_json(&library_path)?;
+        let from_library = library
+            .models
+            .into_iter()
+            .filter(|m| m.full_identifier == model_id)
+            .filter_map(|m| m.manifest_path)
+            .map(PathBuf::from)
+            .find(|p| p.is_file());
+        if let Some(path) = from_library {
+            return Ok(path);
+        }
+    }
+
+    let mut matches: Vec<PathBuf> = list_manifest_files(models_root)
+        .into_iter()
+        .filter(|path| {
+            parse_model_path(&path.to_string_lossy())
+                .map(|(provider, _, name, version)| {
+                    format!("{}:{}:{}", provider, name, version) == model_id
+                })
+                .unwrap_or(false)
+        })
+        .collect();
+    match matches.len() {
+        0 => Err(format!("Model {} was not found", model_id)),
+        1 => Ok(matches.remove(0)),
+        _ => Err(format!(
+            "Model {} matches several manifests:\n  {}",
+            model_id,
+            matches
+                .iter()
+                .map(|p| p.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("\n  ")
+        )),
+    }
+}
+
+/// Every manifest under `manifests/{provider}/{library}/{name}/{version}`,
+/// either a bare file or a directory holding `manifest.json`.
+pub fn list_manifest_files(models_root: &Path) -> Vec<PathBuf> {
+    let mut level = vec![models_root.join("manifests")];
+    for _ in 0..3 {
+        level = level
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+    }
+    let mut manifests: Vec<PathBuf> = level
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter_map(|path| {
+            if path.is_file() {
+                Some(path)
+            } else {
+                let file = path.join("manifest.json");
+                file.is_file().then_some(file)
+            }
+        })
+        .collect();
+    manifests.sort();
+    manifests
+}
+
+/// `(digest, size)` of the config blob, then of every layer.
+pub(crate) fn manifest_blobs(manifest: &ModelManifest) -> Vec<(String, u64)> {
+    std::iter::once((manifest.config.digest.clone(), manifest.config.size))
+        .chain(manifest.layers.iter().map(|l| (l.digest.clone(), l.size)))
+        .collect()
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn is_active_partial(path: &Path, active_partials: &[PathBuf]) -> bool {
+    let name = path.to_string_lossy();
+    active_partials.iter().any(|active| {
+        let active = active.to_string_lossy();
+        name == active || name.starts_with(&format!("{}.", active))
+    })
+}
+
+/// Deletes the manifest and any directories left empty, up to `manifests/`.
+fn remove_manifest(models_root: &Path, manifest_path: &Path) -> Result<(), String> {
+    fs::remove_file(manifest_path)
+        .map_err(|e| format!("Failed to delete {}: {}", manifest_path.display(), e))?;
+    let manifests_root = models_root.join("manifests");
+    let mut dir = manifest_path.parent();
+    while let Some(current) = dir {
+        if current == manifests_root || !current.starts_with(&manifests_root) {
+            break;
+        }
+        // Fails, and stops the walk, once a directory still has content.
+        if fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+    Ok(())
+}
+
+fn remove_from_library(models_root: &Path, model_id: &str) -> Result<(), String> {
+    let library_path = models_root.join("modelLibrary.json");
+    if !library_path.exists() {
+        return Ok(());
+    }
+    let mut library: ModelLibrary = crate::utils::read_json(&library_path)?;
+    let before = library.models.len();
+    library.models.retain(|m| m.full_identifier != model_id);
+    if library.models.len() != before {
+        crate::utils::save_json(&library_path, &library)?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command — directory audit
+// ---------------------------------------------------------------------------
+
+/// Checks that every manifest's blobs exist with the manifest size and, with
+/// `rehash`, that they hash to their digest. Also lists orphan blobs and
+/// `.partial` files. Read-only; the UI re-downloads or deletes from the report.
+#[command]
+pub async fn verify_models_directory(
+    models_root: String,
+    rehash: Option<bool>,
+) -> Result<ModelsDirectoryReport, String> {
+    verify_models_directory_at_root(Path::new(&models_root), rehash.unwrap_or(false)).await
+}
+
+pub async fn verify_models_directory_at_root(
+    models_root: &Path,
+    rehash: bool,
+) -> Result<ModelsDirectoryReport, String> {
+    let mut referenced = HashSet::new();
+    // Blobs shared between manifests are hashed once.
+    let mut hashes = HashMap::new();
+    let mut manifests = Vec::new();
+
+    for manifest_path in list_manifest_files(models_root) {
+        let manifest_path = manifest_path.to_string_lossy().to_string();
+        let model_id = parse_model_path(&manifest_path)
+            .map(|(provider, _, name, version)| format!("{}:{}:{}", provider, name, version))
+            .unwrap_or_else(|_| manifest_path.clone());
+        let manifest: ModelManifest = match crate::utils::read_json(Path::new(&manifest_path)) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                manifests.push(ManifestCheck {
+                    model_id,
+                    manifest_path,
+                    blobs: Vec::new(),
+                    error: Some(e),
+                    healthy: false,
+                });
+                continue;
+            }
+        };
+
+        let mut blobs = Vec::new();
+        for (digest, size) in manifest_blobs(&manifest) {
+            referenced.insert(digest_to_blob_filename(&digest));
+            blobs.push(check_blob(models_root, &digest, size, rehash, &mut hashes).await);
+        }
+        manifests.push(ManifestCheck {
+            model_id,
+            manifest_path,
+            healthy: blobs.iter().all(|b| b.status == BlobStatus::Ok),
+            blobs,
+            error: None,
+        });
+    }
+
+    let mut orphan_blobs = Vec::new();
+    let mut partial_files = Vec::new();
+    let mut total_bytes = 0;
+    if let Ok(entries) = fs::read_dir(models_root.join("blobs")) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            total_bytes += bytes;
+            let file = BlobFile {
+                path: entry.path().to_string_lossy().to_string(),
+                bytes,
+            };
+            if name.contains(".partial") {
+                partial_files.push(file);
+            } else if name.starts_with("sha256-") && !referenced.contains(&name) {
+                orphan_blobs.push(file);
+            }
+        }
+    }
+    orphan_blobs.sort_by(|a, b| a.path.cmp(&b.path));
+    partial_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(ModelsDirectoryReport {
+        models_root: models_root.to_string_lossy().to_string(),
+        rehashed: rehash,
+        manifests,
+        reclaimable_bytes: orphan_blobs
+            .iter()
+            .chain(&partial_files)
+            .map(|f| f.bytes)
+            .sum(),
+        orphan_blobs,
+        partial_files,
+        total_bytes,
+    })
+}
+
+async fn check_blob(
+    models_root: &Path,
+    digest: &str,
+    expected_size: u64,
+    rehash: bool,
+    hashes: &mut HashMap<String, String>,
+) -> BlobCheck {
+    let Some(path) = find_model_blob_path(models_root, digest) else {
+        return BlobCheck {
+            digest: digest.to_string(),
+            path: None,
+            expected_size,
+            actual_size: None,
+            status: BlobStatus::Missing,
+            error: None,
+        };
+    };
+    let actual_size = fs::metadata(&path).map(|m| m.len()).ok();
+
+    let mut error = None;
+    let status = if expected_size > 0 && actual_size != Some(expected_size) {
+        BlobStatus::SizeMismatch
+    } else if let (true, Some(expected)) = (rehash, digest.strip_prefix("sha256:")) {
+        let actual = match hashes.get(&path) {
+            Some(hash) => Ok(hash.clone()),
+            None => sha256_file(Path::new(&path)).await.inspect(|hash| {
+                hashes.insert(path.clone(), hash.clone());
+            }),
+        };
+        match actual {
+            Ok(actual) if actual.eq_ignore_ascii_case(expected) => BlobStatus::Ok,
+            Ok(_) => BlobStatus::DigestMismatch,
+            // One bad blob should not end the audit of all the others.
+            Err(e) => {
+                error = Some(e);
+                BlobStatus::Unreadable
+            }
+        }
+    } else {
+        BlobStatus::Ok
+    };
+
+    BlobCheck {
+        digest: digest.to_string(),
+        path: Some(path),
+        expected_size,
+        actual_size,
+        status,
+        error,
+    }
+}
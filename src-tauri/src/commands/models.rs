@@ -5,14 +5,21 @@ use tauri::AppHandle;
 use tauri::Emitter;
 use tauri::Manager; // required to call .emit() on AppHandle
 
-use crate::models::{ModelInfo, ModelLibrary, ModelManifest};
+use crate::infrastructure::blob_download;
+use crate::infrastructure::gguf;
+use crate::infrastructure::modelfile::{self, ModelfileLayers};
+use crate::infrastructure::nvidia_smi::NvidiaSmi;
+use crate::infrastructure::registry_client::RegistryClient;
+use crate::services::downloads::DownloadProgressFn;
+use crate::models::{
+    AppConfig, DownloadProgress, GgufInfo, MemoryEstimate, MemoryEstimateOptions, ModelInfo, ModelLibrary, ModelManifest,
+};
 use futures::StreamExt;
-use reqwest::header::{ACCEPT, USER_AGENT};
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
+use serde_json::Value;
 use sha2::{Digest, Sha256};
-use std::io::{BufReader, Read};
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::fs as tokio_fs;
 use tokio::io::AsyncWriteExt;
 
@@ -24,12 +31,6 @@ const DEFAULT_REGISTRY: &str = "registry.ollama.ai";
 const DEFAULT_LIBRARY: &str = "library";
 const DEFAULT_VERSION: &str = "latest";
 
-// Multi-value Accept header required by Ollama's registry — order matters,
-// OCI manifest format takes priority over Docker v2 schema.
-const MANIFEST_ACCEPT: &str = "application/vnd.ollama.manifest.v1+json, \
-    application/vnd.oci.image.manifest.v1+json, \
-    application/vnd.docker.distribution.manifest.v2+json";
-
 const HF_HOST: &str = "hf.co";
 const HF_HOST_LONG: &str = "huggingface.co";
 
@@ -38,9 +39,9 @@ const HF_REVISION_DEFAULT: &str = "main";
 
 // Media types mimic Ollama's blob conventions so the manifest directory
 // stays compatible with ollama CLI tooling.
-const HF_MANIFEST_MEDIA_TYPE: &str = "application/vnd.ollama.manifest.v1+json";
-const HF_MODEL_MEDIA_TYPE: &str = "application/vnd.ollama.image.model";
-const HF_CONFIG_MEDIA_TYPE: &str = "application/vnd.ollama.image.config";
+pub(crate) const HF_MANIFEST_MEDIA_TYPE: &str = "application/vnd.ollama.manifest.v1+json";
+pub(crate) const HF_MODEL_MEDIA_TYPE: &str = "application/vnd.ollama.image.model";
+pub(crate) const HF_CONFIG_MEDIA_TYPE: &str = "application/vnd.ollama.image.config";
 
 // Chunk size at which download progress events are emitted.
 // 1 MiB keeps the event rate reasonable without hiding progress for large models.
@@ -74,13 +75,16 @@ pub(crate) struct HfModelRef {
 // ---------------------------------------------------------------------------
 
 #[derive(Debug, Deserialize)]
-struct HfApiModel {
-    siblings: Option<Vec<HfApiSibling>>,
+pub(crate) struct HfApiModel {
+    pub(crate) siblings: Option<Vec<HfApiSibling>>,
 }
 
 #[derive(Debug, Deserialize)]
-struct HfApiSibling {
-    rfilename: String,
+pub(crate) struct HfApiSibling {
+    pub(crate) rfilename: String,
+    // Only present when the API is queried with `blobs=true`.
+    #[serde(default)]
+    pub(crate) size: Option<u64>,
 }
 
 // Stored as the config blob so the runtime knows where a model came from.
@@ -113,7 +117,7 @@ fn is_hf_reference(value: &str) -> bool {
 }
 
 /// Rejects segments that could escape the models directory via path traversal.
-fn ensure_clean_segment(segment: &str, label: &str) -> Result<(), String> {
+pub(crate) fn ensure_clean_segment(segment: &str, label: &str) -> Result<(), String> {
     if segment.is_empty() {
         return Err(format!("{} cannot be empty", label));
     }
@@ -123,6 +127,18 @@ fn ensure_clean_segment(segment: &str, label: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Accepts only `sha256:` plus 64 lowercase hex digits, the one digest form
+/// that is safe to turn into a blob filename.
+pub(crate) fn ensure_sha256_digest(digest: &str) -> Result<(), String> {
+    let valid = digest.strip_prefix("sha256:").is_some_and(|hex| {
+        hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    });
+    if !valid {
+        return Err(format!("Invalid blob digest {:?}", digest));
+    }
+    Ok(())
+}
+
 /// Splits "name:version" using the last colon so names with colons still work.
 fn split_name_version(value: &str) -> Result<(String, String), String> {
     let mut parts = value.rsplitn(2, ':');
@@ -241,6 +257,34 @@ fn parse_hf_reference(reference: &str) -> Result<HfModelRef, String> {
     })
 }
 
+/// `https://huggingface.co`, or the mirror set in `AppConfig::hf_endpoint`.
+pub(crate) fn hf_base_url(config: &AppConfig) -> String {
+    config
+        .hf_endpoint
+        .as_deref()
+        .map(|url| url.trim().trim_end_matches('/'))
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("https://{}", HF_HOST_LONG))
+}
+
+/// HTTP client that sends the configured HF token, for gated repositories.
+/// reqwest drops the header when a file redirects to the CDN.
+pub(crate) fn hf_client(config: &AppConfig) -> Result<reqwest::Client, String> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(token) = config.hf_token.as_deref().filter(|t| !t.trim().is_empty()) {
+        let mut value = reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token.trim()))
+            .map_err(|e| format!("Invalid Hugging Face token: {}", e))?;
+        value.set_sensitive(true);
+        headers.insert(reqwest::header::AUTHORIZATION, value);
+    }
+    reqwest::Client::builder()
+        .user_agent("llama-desktop")
+        .default_headers(headers)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
 /// A 40-character hex string is treated as a Git commit SHA.
 fn is_git_commit_hash(s: &str) -> bool {
     s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())
@@ -344,7 +388,10 @@ fn hex_encode(bytes: &[u8]) -> String {
 ///
 /// Uses a temp-then-rename strategy so a crashed write never leaves a partial
 /// blob at the canonical path.
-async fn write_blob_from_bytes(blobs_dir: &Path, bytes: &[u8]) -> Result<(String, u64), String> {
+pub(crate) async fn write_blob_from_bytes(
+    blobs_dir: &Path,
+    bytes: &[u8],
+) -> Result<(String, u64), String> {
     tokio_fs::create_dir_all(blobs_dir)
         .await
         .map_err(|e| format!("Failed to create blobs directory: {}", e))?;
@@ -375,54 +422,92 @@ async fn write_blob_from_bytes(blobs_dir: &Path, bytes: &[u8]) -> Result<(String
     Ok((digest, bytes.len() as u64))
 }
 
+/// Where a pull of `repo_id@revision/filename` keeps its bytes until it is
+/// complete. Stable across runs, so a paused pull picks up where it stopped.
+pub fn hf_partial_path(blobs_dir: &Path, repo_id: &str, revision: &str, filename: &str) -> PathBuf {
+    let key = Sha256::digest(format!("{}@{}/{}", repo_id, revision, filename).as_bytes());
+    blobs_dir.join(format!("hf-download-{}.partial", &hex_encode(&key)[..16]))
+}
+
 /// Stream a HuggingFace model file to disk, computing its sha256 on the fly.
-/// Emits `download:progress` Tauri events every `PROGRESS_EMIT_INTERVAL_BYTES`
-/// so the frontend can show a real progress bar.
+/// Reports to `progress` every `PROGRESS_EMIT_INTERVAL_BYTES` so the frontend
+/// can show a real progress bar. Bytes already in `tmp_path` are kept and the
+/// rest is requested with a Range header.
 ///
 /// Returns `(digest, byte_count)`. The digest can be used for manifest construction
 /// — we don't receive an expected digest from HF, so we produce our own.
-async fn download_hf_file(
-    app: &AppHandle,
+pub async fn download_hf_file(
     client: &reqwest::Client,
     url: &str,
     filename: &str,
     blobs_dir: &Path,
+    tmp_path: &Path,
+    progress: &DownloadProgressFn,
 ) -> Result<(String, u64), String> {
-    let response = client
-        .get(url)
+    let existing = tokio_fs::metadata(tmp_path)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+    }
+    let response = request
         .send()
         .await
         .map_err(|e| format!("Failed to download model file: {}", e))?;
 
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download model file: HTTP {}",
-            response.status()
-        ));
-    }
+    let resumed = match response.status() {
+        reqwest::StatusCode::PARTIAL_CONTENT if existing > 0 => {
+            blob_download::check_range_start(&response, existing)?;
+            true
+        }
+        // The server ignored the Range header and sent the whole file.
+        status if status.is_success() => false,
+        reqwest::StatusCode::RANGE_NOT_SATISFIABLE => {
+            let _ = tokio_fs::remove_file(tmp_path).await;
+            return Err("Failed to download model file: server rejected resume range".to_string());
+        }
+        status => {
+            return Err(format!("Failed to download model file: HTTP {}", status));
+        }
+    };
 
     // Content-Length is optional — surface it as total_bytes = 0 when absent.
-    let total_bytes: u64 = response.content_length().unwrap_or(0);
+    let already = if resumed { existing } else { 0 };
+    let total_bytes: u64 = response
+        .content_length()
+        .map(|len| len + already)
+        .unwrap_or(0);
 
     tokio_fs::create_dir_all(blobs_dir)
         .await
         .map_err(|e| format!("Failed to create blobs directory: {}", e))?;
 
-    // Unique temp name avoids collision when two HF downloads run concurrently.
-    let tmp_path = blobs_dir.join(format!(
-        "hf-download-{}.partial",
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.subsec_nanos())
-            .unwrap_or(0)
-    ));
-
-    let mut file = tokio_fs::File::create(&tmp_path)
+    let mut hasher = Sha256::new();
+    if resumed {
+        blob_download::hash_file_into(tmp_path, &mut hasher).await?;
+    }
+    let mut file = tokio_fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(tmp_path)
         .await
         .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let report = |downloaded: u64| {
+        progress(DownloadProgress {
+            blob: filename.to_string(),
+            blob_downloaded: downloaded,
+            blob_total: total_bytes,
+            downloaded,
+            total: total_bytes,
+            partial_path: Some(tmp_path.to_string_lossy().to_string()),
+        })
+    };
 
-    let mut hasher = Sha256::new();
-    let mut downloaded: u64 = 0;
+    let mut downloaded: u64 = already;
     let mut since_last_emit: u64 = 0;
 
     let mut stream = response.bytes_stream();
@@ -441,14 +526,7 @@ async fn download_hf_file(
         // Throttle events to avoid flooding the frontend IPC channel.
         if since_last_emit >= PROGRESS_EMIT_INTERVAL_BYTES {
             since_last_emit = 0;
-            let _ = app.emit(
-                "download:progress",
-                serde_json::json!({
-                    "filename": filename,
-                    "downloaded": downloaded,
-                    "total": total_bytes,
-                }),
-            );
+            report(downloaded);
         }
     }
 
@@ -458,14 +536,7 @@ async fn download_hf_file(
 
     // Final progress event so the frontend reaches 100 % even if the last
     // chunk didn't cross the emit threshold.
-    let _ = app.emit(
-        "download:progress",
-        serde_json::json!({
-            "filename": filename,
-            "downloaded": downloaded,
-            "total": total_bytes,
-        }),
-    );
+    report(downloaded);
 
     let digest = format!("sha256:{}", hex_encode(&hasher.finalize()));
     let blob_filename = digest_to_blob_filename(&digest);
@@ -473,11 +544,11 @@ async fn download_hf_file(
 
     if tokio_fs::metadata(&blob_path).await.is_ok() {
         // A previous download produced the same file — discard the duplicate.
-        tokio_fs::remove_file(&tmp_path)
+        tokio_fs::remove_file(tmp_path)
             .await
             .map_err(|e| format!("Failed to cleanup temp file: {}", e))?;
     } else {
-        tokio_fs::rename(&tmp_path, &blob_path)
+        tokio_fs::rename(tmp_path, &blob_path)
             .await
             .map_err(|e| format!("Failed to finalise model file: {}", e))?;
     }
@@ -485,124 +556,52 @@ async fn download_hf_file(
     Ok((digest, downloaded))
 }
 
-/// Download a single content-addressed blob from an Ollama-compatible registry.
-/// Skips the download when a blob of the expected size already exists locally.
-///
-/// Verifies the downloaded file size against `size` (when non-zero). SHA-256
-/// is intentionally not re-verified here because the registry delivers a
-/// content-addressed URL — the digest IS the address.
+/// Progress of a whole model pull; `done` is what earlier blobs contributed.
+struct PullProgress<'a> {
+    done: u64,
+    total: u64,
+    report: &'a DownloadProgressFn,
+}
+
+/// Download a single content-addressed blob from an OCI registry.
+/// Skips the download when a blob of the expected size already exists locally,
+/// resumes a leftover `.partial` file and verifies the SHA-256 against `digest`.
+/// Progress is reported for the whole model as well as for this blob.
 async fn download_blob(
-    app: &AppHandle,
-    client: &reqwest::Client,
-    registry: &str,
-    repository: &str,
+    client: &blob_download::BlobClient,
+    url: &str,
     digest: &str,
     size: u64,
     blobs_dir: &Path,
+    progress: &PullProgress<'_>,
 ) -> Result<(), String> {
-    let blob_filename = digest_to_blob_filename(digest);
-    let blob_path = blobs_dir.join(&blob_filename);
-
-    // Fast path — already cached and size matches.
-    if let Ok(metadata) = tokio_fs::metadata(&blob_path).await {
-        if size == 0 || metadata.len() == size {
-            return Ok(());
-        }
-        // Size mismatch — cached blob is corrupt, re-download.
-    }
-
-    tokio_fs::create_dir_all(blobs_dir)
-        .await
-        .map_err(|e| format!("Failed to create blobs directory: {}", e))?;
-
-    let url = format!("https://{}/v2/{}/blobs/{}", registry, repository, digest);
-
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to download blob {}: {}", digest, e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download blob {}: HTTP {}",
-            digest,
-            response.status()
-        ));
-    }
-
-    let total_bytes: u64 = response.content_length().unwrap_or(size);
-
-    // .partial suffix on the blob filename makes cleanup easy on restart.
-    let tmp_path = blob_path.with_extension("partial");
-    let mut file = tokio_fs::File::create(&tmp_path)
-        .await
-        .map_err(|e| format!("Failed to create temp file: {}", e))?;
-
-    let mut downloaded: u64 = 0;
-    let mut since_last_emit: u64 = 0;
-    let short_digest = &digest[..digest.len().min(19)];
-
-    let mut stream = response.bytes_stream();
-    while let Some(chunk) = stream.next().await {
-        let bytes =
-            chunk.map_err(|e| format!("Failed while downloading blob {}: {}", digest, e))?;
-
-        let len = bytes.len() as u64;
-        downloaded += len;
-        since_last_emit += len;
-
-        file.write_all(&bytes)
-            .await
-            .map_err(|e| format!("Failed to write blob {}: {}", digest, e))?;
-
-        if since_last_emit >= PROGRESS_EMIT_INTERVAL_BYTES {
-            since_last_emit = 0;
-            let _ = app.emit(
-                "download:progress",
-                serde_json::json!({
-                    "digest": short_digest,
-                    "downloaded": downloaded,
-                    "total": total_bytes,
-                }),
-            );
-        }
-    }
-
-    file.flush()
-        .await
-        .map_err(|e| format!("Failed to flush blob {}: {}", digest, e))?;
-
-    // Verify size only when the manifest told us what to expect.
-    if size > 0 {
-        let metadata = tokio_fs::metadata(&tmp_path)
-            .await
-            .map_err(|e| format!("Failed to stat blob {}: {}", digest, e))?;
-
-        if metadata.len() != size {
-            // Remove corrupt partial file before returning so a retry starts clean.
-            let _ = tokio_fs::remove_file(&tmp_path).await;
-            return Err(format!(
-                "Blob {} size mismatch (expected {}, got {})",
-                digest,
-                size,
-                metadata.len()
-            ));
-        }
-    }
-
-    // Overwrite any stale blob at the canonical path.
-    if tokio_fs::metadata(&blob_path).await.is_ok() {
-        tokio_fs::remove_file(&blob_path)
-            .await
-            .map_err(|e| format!("Failed to remove stale blob: {}", e))?;
-    }
-
-    tokio_fs::rename(&tmp_path, &blob_path)
-        .await
-        .map_err(|e| format!("Failed to finalise blob {}: {}", digest, e))?;
+    let partial_path = blob_download::partial_path(&blobs_dir.join(digest_to_blob_filename(digest)));
+    let report = progress.report.clone();
+    let (done, total) = (progress.done, progress.total);
+    let blob = digest.to_string();
+    let partial = partial_path.to_string_lossy().to_string();
+    let blob_progress: blob_download::ProgressFn = Arc::new(move |downloaded, blob_total| {
+        report(DownloadProgress {
+            blob: blob.clone(),
+            blob_downloaded: downloaded,
+            blob_total,
+            downloaded: done + downloaded,
+            total,
+            partial_path: Some(partial.clone()),
+        })
+    });
 
-    Ok(())
+    blob_download::download_blob(
+        client,
+        url,
+        digest,
+        size,
+        blobs_dir,
+        &blob_download::DownloadOptions::default(),
+        blob_progress,
+    )
+    .await
+    .map(|_| ())
 }
 
 // ---------------------------------------------------------------------------
@@ -610,19 +609,19 @@ async fn download_blob(
 // ---------------------------------------------------------------------------
 
 async fn download_model_from_hf(
-    app: AppHandle,
+    app: &AppHandle,
     model_reference: String,
     models_root: String,
+    progress: &DownloadProgressFn,
 ) -> Result<ModelInfo, String> {
     let model_ref = parse_hf_reference(&model_reference)?;
 
-    let client = reqwest::Client::builder()
-        .user_agent("llama-desktop")
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let app_config = crate::commands::config::get_config(app).unwrap_or_default();
+    let base_url = hf_base_url(&app_config);
+    let client = hf_client(&app_config)?;
 
     // Query the HF API for the file list — we need siblings to pick the GGUF.
-    let api_url = format!("https://{}/api/models/{}", HF_HOST_LONG, model_ref.repo_id);
+    let api_url = format!("{}/api/models/{}", base_url, model_ref.repo_id);
 
     let api_response = client
         .get(&api_url)
@@ -663,14 +662,22 @@ async fn download_model_from_hf(
     };
 
     let file_url = format!(
-        "https://{}/{}/resolve/{}/{}",
-        HF_HOST_LONG, model_ref.repo_id, model_ref.revision, filename
+        "{}/{}/resolve/{}/{}",
+        base_url, model_ref.repo_id, model_ref.revision, filename
     );
 
     let blobs_dir = PathBuf::from(&models_root).join("blobs");
 
-    let (model_digest, model_size) =
-        download_hf_file(&app, &client, &file_url, &filename, &blobs_dir).await?;
+    let tmp_path = hf_partial_path(
+        &blobs_dir,
+        &model_ref.repo_id,
+        &model_ref.revision,
+        &filename,
+    );
+    let (model_digest, model_size) = download_hf_file(
+        &client, &file_url, &filename, &blobs_dir, &tmp_path, progress,
+    )
+    .await?;
 
     // Build a small config blob recording provenance so the UI can display
     // "from hf.co/..." and tools can trace back to the source.
@@ -722,7 +729,7 @@ async fn download_model_from_hf(
     // Best-effort: pull the Jinja chat template for automatic prompt formatting.
     // Failure is non-fatal — the model still works without it.
     let _ = crate::services::templates::ensure_hf_chat_template(
-        &app,
+        app,
         &model_ref.repo_id,
         Some(&model_ref.revision),
     )
@@ -732,7 +739,7 @@ async fn download_model_from_hf(
         .to_str()
         .ok_or_else(|| "Failed to build manifest path string".to_string())?;
 
-    let metadata_root = get_metadata_root(&app)?;
+    let metadata_root = get_metadata_root(app)?;
     parse_model_manifest_sync(manifest_path_str.to_string(), models_root, &metadata_root)
 }
 
@@ -797,6 +804,33 @@ pub fn find_model_blob_path(models_root: &Path, digest: &str) -> Option<String>
 // Tauri commands — manifest parsing
 // ---------------------------------------------------------------------------
 
+/// Reads the header of any GGUF file, library model or not.
+#[command]
+pub async fn read_gguf_info(path: String) -> Result<GgufInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || gguf::read_gguf_info(Path::new(&path)))
+        .await
+        .map_err(|e| format!("GGUF read task failed: {}", e))?
+}
+
+/// Predicts memory use for launching `model_path` with `options`. Without
+/// explicit budgets, free VRAM reported by nvidia-smi is used.
+#[command]
+pub async fn estimate_memory(
+    model_path: String,
+    options: MemoryEstimateOptions,
+) -> Result<MemoryEstimate, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let info = gguf::read_gguf_info(Path::new(&model_path))?;
+        let mut options = options;
+        if options.vram_budget_bytes.is_empty() {
+            options.vram_budget_bytes = NvidiaSmi::free_vram_bytes();
+        }
+        crate::services::memory_estimate::estimate_memory(&info, &options)
+    })
+    .await
+    .map_err(|e| format!("Memory estimate task failed: {}", e))?
+}
+
 #[command]
 pub async fn parse_model_manifest(
     app: AppHandle,
@@ -830,15 +864,19 @@ pub fn parse_model_manifest_sync(
     // provider:name:version uniquely identifies a model within the library.
     let full_identifier = format!("{}:{}:{}", provider, name, version);
 
-    let tokenizer_metadata = model_file_path.as_ref().and_then(|path| {
-        match get_or_extract_tokenizer_metadata(metadata_root, &full_identifier, path) {
-            Ok(value) => value,
-            Err(err) => {
-                eprintln!("[llama-desktop] Failed to read GGUF metadata: {}", err);
-                None
+    let (tokenizer_metadata, gguf) = model_file_path
+        .as_ref()
+        .map(|path| {
+            match get_or_extract_gguf_metadata(metadata_root, &full_identifier, path) {
+                Ok(found) => found,
+                Err(err) => {
+                    eprintln!("[llama-desktop] Failed to read GGUF metadata: {}", err);
+                    (None, None)
+                }
             }
-        }
-    });
+        })
+        .unwrap_or_default();
+    let layers = read_modelfile_layers(models_root_path, &manifest);
 
     Ok(ModelInfo {
         provider,
@@ -847,22 +885,77 @@ pub fn parse_model_manifest_sync(
         version,
         manifest_data: manifest,
         tokenizer_metadata,
+        gguf,
+        system_prompt: layers.system,
+        template: layers.template,
+        params: layers.params,
+        stop_sequences: layers.stop,
+        license: layers.license,
         manifest_path: Some(model_path),
         model_file_path,
         full_identifier,
     })
 }
 
+/// Reads the template, system, params and license layers that are present.
+/// A broken layer is logged and skipped rather than failing the whole model.
+fn read_modelfile_layers(models_root: &Path, manifest: &ModelManifest) -> ModelfileLayers {
+    let mut layers = ModelfileLayers::default();
+    for layer in &manifest.layers {
+        if !modelfile::is_modelfile_layer(&layer.media_type) {
+            continue;
+        }
+        let Some(path) = find_model_blob_path(models_root, &layer.digest) else {
+            continue;
+        };
+        let result = match fs::metadata(&path) {
+            Ok(meta) if meta.len() > modelfile::MAX_LAYER_BYTES => Err(format!(
+                "{} layer is {} bytes",
+                layer.media_type,
+                meta.len()
+            )),
+            _ => fs::read(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))
+                .and_then(|bytes| layers.add_layer(&layer.media_type, &bytes)),
+        };
+        if let Err(err) = result {
+            eprintln!("[llama-desktop] Skipping model layer {}: {}", layer.digest, err);
+        }
+    }
+    layers
+}
+
 // ---------------------------------------------------------------------------
 // GGUF metadata cache
 // ---------------------------------------------------------------------------
 
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 struct ModelsMetadataCache {
+    /// Tokenizer metadata keyed by full identifier; `null` for a GGUF
+    /// without tokenizer keys, so it is not read again.
     models: std::collections::HashMap<String, Value>,
+    /// Header summaries keyed by full identifier; absent in older caches.
+    #[serde(default)]
+    gguf: std::collections::HashMap<String, GgufInfo>,
+}
+
+impl ModelsMetadataCache {
+    /// Fills whichever of the two cached fields the model is missing.
+    fn apply_to(&self, model: &mut ModelInfo) {
+        if model.tokenizer_metadata.is_none() {
+            if let Some(found) = self.models.get(&model.full_identifier) {
+                model.tokenizer_metadata = Some(found.clone()).filter(|v| !v.is_null());
+            }
+        }
+        if model.gguf.is_none() {
+            if let Some(found) = self.gguf.get(&model.full_identifier) {
+                model.gguf = Some(found.clone());
+            }
+        }
+    }
 }
 
-fn get_metadata_root(app: &AppHandle) -> Result<String, String> {
+pub(crate) fn get_metadata_root(app: &AppHandle) -> Result<String, String> {
     let root = app
         .path()
         .app_data_dir()
@@ -890,212 +983,48 @@ fn save_models_metadata_cache(
     crate::utils::save_json(&path, cache)
 }
 
-fn get_or_extract_tokenizer_metadata(
+/// Drops a model's cached tokenizer metadata and header summary.
+pub fn remove_from_metadata_cache(metadata_root: &str, full_identifier: &str) -> Result<(), String> {
+    let mut cache = load_models_metadata_cache(metadata_root)?;
+    let removed_tokenizer = cache.models.remove(full_identifier).is_some();
+    let removed_gguf = cache.gguf.remove(full_identifier).is_some();
+    if removed_tokenizer || removed_gguf {
+        save_models_metadata_cache(metadata_root, &cache)?;
+    }
+    Ok(())
+}
+
+/// Tokenizer metadata and header summary for a model, reading the GGUF
+/// header only when either is missing from `models.json`. The summary keeps
+/// no tensor table (see `GgufInfo::without_tensor_table`).
+fn get_or_extract_gguf_metadata(
     metadata_root: &str,
     full_identifier: &str,
     model_file_path: &str,
-) -> Result<Option<Value>, String> {
+) -> Result<(Option<Value>, Option<GgufInfo>), String> {
     let mut cache = load_models_metadata_cache(metadata_root)?;
-    if let Some(found) = cache.models.get(full_identifier) {
-        return Ok(Some(found.clone()));
+    if let (Some(tokenizer), Some(info)) = (
+        cache.models.get(full_identifier),
+        cache.gguf.get(full_identifier),
+    ) {
+        let tokenizer = Some(tokenizer.clone()).filter(|v| !v.is_null());
+        return Ok((tokenizer, Some(info.clone())));
     }
 
-    let metadata = read_gguf_tokenizer_metadata(model_file_path)?;
-    if metadata.is_empty() {
-        return Ok(None);
-    }
+    let header = gguf::read_gguf_file(Path::new(model_file_path))?;
+    let tokenizer = header.tokenizer_metadata();
+    let tokenizer = (!tokenizer.is_empty()).then_some(Value::Object(tokenizer));
+    let info = header.info().without_tensor_table();
 
-    let value = Value::Object(metadata);
+    cache.models.insert(
+        full_identifier.to_string(),
+        tokenizer.clone().unwrap_or(Value::Null),
+    );
     cache
-        .models
-        .insert(full_identifier.to_string(), value.clone());
+        .gguf
+        .insert(full_identifier.to_string(), info.clone());
     save_models_metadata_cache(metadata_root, &cache)?;
-    Ok(Some(value))
-}
-
-// ---------------------------------------------------------------------------
-// GGUF metadata reader (tokenizer.* keys)
-// ---------------------------------------------------------------------------
-
-const GGUF_MAGIC: &[u8; 4] = b"GGUF";
-
-const GGUF_VALUE_TYPE_UINT8: u32 = 0;
-const GGUF_VALUE_TYPE_INT8: u32 = 1;
-const GGUF_VALUE_TYPE_UINT16: u32 = 2;
-const GGUF_VALUE_TYPE_INT16: u32 = 3;
-const GGUF_VALUE_TYPE_UINT32: u32 = 4;
-const GGUF_VALUE_TYPE_INT32: u32 = 5;
-const GGUF_VALUE_TYPE_FLOAT32: u32 = 6;
-const GGUF_VALUE_TYPE_BOOL: u32 = 7;
-const GGUF_VALUE_TYPE_STRING: u32 = 8;
-const GGUF_VALUE_TYPE_ARRAY: u32 = 9;
-const GGUF_VALUE_TYPE_UINT64: u32 = 10;
-const GGUF_VALUE_TYPE_INT64: u32 = 11;
-const GGUF_VALUE_TYPE_FLOAT64: u32 = 12;
-
-fn read_gguf_tokenizer_metadata(path: &str) -> Result<Map<String, Value>, String> {
-    let file = std::fs::File::open(path)
-        .map_err(|e| format!("Failed to open model file {}: {}", path, e))?;
-    let mut reader = BufReader::new(file);
-
-    let magic = read_bytes(&mut reader, 4)?;
-    if magic.as_slice() != GGUF_MAGIC {
-        return Err("Not a GGUF file".to_string());
-    }
-
-    let _version = read_u32(&mut reader)?;
-    let _tensor_count = read_u64(&mut reader)?;
-    let kv_count = read_u64(&mut reader)?;
-
-    let mut result = Map::new();
-    for _ in 0..kv_count {
-        let key = read_string(&mut reader)?;
-        let value_type = read_u32(&mut reader)?;
-        let value = read_value(&mut reader, value_type)?;
-
-        if key.starts_with("tokenizer.") {
-            if key == "tokenizer.ggml.tokens"
-                || key == "tokenizer.ggml.token_type"
-                || key == "tokenizer.ggml.merges"
-            {
-                continue;
-            }
-            result.insert(key, value);
-        }
-    }
-
-    Ok(result)
-}
-
-fn read_bytes(reader: &mut BufReader<std::fs::File>, len: usize) -> Result<Vec<u8>, String> {
-    let mut buf = vec![0u8; len];
-    reader
-        .read_exact(&mut buf)
-        .map_err(|e| format!("Failed to read bytes: {}", e))?;
-    Ok(buf)
-}
-
-fn read_u32(reader: &mut BufReader<std::fs::File>) -> Result<u32, String> {
-    let mut buf = [0u8; 4];
-    reader
-        .read_exact(&mut buf)
-        .map_err(|e| format!("Failed to read u32: {}", e))?;
-    Ok(u32::from_le_bytes(buf))
-}
-
-fn read_u64(reader: &mut BufReader<std::fs::File>) -> Result<u64, String> {
-    let mut buf = [0u8; 8];
-    reader
-        .read_exact(&mut buf)
-        .map_err(|e| format!("Failed to read u64: {}", e))?;
-    Ok(u64::from_le_bytes(buf))
-}
-
-fn read_string(reader: &mut BufReader<std::fs::File>) -> Result<String, String> {
-    let len = read_u64(reader)? as usize;
-    let bytes = read_bytes(reader, len)?;
-    String::from_utf8(bytes).map_err(|e| format!("Failed to parse string: {}", e))
-}
-
-fn read_value(reader: &mut BufReader<std::fs::File>, value_type: u32) -> Result<Value, String> {
-    match value_type {
-        GGUF_VALUE_TYPE_UINT8 => {
-            let mut buf = [0u8; 1];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read u8: {}", e))?;
-            Ok(Value::Number(serde_json::Number::from(buf[0] as u64)))
-        }
-        GGUF_VALUE_TYPE_INT8 => {
-            let mut buf = [0u8; 1];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read i8: {}", e))?;
-            Ok(Value::Number(serde_json::Number::from(buf[0] as i64)))
-        }
-        GGUF_VALUE_TYPE_UINT16 => {
-            let mut buf = [0u8; 2];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read u16: {}", e))?;
-            Ok(Value::Number(serde_json::Number::from(
-                u16::from_le_bytes(buf) as u64,
-            )))
-        }
-        GGUF_VALUE_TYPE_INT16 => {
-            let mut buf = [0u8; 2];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read i16: {}", e))?;
-            Ok(Value::Number(serde_json::Number::from(
-                i16::from_le_bytes(buf) as i64,
-            )))
-        }
-        GGUF_VALUE_TYPE_UINT32 => {
-            let value = read_u32(reader)?;
-            Ok(Value::Number(serde_json::Number::from(value)))
-        }
-        GGUF_VALUE_TYPE_INT32 => {
-            let mut buf = [0u8; 4];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read i32: {}", e))?;
-            Ok(Value::Number(serde_json::Number::from(
-                i32::from_le_bytes(buf) as i64,
-            )))
-        }
-        GGUF_VALUE_TYPE_FLOAT32 => {
-            let mut buf = [0u8; 4];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read f32: {}", e))?;
-            let value = f32::from_le_bytes(buf);
-            serde_json::Number::from_f64(value as f64)
-                .map(Value::Number)
-                .ok_or_else(|| "Invalid f32 value".to_string())
-        }
-        GGUF_VALUE_TYPE_BOOL => {
-            let mut buf = [0u8; 1];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read bool: {}", e))?;
-            Ok(Value::Bool(buf[0] != 0))
-        }
-        GGUF_VALUE_TYPE_STRING => Ok(Value::String(read_string(reader)?)),
-        GGUF_VALUE_TYPE_ARRAY => {
-            let element_type = read_u32(reader)?;
-            let len = read_u64(reader)? as usize;
-            let mut values = Vec::with_capacity(len);
-            for _ in 0..len {
-                values.push(read_value(reader, element_type)?);
-            }
-            Ok(Value::Array(values))
-        }
-        GGUF_VALUE_TYPE_UINT64 => {
-            let value = read_u64(reader)?;
-            Ok(Value::Number(serde_json::Number::from(value)))
-        }
-        GGUF_VALUE_TYPE_INT64 => {
-            let mut buf = [0u8; 8];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read i64: {}", e))?;
-            Ok(Value::Number(serde_json::Number::from(i64::from_le_bytes(
-                buf,
-            ))))
-        }
-        GGUF_VALUE_TYPE_FLOAT64 => {
-            let mut buf = [0u8; 8];
-            reader
-                .read_exact(&mut buf)
-                .map_err(|e| format!("Failed to read f64: {}", e))?;
-            let value = f64::from_le_bytes(buf);
-            serde_json::Number::from_f64(value)
-                .map(Value::Number)
-                .ok_or_else(|| "Invalid f64 value".to_string())
-        }
-        other => Err(format!("Unsupported GGUF value type: {}", other)),
-    }
+    Ok((tokenizer, Some(info)))
 }
 
 // ---------------------------------------------------------------------------
@@ -1231,11 +1160,7 @@ pub async fn load_model_library(
     let metadata_root = get_metadata_root(&app)?;
     if let Ok(cache) = load_models_metadata_cache(&metadata_root) {
         for model in &mut library.models {
-            if model.tokenizer_metadata.is_none() {
-                if let Some(found) = cache.models.get(&model.full_identifier) {
-                    model.tokenizer_metadata = Some(found.clone());
-                }
-            }
+            cache.apply_to(model);
         }
     }
     Ok(library.models)
@@ -1245,50 +1170,80 @@ pub async fn load_model_library(
 // Tauri command — model download entry point
 // ---------------------------------------------------------------------------
 
+/// Pulls a model in the foreground, emitting the global `download:progress` event.
+/// The download manager (`start_download`) is the cancellable alternative.
 #[command]
 pub async fn download_model_from_registry(
     app: AppHandle,
     model_reference: String,
     models_root: String,
+) -> Result<ModelInfo, String> {
+    let emitter = app.clone();
+    let progress: DownloadProgressFn = Arc::new(move |update: DownloadProgress| {
+        // Registry blobs are keyed by a shortened digest, HF files by name.
+        let payload = if update.blob.starts_with("sha256:") {
+            serde_json::json!({
+                "digest": &update.blob[..update.blob.len().min(19)],
+                "downloaded": update.blob_downloaded,
+                "total": update.blob_total,
+            })
+        } else {
+            serde_json::json!({
+                "filename": update.blob,
+                "downloaded": update.blob_downloaded,
+                "total": update.blob_total,
+            })
+        };
+        let _ = emitter.emit("download:progress", payload);
+    });
+    pull_model(&app, model_reference, models_root, progress).await
+}
+
+/// Downloads the manifest and blobs for `model_reference` into `models_root`
+/// and returns the parsed model. Blobs already on disk are reused and
+/// `.partial` files are resumed, so calling this again continues a pull.
+pub async fn pull_model(
+    app: &AppHandle,
+    model_reference: String,
+    models_root: String,
+    progress: DownloadProgressFn,
 ) -> Result<ModelInfo, String> {
     // Route HuggingFace references through their own download path.
     if is_hf_reference(&model_reference) {
-        return download_model_from_hf(app, model_reference, models_root).await;
+        return download_model_from_hf(app, model_reference, models_root, &progress).await;
     }
 
-    let model_ref = parse_model_reference(&model_reference)?;
-    let repository = format!("{}/{}", model_ref.library, model_ref.name);
+    let app_config = crate::commands::config::get_config(app).unwrap_or_default();
+    let metadata_root = get_metadata_root(app)?;
+    pull_registry_model_at_root(
+        &model_reference,
+        models_root,
+        &metadata_root,
+        &app_config,
+        progress,
+    )
+    .await
+}
 
-    let client = reqwest::Client::builder()
-        .user_agent("llama-desktop")
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+/// Registry half of `pull_model`. Credentials and plain-HTTP hosts come from
+/// `app_config.registry_credentials` / `insecure_registries`.
+pub async fn pull_registry_model_at_root(
+    model_reference: &str,
+    models_root: String,
+    metadata_root: &str,
+    app_config: &AppConfig,
+    progress: DownloadProgressFn,
+) -> Result<ModelInfo, String> {
+    let model_ref = parse_model_reference(model_reference)?;
+    let repository = format!("{}/{}", model_ref.library, model_ref.name);
 
     // Fetch the manifest first — it describes which blobs we need to pull.
-    let manifest_url = format!(
-        "https://{}/v2/{}/manifests/{}",
-        model_ref.registry, repository, model_ref.version
-    );
-
-    let manifest_response = client
-        .get(&manifest_url)
-        .header(USER_AGENT, "llama-desktop")
-        .header(ACCEPT, MANIFEST_ACCEPT)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch manifest: {}", e))?;
-
-    if !manifest_response.status().is_success() {
-        return Err(format!(
-            "Failed to fetch manifest: HTTP {}",
-            manifest_response.status()
-        ));
-    }
-
-    let manifest: ModelManifest = manifest_response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse manifest JSON: {}", e))?;
+    // This also negotiates the token the blob downloads start out with.
+    let mut registry = RegistryClient::from_config(&model_ref.registry, app_config)?;
+    let manifest = registry
+        .fetch_manifest(&repository, &model_ref.version)
+        .await?;
+    let client = registry.blob_client(&repository)?;
 
     // Persist the manifest before downloading blobs so a crash during download
     // leaves us with a restorable state on the next launch.
@@ -1303,39 +1258,47 @@ pub async fn download_model_from_registry(
     crate::utils::save_json(&manifest_path, &manifest)?;
 
     let blobs_dir = PathBuf::from(&models_root).join("blobs");
+    let total = manifest.config.size + manifest.layers.iter().map(|l| l.size).sum::<u64>();
 
     // Config blob is usually tiny — download it first so failure here doesn't
     // waste time on a large model layer.
     download_blob(
-        &app,
         &client,
-        &model_ref.registry,
-        &repository,
+        &registry.blob_url(&repository, &manifest.config.digest),
         &manifest.config.digest,
         manifest.config.size,
         &blobs_dir,
+        &PullProgress {
+            done: 0,
+            total,
+            report: &progress,
+        },
     )
     .await?;
 
+    let mut done = manifest.config.size;
     for layer in &manifest.layers {
         download_blob(
-            &app,
             &client,
-            &model_ref.registry,
-            &repository,
+            &registry.blob_url(&repository, &layer.digest),
             &layer.digest,
             layer.size,
             &blobs_dir,
+            &PullProgress {
+                done,
+                total,
+                report: &progress,
+            },
         )
         .await?;
+        done += layer.size;
     }
 
     let manifest_path_str = manifest_path
         .to_str()
         .ok_or_else(|| "Failed to build manifest path string".to_string())?;
 
-    let metadata_root = get_metadata_root(&app)?;
-    parse_model_manifest_sync(manifest_path_str.to_string(), models_root, &metadata_root)
+    parse_model_manifest_sync(manifest_path_str.to_string(), models_root, metadata_root)
 }
 
 // ---------------------------------------------------------------------------
@@ -1401,11 +1364,7 @@ pub mod test_utils {
         let mut library: ModelLibrary = crate::utils::read_json(Path::new(&library_path))?;
         if let Ok(cache) = load_models_metadata_cache(metadata_root) {
             for model in &mut library.models {
-                if model.tokenizer_metadata.is_none() {
-                    if let Some(found) = cache.models.get(&model.full_identifier) {
-                        model.tokenizer_metadata = Some(found.clone());
-                    }
-                }
+                cache.apply_to(model);
             }
         }
         Ok(library.models)
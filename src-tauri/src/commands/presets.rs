@@ -0,0 +1,260 @@
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+
+use crate::models::{
+    AppConfig, LaunchPreset, LlamaCppConfig, ModelInfo, ModelLibrary, ModelPresets, PresetLibrary,
+    SamplingParams,
+};
+
+/// GPU layers used when neither the preset nor the caller sets them.
+pub const DEFAULT_N_GPU_LAYERS: i32 = 33;
+
+fn models_root(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::commands::config::get_config(app)?
+        .models_directory
+        .map(PathBuf::from)
+        .ok_or_else(|| "Models directory is not configured".to_string())
+}
+
+#[command]
+pub async fn get_launch_presets(app: AppHandle, model_id: String) -> Result<ModelPresets, String> {
+    let path = build_presets_path(&models_root(&app)?);
+    get_model_presets_at_path(&path, &model_id)
+}
+
+/// Creates or replaces the preset with the same name.
+#[command]
+pub async fn save_launch_preset(
+    app: AppHandle,
+    model_id: String,
+    preset: LaunchPreset,
+    make_default: Option<bool>,
+) -> Result<ModelPresets, String> {
+    let path = build_presets_path(&models_root(&app)?);
+    save_launch_preset_at_path(&path, &model_id, preset, make_default.unwrap_or(false))
+}
+
+#[command]
+pub async fn delete_launch_preset(
+    app: AppHandle,
+    model_id: String,
+    name: String,
+) -> Result<ModelPresets, String> {
+    let path = build_presets_path(&models_root(&app)?);
+    delete_launch_preset_at_path(&path, &model_id, &name)
+}
+
+/// `name: None` clears the default so launches use the app settings again.
+#[command]
+pub async fn set_default_launch_preset(
+    app: AppHandle,
+    model_id: String,
+    name: Option<String>,
+) -> Result<ModelPresets, String> {
+    let path = build_presets_path(&models_root(&app)?);
+    set_default_launch_preset_at_path(&path, &model_id, name)
+}
+
+/// Sampling defaults for chats with the model; `None` drops them. Applied the
+/// next time the model is started.
+#[command]
+pub async fn set_model_sampling(
+    app: AppHandle,
+    model_id: String,
+    sampling: Option<SamplingParams>,
+) -> Result<ModelPresets, String> {
+    let path = build_presets_path(&models_root(&app)?);
+    set_model_sampling_at_path(&path, &model_id, sampling)
+}
+
+/// Looks up a library model and its preset for `start_llama_server`.
+pub fn resolve_model_launch(
+    app: &AppHandle,
+    model_id: &str,
+    preset: Option<&str>,
+) -> Result<(ModelInfo, Option<LaunchPreset>), String> {
+    resolve_model_launch_at_root(&models_root(app)?, model_id, preset)
+}
+
+/// The sampling defaults saved for `model_id`, for `start_llama_server`.
+pub fn model_sampling(app: &AppHandle, model_id: &str) -> Result<Option<SamplingParams>, String> {
+    let path = build_presets_path(&models_root(app)?);
+    Ok(get_model_presets_at_path(&path, model_id)?.sampling)
+}
+
+pub fn build_presets_path(models_root: &Path) -> PathBuf {
+    models_root.join("launchPresets.json")
+}
+
+pub fn load_presets_from_path(path: &Path) -> Result<PresetLibrary, String> {
+    if !path.exists() {
+        return Ok(PresetLibrary::default());
+    }
+    crate::utils::read_json(path)
+}
+
+pub fn save_presets_to_path(path: &Path, library: &PresetLibrary) -> Result<(), String> {
+    crate::utils::save_json(path, library)
+}
+
+pub fn get_model_presets_at_path(path: &Path, model_id: &str) -> Result<ModelPresets, String> {
+    Ok(load_presets_from_path(path)?
+        .models
+        .remove(model_id)
+        .unwrap_or_default())
+}
+
+pub fn save_launch_preset_at_path(
+    path: &Path,
+    model_id: &str,
+    preset: LaunchPreset,
+    make_default: bool,
+) -> Result<ModelPresets, String> {
+    preset.validate()?;
+    update_model_presets(path, model_id, |presets| {
+        if make_default {
+            presets.default_preset = Some(preset.name.clone());
+        }
+        presets.upsert(preset);
+        Ok(())
+    })
+}
+
+pub fn delete_launch_preset_at_path(
+    path: &Path,
+    model_id: &str,
+    name: &str,
+) -> Result<ModelPresets, String> {
+    update_model_presets(path, model_id, |presets| {
+        if presets.remove(name) {
+            Ok(())
+        } else {
+            Err(format!("Launch preset '{}' not found", name))
+        }
+    })
+}
+
+pub fn set_default_launch_preset_at_path(
+    path: &Path,
+    model_id: &str,
+    name: Option<String>,
+) -> Result<ModelPresets, String> {
+    update_model_presets(path, model_id, |presets| {
+        if let Some(name) = &name {
+            presets.resolve(Some(name))?;
+        }
+        presets.default_preset = name;
+        Ok(())
+    })
+}
+
+pub fn set_model_sampling_at_path(
+    path: &Path,
+    model_id: &str,
+    sampling: Option<SamplingParams>,
+) -> Result<ModelPresets, String> {
+    if let Some(sampling) = &sampling {
+        sampling.validate()?;
+    }
+    update_model_presets(path, model_id, |presets| {
+        presets.sampling = sampling.filter(|s| *s != SamplingParams::default());
+        Ok(())
+    })
+}
+
+fn update_model_presets<F>(path: &Path, model_id: &str, update: F) -> Result<ModelPresets, String>
+where
+    F: FnOnce(&mut ModelPresets) -> Result<(), String>,
+{
+    let mut library = load_presets_from_path(path)?;
+    let mut presets = library.models.remove(model_id).unwrap_or_default();
+    update(&mut presets)?;
+    if presets == ModelPresets::default() {
+        // Don't leave empty entries behind for models without presets.
+        library.models.remove(model_id);
+    } else {
+        library.models.insert(model_id.to_string(), presets.clone());
+    }
+    save_presets_to_path(path, &library)?;
+    Ok(presets)
+}
+
+pub fn resolve_model_launch_at_root(
+    models_root: &Path,
+    model_id: &str,
+    preset: Option<&str>,
+) -> Result<(ModelInfo, Option<LaunchPreset>), String> {
+    let library_path = models_root.join("modelLibrary.json");
+    if !library_path.exists() {
+        return Err(format!("Model {} is not in the library", model_id));
+    }
+    let library: ModelLibrary = crate::utils::read_json(&library_path)?;
+    let model = library
+        .models
+        .into_iter()
+        .find(|m| m.full_identifier == model_id)
+        .ok_or_else(|| format!("Model {} is not in the library", model_id))?;
+
+    let presets = get_model_presets_at_path(&build_presets_path(models_root), model_id)?;
+    let preset = presets.resolve(preset)?.cloned();
+    Ok((model, preset))
+}
+
+/// Server config for a library model: preset values first, then the model's
+/// own defaults (e.g. Ollama `num_ctx`), then app settings.
+pub fn config_from_preset(
+    model: &ModelInfo,
+    preset: Option<&LaunchPreset>,
+    app_config: &AppConfig,
+) -> Result<LlamaCppConfig, String> {
+    let model_path = model
+        .model_file_path
+        .clone()
+        .ok_or_else(|| format!("Model {} has no local file", model.full_identifier))?;
+    let preset = preset.cloned();
+    let binary_path = preset
+        .as_ref()
+        .and_then(|p| p.binary_path.clone())
+        .or_else(|| app_config.llama_directory.clone())
+        .ok_or_else(|| "Llama Server path not configured".to_string())?;
+
+    let (chat_template, chat_template_file) = match &preset {
+        Some(p) if p.chat_template.is_some() || p.chat_template_file.is_some() => {
+            (p.chat_template.clone(), p.chat_template_file.clone())
+        }
+        _ => (embedded_chat_template(model), None),
+    };
+
+    Ok(LlamaCppConfig {
+        llama_cpp_path: binary_path,
+        model_path,
+        port: preset
+            .as_ref()
+            .and_then(|p| p.port)
+            .unwrap_or(app_config.server_port),
+        ctx_size: preset
+            .as_ref()
+            .and_then(|p| p.ctx_size)
+            .or(model.params.as_ref().and_then(|p| p.num_ctx))
+            .unwrap_or(app_config.context_size),
+        parallel: preset.as_ref().and_then(|p| p.parallel).unwrap_or(1),
+        n_gpu_layers: preset
+            .as_ref()
+            .and_then(|p| p.n_gpu_layers)
+            .unwrap_or(DEFAULT_N_GPU_LAYERS),
+        chat_template,
+        chat_template_file,
+        port_range: app_config.server_port_range,
+        restart_policy: preset.as_ref().and_then(|p| p.restart_policy),
+        launch_params: preset.map(|p| p.launch_params).unwrap_or_default(),
+    })
+}
+
+fn embedded_chat_template(model: &ModelInfo) -> Option<String> {
+    model
+        .tokenizer_metadata
+        .as_ref()
+        .and_then(|meta| meta.get("tokenizer.chat_template"))
+        .and_then(|value| value.as_str())
+        .map(|s| s.to_string())
+}
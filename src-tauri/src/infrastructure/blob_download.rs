@@ -0,0 +1,581 @@
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use reqwest::header::{CONTENT_RANGE, RANGE, WWW_AUTHENTICATE};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::fs as tokio_fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+// ---------------------------------------------------------------------------
+// Content-addressed blob downloads.
+//
+// Single stream: bytes go to `<blob>.partial`; a later attempt resumes it
+// with a Range request. Chunked: each connection owns `<blob>.partial.<n>`
+// so every chunk resumes on its own, and the chunks are joined into
+// `<blob>.partial` once all are complete. Either way the SHA-256 is checked
+// against the digest before the rename to the canonical path.
+// ---------------------------------------------------------------------------
+
+const PROGRESS_INTERVAL_BYTES: u64 = 1024 * 1024;
+const HASH_BUFFER_BYTES: usize = 1024 * 1024;
+
+/// Called with `(downloaded, total)`; `total` is 0 when unknown.
+pub type ProgressFn = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// Answers a 401 and returns the client to retry with. Gets the
+/// `WWW-Authenticate` header of the rejected response, if there was one.
+pub type ReauthorizeFn = Arc<
+    dyn Fn(Option<String>) -> BoxFuture<'static, Result<reqwest::Client, String>> + Send + Sync,
+>;
+
+/// HTTP client for blob requests. Registry tokens are short-lived and may
+/// expire during a long pull, so a request that gets a 401 is
+/// re-authorized once and sent again.
+#[derive(Clone)]
+pub struct BlobClient {
+    /// The client in use and how many times it has been replaced.
+    current: Arc<Mutex<(reqwest::Client, u64)>>,
+    reauthorize: Option<ReauthorizeFn>,
+    /// Parallel chunks that hit a 401 together share one new token.
+    reauthorizing: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl BlobClient {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            current: Arc::new(Mutex::new((client, 0))),
+            reauthorize: None,
+            reauthorizing: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    pub fn with_reauthorize(mut self, reauthorize: ReauthorizeFn) -> Self {
+        self.reauthorize = Some(reauthorize);
+        self
+    }
+
+    fn current(&self) -> (reqwest::Client, u64) {
+        self.current
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// GETs `url` with an optional `Range` header value.
+    async fn get(&self, url: &str, range: Option<&str>) -> Result<reqwest::Response, String> {
+        let (client, generation) = self.current();
+        let response = send_get(&client, url, range).await?;
+        let Some(reauthorize) = &self.reauthorize else {
+            return Ok(response);
+        };
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        let client = {
+            let _guard = self.reauthorizing.lock().await;
+            let (current, now) = self.current();
+            if now != generation {
+                // Another request already renewed the credentials.
+                current
+            } else {
+                let challenge = response
+                    .headers()
+                    .get(WWW_AUTHENTICATE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                let fresh = reauthorize(challenge).await?;
+                *self.current.lock().unwrap_or_else(|e| e.into_inner()) =
+                    (fresh.clone(), generation + 1);
+                fresh
+            }
+        };
+        send_get(&client, url, range).await
+    }
+}
+
+async fn send_get(
+    client: &reqwest::Client,
+    url: &str,
+    range: Option<&str>,
+) -> Result<reqwest::Response, String> {
+    let mut request = client.get(url);
+    if let Some(range) = range {
+        request = request.header(RANGE, range);
+    }
+    request.send().await.map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Connections used for blobs at or above `parallel_threshold`.
+    pub connections: usize,
+    pub parallel_threshold: u64,
+    pub max_attempts: u32,
+    /// Multiplied by the attempt number between retries.
+    pub retry_delay: Duration,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            connections: 4,
+            parallel_threshold: 256 * 1024 * 1024,
+            max_attempts: 3,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+pub fn blob_filename(digest: &str) -> String {
+    digest.replace(':', "-")
+}
+
+pub fn partial_path(blob_path: &Path) -> PathBuf {
+    blob_path.with_extension("partial")
+}
+
+fn chunk_path(blob_path: &Path, index: usize) -> PathBuf {
+    blob_path.with_extension(format!("partial.{}", index))
+}
+
+/// Downloads `url` into `blobs_dir` under the name derived from `digest`,
+/// returning the final path. An existing blob of the right size is kept.
+pub async fn download_blob(
+    client: &BlobClient,
+    url: &str,
+    digest: &str,
+    size: u64,
+    blobs_dir: &Path,
+    options: &DownloadOptions,
+    progress: ProgressFn,
+) -> Result<PathBuf, String> {
+    // Refuse before any bytes are fetched; finalize would reject it anyway.
+    expected_sha256(digest)?;
+    let blob_path = blobs_dir.join(blob_filename(digest));
+    if let Ok(metadata) = tokio_fs::metadata(&blob_path).await {
+        if size == 0 || metadata.len() == size {
+            return Ok(blob_path);
+        }
+    }
+    tokio_fs::create_dir_all(blobs_dir)
+        .await
+        .map_err(|e| format!("Failed to create blobs directory: {}", e))?;
+
+    let progress = Arc::new(Progress {
+        downloaded: AtomicU64::new(0),
+        total: size,
+        callback: progress,
+    });
+    let mut attempt = 1;
+    loop {
+        // Every attempt recounts what is already on disk.
+        progress.downloaded.store(0, Ordering::Relaxed);
+        let result = if options.connections > 1 && size >= options.parallel_threshold.max(1) {
+            download_chunked(
+                client,
+                url,
+                size,
+                &blob_path,
+                options.connections,
+                &progress,
+            )
+            .await
+        } else {
+            download_single(client, url, size, &blob_path, &progress).await
+        };
+        let result = match result {
+            Ok(hash) => finalize(&blob_path, digest, size, hash).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(()) => {
+                progress.finish();
+                return Ok(blob_path);
+            }
+            Err(err) if attempt >= options.max_attempts => {
+                return Err(format!("Failed to download blob {}: {}", digest, err));
+            }
+            Err(err) => {
+                eprintln!(
+                    "[download] Blob {} attempt {} failed: {}",
+                    digest, attempt, err
+                );
+                tokio::time::sleep(options.retry_delay * attempt).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// The hex part of a `sha256:<hex>` digest. Any other algorithm, or none,
+/// is an error: the blob could not be verified.
+fn expected_sha256(digest: &str) -> Result<&str, String> {
+    match digest.split_once(':') {
+        Some(("sha256", hex)) => Ok(hex),
+        Some((algorithm, _)) => Err(format!(
+            "unsupported digest algorithm {:?} in {}",
+            algorithm, digest
+        )),
+        None => Err(format!("digest {:?} has no algorithm prefix", digest)),
+    }
+}
+
+/// Checks size and digest of `<blob>.partial`, then moves it into place.
+/// A mismatching file is deleted so the next attempt starts clean.
+async fn finalize(blob_path: &Path, digest: &str, size: u64, hash: String) -> Result<(), String> {
+    let expected = expected_sha256(digest)?;
+    let tmp_path = partial_path(blob_path);
+    let actual_size = tokio_fs::metadata(&tmp_path)
+        .await
+        .map_err(|e| format!("Failed to stat partial file: {}", e))?
+        .len();
+    if size > 0 && actual_size != size {
+        let _ = tokio_fs::remove_file(&tmp_path).await;
+        return Err(format!(
+            "size mismatch (expected {}, got {})",
+            size, actual_size
+        ));
+    }
+    if !expected.eq_ignore_ascii_case(&hash) {
+        let _ = tokio_fs::remove_file(&tmp_path).await;
+        return Err(format!(
+            "digest mismatch (expected sha256:{}, got sha256:{})",
+            expected, hash
+        ));
+    }
+
+    if tokio_fs::metadata(blob_path).await.is_ok() {
+        tokio_fs::remove_file(blob_path)
+            .await
+            .map_err(|e| format!("Failed to remove stale blob: {}", e))?;
+    }
+    tokio_fs::rename(&tmp_path, blob_path)
+        .await
+        .map_err(|e| format!("Failed to finalise blob: {}", e))
+}
+
+/// Streams into `<blob>.partial`, resuming from its current length.
+/// Returns the hex SHA-256 of the whole file.
+async fn download_single(
+    client: &BlobClient,
+    url: &str,
+    size: u64,
+    blob_path: &Path,
+    progress: &Progress,
+) -> Result<String, String> {
+    let tmp_path = partial_path(blob_path);
+    let mut existing = file_len(&tmp_path).await;
+    if size > 0 && existing > size {
+        truncate(&tmp_path, 0).await?;
+        existing = 0;
+    }
+
+    let mut hasher = Sha256::new();
+    if size > 0 && existing == size {
+        hash_file_into(&tmp_path, &mut hasher).await?;
+        progress.add(existing);
+        return Ok(hex(hasher));
+    }
+
+    let range = (existing > 0).then(|| format!("bytes={}-", existing));
+    let response = client.get(url, range.as_deref()).await?;
+    let append = match response.status() {
+        StatusCode::PARTIAL_CONTENT if existing > 0 => {
+            check_range_start(&response, existing)?;
+            true
+        }
+        // The server ignored the Range header and sent the whole blob.
+        status if status.is_success() => false,
+        StatusCode::RANGE_NOT_SATISFIABLE => {
+            truncate(&tmp_path, 0).await?;
+            return Err("server rejected resume range".to_string());
+        }
+        status => return Err(format!("HTTP {}", status)),
+    };
+
+    if append {
+        hash_file_into(&tmp_path, &mut hasher).await?;
+        progress.add(existing);
+    }
+    let mut file = open_for_write(&tmp_path, append).await?;
+    write_body(response, &mut file, Some(&mut hasher), progress, None).await?;
+    Ok(hex(hasher))
+}
+
+/// Downloads `size` bytes as `connections` ranges in parallel, each into its
+/// own resumable chunk file, then joins them into `<blob>.partial`. Falls back
+/// to `download_single` when the server ignores the Range header; the other
+/// chunks are cancelled as soon as one gets the whole blob back.
+async fn download_chunked(
+    client: &BlobClient,
+    url: &str,
+    size: u64,
+    blob_path: &Path,
+    connections: usize,
+    progress: &Arc<Progress>,
+) -> Result<String, String> {
+    let ranges = split_ranges(size, connections as u64);
+    let mut tasks: FuturesUnordered<_> = ranges
+        .iter()
+        .enumerate()
+        .map(|(index, &(start, end))| {
+            let client = client.clone();
+            let url = url.to_string();
+            let path = chunk_path(blob_path, index);
+            let progress = progress.clone();
+            async move { download_chunk(&client, &url, &path, start, end, &progress).await }
+        })
+        .collect();
+    let mut ranged = true;
+    while let Some(result) = tasks.next().await {
+        if !result? {
+            ranged = false;
+            break;
+        }
+    }
+    // Dropping the set cancels whatever is still in flight.
+    drop(tasks);
+    if !ranged {
+        for index in 0..ranges.len() {
+            let _ = tokio_fs::remove_file(chunk_path(blob_path, index)).await;
+        }
+        progress.downloaded.store(0, Ordering::Relaxed);
+        return download_single(client, url, size, blob_path, progress).await;
+    }
+
+    let tmp_path = partial_path(blob_path);
+    let mut output = open_for_write(&tmp_path, false).await?;
+    let mut hasher = Sha256::new();
+    for index in 0..ranges.len() {
+        let path = chunk_path(blob_path, index);
+        copy_hashing(&path, &mut output, &mut hasher).await?;
+    }
+    output
+        .flush()
+        .await
+        .map_err(|e| format!("Failed to flush partial file: {}", e))?;
+    for index in 0..ranges.len() {
+        let _ = tokio_fs::remove_file(chunk_path(blob_path, index)).await;
+    }
+    Ok(hex(hasher))
+}
+
+/// Fetches `start..=end` into `path`. `Ok(false)` when the server answered
+/// with the whole blob instead of the range; the body is left unread.
+async fn download_chunk(
+    client: &BlobClient,
+    url: &str,
+    path: &Path,
+    start: u64,
+    end: u64,
+    progress: &Progress,
+) -> Result<bool, String> {
+    let expected = end - start + 1;
+    let mut existing = file_len(path).await;
+    if existing > expected {
+        truncate(path, 0).await?;
+        existing = 0;
+    }
+    progress.add(existing);
+    if existing == expected {
+        return Ok(true);
+    }
+
+    let from = start + existing;
+    let range = format!("bytes={}-{}", from, end);
+    let response = client.get(url, Some(&range)).await?;
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT => {}
+        status if status.is_success() => return Ok(false),
+        status => return Err(format!("HTTP {}", status)),
+    }
+    check_range_start(&response, from)?;
+
+    let mut file = open_for_write(path, true).await?;
+    write_body(
+        response,
+        &mut file,
+        None,
+        progress,
+        Some(expected - existing),
+    )
+    .await?;
+    Ok(true)
+}
+
+/// Splits `[0, size)` into at most `parts` inclusive byte ranges.
+pub fn split_ranges(size: u64, parts: u64) -> Vec<(u64, u64)> {
+    if size == 0 {
+        return Vec::new();
+    }
+    let parts = parts.clamp(1, size);
+    let chunk = size.div_ceil(parts);
+    (0..parts)
+        .map(|i| (i * chunk, ((i + 1) * chunk).min(size) - 1))
+        .filter(|(start, end)| start <= end)
+        .collect()
+}
+
+/// A 206 must start where we asked; anything else would corrupt the file.
+pub(crate) fn check_range_start(response: &reqwest::Response, expected: u64) -> Result<(), String> {
+    let start = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("bytes "))
+        .and_then(|v| v.split('-').next())
+        .and_then(|v| v.parse::<u64>().ok());
+    match start {
+        Some(start) if start != expected => Err(format!(
+            "server returned range starting at {} instead of {}",
+            start, expected
+        )),
+        _ => Ok(()),
+    }
+}
+
+async fn write_body(
+    response: reqwest::Response,
+    file: &mut tokio_fs::File,
+    mut hasher: Option<&mut Sha256>,
+    progress: &Progress,
+    limit: Option<u64>,
+) -> Result<(), String> {
+    let mut written = 0u64;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let bytes = chunk.map_err(|e| format!("Connection error: {}", e))?;
+        if limit.is_some_and(|limit| written + bytes.len() as u64 > limit) {
+            return Err("server sent more bytes than requested".to_string());
+        }
+        if let Some(hasher) = hasher.as_deref_mut() {
+            hasher.update(&bytes);
+        }
+        file.write_all(&bytes)
+            .await
+            .map_err(|e| format!("Failed to write partial file: {}", e))?;
+        written += bytes.len() as u64;
+        progress.add(bytes.len() as u64);
+    }
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to flush partial file: {}", e))?;
+    if limit.is_some_and(|limit| written != limit) {
+        return Err("connection closed before the range was complete".to_string());
+    }
+    Ok(())
+}
+
+async fn file_len(path: &Path) -> u64 {
+    tokio_fs::metadata(path).await.map(|m| m.len()).unwrap_or(0)
+}
+
+async fn truncate(path: &Path, len: u64) -> Result<(), String> {
+    let file = open_for_write(path, true).await?;
+    file.set_len(len)
+        .await
+        .map_err(|e| format!("Failed to truncate {}: {}", path.display(), e))
+}
+
+async fn open_for_write(path: &Path, append: bool) -> Result<tokio_fs::File, String> {
+    let mut options = tokio_fs::OpenOptions::new();
+    options.create(true);
+    if append {
+        options.append(true);
+    } else {
+        options.write(true).truncate(true);
+    }
+    options
+        .open(path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+pub(crate) async fn hash_file_into(path: &Path, hasher: &mut Sha256) -> Result<(), String> {
+    let mut file = tokio_fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut buf = vec![0u8; HASH_BUFFER_BYTES];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            return Ok(());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+async fn copy_hashing(
+    path: &Path,
+    output: &mut tokio_fs::File,
+    hasher: &mut Sha256,
+) -> Result<(), String> {
+    let mut file = tokio_fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut buf = vec![0u8; HASH_BUFFER_BYTES];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            return Ok(());
+        }
+        hasher.update(&buf[..n]);
+        output
+            .write_all(&buf[..n])
+            .await
+            .map_err(|e| format!("Failed to write partial file: {}", e))?;
+    }
+}
+
+/// Hex SHA-256 of a file on disk, streamed.
+pub async fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    hash_file_into(path, &mut hasher).await?;
+    Ok(hex(hasher))
+}
+
+fn hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Byte counter shared by all connections of one blob. The callback fires
+/// roughly every `PROGRESS_INTERVAL_BYTES` and once at the end.
+struct Progress {
+    downloaded: AtomicU64,
+    total: u64,
+    callback: ProgressFn,
+}
+
+impl Progress {
+    fn add(&self, bytes: u64) {
+        if bytes == 0 {
+            return;
+        }
+        let before = self.downloaded.fetch_add(bytes, Ordering::Relaxed);
+        let after = before + bytes;
+        if after / PROGRESS_INTERVAL_BYTES != before / PROGRESS_INTERVAL_BYTES {
+            (self.callback)(after, self.total);
+        }
+    }
+
+    fn finish(&self) {
+        let downloaded = self.downloaded.load(Ordering::Relaxed);
+        (self.callback)(downloaded.max(self.total), self.total);
+    }
+}
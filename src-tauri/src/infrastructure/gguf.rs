@@ -0,0 +1,462 @@
+use crate::models::{GgufInfo, GgufTensorInfo};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+// ---------------------------------------------------------------------------
+// GGUF header reader: metadata key/values and the tensor info table.
+// Tensor data itself is never read.
+// ---------------------------------------------------------------------------
+
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+const GGUF_DEFAULT_ALIGNMENT: u64 = 32;
+
+const GGUF_VALUE_TYPE_UINT8: u32 = 0;
+const GGUF_VALUE_TYPE_INT8: u32 = 1;
+const GGUF_VALUE_TYPE_UINT16: u32 = 2;
+const GGUF_VALUE_TYPE_INT16: u32 = 3;
+const GGUF_VALUE_TYPE_UINT32: u32 = 4;
+const GGUF_VALUE_TYPE_INT32: u32 = 5;
+const GGUF_VALUE_TYPE_FLOAT32: u32 = 6;
+const GGUF_VALUE_TYPE_BOOL: u32 = 7;
+const GGUF_VALUE_TYPE_STRING: u32 = 8;
+const GGUF_VALUE_TYPE_ARRAY: u32 = 9;
+const GGUF_VALUE_TYPE_UINT64: u32 = 10;
+const GGUF_VALUE_TYPE_INT64: u32 = 11;
+const GGUF_VALUE_TYPE_FLOAT64: u32 = 12;
+
+// Guards against allocating from a corrupt length field.
+const MAX_STRING_LEN: u64 = 64 * 1024 * 1024;
+const MAX_TENSOR_DIMS: u32 = 8;
+
+/// Vocabulary-sized arrays are skipped rather than decoded; nothing in the app
+/// reads them and they dominate parse time.
+const SKIPPED_KEYS: &[&str] = &[
+    "tokenizer.ggml.tokens",
+    "tokenizer.ggml.token_type",
+    "tokenizer.ggml.merges",
+];
+
+/// Everything read from a GGUF file before the tensor data section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GgufHeader {
+    pub version: u32,
+    pub metadata: Map<String, Value>,
+    pub tensors: Vec<GgufTensorInfo>,
+    pub data_offset: u64,
+}
+
+impl GgufHeader {
+    pub fn architecture(&self) -> Option<&str> {
+        self.metadata
+            .get("general.architecture")
+            .and_then(Value::as_str)
+    }
+
+    /// `tokenizer.*` keys, minus the skipped vocabulary arrays.
+    pub fn tokenizer_metadata(&self) -> Map<String, Value> {
+        self.metadata
+            .iter()
+            .filter(|(key, _)| key.starts_with("tokenizer."))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    pub fn info(&self) -> GgufInfo {
+        let arch = self.architecture().map(|s| s.to_string());
+        let arch_key = |suffix: &str| format!("{}.{}", arch.as_deref().unwrap_or(""), suffix);
+        let get = |key: &str| self.metadata.get(key);
+        let get_u64 = |key: &str| get(key).and_then(lenient_u64);
+        let get_f64 = |key: &str| get(key).and_then(Value::as_f64);
+
+        let file_type = get_u64("general.file_type").map(|v| v as u32);
+        let quantization = file_type
+            .and_then(file_type_label)
+            .map(|s| s.to_string())
+            .or_else(|| dominant_dtype(&self.tensors));
+        let parameter_count = get_u64("general.parameter_count").or_else(|| {
+            (!self.tensors.is_empty())
+                .then(|| self.tensors.iter().map(GgufTensorInfo::element_count).sum())
+        });
+        let metadata = self
+            .metadata
+            .iter()
+            .filter(|(key, _)| {
+                key.starts_with("general.")
+                    || arch
+                        .as_deref()
+                        .is_some_and(|a| key.strip_prefix(a).is_some_and(|r| r.starts_with('.')))
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        GgufInfo {
+            version: self.version,
+            architecture: arch.clone(),
+            name: get("general.name")
+                .and_then(Value::as_str)
+                .map(|s| s.to_string()),
+            parameter_count,
+            block_count: get_u64(&arch_key("block_count")),
+            embedding_length: get_u64(&arch_key("embedding_length")),
+            feed_forward_length: get_u64(&arch_key("feed_forward_length")),
+            head_count: get_u64(&arch_key("attention.head_count")),
+            head_count_kv: get_u64(&arch_key("attention.head_count_kv")),
+            key_length: get_u64(&arch_key("attention.key_length")),
+            value_length: get_u64(&arch_key("attention.value_length")),
+            context_length: get_u64(&arch_key("context_length")),
+            rope_freq_base: get_f64(&arch_key("rope.freq_base")),
+            rope_dimension_count: get_u64(&arch_key("rope.dimension_count")),
+            rope_scaling_type: get(&arch_key("rope.scaling.type"))
+                .and_then(Value::as_str)
+                .map(|s| s.to_string()),
+            rope_scaling_factor: get_f64(&arch_key("rope.scaling.factor")),
+            file_type,
+            quantization,
+            data_offset: self.data_offset,
+            tensor_bytes: self.tensors.iter().filter_map(|t| t.size_bytes).sum(),
+            tensors: self.tensors.clone(),
+            metadata,
+        }
+    }
+}
+
+pub fn read_gguf_file(path: &Path) -> Result<GgufHeader, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open model file {}: {}", path.display(), e))?;
+    read_gguf(BufReader::new(file))
+}
+
+pub fn read_gguf_info(path: &Path) -> Result<GgufInfo, String> {
+    Ok(read_gguf_file(path)?.info())
+}
+
+pub fn read_gguf<R: Read>(reader: R) -> Result<GgufHeader, String> {
+    let mut reader = CountingReader {
+        inner: reader,
+        position: 0,
+    };
+
+    let magic = reader.bytes(4)?;
+    if magic.as_slice() != GGUF_MAGIC {
+        return Err("Not a GGUF file".to_string());
+    }
+    let version = reader.u32()?;
+    if version < 2 {
+        return Err(format!("Unsupported GGUF version {}", version));
+    }
+    let tensor_count = reader.u64()?;
+    let kv_count = reader.u64()?;
+
+    let mut metadata = Map::new();
+    for _ in 0..kv_count {
+        let key = reader.string()?;
+        let value_type = reader.u32()?;
+        if SKIPPED_KEYS.contains(&key.as_str()) {
+            reader.skip_value(value_type)?;
+            continue;
+        }
+        let value = reader.value(value_type)?;
+        metadata.insert(key, value);
+    }
+
+    let mut tensors = Vec::with_capacity(tensor_count.min(4096) as usize);
+    // Checked here so `GgufHeader::info` can sum counts and sizes freely.
+    let mut total_elements: u64 = 0;
+    let mut total_bytes: u64 = 0;
+    for _ in 0..tensor_count {
+        let name = reader.string()?;
+        let n_dims = reader.u32()?;
+        if n_dims > MAX_TENSOR_DIMS {
+            return Err(format!("Tensor {} has {} dimensions", name, n_dims));
+        }
+        let dims = (0..n_dims)
+            .map(|_| reader.u64())
+            .collect::<Result<Vec<_>, _>>()?;
+        let type_id = reader.u32()?;
+        let offset = reader.u64()?;
+        let elements = dims
+            .iter()
+            .try_fold(1u64, |acc, &dim| acc.checked_mul(dim))
+            .ok_or_else(|| format!("Tensor {} has too many elements", name))?;
+        let size_bytes = ggml_tensor_size(type_id, elements);
+        total_elements = total_elements
+            .checked_add(elements)
+            .ok_or_else(|| "Tensor element count overflows".to_string())?;
+        total_bytes = total_bytes
+            .checked_add(size_bytes.unwrap_or(0))
+            .ok_or_else(|| "Tensor data size overflows".to_string())?;
+        tensors.push(GgufTensorInfo {
+            name,
+            dims,
+            dtype: ggml_type_name(type_id),
+            offset,
+            size_bytes,
+        });
+    }
+
+    let alignment = metadata
+        .get("general.alignment")
+        .and_then(Value::as_u64)
+        .filter(|a| *a > 0)
+        .unwrap_or(GGUF_DEFAULT_ALIGNMENT);
+    let data_offset = reader.position.div_ceil(alignment) * alignment;
+
+    Ok(GgufHeader {
+        version,
+        metadata,
+        tensors,
+        data_offset,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// ggml types
+// ---------------------------------------------------------------------------
+
+/// (name, elements per block, bytes per block), indexed by ggml type id.
+fn ggml_type_layout(type_id: u32) -> Option<(&'static str, u64, u64)> {
+    Some(match type_id {
+        0 => ("F32", 1, 4),
+        1 => ("F16", 1, 2),
+        2 => ("Q4_0", 32, 18),
+        3 => ("Q4_1", 32, 20),
+        6 => ("Q5_0", 32, 22),
+        7 => ("Q5_1", 32, 24),
+        8 => ("Q8_0", 32, 34),
+        9 => ("Q8_1", 32, 36),
+        10 => ("Q2_K", 256, 84),
+        11 => ("Q3_K", 256, 110),
+        12 => ("Q4_K", 256, 144),
+        13 => ("Q5_K", 256, 176),
+        14 => ("Q6_K", 256, 210),
+        15 => ("Q8_K", 256, 292),
+        16 => ("IQ2_XXS", 256, 66),
+        17 => ("IQ2_XS", 256, 74),
+        18 => ("IQ3_XXS", 256, 98),
+        19 => ("IQ1_S", 256, 50),
+        20 => ("IQ4_NL", 32, 18),
+        21 => ("IQ3_S", 256, 110),
+        22 => ("IQ2_S", 256, 82),
+        23 => ("IQ4_XS", 256, 136),
+        24 => ("I8", 1, 1),
+        25 => ("I16", 1, 2),
+        26 => ("I32", 1, 4),
+        27 => ("I64", 1, 8),
+        28 => ("F64", 1, 8),
+        29 => ("IQ1_M", 256, 56),
+        30 => ("BF16", 1, 2),
+        34 => ("TQ1_0", 256, 54),
+        35 => ("TQ2_0", 256, 66),
+        39 => ("MXFP4", 32, 17),
+        _ => return None,
+    })
+}
+
+pub fn ggml_type_name(type_id: u32) -> String {
+    ggml_type_layout(type_id)
+        .map(|(name, _, _)| name.to_string())
+        .unwrap_or_else(|| format!("TYPE_{}", type_id))
+}
+
+pub fn ggml_tensor_size(type_id: u32, elements: u64) -> Option<u64> {
+    let (_, block, bytes) = ggml_type_layout(type_id)?;
+    elements
+        .is_multiple_of(block)
+        .then(|| (elements / block).checked_mul(bytes))
+        .flatten()
+}
+
+/// Label for `general.file_type` (llama.cpp's `llama_ftype`).
+pub fn file_type_label(file_type: u32) -> Option<&'static str> {
+    Some(match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        19 => "IQ2_XXS",
+        20 => "IQ2_XS",
+        21 => "Q2_K_S",
+        22 => "IQ3_XS",
+        23 => "IQ3_XXS",
+        24 => "IQ1_S",
+        25 => "IQ4_NL",
+        26 => "IQ3_S",
+        27 => "IQ3_M",
+        28 => "IQ2_S",
+        29 => "IQ2_M",
+        30 => "IQ4_XS",
+        31 => "IQ1_M",
+        32 => "BF16",
+        36 => "TQ1_0",
+        37 => "TQ2_0",
+        38 => "MXFP4_MOE",
+        _ => return None,
+    })
+}
+
+/// The tensor type holding the most bytes, for files without `general.file_type`.
+fn dominant_dtype(tensors: &[GgufTensorInfo]) -> Option<String> {
+    let mut bytes_by_type: HashMap<&str, u64> = HashMap::new();
+    for tensor in tensors {
+        *bytes_by_type.entry(&tensor.dtype).or_default() += tensor.size_bytes.unwrap_or(0);
+    }
+    bytes_by_type
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(dtype, _)| dtype.to_string())
+}
+
+/// Some architectures store per-layer arrays (e.g. head counts); the
+/// largest entry is the useful single value.
+fn lenient_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Array(items) => items.iter().filter_map(Value::as_u64).max(),
+        other => other.as_u64(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Primitive readers
+// ---------------------------------------------------------------------------
+
+struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R: Read> CountingReader<R> {
+    fn fill(&mut self, buf: &mut [u8], what: &str) -> Result<(), String> {
+        self.inner
+            .read_exact(buf)
+            .map_err(|e| format!("Failed to read {}: {}", what, e))?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+
+    fn array<const N: usize>(&mut self, what: &str) -> Result<[u8; N], String> {
+        let mut buf = [0u8; N];
+        self.fill(&mut buf, what)?;
+        Ok(buf)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<Vec<u8>, String> {
+        let mut buf = vec![0u8; len];
+        self.fill(&mut buf, "bytes")?;
+        Ok(buf)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array("u32")?))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.array("u64")?))
+    }
+
+    fn string_len(&mut self) -> Result<u64, String> {
+        let len = self.u64()?;
+        if len > MAX_STRING_LEN {
+            return Err(format!("String length {} is out of range", len));
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.string_len()? as usize;
+        let bytes = self.bytes(len)?;
+        String::from_utf8(bytes).map_err(|e| format!("Failed to parse string: {}", e))
+    }
+
+    fn skip(&mut self, len: u64) -> Result<(), String> {
+        let copied = std::io::copy(&mut (&mut self.inner).take(len), &mut std::io::sink())
+            .map_err(|e| format!("Failed to skip bytes: {}", e))?;
+        if copied != len {
+            return Err("Unexpected end of file".to_string());
+        }
+        self.position += len;
+        Ok(())
+    }
+
+    fn value(&mut self, value_type: u32) -> Result<Value, String> {
+        let number = |n: serde_json::Number| Ok(Value::Number(n));
+        match value_type {
+            GGUF_VALUE_TYPE_UINT8 => number(self.array::<1>("u8")?[0].into()),
+            GGUF_VALUE_TYPE_INT8 => number((self.array::<1>("i8")?[0] as i8).into()),
+            GGUF_VALUE_TYPE_UINT16 => number(u16::from_le_bytes(self.array("u16")?).into()),
+            GGUF_VALUE_TYPE_INT16 => number(i16::from_le_bytes(self.array("i16")?).into()),
+            GGUF_VALUE_TYPE_UINT32 => number(self.u32()?.into()),
+            GGUF_VALUE_TYPE_INT32 => number(i32::from_le_bytes(self.array("i32")?).into()),
+            GGUF_VALUE_TYPE_UINT64 => number(self.u64()?.into()),
+            GGUF_VALUE_TYPE_INT64 => number(i64::from_le_bytes(self.array("i64")?).into()),
+            GGUF_VALUE_TYPE_FLOAT32 => {
+                let value = f32::from_le_bytes(self.array("f32")?);
+                Ok(float_value(value as f64))
+            }
+            GGUF_VALUE_TYPE_FLOAT64 => Ok(float_value(f64::from_le_bytes(self.array("f64")?))),
+            GGUF_VALUE_TYPE_BOOL => Ok(Value::Bool(self.array::<1>("bool")?[0] != 0)),
+            GGUF_VALUE_TYPE_STRING => Ok(Value::String(self.string()?)),
+            GGUF_VALUE_TYPE_ARRAY => {
+                let element_type = self.u32()?;
+                let len = self.u64()?;
+                let mut values = Vec::with_capacity(len.min(4096) as usize);
+                for _ in 0..len {
+                    values.push(self.value(element_type)?);
+                }
+                Ok(Value::Array(values))
+            }
+            other => Err(format!("Unsupported GGUF value type: {}", other)),
+        }
+    }
+
+    fn skip_value(&mut self, value_type: u32) -> Result<(), String> {
+        match value_type {
+            GGUF_VALUE_TYPE_STRING => {
+                let len = self.string_len()?;
+                self.skip(len)
+            }
+            GGUF_VALUE_TYPE_ARRAY => {
+                let element_type = self.u32()?;
+                let len = self.u64()?;
+                match fixed_value_size(element_type) {
+                    Some(size) => self.skip(len.saturating_mul(size)),
+                    None => (0..len).try_for_each(|_| self.skip_value(element_type)),
+                }
+            }
+            other => match fixed_value_size(other) {
+                Some(size) => self.skip(size),
+                None => Err(format!("Unsupported GGUF value type: {}", other)),
+            },
+        }
+    }
+}
+
+fn fixed_value_size(value_type: u32) -> Option<u64> {
+    match value_type {
+        GGUF_VALUE_TYPE_UINT8 | GGUF_VALUE_TYPE_INT8 | GGUF_VALUE_TYPE_BOOL => Some(1),
+        GGUF_VALUE_TYPE_UINT16 | GGUF_VALUE_TYPE_INT16 => Some(2),
+        GGUF_VALUE_TYPE_UINT32 | GGUF_VALUE_TYPE_INT32 | GGUF_VALUE_TYPE_FLOAT32 => Some(4),
+        GGUF_VALUE_TYPE_UINT64 | GGUF_VALUE_TYPE_INT64 | GGUF_VALUE_TYPE_FLOAT64 => Some(8),
+        _ => None,
+    }
+}
+
+/// NaN and infinities have no JSON form; they become `null`.
+fn float_value(value: f64) -> Value {
+    serde_json::Number::from_f64(value)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
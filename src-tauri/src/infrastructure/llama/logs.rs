@@ -0,0 +1,85 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::broadcast;
+
+use crate::models::{LogStream, ModelId, ServerLogLine};
+
+pub const DEFAULT_LOG_CAPACITY: usize = 500;
+
+/// Last `capacity` lines of llama-server output per model, plus a broadcast
+/// feed for live subscribers.
+pub struct ServerLogs {
+    capacity: usize,
+    buffers: Mutex<HashMap<ModelId, VecDeque<ServerLogLine>>>,
+    sender: broadcast::Sender<ServerLogLine>,
+}
+
+impl ServerLogs {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(256);
+        Self {
+            capacity: capacity.max(1),
+            buffers: Mutex::new(HashMap::new()),
+            sender,
+        }
+    }
+
+    pub fn push(&self, model_id: &ModelId, stream: LogStream, line: &str) {
+        let entry = ServerLogLine {
+            model_id: model_id.clone(),
+            stream,
+            line: line.trim_end_matches(['\r', '\n']).to_string(),
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        };
+
+        if let Ok(mut buffers) = self.buffers.lock() {
+            let buffer = buffers.entry(model_id.clone()).or_default();
+            if buffer.len() == self.capacity {
+                buffer.pop_front();
+            }
+            buffer.push_back(entry.clone());
+        }
+        // No receivers is the common case; nothing to report.
+        let _ = self.sender.send(entry);
+    }
+
+    /// Oldest-first; `limit` keeps only the most recent lines.
+    pub fn tail(&self, model_id: &ModelId, limit: Option<usize>) -> Vec<ServerLogLine> {
+        let Ok(buffers) = self.buffers.lock() else {
+            return Vec::new();
+        };
+        let Some(buffer) = buffers.get(model_id) else {
+            return Vec::new();
+        };
+        let skip = limit.map_or(0, |limit| buffer.len().saturating_sub(limit));
+        buffer.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn contains(&self, model_id: &ModelId) -> bool {
+        self.buffers
+            .lock()
+            .map(|buffers| buffers.contains_key(model_id))
+            .unwrap_or(false)
+    }
+
+    pub fn clear(&self, model_id: &ModelId) {
+        if let Ok(mut buffers) = self.buffers.lock() {
+            buffers.remove(model_id);
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerLogLine> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for ServerLogs {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_CAPACITY)
+    }
+}
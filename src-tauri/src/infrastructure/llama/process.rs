@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::process::ExitStatus;
 use std::sync::Mutex;
 
 use tokio::process::Child;
@@ -9,6 +10,8 @@ pub trait ProcessManager: Send + Sync {
     fn register(&self, model_id: ModelId, child: Child);
     fn get_pid(&self, model_id: &ModelId) -> Option<u32>;
     fn remove(&self, model_id: &ModelId) -> Option<Child>;
+    /// Exit status if the child has terminated; the child stays registered.
+    fn try_wait(&self, model_id: &ModelId) -> Option<ExitStatus>;
 }
 
 pub struct LlamaProcessManager {
@@ -39,6 +42,11 @@ impl ProcessManager for LlamaProcessManager {
         let mut children = self.children.lock().ok()?;
         children.remove(model_id)
     }
+
+    fn try_wait(&self, model_id: &ModelId) -> Option<ExitStatus> {
+        let mut children = self.children.lock().ok()?;
+        children.get_mut(model_id)?.try_wait().ok().flatten()
+    }
 }
 
 impl LlamaProcessManager {
@@ -0,0 +1,132 @@
+use tokio::sync::mpsc;
+
+use crate::models::{LoadPhase, LoadProgress, ModelId};
+
+pub type ProgressSender = mpsc::UnboundedSender<LoadProgress>;
+
+/// What a single llama-server output line tells us about loading.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadSignal {
+    Phase(LoadPhase),
+    Offloaded { layers: u32, total: u32 },
+    ContextSize(u32),
+}
+
+/// Recognizes the log lines llama-server prints while loading. Both the current
+/// (`load_tensors:`, `llama_context:`) and older (`llm_load_tensors:`,
+/// `llama_new_context_with_model:`) prefixes are matched.
+pub fn parse_load_line(line: &str) -> Option<LoadSignal> {
+    let line = line.trim();
+
+    if let Some(rest) = line.split("offloaded ").nth(1) {
+        if line.contains("layers to GPU") {
+            let counts = rest.split_whitespace().next()?;
+            let (layers, total) = counts.split_once('/')?;
+            return Some(LoadSignal::Offloaded {
+                layers: layers.parse().ok()?,
+                total: total.parse().ok()?,
+            });
+        }
+    }
+
+    if line.starts_with("llama_context:") || line.starts_with("llama_new_context_with_model:") {
+        if let Some(value) = line.split("n_ctx").nth(1).and_then(|rest| {
+            let rest = rest.trim_start();
+            rest.strip_prefix('=')
+                .and_then(|v| v.split_whitespace().next())
+                .and_then(|v| v.parse::<u32>().ok())
+        }) {
+            return Some(LoadSignal::ContextSize(value));
+        }
+        return Some(LoadSignal::Phase(LoadPhase::AllocatingContext));
+    }
+
+    if line.contains("server is listening") {
+        return Some(LoadSignal::Phase(LoadPhase::Listening));
+    }
+    if line.contains("warming up the model") {
+        return Some(LoadSignal::Phase(LoadPhase::WarmingUp));
+    }
+    if line.contains("kv_cache") || line.contains("KV self size") {
+        return Some(LoadSignal::Phase(LoadPhase::AllocatingContext));
+    }
+    if line.starts_with("load_tensors:") || line.starts_with("llm_load_tensors:") {
+        return Some(LoadSignal::Phase(LoadPhase::LoadingTensors));
+    }
+    if line.starts_with("llama_model_loader:") || line.starts_with("print_info:") {
+        return Some(LoadSignal::Phase(LoadPhase::LoadingMetadata));
+    }
+    None
+}
+
+/// `/health` answers 503 with a "Loading model" error until the model is ready.
+pub fn is_loading_health_body(body: &str) -> bool {
+    body.to_lowercase().contains("loading model")
+}
+
+/// Folds signals into a monotonic progress state and reports only changes.
+pub struct LoadProgressTracker {
+    model_id: ModelId,
+    phase: LoadPhase,
+    n_ctx: Option<u32>,
+    layers_offloaded: Option<u32>,
+    layers_total: Option<u32>,
+}
+
+impl LoadProgressTracker {
+    pub fn new(model_id: ModelId) -> Self {
+        Self {
+            model_id,
+            phase: LoadPhase::Spawning,
+            n_ctx: None,
+            layers_offloaded: None,
+            layers_total: None,
+        }
+    }
+
+    pub fn snapshot(&self) -> LoadProgress {
+        LoadProgress {
+            model_id: self.model_id.clone(),
+            phase: self.phase,
+            percent: self.phase.percent(),
+            n_ctx: self.n_ctx,
+            layers_offloaded: self.layers_offloaded,
+            layers_total: self.layers_total,
+        }
+    }
+
+    pub fn observe_line(&mut self, line: &str) -> Option<LoadProgress> {
+        match parse_load_line(line)? {
+            LoadSignal::Phase(phase) => self.advance(phase),
+            LoadSignal::Offloaded { layers, total } => {
+                self.layers_offloaded = Some(layers);
+                self.layers_total = Some(total);
+                self.advance(LoadPhase::LoadingTensors);
+                Some(self.snapshot())
+            }
+            LoadSignal::ContextSize(n_ctx) => {
+                self.n_ctx = Some(n_ctx);
+                self.advance(LoadPhase::AllocatingContext);
+                Some(self.snapshot())
+            }
+        }
+    }
+
+    pub fn observe_health_loading(&mut self) -> Option<LoadProgress> {
+        self.advance(LoadPhase::LoadingModel)
+    }
+
+    pub fn ready(&mut self) -> LoadProgress {
+        self.phase = LoadPhase::Ready;
+        self.snapshot()
+    }
+
+    /// Phases only move forward; a late 503 never pulls progress back.
+    fn advance(&mut self, phase: LoadPhase) -> Option<LoadProgress> {
+        if phase <= self.phase {
+            return None;
+        }
+        self.phase = phase;
+        Some(self.snapshot())
+    }
+}
@@ -1,18 +1,55 @@
-use crate::models::{ChatRequest, LlamaCppConfig, ModelInfo};
+use crate::infrastructure::llama::logs::ServerLogs;
+use crate::infrastructure::llama::progress::{
+    is_loading_health_body, LoadProgressTracker, ProgressSender,
+};
+use crate::models::{
+    ChatRequest, ChatStreamEvent, LlamaCppConfig, LlamaServerError, LoadProgress, LogStream, ModelId, ModelInfo,
+};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
 use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
+
+/// Lines of output attached to an early-exit error.
+const EXIT_LOG_TAIL_LINES: usize = 20;
+
+/// Called with every output line, in addition to it being logged.
+type LineHook = Arc<dyn Fn(&str) + Send + Sync>;
+
 pub struct LlamaServer;
 
+/// The output readers outlive `spawn`, so the progress sender is dropped
+/// explicitly on return to close the subscriber's stream.
+struct CloseProgress(Arc<Mutex<Option<ProgressSender>>>);
+
+impl Drop for CloseProgress {
+    fn drop(&mut self) {
+        if let Ok(mut progress) = self.0.lock() {
+            progress.take();
+        }
+    }
+}
+
 impl LlamaServer {
     pub async fn spawn(
+        model_id: ModelId,
         model_entry: Option<ModelInfo>,
         config: LlamaCppConfig,
         client: reqwest::Client,
-    ) -> Result<(u16, Child), String> {
-        println!("Starting model with config: {:?}", config);
+        logs: Arc<ServerLogs>,
+        progress: Option<ProgressSender>,
+    ) -> Result<(u16, Child), LlamaServerError> {
+        println!(
+            "Starting model {} (ctx {}, ngl {})",
+            config.model_path, config.ctx_size, config.n_gpu_layers
+        );
+        config
+            .launch_params
+            .validate()
+            .map_err(|errors| LlamaServerError::InvalidLaunchParams { errors })?;
         let model_path = if let Some(entry) = model_entry {
             if let Some(path) = &entry.model_file_path {
                 PathBuf::from(path)
@@ -24,7 +61,7 @@ impl LlamaServer {
         };
 
         if !model_path.exists() {
-            return Err(format!("Model file not found: {:?}", model_path));
+            return Err(format!("Model file not found: {:?}", model_path).into());
         }
 
         let mut llama_server_path = PathBuf::from(&config.llama_cpp_path);
@@ -85,18 +122,24 @@ impl LlamaServer {
             return Err(format!(
                 "llama-server executable not found: {:?}",
                 llama_server_path
-            ));
+            )
+            .into());
         }
 
-        println!(
-            "[Infrastructure] Spawning llama-server at: {:?} with port {}",
-            llama_server_path, config.port
-        );
         let binary_dir = llama_server_path.parent().unwrap_or(Path::new("."));
         if config.chat_template.is_some() && config.chat_template_file.is_some() {
-            return Err("Use either chat_template or chat_template_file, not both".to_string());
+            return Err("Use either chat_template or chat_template_file, not both"
+                .to_string()
+                .into());
         }
 
+        // The actor resolved and reserved this port before the spawn.
+        let port = config.port;
+        println!(
+            "[Infrastructure] Spawning llama-server at: {:?} with port {}",
+            llama_server_path, port
+        );
+
         let mut cmd = Command::new(&llama_server_path);
         #[cfg(windows)]
         {
@@ -107,35 +150,37 @@ impl LlamaServer {
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .arg("-m")
-            .arg(&model_path)
-            .arg("--port")
-            .arg(config.port.to_string())
-            .arg("-c")
-            .arg(config.ctx_size.to_string())
-            .arg("-np")
-            .arg(config.parallel.to_string())
-            .arg("-ngl")
-            .arg(config.n_gpu_layers.to_string())
-            // Enable Jinja templates for tool calling support.
-            .arg("--jinja");
-
-        if let Some(template) = &config.chat_template {
-            cmd.arg("--chat-template").arg(template);
-        }
-
-        if let Some(template_file) = &config.chat_template_file {
-            cmd.arg("--chat-template-file").arg(template_file);
-        }
+            .args(Self::build_args(&model_path.to_string_lossy(), port, &config));
 
         let mut child = cmd
             .spawn()
             .map_err(|e| format!("Failed to spawn llama-server: {}", e))?;
 
-        Self::pipe_output(&mut child);
+        let tracker = Arc::new(Mutex::new(LoadProgressTracker::new(model_id.clone())));
+        let progress = Arc::new(Mutex::new(progress));
+        let _close_progress = CloseProgress(progress.clone());
+        let emit = Arc::new(move |event: Option<LoadProgress>| {
+            if let (Ok(progress), Some(event)) = (progress.lock(), event) {
+                if let Some(progress) = progress.as_ref() {
+                    let _ = progress.send(event);
+                }
+            }
+        });
+        emit(tracker.lock().ok().map(|t| t.snapshot()));
+
+        let on_line: LineHook = {
+            let tracker = tracker.clone();
+            let emit = emit.clone();
+            Arc::new(move |line: &str| {
+                emit(tracker.lock().ok().and_then(|mut t| t.observe_line(line)));
+            })
+        };
+
+        // Each launch starts a fresh log so the tail always belongs to this run.
+        logs.clear(&model_id);
+        let readers = Self::pipe_output(&mut child, &model_id, &logs, Some(on_line));
 
         // Healthcheck
-        let port = config.port;
         let health_url = format!("http://localhost:{}/health", port);
         let mut attempts = 0;
         let max_attempts = if cfg!(test) { 2 } else { 40 };
@@ -147,7 +192,22 @@ impl LlamaServer {
 
         while attempts < max_attempts {
             if let Ok(Some(status)) = child.try_wait() {
-                return Err(format!("llama-server exited early with status: {}", status));
+                // Give the readers a moment to drain what the process wrote before dying.
+                let _ = tokio::time::timeout(Duration::from_secs(1), async {
+                    for reader in readers {
+                        let _ = reader.await;
+                    }
+                })
+                .await;
+                let log_tail = logs
+                    .tail(&model_id, Some(EXIT_LOG_TAIL_LINES))
+                    .into_iter()
+                    .map(|entry| entry.line)
+                    .collect();
+                return Err(LlamaServerError::ExitedEarly {
+                    status: status.to_string(),
+                    log_tail,
+                });
             }
 
             if let Ok(res) = client
@@ -157,21 +217,38 @@ impl LlamaServer {
                 .await
             {
                 if res.status().is_success() {
+                    emit(tracker.lock().ok().map(|mut t| t.ready()));
                     return Ok((port, child));
                 }
+                if res.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+                    let body = res.text().await.unwrap_or_default();
+                    if is_loading_health_body(&body) {
+                        emit(tracker.lock().ok().and_then(|mut t| t.observe_health_loading()));
+                    }
+                }
             }
             tokio::time::sleep(sleep_duration).await;
             attempts += 1;
         }
 
         let _ = child.kill().await;
-        Err("Failed to start model: Healthcheck timed out".to_string())
+        Err("Failed to start model: Healthcheck timed out".to_string().into())
     }
 
-    fn pipe_output(child: &mut Child) {
+    fn pipe_output(
+        child: &mut Child,
+        model_id: &ModelId,
+        logs: &Arc<ServerLogs>,
+        on_line: Option<LineHook>,
+    ) -> Vec<JoinHandle<()>> {
+        let mut readers = Vec::new();
+
         if let Some(stdout) = child.stdout.take() {
             let mut reader = tokio::io::BufReader::new(stdout);
-            tauri::async_runtime::spawn(async move {
+            let model_id = model_id.clone();
+            let logs = logs.clone();
+            let on_line = on_line.clone();
+            readers.push(tauri::async_runtime::spawn(async move {
                 use tokio::io::AsyncBufReadExt;
                 let mut line = String::new();
                 while let Ok(n) = reader.read_line(&mut line).await {
@@ -179,14 +256,21 @@ impl LlamaServer {
                         break;
                     }
                     print!("[llama-server] {}", line);
+                    logs.push(&model_id, LogStream::Stdout, &line);
+                    if let Some(on_line) = &on_line {
+                        on_line(&line);
+                    }
                     line.clear();
                 }
-            });
+            }));
         }
 
         if let Some(stderr) = child.stderr.take() {
             let mut reader = tokio::io::BufReader::new(stderr);
-            tauri::async_runtime::spawn(async move {
+            let model_id = model_id.clone();
+            let logs = logs.clone();
+            let on_line = on_line.clone();
+            readers.push(tauri::async_runtime::spawn(async move {
                 use tokio::io::AsyncBufReadExt;
                 let mut line = String::new();
                 while let Ok(n) = reader.read_line(&mut line).await {
@@ -194,23 +278,94 @@ impl LlamaServer {
                         break;
                     }
                     eprint!("[llama-server] {}", line);
+                    logs.push(&model_id, LogStream::Stderr, &line);
+                    if let Some(on_line) = &on_line {
+                        on_line(&line);
+                    }
                     line.clear();
                 }
-            });
+            }));
         }
+
+        readers
     }
 
+    /// Full llama-server argv (without the program) for `config`, listening on `port`.
+    pub fn build_args(model_path: &str, port: u16, config: &LlamaCppConfig) -> Vec<String> {
+        let mut args = vec![
+            "-m".to_string(),
+            model_path.to_string(),
+            "--port".to_string(),
+            port.to_string(),
+            "-c".to_string(),
+            config.ctx_size.to_string(),
+            "-np".to_string(),
+            config.parallel.to_string(),
+            "-ngl".to_string(),
+            config.n_gpu_layers.to_string(),
+        ];
+        let params = &config.launch_params;
+        if params.jinja {
+            // Enable Jinja templates for tool calling support.
+            args.push("--jinja".to_string());
+        }
+        if let Some(template) = &config.chat_template {
+            args.push("--chat-template".to_string());
+            args.push(template.clone());
+        }
+        if let Some(template_file) = &config.chat_template_file {
+            args.push("--chat-template-file".to_string());
+            args.push(template_file.clone());
+        }
+        args.extend(params.to_args());
+        args.extend(params.extra_args.iter().cloned());
+        args
+    }
+
+    /// Adds the bearer token when the server was started with `--api-key`.
+    fn authorize(builder: reqwest::RequestBuilder, api_key: Option<&str>) -> reqwest::RequestBuilder {
+        match api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    /// Text-only stream: tool call deltas are dropped and errors arrive as
+    /// `Error: ...` chunks.
     pub async fn stream_chat(
         client: reqwest::Client,
         port: u16,
+        api_key: Option<String>,
         request: ChatRequest,
     ) -> Result<mpsc::Receiver<String>, String> {
+        let mut events = Self::stream_chat_events(client, port, api_key, request).await?;
+        let (tx, rx) = mpsc::channel(32);
+
+        tauri::async_runtime::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let chunk = match event {
+                    ChatStreamEvent::Text(text) | ChatStreamEvent::Error(text) => text,
+                    ChatStreamEvent::ToolCallDelta(_) => continue,
+                };
+                if tx.send(chunk).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(rx)
+    }
+
+    pub async fn stream_chat_events(
+        client: reqwest::Client,
+        port: u16,
+        api_key: Option<String>,
+        request: ChatRequest,
+    ) -> Result<mpsc::Receiver<ChatStreamEvent>, String> {
         let url = format!("http://localhost:{}/v1/chat/completions", port);
         let (tx, rx) = mpsc::channel(32);
 
         tauri::async_runtime::spawn(async move {
-            let res = client
-                .post(&url)
+            let res = Self::authorize(client.post(&url), api_key.as_deref())
                 .json(&request)
                 .timeout(Duration::from_secs(300))
                 .send()
@@ -219,7 +374,10 @@ impl LlamaServer {
                 Ok(mut response) => {
                     if !response.status().is_success() {
                         let _ = tx
-                            .send(format!("Error: Status {}", response.status()))
+                            .send(ChatStreamEvent::Error(format!(
+                                "Error: Status {}",
+                                response.status()
+                            )))
                             .await;
                         return;
                     }
@@ -253,12 +411,9 @@ impl LlamaServer {
                                 if let Ok(json) =
                                     serde_json::from_str::<serde_json::Value>(payload)
                                 {
-                                    let chunks = extract_stream_chunks(&json);
-                                    if !chunks.is_empty() {
-                                        for chunk in chunks {
-                                            if tx.send(chunk).await.is_err() {
-                                                return;
-                                            }
+                                    for event in extract_stream_events(&json) {
+                                        if tx.send(event).await.is_err() {
+                                            return;
                                         }
                                     }
                                 }
@@ -267,7 +422,9 @@ impl LlamaServer {
                     }
                 }
                 Err(e) => {
-                    let _ = tx.send(format!("Error: Connect {}", e)).await;
+                    let _ = tx
+                        .send(ChatStreamEvent::Error(format!("Error: Connect {}", e)))
+                        .await;
                 }
             }
         });
@@ -277,11 +434,11 @@ impl LlamaServer {
     pub async fn chat_completion(
         client: reqwest::Client,
         port: u16,
+        api_key: Option<String>,
         request: ChatRequest,
     ) -> Result<serde_json::Value, String> {
         let url = format!("http://localhost:{}/v1/chat/completions", port);
-        let response = client
-            .post(&url)
+        let response = Self::authorize(client.post(&url), api_key.as_deref())
             .json(&request)
             .timeout(Duration::from_secs(300))
             .send()
@@ -305,9 +462,26 @@ impl LlamaServer {
 }
 
 impl LlamaServer {
-    pub fn test_pipe_output(child: &mut Child) {
-        Self::pipe_output(child);
+    pub fn test_pipe_output(
+        child: &mut Child,
+        model_id: &ModelId,
+        logs: &Arc<ServerLogs>,
+    ) -> Vec<JoinHandle<()>> {
+        Self::pipe_output(child, model_id, logs, None)
+    }
+}
+
+fn extract_stream_events(json: &serde_json::Value) -> Vec<ChatStreamEvent> {
+    let mut events: Vec<ChatStreamEvent> = extract_stream_chunks(json)
+        .into_iter()
+        .map(ChatStreamEvent::Text)
+        .collect();
+    for key in ["delta", "message"] {
+        if let Some(calls) = json["choices"][0][key]["tool_calls"].as_array() {
+            events.extend(calls.iter().cloned().map(ChatStreamEvent::ToolCallDelta));
+        }
     }
+    events
 }
 
 fn extract_stream_chunks(json: &serde_json::Value) -> Vec<String> {
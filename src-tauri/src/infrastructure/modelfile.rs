@@ -0,0 +1,81 @@
+//! Ollama stores the Modelfile's TEMPLATE, SYSTEM, PARAMETER and LICENSE
+//! directives as small manifest layers next to the GGUF weights.
+
+use serde_json::Value;
+
+use crate::models::ModelParams;
+
+pub const TEMPLATE_MEDIA_TYPE: &str = "application/vnd.ollama.image.template";
+pub const SYSTEM_MEDIA_TYPE: &str = "application/vnd.ollama.image.system";
+pub const PARAMS_MEDIA_TYPE: &str = "application/vnd.ollama.image.params";
+pub const LICENSE_MEDIA_TYPE: &str = "application/vnd.ollama.image.license";
+
+/// Text layers are a few KiB; anything bigger is not what we expect.
+pub const MAX_LAYER_BYTES: u64 = 1 << 20;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelfileLayers {
+    pub template: Option<String>,
+    pub system: Option<String>,
+    pub params: Option<ModelParams>,
+    pub stop: Vec<String>,
+    /// Models may ship several licenses; they are joined with blank lines.
+    pub license: Option<String>,
+}
+
+pub fn is_modelfile_layer(media_type: &str) -> bool {
+    matches!(
+        media_type,
+        TEMPLATE_MEDIA_TYPE | SYSTEM_MEDIA_TYPE | PARAMS_MEDIA_TYPE | LICENSE_MEDIA_TYPE
+    )
+}
+
+impl ModelfileLayers {
+    /// Records one layer's content; other media types are ignored.
+    pub fn add_layer(&mut self, media_type: &str, bytes: &[u8]) -> Result<(), String> {
+        let text = || String::from_utf8_lossy(bytes).to_string();
+        match media_type {
+            TEMPLATE_MEDIA_TYPE => self.template = Some(text()),
+            SYSTEM_MEDIA_TYPE => self.system = Some(text()).filter(|s| !s.trim().is_empty()),
+            LICENSE_MEDIA_TYPE => {
+                self.license = Some(match self.license.take() {
+                    Some(existing) => format!("{}\n\n{}", existing, text()),
+                    None => text(),
+                })
+            }
+            PARAMS_MEDIA_TYPE => {
+                let (params, stop) = parse_params(bytes)?;
+                self.params = Some(params);
+                self.stop = stop;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Parses a `params` blob into typed options plus its stop sequences.
+/// A repeated `PARAMETER` becomes an array; single-element arrays are
+/// unwrapped so e.g. `{"temperature": [0.7]}` still reads as a number.
+pub fn parse_params(bytes: &[u8]) -> Result<(ModelParams, Vec<String>), String> {
+    let mut map: serde_json::Map<String, Value> =
+        serde_json::from_slice(bytes).map_err(|e| format!("Invalid params layer: {}", e))?;
+    let stop = match map.remove("stop") {
+        Some(Value::String(s)) => vec![s],
+        Some(Value::Array(items)) => items
+            .into_iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    };
+    for value in map.values_mut() {
+        if let Value::Array(items) = value {
+            if items.len() == 1 {
+                *value = items.remove(0);
+            }
+        }
+    }
+    let params = serde_json::from_value(Value::Object(map))
+        .map_err(|e| format!("Invalid params layer: {}", e))?;
+    Ok((params, stop))
+}
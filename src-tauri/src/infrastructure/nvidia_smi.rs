@@ -39,6 +39,22 @@ impl NvidiaSmi {
 
         (gpu_load, vram_usage)
     }
+
+    /// Free memory per GPU in bytes; empty when nvidia-smi is unavailable.
+    pub fn free_vram_bytes() -> Vec<u64> {
+        match run_nvidia_smi(&["--query-gpu=memory.free", "--format=csv,noheader,nounits"]) {
+            Ok(output) if output.success => parse_free_memory(&output.stdout),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn parse_free_memory(stdout: &str) -> Vec<u64> {
+    stdout
+        .lines()
+        .filter_map(|line| line.trim().parse::<u64>().ok())
+        .map(|mib| mib * 1024 * 1024)
+        .collect()
 }
 
 fn parse_gpu_query(stdout: &str) -> (Option<f32>, Option<f32>) {
@@ -116,6 +132,10 @@ pub fn test_parse_gpu_query(stdout: &str) -> (Option<f32>, Option<f32>) {
     parse_gpu_query(stdout)
 }
 
+pub fn test_parse_free_memory(stdout: &str) -> Vec<u64> {
+    parse_free_memory(stdout)
+}
+
 pub fn test_parse_process_list(stdout: &str, pid: u32) -> Option<u32> {
     parse_process_list(stdout, pid)
 }
@@ -0,0 +1,185 @@
+use std::net::TcpListener;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::models::{LlamaServerError, PortRange};
+
+/// Process currently listening on a TCP port.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: Option<String>,
+}
+
+/// llama-server binds to 127.0.0.1 by default, so that is the address probed.
+pub fn is_port_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+pub fn find_free_port(range: PortRange) -> Option<u16> {
+    (range.start..=range.end).find(|port| is_port_free(*port))
+}
+
+/// Returns `preferred` if it can be bound, otherwise the first free port in
+/// `range`. Without a range a busy port is reported together with its owner.
+/// `reserved` ports belong to servers that may not be listening yet and are
+/// never returned.
+pub fn resolve_port(
+    preferred: u16,
+    range: Option<PortRange>,
+    reserved: &[u16],
+) -> Result<u16, LlamaServerError> {
+    let usable = |port: u16| !reserved.contains(&port) && is_port_free(port);
+    if usable(preferred) {
+        return Ok(preferred);
+    }
+
+    match range {
+        Some(range) => (range.start..=range.end).find(|port| usable(*port)).ok_or(
+            LlamaServerError::NoFreePort {
+                start: range.start,
+                end: range.end,
+            },
+        ),
+        None => {
+            let owner = find_port_owner(preferred);
+            Err(LlamaServerError::PortInUse {
+                port: preferred,
+                pid: owner.as_ref().map(|o| o.pid),
+                process_name: owner.and_then(|o| o.name),
+            })
+        }
+    }
+}
+
+/// Best-effort lookup of the process listening on `port`.
+pub fn find_port_owner(port: u16) -> Option<PortOwner> {
+    let pid = find_listening_pid(port)?;
+    Some(PortOwner {
+        pid,
+        name: process_name(pid),
+    })
+}
+
+fn process_name(pid: u32) -> Option<String> {
+    let sys_pid = Pid::from(pid as usize);
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[sys_pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    sys.process(sys_pid)
+        .map(|process| process.name().to_string_lossy().to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn find_listening_pid(port: u16) -> Option<u32> {
+    let inodes: Vec<u64> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|content| parse_proc_net_tcp(&content, port))
+        .collect();
+    if inodes.is_empty() {
+        return None;
+    }
+
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            if inodes
+                .iter()
+                .any(|inode| target == format!("socket:[{}]", inode))
+            {
+                return Some(pid);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(windows)]
+fn find_listening_pid(port: u16) -> Option<u32> {
+    let mut cmd = std::process::Command::new("netstat");
+    // Avoid flashing a console window when invoking netstat.
+    cmd.creation_flags(0x08000000);
+    let output = cmd.args(["-ano", "-p", "TCP"]).output().ok()?;
+    parse_netstat_output(&String::from_utf8_lossy(&output.stdout), port)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn find_listening_pid(port: u16) -> Option<u32> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-t"])
+        .output()
+        .ok()?;
+    parse_lsof_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Socket inodes of LISTEN entries on `port` in a `/proc/net/tcp{,6}` table.
+fn parse_proc_net_tcp(content: &str, port: u16) -> Vec<u64> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                return None;
+            }
+            let local_port = fields[1].rsplit(':').next()?;
+            let local_port = u16::from_str_radix(local_port, 16).ok()?;
+            // State 0A is TCP_LISTEN.
+            if local_port != port || fields[3] != "0A" {
+                return None;
+            }
+            fields[9].parse::<u64>().ok()
+        })
+        .collect()
+}
+
+fn parse_netstat_output(stdout: &str, port: u16) -> Option<u32> {
+    let suffix = format!(":{}", port);
+    stdout.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 5 || !fields[0].eq_ignore_ascii_case("TCP") {
+            return None;
+        }
+        if !fields[1].ends_with(&suffix) || !fields[3].eq_ignore_ascii_case("LISTENING") {
+            return None;
+        }
+        fields[4].parse::<u32>().ok()
+    })
+}
+
+fn parse_lsof_output(stdout: &str) -> Option<u32> {
+    stdout
+        .lines()
+        .find_map(|line| line.trim().parse::<u32>().ok())
+}
+
+pub fn test_parse_proc_net_tcp(content: &str, port: u16) -> Vec<u64> {
+    parse_proc_net_tcp(content, port)
+}
+
+pub fn test_parse_netstat_output(stdout: &str, port: u16) -> Option<u32> {
+    parse_netstat_output(stdout, port)
+}
+
+pub fn test_parse_lsof_output(stdout: &str) -> Option<u32> {
+    parse_lsof_output(stdout)
+}
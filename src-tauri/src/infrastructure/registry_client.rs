@@ -0,0 +1,374 @@
+//! Minimal OCI distribution client used for model pulls.
+//!
+//! Handles the `WWW-Authenticate` dance registries use: a request without
+//! credentials gets a 401 with either a `Basic` challenge (answered with the
+//! stored username/password) or a `Bearer` challenge naming a token endpoint,
+//! where we trade the stored credentials (or nothing, for anonymous pulls) for
+//! a short-lived token scoped to the repository.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use base64::Engine;
+use futures::FutureExt;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, WWW_AUTHENTICATE};
+use reqwest::{Client, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::infrastructure::blob_download::{BlobClient, ReauthorizeFn};
+use crate::models::{AppConfig, ModelManifest, RegistryCredential};
+
+/// Manifest formats we can pull, most specific first. The index types let
+/// the registry answer with a multi-manifest index that we then resolve.
+pub const MANIFEST_ACCEPT: &str = "application/vnd.ollama.manifest.v1+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.v2+json, \
+    application/vnd.oci.image.index.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json";
+
+const USER_AGENT: &str = "llama-desktop";
+
+/// A parsed `WWW-Authenticate` header, e.g.
+/// `Bearer realm="https://auth.example/token",service="registry",scope="repository:a/b:pull"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthChallenge {
+    pub scheme: String,
+    pub params: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct RegistryClient {
+    http: Client,
+    host: String,
+    base_url: String,
+    insecure: bool,
+    credential: Option<RegistryCredential>,
+    /// Current `Authorization` header value, filled in after a challenge.
+    authorization: Option<String>,
+}
+
+impl RegistryClient {
+    /// `insecure` switches to plain HTTP, for local registries without TLS.
+    pub fn new(
+        host: &str,
+        credential: Option<RegistryCredential>,
+        insecure: bool,
+    ) -> Result<Self, String> {
+        let http = Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        let scheme = if insecure { "http" } else { "https" };
+        let authorization = match &credential {
+            Some(RegistryCredential::Token { token }) => Some(format!("Bearer {}", token)),
+            _ => None,
+        };
+        Ok(Self {
+            http,
+            host: host.to_string(),
+            base_url: format!("{}://{}", scheme, host),
+            insecure,
+            credential,
+            authorization,
+        })
+    }
+
+    /// Uses the credentials and insecure-registry list stored in the settings.
+    pub fn from_config(host: &str, config: &AppConfig) -> Result<Self, String> {
+        let credential = config.registry_credentials.get(host).cloned();
+        let insecure = config.insecure_registries.iter().any(|h| h == host);
+        Self::new(host, credential, insecure)
+    }
+
+    pub fn blob_url(&self, repository: &str, digest: &str) -> String {
+        format!("{}/v2/{}/blobs/{}", self.base_url, repository, digest)
+    }
+
+    /// GETs `url`, answering one authentication challenge if the registry asks.
+    pub async fn get(
+        &mut self,
+        url: &str,
+        accept: Option<&str>,
+        repository: &str,
+    ) -> Result<Response, String> {
+        let response = self.send(url, accept).await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        let challenge = response
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok());
+        self.answer_challenge(challenge, repository).await?;
+        self.send(url, accept).await
+    }
+
+    /// Authenticates against the `WWW-Authenticate` header of a 401.
+    async fn answer_challenge(
+        &mut self,
+        header: Option<&str>,
+        repository: &str,
+    ) -> Result<(), String> {
+        let challenge = header.and_then(parse_challenge).ok_or_else(|| {
+            format!(
+                "Registry {} requires authentication but sent no challenge",
+                self.host
+            )
+        })?;
+        self.authenticate(&challenge, repository).await
+    }
+
+    async fn send(&self, url: &str, accept: Option<&str>) -> Result<Response, String> {
+        let mut request = self.http.get(url);
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        if let Some(authorization) = &self.authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach registry {}: {}", self.host, e))
+    }
+
+    async fn authenticate(
+        &mut self,
+        challenge: &AuthChallenge,
+        repository: &str,
+    ) -> Result<(), String> {
+        match challenge.scheme.to_ascii_lowercase().as_str() {
+            "basic" => match &self.credential {
+                Some(RegistryCredential::Basic { username, password }) => {
+                    self.authorization = Some(basic_authorization(username, password));
+                    Ok(())
+                }
+                _ => Err(format!(
+                    "Registry {} requires a username and password",
+                    self.host
+                )),
+            },
+            "bearer" => {
+                let realm = challenge.params.get("realm").ok_or_else(|| {
+                    format!("Registry {} sent a challenge without realm", self.host)
+                })?;
+                let scope = challenge
+                    .params
+                    .get("scope")
+                    .cloned()
+                    .unwrap_or_else(|| format!("repository:{}:pull", repository));
+                let mut token_url = reqwest::Url::parse(realm)
+                    .map_err(|e| format!("Invalid token endpoint {}: {}", realm, e))?;
+                if matches!(self.credential, Some(RegistryCredential::Basic { .. }))
+                    && !self.may_send_password_to(&token_url)
+                {
+                    return Err(format!(
+                        "Registry {} asked for the password over plain HTTP ({}); refusing",
+                        self.host, realm
+                    ));
+                }
+                {
+                    let mut query = token_url.query_pairs_mut();
+                    if let Some(service) = challenge.params.get("service") {
+                        query.append_pair("service", service);
+                    }
+                    query.append_pair("scope", &scope);
+                }
+                let mut request = self.http.get(token_url);
+                request = match &self.credential {
+                    Some(RegistryCredential::Basic { username, password }) => {
+                        request.basic_auth(username, Some(password))
+                    }
+                    Some(RegistryCredential::Token { token }) => request.bearer_auth(token),
+                    None => request,
+                };
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| format!("Failed to reach token endpoint {}: {}", realm, e))?;
+                if !response.status().is_success() {
+                    return Err(format!(
+                        "Registry {} refused a token: HTTP {}",
+                        self.host,
+                        response.status()
+                    ));
+                }
+                let body: TokenResponse = response
+                    .json()
+                    .await
+                    .map_err(|e| format!("Failed to parse token response: {}", e))?;
+                let token = body
+                    .token
+                    .or(body.access_token)
+                    .ok_or_else(|| "Token response contains no token".to_string())?;
+                self.authorization = Some(format!("Bearer {}", token));
+                Ok(())
+            }
+            other => Err(format!(
+                "Registry {} uses unsupported authentication scheme {}",
+                self.host, other
+            )),
+        }
+    }
+
+    /// A password only goes out over HTTPS, or to the registry itself when
+    /// the user marked it as a plain-HTTP registry.
+    fn may_send_password_to(&self, url: &reqwest::Url) -> bool {
+        if url.scheme() == "https" {
+            return true;
+        }
+        let authority = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return false,
+        };
+        self.insecure && url.scheme() == "http" && authority == self.host
+    }
+
+    /// Fetches a manifest by tag or digest. An OCI index (or Docker manifest
+    /// list) is resolved to the first model manifest it references.
+    pub async fn fetch_manifest(
+        &mut self,
+        repository: &str,
+        reference: &str,
+    ) -> Result<ModelManifest, String> {
+        let mut body = self.fetch_manifest_json(repository, reference).await?;
+        if let Some(entries) = body.get("manifests").and_then(Value::as_array) {
+            let digest = select_index_entry(entries).ok_or_else(|| {
+                format!("Index for {}:{} lists no manifests", repository, reference)
+            })?;
+            body = self.fetch_manifest_json(repository, &digest).await?;
+            if body.get("manifests").is_some() {
+                return Err("Nested manifest indexes are not supported".to_string());
+            }
+        }
+        serde_json::from_value(body).map_err(|e| format!("Failed to parse manifest JSON: {}", e))
+    }
+
+    async fn fetch_manifest_json(
+        &mut self,
+        repository: &str,
+        reference: &str,
+    ) -> Result<Value, String> {
+        let url = format!(
+            "{}/v2/{}/manifests/{}",
+            self.base_url, repository, reference
+        );
+        let response = self.get(&url, Some(MANIFEST_ACCEPT), repository).await?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Failed to fetch manifest: HTTP {}",
+                response.status()
+            ));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse manifest JSON: {}", e))
+    }
+
+    /// A client for the blobs of `repository` that sends the negotiated
+    /// credentials with every request and, once they expire, answers the
+    /// next challenge the same way `get` does.
+    pub fn blob_client(&self, repository: &str) -> Result<BlobClient, String> {
+        let registry = Arc::new(tokio::sync::Mutex::new(self.clone()));
+        let repository = repository.to_string();
+        let reauthorize: ReauthorizeFn = Arc::new(move |challenge| {
+            let registry = registry.clone();
+            let repository = repository.clone();
+            async move {
+                let mut registry = registry.lock().await;
+                registry
+                    .answer_challenge(challenge.as_deref(), &repository)
+                    .await?;
+                registry.download_client()
+            }
+            .boxed()
+        });
+        Ok(BlobClient::new(self.download_client()?).with_reauthorize(reauthorize))
+    }
+
+    /// Sends the credentials as a default header because reqwest drops the
+    /// per-request one when a blob redirects to another host.
+    fn download_client(&self) -> Result<Client, String> {
+        let mut headers = HeaderMap::new();
+        if let Some(authorization) = &self.authorization {
+            let mut value = HeaderValue::from_str(authorization)
+                .map_err(|e| format!("Invalid authorization header: {}", e))?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+        Client::builder()
+            .user_agent(USER_AGENT)
+            .default_headers(headers)
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+}
+
+fn basic_authorization(username: &str, password: &str) -> String {
+    let encoded =
+        base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+    format!("Basic {}", encoded)
+}
+
+/// Skips attestation entries (`unknown/unknown` platform) that registries add
+/// next to the real manifest.
+fn select_index_entry(entries: &[Value]) -> Option<String> {
+    entries
+        .iter()
+        .find(|entry| {
+            entry
+                .pointer("/platform/os")
+                .and_then(Value::as_str)
+                .is_none_or(|os| os != "unknown")
+        })
+        .or_else(|| entries.first())
+        .and_then(|entry| entry.get("digest"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Parses `Scheme key="value", key=value`; quoted values may contain commas.
+pub fn parse_challenge(header: &str) -> Option<AuthChallenge> {
+    let header = header.trim();
+    let (scheme, rest) = header.split_once(' ').unwrap_or((header, ""));
+    if scheme.is_empty() {
+        return None;
+    }
+
+    let mut params = HashMap::new();
+    let mut chars = rest.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        let key: String = std::iter::from_fn(|| chars.next_if(|c| *c != '=')).collect();
+        if key.is_empty() || chars.next().is_none() {
+            break;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => value.extend(chars.next()),
+                    c => value.push(c),
+                }
+            }
+        } else {
+            value = std::iter::from_fn(|| chars.next_if(|c| *c != ',')).collect();
+        }
+        params.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+
+    Some(AuthChallenge {
+        scheme: scheme.to_string(),
+        params,
+    })
+}
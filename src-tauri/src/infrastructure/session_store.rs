@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::models::ChatMessage;
+
+/// Backing storage for chat session histories.
+///
+/// The orchestrator keeps a hot copy of every active session in memory and
+/// writes each mutation through to the store, so a restart can rebuild the
+/// Rust-side context without the frontend pushing it back.
+pub trait SessionStore: Send + Sync {
+    /// Returns the stored history, or `None` when the session is unknown.
+    fn load(&self, session_id: &str) -> Result<Option<Vec<ChatMessage>>, String>;
+    fn append(&self, session_id: &str, message: &ChatMessage) -> Result<(), String>;
+    fn replace(&self, session_id: &str, history: &[ChatMessage]) -> Result<(), String>;
+    fn delete(&self, session_id: &str) -> Result<(), String>;
+}
+
+/// Volatile store used when no data directory is available (tests, early startup).
+pub struct InMemorySessionStore {
+    sessions: Mutex<HashMap<String, Vec<ChatMessage>>>,
+}
+
+impl InMemorySessionStore {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for InMemorySessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn load(&self, session_id: &str) -> Result<Option<Vec<ChatMessage>>, String> {
+        let sessions = self
+            .sessions
+            .lock()
+            .map_err(|_| "Session store lock poisoned".to_string())?;
+        Ok(sessions.get(session_id).cloned())
+    }
+
+    fn append(&self, session_id: &str, message: &ChatMessage) -> Result<(), String> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .map_err(|_| "Session store lock poisoned".to_string())?;
+        sessions
+            .entry(session_id.to_string())
+            .or_default()
+            .push(message.clone());
+        Ok(())
+    }
+
+    fn replace(&self, session_id: &str, history: &[ChatMessage]) -> Result<(), String> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .map_err(|_| "Session store lock poisoned".to_string())?;
+        sessions.insert(session_id.to_string(), history.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, session_id: &str) -> Result<(), String> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .map_err(|_| "Session store lock poisoned".to_string())?;
+        sessions.remove(session_id);
+        Ok(())
+    }
+}
+
+/// One JSONL file per session under `base_dir`, one `ChatMessage` per line.
+///
+/// Appends are a single `writeln!`; edits that touch earlier messages rewrite
+/// the file through a temp-then-rename so a crash never leaves it half written.
+pub struct FileSessionStore {
+    base_dir: PathBuf,
+}
+
+impl FileSessionStore {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self { base_dir }
+    }
+
+    pub fn session_path(&self, session_id: &str) -> PathBuf {
+        session_file_path(&self.base_dir, session_id)
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(&self, session_id: &str) -> Result<Option<Vec<ChatMessage>>, String> {
+        let path = self.session_path(session_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let file = fs::File::open(&path)
+            .map_err(|e| format!("Failed to open session file {}: {}", path.display(), e))?;
+        let mut history = Vec::new();
+        for (idx, line) in BufReader::new(file).lines().enumerate() {
+            let line =
+                line.map_err(|e| format!("Failed to read session file {}: {}", path.display(), e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<ChatMessage>(&line) {
+                Ok(message) => history.push(message),
+                // A torn final line from a crash mid-append is dropped, not fatal.
+                Err(e) => eprintln!(
+                    "[SessionStore] Skipping invalid line {} in {}: {}",
+                    idx + 1,
+                    path.display(),
+                    e
+                ),
+            }
+        }
+        Ok(Some(history))
+    }
+
+    fn append(&self, session_id: &str, message: &ChatMessage) -> Result<(), String> {
+        crate::utils::append_jsonl(&self.session_path(session_id), message)
+    }
+
+    fn replace(&self, session_id: &str, history: &[ChatMessage]) -> Result<(), String> {
+        crate::utils::save_jsonl(&self.session_path(session_id), history)
+    }
+
+    fn delete(&self, session_id: &str) -> Result<(), String> {
+        let path = self.session_path(session_id);
+        if !path.exists() {
+            return Ok(());
+        }
+        fs::remove_file(&path)
+            .map_err(|e| format!("Failed to delete session file {}: {}", path.display(), e))
+    }
+}
+
+/// Maps a session id to `<base_dir>/<id>.jsonl`, percent-encoding every byte
+/// outside `[a-z0-9_-]` so the name cannot escape the directory and two ids
+/// never share a file. Uppercase letters are encoded too, so "Abc" and "abc"
+/// stay apart on case-insensitive filesystems. The empty id becomes `%`,
+/// which no other id produces.
+pub fn session_file_path(base_dir: &Path, session_id: &str) -> PathBuf {
+    let mut safe = String::with_capacity(session_id.len());
+    for byte in session_id.bytes() {
+        if byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-' || byte == b'_' {
+            safe.push(byte as char);
+        } else {
+            safe.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    if safe.is_empty() {
+        safe.push('%');
+    }
+    base_dir.join(format!("{}.jsonl", safe))
+}
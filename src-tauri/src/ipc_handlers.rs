@@ -20,6 +20,8 @@ pub fn configure_ipc(builder: Builder<Wry>) -> Builder<Wry> {
         commands::chat_actions::chat_action_share,
         commands::chat_actions::chat_action_regenerate,
         commands::models::parse_model_manifest,
+        commands::models::read_gguf_info,
+        commands::models::estimate_memory,
         commands::models::scan_models_directory,
         commands::models::save_model_library,
         commands::models::load_model_library,
@@ -27,6 +29,8 @@ pub fn configure_ipc(builder: Builder<Wry>) -> Builder<Wry> {
         commands::config::load_config,
         commands::config::save_config,
         commands::config::reset_config,
+        commands::config::set_registry_credential,
+        commands::config::set_hf_token,
         commands::config::get_config_path_string,
         commands::mcp_config::load_mcp_config,
         commands::mcp_config::load_default_mcp_config,
@@ -50,12 +54,35 @@ pub fn configure_ipc(builder: Builder<Wry>) -> Builder<Wry> {
         commands::llama_cpp::ensure_chat_template,
         commands::llama_cpp::start_llama_server,
         commands::llama_cpp::stop_llama_server,
+        commands::llama_cpp::list_running_models,
+        commands::llama_cpp::validate_launch_params,
+        commands::llama_cpp::get_server_logs,
+        commands::llama_cpp::subscribe_server_logs,
+        commands::llama_cpp::subscribe_model_events,
         commands::llama_cpp::check_server_health,
         commands::llama_cpp::is_server_running,
         commands::llama_cpp::check_server_health_detail,
         commands::llama_cpp::get_llama_config,
         commands::llama_cpp::get_server_metrics,
+        commands::presets::get_launch_presets,
+        commands::presets::save_launch_preset,
+        commands::presets::delete_launch_preset,
+        commands::presets::set_default_launch_preset,
+        commands::presets::set_model_sampling,
+        commands::downloads::start_download,
+        commands::downloads::pause_download,
+        commands::downloads::resume_download,
+        commands::downloads::cancel_download,
+        commands::downloads::list_downloads,
+        commands::downloads::subscribe_download_events,
+        commands::model_import::import_gguf,
+        commands::model_bundle::export_model,
+        commands::model_bundle::import_model_bundle,
+        commands::hf_files::hf_list_gguf_files,
+        commands::model_maintenance::delete_model,
+        commands::model_maintenance::verify_models_directory,
         commands::chat::load_history_context,
         commands::chat::generate_chat_title,
+        commands::chat::respond_tool_approval,
     ])
 }
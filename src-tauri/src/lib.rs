@@ -2,20 +2,34 @@ pub mod commands {
     pub mod chat;
     pub mod chat_actions;
     pub mod config;
+    pub mod downloads;
     pub mod general;
+    pub mod hf_files;
     pub mod llama_cpp;
     pub mod mcp;
     pub mod mcp_config;
+    pub mod model_bundle;
+    pub mod model_import;
+    pub mod model_maintenance;
     pub mod models;
+    pub mod presets;
 }
 
 pub mod infrastructure {
+    pub mod blob_download;
+    pub mod gguf;
     pub mod llama {
+        pub mod logs;
         pub mod process;
+        pub mod progress;
         pub mod server;
     }
     pub mod metrics;
+    pub mod modelfile;
     pub mod nvidia_smi;
+    pub mod ports;
+    pub mod registry_client;
+    pub mod session_store;
 }
 
 #[cfg(not(test))]
@@ -23,16 +37,38 @@ pub mod ipc_handlers;
 
 pub mod models {
     pub mod app_settings_model;
+    pub mod bundle_model;
     pub mod chat_model;
+    pub mod download_model;
+    pub mod gguf_model;
+    pub mod hf_model;
+    pub mod import_model;
+    pub mod launch_params_model;
     pub mod llama_model;
+    pub mod maintenance_model;
     pub mod manifest_model;
     pub mod mcp_model;
+    pub mod memory_estimate_model;
+    pub mod modelfile_model;
+    pub mod preset_model;
+    pub mod registry_model;
 
     pub use app_settings_model::*;
+    pub use bundle_model::*;
     pub use chat_model::*;
+    pub use download_model::*;
+    pub use gguf_model::*;
+    pub use hf_model::*;
+    pub use import_model::*;
+    pub use launch_params_model::*;
     pub use llama_model::*;
+    pub use maintenance_model::*;
     pub use manifest_model::*;
     pub use mcp_model::*;
+    pub use memory_estimate_model::*;
+    pub use modelfile_model::*;
+    pub use preset_model::*;
+    pub use registry_model::*;
 }
 
 pub mod services {
@@ -49,6 +85,9 @@ pub mod services {
         pub use service::McpService;
     }
     pub mod capability_registry;
+    pub mod downloads;
+    pub mod go_template;
+    pub mod memory_estimate;
     pub mod orchestrator;
     pub mod subagent;
     pub mod templates;
@@ -91,10 +130,23 @@ pub fn run() {
                 .unwrap_or_else(|| std::path::PathBuf::from("E:\\models"));
 
             let resource_dir = app.path().resource_dir().ok();
-            app.manage(AppState::new(models_path, mcp_config, resource_dir));
+            let sessions_dir = app.path().app_data_dir().ok().map(|dir| dir.join("sessions"));
+            let downloads_path = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .map(|dir| dir.join("downloads.json"));
+            app.manage(AppState::new(
+                models_path,
+                mcp_config,
+                resource_dir,
+                sessions_dir,
+                downloads_path,
+            ));
 
             // Hydrate capability registry on startup
             let state = app.state::<AppState>();
+            commands::downloads::start_download_manager(app.handle(), &state.downloads);
             let orchestrator = state.orchestrator.clone();
             tauri::async_runtime::spawn(async move {
                 if let Err(e) = orchestrator.refresh_capabilities().await {
@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{PortRange, RegistryCredential, SamplingParams, ToolLoopPolicy};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase", default)]
@@ -10,12 +13,29 @@ pub struct AppConfig {
     pub max_tokens: u32,
     pub context_size: u32,
     pub temperature: f32,
+    /// Samplers sent with every message besides `temperature`; unset fields
+    /// use the model's defaults.
+    pub sampling: SamplingParams,
     pub auto_save_chat: bool,
     pub chat_history_limit: u32,
     pub server_port: u16,
+    /// Fallback ports tried when `server_port` is taken; `None` fails instead.
+    pub server_port_range: Option<PortRange>,
     pub web_search_provider: String,
     pub web_search_mcp_id: Option<String>,
     pub chat_header_style: String,
+    /// Credentials for private model registries, keyed by `host[:port]`.
+    /// Set with `set_registry_credential`; `load_config` leaves them out.
+    pub registry_credentials: HashMap<String, RegistryCredential>,
+    /// Registry hosts reached over plain HTTP, e.g. a local `localhost:5000`.
+    pub insecure_registries: Vec<String>,
+    /// Base URL of a Hugging Face mirror; `None` uses huggingface.co.
+    pub hf_endpoint: Option<String>,
+    /// Access token for gated or private Hugging Face repositories. Set with
+    /// `set_hf_token`; `load_config` leaves it out.
+    pub hf_token: Option<String>,
+    /// Tool-loop limits for messages that do not send their own.
+    pub tool_loop_policy: ToolLoopPolicy,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,12 +65,29 @@ impl Default for AppConfig {
             max_tokens: 2048,
             context_size: 8192,
             temperature: 0.7,
+            sampling: SamplingParams::default(),
             auto_save_chat: true,
             chat_history_limit: 50,
             server_port: 8080,
+            server_port_range: None,
             web_search_provider: "tavily".to_string(),
             web_search_mcp_id: None,
             chat_header_style: "default".to_string(),
+            registry_credentials: HashMap::new(),
+            insecure_registries: Vec::new(),
+            hf_endpoint: None,
+            hf_token: None,
+            tool_loop_policy: ToolLoopPolicy::default(),
         }
     }
 }
+
+impl AppConfig {
+    /// The config as the frontend gets it: registry credentials and the
+    /// Hugging Face token never leave the backend.
+    pub fn without_secrets(mut self) -> Self {
+        self.registry_credentials.clear();
+        self.hf_token = None;
+        self
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Bumped when the archive layout changes incompatibly.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// A blob stored in a bundle under `blobs/sha256-…`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleBlob {
+    pub digest: String,
+    pub size: u64,
+    pub media_type: String,
+}
+
+/// `index.json`, the first entry of a model bundle. It names the model and
+/// lists every blob the manifest (stored as `manifest.json`) references.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleIndex {
+    pub format_version: u32,
+    pub provider: String,
+    pub library: String,
+    pub name: String,
+    pub version: String,
+    /// Config blob first, then the layers.
+    pub blobs: Vec<BundleBlob>,
+    pub total_bytes: u64,
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -10,9 +11,13 @@ pub struct ChatMessage {
     pub tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<serde_json::Value>>,
+    /// How the user answered the `tool_calls` that needed approval, keyed by
+    /// call id. History only; the orchestrator drops it from requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_approvals: Option<BTreeMap<String, String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ChatRequest {
     pub model: String,
     pub session_id: Option<String>,
@@ -35,9 +40,322 @@ pub struct ChatRequest {
     pub tools: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_last_n: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirostat: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirostat_tau: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirostat_eta: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_multiplier: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_base: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_allowed_length: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_penalty_last_n: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logit_bias: Option<serde_json::Value>,
     pub stream: bool,
 }
 
+/// Sampler settings for a chat, named as llama-server expects them. Unset
+/// fields fall back to the model's defaults, then to the app's.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplingParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    /// Tokens considered by `repeat_penalty`; -1 means the whole context.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_last_n: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    /// 0 disables Mirostat, 1 and 2 select the algorithm version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirostat: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirostat_tau: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirostat_eta: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_multiplier: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_base: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_allowed_length: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_penalty_last_n: Option<i32>,
+    /// `{"token id or text": bias}` or `[[token, bias], ...]`, passed through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logit_bias: Option<serde_json::Value>,
+}
+
+impl SamplingParams {
+    /// Fields set here win; the rest come from `fallback`.
+    pub fn or(self, fallback: &SamplingParams) -> SamplingParams {
+        SamplingParams {
+            temperature: self.temperature.or(fallback.temperature),
+            top_p: self.top_p.or(fallback.top_p),
+            top_k: self.top_k.or(fallback.top_k),
+            min_p: self.min_p.or(fallback.min_p),
+            repeat_penalty: self.repeat_penalty.or(fallback.repeat_penalty),
+            repeat_last_n: self.repeat_last_n.or(fallback.repeat_last_n),
+            presence_penalty: self.presence_penalty.or(fallback.presence_penalty),
+            frequency_penalty: self.frequency_penalty.or(fallback.frequency_penalty),
+            seed: self.seed.or(fallback.seed),
+            stop: self.stop.or_else(|| fallback.stop.clone()),
+            mirostat: self.mirostat.or(fallback.mirostat),
+            mirostat_tau: self.mirostat_tau.or(fallback.mirostat_tau),
+            mirostat_eta: self.mirostat_eta.or(fallback.mirostat_eta),
+            dry_multiplier: self.dry_multiplier.or(fallback.dry_multiplier),
+            dry_base: self.dry_base.or(fallback.dry_base),
+            dry_allowed_length: self.dry_allowed_length.or(fallback.dry_allowed_length),
+            dry_penalty_last_n: self.dry_penalty_last_n.or(fallback.dry_penalty_last_n),
+            logit_bias: self.logit_bias.or_else(|| fallback.logit_bias.clone()),
+        }
+    }
+
+    /// Sets the optional samplers the request leaves open. Temperature,
+    /// top-p and top-k are plain fields of the request and are not touched.
+    pub fn fill_request(&self, request: &mut ChatRequest) {
+        request.min_p = request.min_p.or(self.min_p);
+        request.repeat_penalty = request.repeat_penalty.or(self.repeat_penalty);
+        request.repeat_last_n = request.repeat_last_n.or(self.repeat_last_n);
+        request.presence_penalty = request.presence_penalty.or(self.presence_penalty);
+        request.frequency_penalty = request.frequency_penalty.or(self.frequency_penalty);
+        request.seed = request.seed.or(self.seed);
+        if request.stop.is_none() {
+            request.stop = self.stop.clone().filter(|stop| !stop.is_empty());
+        }
+        request.mirostat = request.mirostat.or(self.mirostat);
+        request.mirostat_tau = request.mirostat_tau.or(self.mirostat_tau);
+        request.mirostat_eta = request.mirostat_eta.or(self.mirostat_eta);
+        request.dry_multiplier = request.dry_multiplier.or(self.dry_multiplier);
+        request.dry_base = request.dry_base.or(self.dry_base);
+        request.dry_allowed_length = request.dry_allowed_length.or(self.dry_allowed_length);
+        request.dry_penalty_last_n = request.dry_penalty_last_n.or(self.dry_penalty_last_n);
+        if request.logit_bias.is_none() {
+            request.logit_bias = self.logit_bias.clone();
+        }
+    }
+
+    /// Collects every out-of-range value so the UI can show them together.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+
+        check(
+            self.temperature.is_none_or(|t| (0.0..=5.0).contains(&t)),
+            "temperature must be between 0 and 5",
+        );
+        check(
+            self.top_p.is_none_or(|p| (0.0..=1.0).contains(&p)),
+            "top_p must be between 0 and 1",
+        );
+        check(
+            self.top_k.is_none_or(|k| k >= 0),
+            "top_k cannot be negative",
+        );
+        check(
+            self.min_p.is_none_or(|p| (0.0..=1.0).contains(&p)),
+            "min_p must be between 0 and 1",
+        );
+        check(
+            self.repeat_penalty.is_none_or(|p| p >= 0.0),
+            "repeat_penalty cannot be negative",
+        );
+        check(
+            self.repeat_last_n.is_none_or(|n| n >= -1),
+            "repeat_last_n must be -1 or greater",
+        );
+        for (name, value) in [
+            ("presence_penalty", self.presence_penalty),
+            ("frequency_penalty", self.frequency_penalty),
+        ] {
+            check(
+                value.is_none_or(|p| (-2.0..=2.0).contains(&p)),
+                &format!("{} must be between -2 and 2", name),
+            );
+        }
+        check(
+            self.stop
+                .as_ref()
+                .is_none_or(|stop| stop.iter().all(|s| !s.is_empty())),
+            "stop sequences cannot be empty",
+        );
+        check(
+            self.mirostat.is_none_or(|m| m <= 2),
+            "mirostat must be 0, 1 or 2",
+        );
+        check(
+            self.mirostat_tau.is_none_or(|t| t >= 0.0),
+            "mirostat_tau cannot be negative",
+        );
+        check(
+            self.mirostat_eta.is_none_or(|e| e > 0.0),
+            "mirostat_eta must be greater than 0",
+        );
+        check(
+            self.dry_multiplier.is_none_or(|m| m >= 0.0),
+            "dry_multiplier cannot be negative",
+        );
+        check(
+            self.dry_base.is_none_or(|b| b >= 1.0),
+            "dry_base must be at least 1",
+        );
+        check(
+            self.dry_allowed_length.is_none_or(|n| n >= 0),
+            "dry_allowed_length cannot be negative",
+        );
+        check(
+            self.dry_penalty_last_n.is_none_or(|n| n >= -1),
+            "dry_penalty_last_n must be -1 or greater",
+        );
+        check(
+            self.logit_bias
+                .as_ref()
+                .is_none_or(|bias| bias.is_object() || bias.is_array()),
+            "logit_bias must be an object or an array of pairs",
+        );
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+/// One item of a streamed chat completion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatStreamEvent {
+    /// Content, or reasoning wrapped in `<think>` tags.
+    Text(String),
+    /// One `delta.tool_calls` entry; fragments of the same call share `index`.
+    ToolCallDelta(serde_json::Value),
+    Error(String),
+}
+
+/// Limits of the agentic tool loop in `ChatOrchestrator::process`. Sent with
+/// each message, with `AppConfig::tool_loop_policy` as the fallback.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolLoopPolicy {
+    /// Model turns that may call tools before the answer is forced.
+    pub max_iterations: usize,
+    /// Times one tool may be called in a single user turn.
+    pub max_calls_per_tool: usize,
+    /// Tokens of each tool result kept in the context.
+    pub result_token_budget: usize,
+    /// Caps the user's temperature during tool turns; `None` leaves it as is.
+    /// A tool turn that calls no tool is the answer, so this applies to it too.
+    pub max_temperature: Option<f32>,
+    pub top_p: f32,
+    pub top_k: i32,
+}
+
+impl Default for ToolLoopPolicy {
+    fn default() -> Self {
+        Self {
+            max_iterations: 3,
+            max_calls_per_tool: 2,
+            result_token_budget: 512,
+            max_temperature: Some(0.5),
+            top_p: 0.95,
+            top_k: 40,
+        }
+    }
+}
+
+impl ToolLoopPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_calls_per_tool == 0 {
+            return Err("maxCallsPerTool must be greater than 0".to_string());
+        }
+        if self.result_token_budget == 0 {
+            return Err("resultTokenBudget must be greater than 0".to_string());
+        }
+        if let Some(max_temperature) = self.max_temperature {
+            if !(0.0..=2.0).contains(&max_temperature) {
+                return Err("maxTemperature must be between 0 and 2".to_string());
+            }
+        }
+        if !(self.top_p > 0.0 && self.top_p <= 1.0) {
+            return Err("topP must be in (0, 1]".to_string());
+        }
+        if self.top_k < 0 {
+            return Err("topK cannot be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// Temperature used for tool turns given the one the user asked for.
+    pub fn tool_temperature(&self, requested: f32) -> f32 {
+        self.max_temperature
+            .map_or(requested, |max| requested.min(max))
+    }
+
+    /// Sampling for tool turns: the chat's own, with this policy's top-p,
+    /// top-k and temperature cap.
+    pub fn tool_sampling(&self, sampling: &SamplingParams) -> SamplingParams {
+        SamplingParams {
+            temperature: sampling.temperature.map(|t| self.tool_temperature(t)),
+            top_p: Some(self.top_p),
+            top_k: Some(self.top_k),
+            ..sampling.clone()
+        }
+    }
+}
+
+/// How one chat turn is generated, as sent with `send_message` and
+/// `chat_action_regenerate`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatTurnOptions {
+    /// A loaded model to route to; `None` uses the default one.
+    #[serde(default)]
+    pub model: Option<String>,
+    pub max_tokens: i32,
+    /// Unset fields fall back to the model's defaults.
+    #[serde(default)]
+    pub sampling: SamplingParams,
+    /// `None` falls back to `AppConfig::tool_loop_policy`.
+    #[serde(default)]
+    pub tool_loop_policy: Option<ToolLoopPolicy>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ChatChoice {
     pub message: ChatMessage,
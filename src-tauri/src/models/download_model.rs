@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use super::ModelInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    Queued,
+    Downloading,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl DownloadStatus {
+    /// Jobs that still hold a place in the queue and are persisted across launches.
+    pub fn is_active(self) -> bool {
+        matches!(self, Self::Queued | Self::Downloading | Self::Paused)
+    }
+}
+
+/// One model pull tracked by the download manager.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadJob {
+    pub id: String,
+    pub model_reference: String,
+    pub models_root: String,
+    pub status: DownloadStatus,
+    /// Bytes of the whole model (all blobs) on disk so far.
+    #[serde(default)]
+    pub downloaded_bytes: u64,
+    /// 0 until the manifest or the first response tells us.
+    #[serde(default)]
+    pub total_bytes: u64,
+    /// Digest of the registry blob or name of the HF file being fetched.
+    #[serde(default)]
+    pub current_blob: Option<String>,
+    /// Temp files written by this job, removed when it is cancelled.
+    #[serde(default)]
+    pub partial_files: Vec<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Set once the job completes; not persisted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelInfo>,
+}
+
+impl DownloadJob {
+    pub fn new(id: String, model_reference: String, models_root: String) -> Self {
+        Self {
+            id,
+            model_reference,
+            models_root,
+            status: DownloadStatus::Queued,
+            downloaded_bytes: 0,
+            total_bytes: 0,
+            current_blob: None,
+            partial_files: Vec::new(),
+            error: None,
+            model: None,
+        }
+    }
+}
+
+/// Progress reported by a running pull.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    /// Digest of the registry blob or name of the HF file.
+    pub blob: String,
+    pub blob_downloaded: u64,
+    pub blob_total: u64,
+    /// Totals over every blob of the model.
+    pub downloaded: u64,
+    pub total: u64,
+    /// Temp file the blob is written to; chunked downloads add `.N` siblings.
+    pub partial_path: Option<String>,
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One entry of the GGUF tensor info table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GgufTensorInfo {
+    pub name: String,
+    /// Dimensions as stored in the file (innermost first).
+    pub dims: Vec<u64>,
+    /// ggml type name, e.g. `Q4_K` or `F16`.
+    pub dtype: String,
+    /// Offset from the start of the tensor data section.
+    pub offset: u64,
+    /// `None` for ggml types this reader doesn't know the block size of.
+    pub size_bytes: Option<u64>,
+}
+
+impl GgufTensorInfo {
+    pub fn element_count(&self) -> u64 {
+        self.dims.iter().product()
+    }
+}
+
+/// Model-level facts read from a GGUF header.
+///
+/// The typed fields come from `general.*` and `<arch>.*` keys; `metadata`
+/// keeps all of those keys as raw JSON for anything not covered here.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GgufInfo {
+    pub version: u32,
+    pub architecture: Option<String>,
+    pub name: Option<String>,
+    /// `general.parameter_count`, or the sum of tensor elements when absent.
+    pub parameter_count: Option<u64>,
+    pub block_count: Option<u64>,
+    pub embedding_length: Option<u64>,
+    pub feed_forward_length: Option<u64>,
+    pub head_count: Option<u64>,
+    pub head_count_kv: Option<u64>,
+    pub key_length: Option<u64>,
+    pub value_length: Option<u64>,
+    /// Context length the model was trained with.
+    pub context_length: Option<u64>,
+    pub rope_freq_base: Option<f64>,
+    pub rope_dimension_count: Option<u64>,
+    pub rope_scaling_type: Option<String>,
+    pub rope_scaling_factor: Option<f64>,
+    pub file_type: Option<u32>,
+    /// Quantization label derived from `general.file_type`, e.g. `Q4_K_M`.
+    pub quantization: Option<String>,
+    /// Byte offset of the tensor data section.
+    pub data_offset: u64,
+    /// Sum of known tensor sizes.
+    pub tensor_bytes: u64,
+    #[serde(default)]
+    pub tensors: Vec<GgufTensorInfo>,
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+}
+
+impl GgufInfo {
+    /// Drops the tensor table except `output.weight` and `token_embd.weight`,
+    /// which carry the vocabulary size. Enough for listings and caches.
+    pub fn without_tensor_table(mut self) -> Self {
+        self.tensors
+            .retain(|t| t.name == "output.weight" || t.name == "token_embd.weight");
+        self
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use super::ModelFit;
+
+/// One downloadable GGUF model in a Hugging Face repository. Split models
+/// (`-00001-of-00003.gguf`) are listed once with all their parts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HfGgufFile {
+    /// Path to use as the download selector; the first part for split models.
+    pub filename: String,
+    /// Every file of the model in order; only `filename` when not split.
+    pub parts: Vec<String>,
+    /// Sum over all parts; 0 when the API did not report sizes.
+    pub size_bytes: u64,
+    /// Quant label parsed from the file name, e.g. `Q4_K_M` or `IQ3_XS`.
+    pub quantization: Option<String>,
+    /// `None` when the size is unknown.
+    pub fit: Option<ModelFit>,
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// How an imported file gets into `blobs/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Leaves the original in place and shares its disk space; falls back to
+    /// `Copy` when the file is on another filesystem.
+    #[default]
+    HardLink,
+    Copy,
+    /// Renames the original into the library, copying across filesystems.
+    Move,
+}
@@ -0,0 +1,391 @@
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever a field changes meaning; stored configs carry the version
+/// they were written with.
+pub const LAUNCH_PARAMS_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlashAttention {
+    On,
+    Off,
+    Auto,
+}
+
+impl FlashAttention {
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            FlashAttention::On => "on",
+            FlashAttention::Off => "off",
+            FlashAttention::Auto => "auto",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KvCacheType {
+    F32,
+    F16,
+    Bf16,
+    Q8_0,
+    Q4_0,
+    Q4_1,
+    Iq4Nl,
+    Q5_0,
+    Q5_1,
+}
+
+impl KvCacheType {
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            KvCacheType::F32 => "f32",
+            KvCacheType::F16 => "f16",
+            KvCacheType::Bf16 => "bf16",
+            KvCacheType::Q8_0 => "q8_0",
+            KvCacheType::Q4_0 => "q4_0",
+            KvCacheType::Q4_1 => "q4_1",
+            KvCacheType::Iq4Nl => "iq4_nl",
+            KvCacheType::Q5_0 => "q5_0",
+            KvCacheType::Q5_1 => "q5_1",
+        }
+    }
+
+    /// (elements per block, bytes per block) in the KV cache.
+    pub fn block_layout(self) -> (u64, u64) {
+        match self {
+            KvCacheType::F32 => (1, 4),
+            KvCacheType::F16 | KvCacheType::Bf16 => (1, 2),
+            KvCacheType::Q8_0 => (32, 34),
+            KvCacheType::Q4_0 | KvCacheType::Iq4Nl => (32, 18),
+            KvCacheType::Q4_1 => (32, 20),
+            KvCacheType::Q5_0 => (32, 22),
+            KvCacheType::Q5_1 => (32, 24),
+        }
+    }
+
+    pub fn is_quantized(self) -> bool {
+        !matches!(
+            self,
+            KvCacheType::F32 | KvCacheType::F16 | KvCacheType::Bf16
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RopeScaling {
+    None,
+    Linear,
+    Yarn,
+}
+
+impl RopeScaling {
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            RopeScaling::None => "none",
+            RopeScaling::Linear => "linear",
+            RopeScaling::Yarn => "yarn",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoraAdapter {
+    pub path: String,
+    /// Passed with `--lora-scaled` when set, plain `--lora` otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+}
+
+/// Optional llama-server flags beyond the basics on `LlamaCppConfig`.
+///
+/// Every `None`/`false`/empty field leaves llama-server's own default in place.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchParams {
+    pub version: u32,
+    /// `--jinja`; required for tool calling, so on unless explicitly disabled.
+    pub jinja: bool,
+    pub flash_attn: Option<FlashAttention>,
+    pub cache_type_k: Option<KvCacheType>,
+    pub cache_type_v: Option<KvCacheType>,
+    pub threads: Option<u32>,
+    pub threads_batch: Option<u32>,
+    pub batch_size: Option<u32>,
+    pub ubatch_size: Option<u32>,
+    pub mlock: bool,
+    pub no_mmap: bool,
+    pub rope_scaling: Option<RopeScaling>,
+    pub rope_scale: Option<f32>,
+    pub rope_freq_base: Option<f32>,
+    pub yarn_orig_ctx: Option<u32>,
+    /// Fraction of the model per GPU, e.g. `[3.0, 1.0]`.
+    pub tensor_split: Vec<f32>,
+    pub main_gpu: Option<u32>,
+    pub draft_model: Option<String>,
+    pub draft_n_gpu_layers: Option<i32>,
+    pub draft_max: Option<u32>,
+    pub draft_min: Option<u32>,
+    pub lora: Vec<LoraAdapter>,
+    pub api_key: Option<String>,
+    /// Appended verbatim after everything else.
+    pub extra_args: Vec<String>,
+}
+
+impl Default for LaunchParams {
+    fn default() -> Self {
+        Self {
+            version: LAUNCH_PARAMS_VERSION,
+            jinja: true,
+            flash_attn: None,
+            cache_type_k: None,
+            cache_type_v: None,
+            threads: None,
+            threads_batch: None,
+            batch_size: None,
+            ubatch_size: None,
+            mlock: false,
+            no_mmap: false,
+            rope_scaling: None,
+            rope_scale: None,
+            rope_freq_base: None,
+            yarn_orig_ctx: None,
+            tensor_split: Vec::new(),
+            main_gpu: None,
+            draft_model: None,
+            draft_n_gpu_layers: None,
+            draft_max: None,
+            draft_min: None,
+            lora: Vec::new(),
+            api_key: None,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl LaunchParams {
+    /// Flags for the typed fields, in a fixed order.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut push = |flag: &str, value: String| {
+            args.push(flag.to_string());
+            args.push(value);
+        };
+
+        if let Some(fa) = self.flash_attn {
+            push("--flash-attn", fa.as_arg().to_string());
+        }
+        if let Some(kind) = self.cache_type_k {
+            push("--cache-type-k", kind.as_arg().to_string());
+        }
+        if let Some(kind) = self.cache_type_v {
+            push("--cache-type-v", kind.as_arg().to_string());
+        }
+        if let Some(n) = self.threads {
+            push("--threads", n.to_string());
+        }
+        if let Some(n) = self.threads_batch {
+            push("--threads-batch", n.to_string());
+        }
+        if let Some(n) = self.batch_size {
+            push("--batch-size", n.to_string());
+        }
+        if let Some(n) = self.ubatch_size {
+            push("--ubatch-size", n.to_string());
+        }
+        if let Some(scaling) = self.rope_scaling {
+            push("--rope-scaling", scaling.as_arg().to_string());
+        }
+        if let Some(scale) = self.rope_scale {
+            push("--rope-scale", scale.to_string());
+        }
+        if let Some(base) = self.rope_freq_base {
+            push("--rope-freq-base", base.to_string());
+        }
+        if let Some(n) = self.yarn_orig_ctx {
+            push("--yarn-orig-ctx", n.to_string());
+        }
+        if !self.tensor_split.is_empty() {
+            let split: Vec<String> = self.tensor_split.iter().map(|v| v.to_string()).collect();
+            push("--tensor-split", split.join(","));
+        }
+        if let Some(gpu) = self.main_gpu {
+            push("--main-gpu", gpu.to_string());
+        }
+        if let Some(path) = &self.draft_model {
+            push("--model-draft", path.clone());
+        }
+        if let Some(n) = self.draft_n_gpu_layers {
+            push("--gpu-layers-draft", n.to_string());
+        }
+        if let Some(n) = self.draft_max {
+            push("--draft-max", n.to_string());
+        }
+        if let Some(n) = self.draft_min {
+            push("--draft-min", n.to_string());
+        }
+        if let Some(key) = &self.api_key {
+            push("--api-key", key.clone());
+        }
+
+        if self.mlock {
+            args.push("--mlock".to_string());
+        }
+        if self.no_mmap {
+            args.push("--no-mmap".to_string());
+        }
+        for adapter in &self.lora {
+            match adapter.scale {
+                Some(scale) => {
+                    args.push("--lora-scaled".to_string());
+                    args.push(adapter.path.clone());
+                    args.push(scale.to_string());
+                }
+                None => {
+                    args.push("--lora".to_string());
+                    args.push(adapter.path.clone());
+                }
+            }
+        }
+        args
+    }
+
+    /// Collects every problem instead of stopping at the first, so the UI can
+    /// show them together.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.version > LAUNCH_PARAMS_VERSION {
+            errors.push(format!(
+                "Launch parameters version {} is newer than supported version {}",
+                self.version, LAUNCH_PARAMS_VERSION
+            ));
+        }
+        for (name, value) in [
+            ("threads", self.threads),
+            ("threads_batch", self.threads_batch),
+            ("batch_size", self.batch_size),
+            ("ubatch_size", self.ubatch_size),
+        ] {
+            if value == Some(0) {
+                errors.push(format!("{} must be greater than 0", name));
+            }
+        }
+        if let (Some(batch), Some(ubatch)) = (self.batch_size, self.ubatch_size) {
+            if ubatch > batch {
+                errors.push(format!(
+                    "ubatch_size ({}) cannot exceed batch_size ({})",
+                    ubatch, batch
+                ));
+            }
+        }
+        if self.cache_type_v.is_some_and(KvCacheType::is_quantized)
+            && self.flash_attn != Some(FlashAttention::On)
+        {
+            errors.push("A quantized V cache requires flash_attn to be on".to_string());
+        }
+        if self.rope_scaling.is_none() && self.rope_scale.is_some() {
+            errors.push("rope_scale requires rope_scaling".to_string());
+        }
+        if self.yarn_orig_ctx.is_some() && self.rope_scaling != Some(RopeScaling::Yarn) {
+            errors.push("yarn_orig_ctx requires rope_scaling = yarn".to_string());
+        }
+        if self.rope_scale.is_some_and(|v| !v.is_finite() || v <= 0.0)
+            || self
+                .rope_freq_base
+                .is_some_and(|v| !v.is_finite() || v <= 0.0)
+        {
+            errors.push("rope_scale and rope_freq_base must be positive".to_string());
+        }
+        if !self.tensor_split.is_empty() {
+            if self.tensor_split.iter().any(|v| !v.is_finite() || *v < 0.0)
+                || self.tensor_split.iter().all(|v| *v == 0.0)
+            {
+                errors.push(
+                    "tensor_split needs non-negative values with at least one above 0".to_string(),
+                );
+            }
+            if let Some(gpu) = self.main_gpu {
+                if gpu as usize >= self.tensor_split.len() {
+                    errors.push(format!(
+                        "main_gpu {} is outside tensor_split ({} devices)",
+                        gpu,
+                        self.tensor_split.len()
+                    ));
+                }
+            }
+        }
+        if self.draft_model.is_none()
+            && (self.draft_n_gpu_layers.is_some()
+                || self.draft_max.is_some()
+                || self.draft_min.is_some())
+        {
+            errors.push("Draft options require draft_model".to_string());
+        }
+        if let (Some(min), Some(max)) = (self.draft_min, self.draft_max) {
+            if min > max {
+                errors.push(format!(
+                    "draft_min ({}) cannot exceed draft_max ({})",
+                    min, max
+                ));
+            }
+        }
+        if self.draft_model.as_deref().is_some_and(str::is_empty) {
+            errors.push("draft_model cannot be empty".to_string());
+        }
+        for adapter in &self.lora {
+            if adapter.path.trim().is_empty() {
+                errors.push("LoRA adapter path cannot be empty".to_string());
+            }
+            if adapter.scale.is_some_and(|s| !s.is_finite()) {
+                errors.push(format!("LoRA scale for {} must be finite", adapter.path));
+            }
+        }
+        if self.api_key.as_deref().is_some_and(|k| k.trim().is_empty()) {
+            errors.push("api_key cannot be empty".to_string());
+        }
+
+        // Raw args must not fight the flags we already emit.
+        let reserved = reserved_flags(&self.to_args());
+        for arg in &self.extra_args {
+            let flag = arg.split('=').next().unwrap_or(arg);
+            if reserved.iter().any(|r| r == flag) {
+                errors.push(format!(
+                    "extra_args cannot set {}; it is managed by the launcher",
+                    flag
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Flags always set from `LlamaCppConfig`, with their short aliases.
+const BASE_FLAGS: &[&str] = &[
+    "-m",
+    "--model",
+    "--port",
+    "-c",
+    "--ctx-size",
+    "-np",
+    "--parallel",
+    "-ngl",
+    "--gpu-layers",
+    "--n-gpu-layers",
+    "--chat-template",
+    "--chat-template-file",
+];
+
+fn reserved_flags(typed_args: &[String]) -> Vec<String> {
+    BASE_FLAGS
+        .iter()
+        .map(|flag| flag.to_string())
+        .chain(typed_args.iter().filter(|a| a.starts_with('-')).cloned())
+        .collect()
+}
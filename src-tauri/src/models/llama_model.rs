@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::LaunchParams;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ModelId(pub String);
 
@@ -21,18 +23,292 @@ pub struct LlamaCppConfig {
     pub chat_template: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_template_file: Option<String>,
+    /// When set and `port` is busy, the first free port in this range is used instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port_range: Option<PortRange>,
+    /// Restart automatically after a crash; `None` leaves a crashed model down.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<RestartPolicy>,
+    #[serde(default)]
+    pub launch_params: LaunchParams,
+}
+
+/// Explicit `start_llama_server` arguments; each one set replaces the preset's value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchOverrides {
+    pub binary_path: Option<String>,
+    pub model_path: Option<String>,
+    pub port: Option<u16>,
+    pub ctx_size: Option<u32>,
+    pub n_gpu_layers: Option<i32>,
+    pub parallel: Option<u32>,
+    pub chat_template: Option<String>,
+    pub chat_template_file: Option<String>,
+    pub port_range: Option<PortRange>,
+    pub restart_policy: Option<RestartPolicy>,
+    pub launch_params: Option<LaunchParams>,
+}
+
+impl LaunchOverrides {
+    /// Setting either chat template field replaces both, so a preset's file
+    /// does not win over an inline template.
+    pub fn apply_to(self, config: &mut LlamaCppConfig) {
+        if let Some(binary_path) = self.binary_path {
+            config.llama_cpp_path = binary_path;
+        }
+        if let Some(model_path) = self.model_path {
+            config.model_path = model_path;
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(ctx_size) = self.ctx_size {
+            config.ctx_size = ctx_size;
+        }
+        if let Some(n_gpu_layers) = self.n_gpu_layers {
+            config.n_gpu_layers = n_gpu_layers;
+        }
+        if let Some(parallel) = self.parallel {
+            config.parallel = parallel;
+        }
+        if self.chat_template.is_some() || self.chat_template_file.is_some() {
+            config.chat_template = self.chat_template;
+            config.chat_template_file = self.chat_template_file;
+        }
+        if self.port_range.is_some() {
+            config.port_range = self.port_range;
+        }
+        if self.restart_policy.is_some() {
+            config.restart_policy = self.restart_policy;
+        }
+        if let Some(launch_params) = self.launch_params {
+            config.launch_params = launch_params;
+        }
+    }
+}
+
+/// How the supervisor retries a model whose llama-server died unexpectedly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// Attempts allowed until the model is started or stopped again by the user.
+    pub max_restarts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Doubles per attempt (1-based), capped at `max_backoff_ms`.
+    pub fn backoff_ms(&self, attempt: u32) -> u64 {
+        let factor = 1u64
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        self.initial_backoff_ms
+            .saturating_mul(factor)
+            .min(self.max_backoff_ms)
+    }
+}
+
+/// Inclusive range of ports llama-server may fall back to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of llama-server output, as kept in the log buffer and streamed to the UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerLogLine {
+    pub model_id: ModelId,
+    pub stream: LogStream,
+    pub line: String,
+    pub timestamp_ms: u64,
+}
+
+/// Startup stages of llama-server, in the order they happen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadPhase {
+    Spawning,
+    /// Only known from `/health` answering 503; no log line seen yet.
+    LoadingModel,
+    LoadingMetadata,
+    LoadingTensors,
+    AllocatingContext,
+    WarmingUp,
+    Listening,
+    Ready,
+}
+
+impl LoadPhase {
+    /// Rough position in the startup sequence; llama-server reports no finer progress.
+    pub fn percent(self) -> f32 {
+        match self {
+            LoadPhase::Spawning => 0.0,
+            LoadPhase::LoadingModel => 5.0,
+            LoadPhase::LoadingMetadata => 10.0,
+            LoadPhase::LoadingTensors => 30.0,
+            LoadPhase::AllocatingContext => 70.0,
+            LoadPhase::WarmingUp => 85.0,
+            LoadPhase::Listening => 95.0,
+            LoadPhase::Ready => 100.0,
+        }
+    }
+}
+
+/// Startup progress event streamed to the UI while a model loads.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadProgress {
+    pub model_id: ModelId,
+    pub phase: LoadPhase,
+    pub percent: f32,
+    pub n_ctx: Option<u32>,
+    pub layers_offloaded: Option<u32>,
+    pub layers_total: Option<u32>,
+}
+
+/// Why a llama-server could not be brought up.
+///
+/// Serialized with a `kind` tag so the frontend can tell a port clash apart
+/// from the model itself failing to load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LlamaServerError {
+    PortInUse {
+        port: u16,
+        pid: Option<u32>,
+        process_name: Option<String>,
+    },
+    NoFreePort {
+        start: u16,
+        end: u16,
+    },
+    InvalidLaunchParams {
+        errors: Vec<String>,
+    },
+    /// The process died before passing its healthcheck; `log_tail` holds its last output lines.
+    ExitedEarly {
+        status: String,
+        log_tail: Vec<String>,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl std::fmt::Display for LlamaServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LlamaServerError::PortInUse {
+                port,
+                pid,
+                process_name,
+            } => match (pid, process_name) {
+                (Some(pid), Some(name)) => {
+                    write!(f, "Port {} is already in use by {} (pid {})", port, name, pid)
+                }
+                (Some(pid), None) => write!(f, "Port {} is already in use by pid {}", port, pid),
+                _ => write!(f, "Port {} is already in use", port),
+            },
+            LlamaServerError::NoFreePort { start, end } => {
+                write!(f, "No free port in range {}-{}", start, end)
+            }
+            LlamaServerError::InvalidLaunchParams { errors } => {
+                write!(f, "Invalid launch parameters: {}", errors.join("; "))
+            }
+            LlamaServerError::ExitedEarly { status, log_tail } => {
+                write!(f, "llama-server exited early with status: {}", status)?;
+                if !log_tail.is_empty() {
+                    write!(f, "\n{}", log_tail.join("\n"))?;
+                }
+                Ok(())
+            }
+            LlamaServerError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for LlamaServerError {
+    fn from(message: String) -> Self {
+        LlamaServerError::Failed { message }
+    }
 }
 
 pub type ActiveModel = Option<ModelId>;
 
 pub enum ModelState {
     Stopped,
-    Starting,
+    Starting {
+        port: u16,
+    },
     Running {
         port: u16,
         pid: u32,
-        config: LlamaCppConfig,
+        config: Box<LlamaCppConfig>,
+    },
+    /// The process exited without being asked to; `last_log` is its final output.
+    Crashed {
+        exit_status: String,
+        last_log: Vec<String>,
+    },
+}
+
+/// Lifecycle notifications from the model supervisor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModelEvent {
+    Crashed {
+        model_id: ModelId,
+        exit_status: String,
+        last_log: Vec<String>,
     },
+    Restarting {
+        model_id: ModelId,
+        attempt: u32,
+        delay_ms: u64,
+    },
+    Restarted {
+        model_id: ModelId,
+        pid: u32,
+    },
+    RestartFailed {
+        model_id: ModelId,
+        attempt: u32,
+        error: String,
+    },
+    RestartsExhausted {
+        model_id: ModelId,
+        attempts: u32,
+    },
+}
+
+/// A llama-server instance as reported to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningModel {
+    pub model_id: ModelId,
+    pub port: u16,
+    pub pid: u32,
+    /// Target for chat requests that do not name a model.
+    pub is_default: bool,
+    pub config: LlamaCppConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
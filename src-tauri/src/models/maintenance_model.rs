@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+/// A file under `blobs/` and its size.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobFile {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeleteModelReport {
+    pub model_id: String,
+    /// Nothing was touched; the report lists what would be removed.
+    pub dry_run: bool,
+    pub manifest_path: String,
+    /// Blobs of the deleted model that no other manifest references.
+    pub removed_blobs: Vec<BlobFile>,
+    /// Leftover `.partial` files not owned by an active download.
+    pub removed_partials: Vec<BlobFile>,
+    /// Digests of the deleted model that other manifests still use.
+    pub shared_blobs: Vec<String>,
+    pub bytes_reclaimed: u64,
+    /// Problems that made the cleanup more conservative, e.g. unreadable manifests.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlobStatus {
+    Ok,
+    Missing,
+    SizeMismatch,
+    /// Only reported when the audit re-hashes blobs.
+    DigestMismatch,
+    /// Re-hashing failed; `BlobCheck::error` says why.
+    Unreadable,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlobCheck {
+    pub digest: String,
+    /// `None` when the blob is missing.
+    pub path: Option<String>,
+    pub expected_size: u64,
+    pub actual_size: Option<u64>,
+    pub status: BlobStatus,
+    /// Set with `BlobStatus::Unreadable`.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestCheck {
+    /// `provider:name:version`, as in `ModelInfo.full_identifier`.
+    pub model_id: String,
+    pub manifest_path: String,
+    /// Config blob first, then the layers.
+    pub blobs: Vec<BlobCheck>,
+    /// Set when the manifest itself could not be read.
+    pub error: Option<String>,
+    pub healthy: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelsDirectoryReport {
+    pub models_root: String,
+    pub rehashed: bool,
+    pub manifests: Vec<ManifestCheck>,
+    /// Blobs no manifest references.
+    pub orphan_blobs: Vec<BlobFile>,
+    /// Leftover `.partial` files, including those of downloads in progress.
+    pub partial_files: Vec<BlobFile>,
+    /// Everything under `blobs/`.
+    pub total_bytes: u64,
+    /// Orphans plus partial files, i.e. what a cleanup could reclaim.
+    pub reclaimable_bytes: u64,
+}
@@ -39,8 +39,8 @@ pub struct ModelManifest {
     #[serde(rename = "schemaVersion")]
     pub schema_version: u32,
 
-    /// The overall media type of the manifest.
-    #[serde(rename = "mediaType")]
+    /// The overall media type of the manifest (optional in OCI manifests).
+    #[serde(rename = "mediaType", default)]
     pub media_type: String,
 
     /// Core configuration for this model version.
@@ -74,6 +74,30 @@ pub struct ModelInfo {
     #[serde(default)]
     pub tokenizer_metadata: Option<serde_json::Value>,
 
+    /// Architecture, quantization and tensor table read from the GGUF header (models.json).
+    #[serde(default)]
+    pub gguf: Option<super::GgufInfo>,
+
+    /// Default system prompt from an Ollama `system` layer.
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+
+    /// Go prompt template from an Ollama `template` layer.
+    #[serde(default)]
+    pub template: Option<String>,
+
+    /// Sampling and runtime defaults from an Ollama `params` layer.
+    #[serde(default)]
+    pub params: Option<super::ModelParams>,
+
+    /// Stop sequences from the `params` layer.
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+
+    /// Text of the model's `license` layers.
+    #[serde(default)]
+    pub license: Option<String>,
+
     /// Absolute path to the model binary (blob) on the local filesystem.
     pub model_file_path: Option<String>,
 
@@ -23,18 +23,66 @@ pub struct McpServerConfig {
     pub headers: Option<HashMap<String, String>>,
     pub tool_allowlist: Option<Vec<String>>,
     pub resource_allowlist: Option<Vec<String>>,
+    /// Policy for tools without an override; `None` allows them.
+    pub tool_approval: Option<ToolApproval>,
+    /// Per-tool policies, keyed by tool name.
+    pub tool_approval_overrides: Option<HashMap<String, ToolApproval>>,
+}
+
+impl McpServerConfig {
+    pub fn approval_for(&self, tool_name: &str) -> ToolApproval {
+        self.tool_approval_overrides
+            .as_ref()
+            .and_then(|overrides| overrides.get(tool_name))
+            .or(self.tool_approval.as_ref())
+            .copied()
+            .unwrap_or(ToolApproval::Allow)
+    }
+}
+
+/// Whether a tool call the model asks for runs right away, waits for the
+/// user, or is refused.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolApproval {
+    Allow,
+    Ask,
+    Deny,
+}
+
+/// The user's answer to an approval request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ToolApprovalDecision {
+    Approve,
+    /// Run the call with these arguments instead of the model's.
+    Edit {
+        arguments: serde_json::Value,
+    },
+    Reject {
+        #[serde(default)]
+        reason: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct McpConfig {
     pub servers: Vec<McpServerConfig>,
+    /// How many tool calls from one model turn may run at the same time.
+    #[serde(default = "default_max_parallel_tool_calls")]
+    pub max_parallel_tool_calls: usize,
+}
+
+fn default_max_parallel_tool_calls() -> usize {
+    4
 }
 
 impl Default for McpConfig {
     fn default() -> Self {
         Self {
             servers: Vec::new(),
+            max_parallel_tool_calls: default_max_parallel_tool_calls(),
         }
     }
 }
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use super::LaunchParams;
+
+/// Launch settings the estimate is computed for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEstimateOptions {
+    pub ctx_size: u32,
+    #[serde(default = "default_parallel")]
+    pub parallel: u32,
+    /// Negative means "all layers", as with `-ngl -1`.
+    pub n_gpu_layers: i32,
+    /// Cache types, ubatch size, flash attention and tensor split are read from here.
+    #[serde(default)]
+    pub launch_params: LaunchParams,
+    /// Usable VRAM per GPU. Empty means no budget: nothing is recommended.
+    #[serde(default)]
+    pub vram_budget_bytes: Vec<u64>,
+}
+
+fn default_parallel() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceMemoryEstimate {
+    /// `CPU`, or `GPU0`, `GPU1`, ...
+    pub device: String,
+    /// Layers (repeating blocks plus the output layer) placed on this device.
+    pub layers: u32,
+    pub weights_bytes: u64,
+    pub kv_cache_bytes: u64,
+    pub compute_bytes: u64,
+    pub total_bytes: u64,
+    pub budget_bytes: Option<u64>,
+    pub fits: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEstimate {
+    /// Repeating blocks plus the output layer, i.e. the `-ngl` value for a full offload.
+    pub layers_total: u32,
+    pub n_gpu_layers: u32,
+    /// CPU first, then one entry per GPU.
+    pub devices: Vec<DeviceMemoryEstimate>,
+    pub total_bytes: u64,
+    /// Largest `n_gpu_layers` whose GPU totals fit every budget; `None` without budgets.
+    pub recommended_n_gpu_layers: Option<u32>,
+    /// Whether every GPU fits its budget; `None` without budgets.
+    pub fits: Option<bool>,
+}
+
+/// Where a model could run, judged from its file size alone (before download).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelFit {
+    /// Fits entirely in free VRAM.
+    Gpu,
+    /// Needs layers split between VRAM and system RAM.
+    Partial,
+    /// No usable GPU memory, but fits in system RAM.
+    Cpu,
+    TooLarge,
+}
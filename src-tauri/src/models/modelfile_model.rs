@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use super::{ChatMessage, ChatRequest, ModelInfo, SamplingParams};
+
+/// Options from an Ollama `params` layer (Modelfile `PARAMETER` lines).
+/// Keys without a typed field are kept in `other`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Context window; used as the launch `ctx_size` when no preset sets one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ModelParams {
+    /// The samplers these parameters set. Ollama names the less common ones
+    /// like llama-server does, so they are read from `other`.
+    pub fn sampling(&self) -> SamplingParams {
+        let float = |key: &str| {
+            self.other
+                .get(key)
+                .and_then(|v| v.as_f64())
+                .map(|v| v as f32)
+        };
+        let int = |key: &str| self.other.get(key).and_then(|v| v.as_i64());
+        SamplingParams {
+            temperature: self.temperature,
+            top_p: self.top_p,
+            top_k: self.top_k,
+            min_p: self.min_p,
+            repeat_penalty: self.repeat_penalty,
+            repeat_last_n: int("repeat_last_n").map(|n| n as i32),
+            presence_penalty: float("presence_penalty"),
+            frequency_penalty: float("frequency_penalty"),
+            seed: self.seed,
+            mirostat: int("mirostat").and_then(|m| u8::try_from(m).ok()),
+            mirostat_tau: float("mirostat_tau"),
+            mirostat_eta: float("mirostat_eta"),
+            ..SamplingParams::default()
+        }
+    }
+}
+
+/// What a library model contributes to every chat request sent to it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelChatDefaults {
+    pub system_prompt: Option<String>,
+    /// The user's sampling defaults over the Modelfile parameters.
+    pub sampling: SamplingParams,
+}
+
+impl ModelChatDefaults {
+    /// `sampling` is what the user saved for the model (`ModelPresets::sampling`).
+    /// `None` when the model carries nothing to apply.
+    pub fn from_model(model: &ModelInfo, sampling: Option<&SamplingParams>) -> Option<Self> {
+        let mut modelfile = model.params.clone().unwrap_or_default().sampling();
+        if !model.stop_sequences.is_empty() {
+            modelfile.stop = Some(model.stop_sequences.clone());
+        }
+        let defaults = Self {
+            system_prompt: model.system_prompt.clone(),
+            sampling: sampling.cloned().unwrap_or_default().or(&modelfile),
+        };
+        (defaults != Self::default()).then_some(defaults)
+    }
+
+    /// Fills in what the request leaves open: a system message when the
+    /// conversation has none, stop sequences and the optional samplers.
+    /// Temperature, top-p, top-k and max tokens are always set by the caller.
+    pub fn apply(&self, request: &mut ChatRequest) {
+        if let Some(system_prompt) = &self.system_prompt {
+            if !request.messages.iter().any(|m| m.role == "system") {
+                request.messages.insert(
+                    0,
+                    ChatMessage {
+                        role: "system".to_string(),
+                        content: system_prompt.clone(),
+                        name: None,
+                        tool_call_id: None,
+                        tool_calls: None,
+                        tool_approvals: None,
+                    },
+                );
+            }
+        }
+        self.sampling.fill_request(request);
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{LaunchParams, RestartPolicy, SamplingParams};
+
+/// A named set of launch settings for one model.
+///
+/// Unset fields fall back to the app settings (binary path, port, context size)
+/// or to llama-server's own defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaunchPreset {
+    pub name: String,
+    #[serde(default)]
+    pub binary_path: Option<String>,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub ctx_size: Option<u32>,
+    #[serde(default)]
+    pub n_gpu_layers: Option<i32>,
+    #[serde(default)]
+    pub parallel: Option<u32>,
+    #[serde(default)]
+    pub chat_template: Option<String>,
+    #[serde(default)]
+    pub chat_template_file: Option<String>,
+    #[serde(default)]
+    pub restart_policy: Option<RestartPolicy>,
+    #[serde(default)]
+    pub launch_params: LaunchParams,
+}
+
+impl LaunchPreset {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if self.ctx_size == Some(0) {
+            return Err("ctx_size must be greater than 0".to_string());
+        }
+        if self.parallel == Some(0) {
+            return Err("parallel must be greater than 0".to_string());
+        }
+        self.launch_params
+            .validate()
+            .map_err(|errors| errors.join("; "))
+    }
+}
+
+/// Presets saved for a single model.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPresets {
+    /// Preset used when a launch names none.
+    #[serde(default)]
+    pub default_preset: Option<String>,
+    #[serde(default)]
+    pub presets: Vec<LaunchPreset>,
+    /// Chat sampling defaults for the model, whichever preset it runs with.
+    #[serde(default)]
+    pub sampling: Option<SamplingParams>,
+}
+
+impl ModelPresets {
+    pub fn get(&self, name: &str) -> Option<&LaunchPreset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+
+    /// The named preset, or the default one when `name` is `None`.
+    /// Only an unknown explicit name is an error.
+    pub fn resolve(&self, name: Option<&str>) -> Result<Option<&LaunchPreset>, String> {
+        match name {
+            Some(name) => self
+                .get(name)
+                .map(Some)
+                .ok_or_else(|| format!("Launch preset '{}' not found", name)),
+            None => Ok(self.default_preset.as_deref().and_then(|n| self.get(n))),
+        }
+    }
+
+    /// Replaces a preset with the same name, or appends it.
+    pub fn upsert(&mut self, preset: LaunchPreset) {
+        match self.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    /// Returns whether a preset was removed. Removing the default clears it.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.presets.len();
+        self.presets.retain(|p| p.name != name);
+        if self.default_preset.as_deref() == Some(name) {
+            self.default_preset = None;
+        }
+        self.presets.len() != before
+    }
+}
+
+/// Contents of `launchPresets.json`, stored next to `modelLibrary.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PresetLibrary {
+    /// Keyed by `ModelInfo.full_identifier`.
+    #[serde(default)]
+    pub models: BTreeMap<String, ModelPresets>,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// Credentials for one registry host, stored in `AppConfig::registry_credentials`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RegistryCredential {
+    /// Username and password (or a personal access token used as password).
+    Basic { username: String, password: String },
+    /// A ready-made bearer token sent as-is.
+    Token { token: String },
+}
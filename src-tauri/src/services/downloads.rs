@@ -0,0 +1,412 @@
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::broadcast;
+
+use crate::models::{DownloadJob, DownloadProgress, DownloadStatus, ModelInfo};
+
+/// Receives progress from a running pull.
+pub type DownloadProgressFn = Arc<dyn Fn(DownloadProgress) + Send + Sync>;
+
+/// Performs the pull for a job. Injected at startup because the real one
+/// needs the `AppHandle`; tests supply their own.
+pub type DownloadRunner = Arc<
+    dyn Fn(DownloadJob, DownloadProgressFn) -> BoxFuture<'static, Result<ModelInfo, String>>
+        + Send
+        + Sync,
+>;
+
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 1;
+
+/// Queue of model pulls with pause, resume and cancel.
+///
+/// Pausing aborts the pull task and keeps its `.partial` files, which the
+/// next run resumes with Range requests. Active jobs are written to
+/// `store_path` on every state change, so a restart picks the queue back up.
+#[derive(Clone)]
+pub struct DownloadManager {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    state: Mutex<ManagerState>,
+    runner: Mutex<Option<DownloadRunner>>,
+    events: broadcast::Sender<DownloadJob>,
+    store_path: Option<PathBuf>,
+    max_concurrent: usize,
+}
+
+#[derive(Default)]
+struct ManagerState {
+    jobs: Vec<DownloadJob>,
+    tasks: HashMap<String, RunningTask>,
+    /// Aborted tasks that may still be unwinding, keyed by job id. The next
+    /// run of the job waits for them before touching its partial files.
+    stopping: HashMap<String, tauri::async_runtime::JoinHandle<()>>,
+    next_run: u64,
+}
+
+/// A spawned pull. `run` tells a resumed run apart from the one it replaced,
+/// so a late progress report or result of the old run is ignored.
+struct RunningTask {
+    run: u64,
+    handle: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl DownloadManager {
+    /// Loads persisted jobs from `store_path`. Nothing runs until `start`.
+    pub fn new(store_path: Option<PathBuf>, max_concurrent: usize) -> Self {
+        let jobs = store_path
+            .as_deref()
+            .map(load_jobs)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|mut job| {
+                // Interrupted by the previous shutdown.
+                if job.status == DownloadStatus::Downloading {
+                    job.status = DownloadStatus::Queued;
+                }
+                job
+            })
+            .collect();
+        Self {
+            inner: Arc::new(Inner {
+                state: Mutex::new(ManagerState {
+                    jobs,
+                    ..Default::default()
+                }),
+                runner: Mutex::new(None),
+                events: broadcast::channel(64).0,
+                store_path,
+                max_concurrent: max_concurrent.max(1),
+            }),
+        }
+    }
+
+    /// Installs the runner and starts queued jobs, including persisted ones.
+    pub fn start(&self, runner: DownloadRunner) {
+        *self.inner.runner.lock().unwrap_or_else(|e| e.into_inner()) = Some(runner);
+        self.pump();
+    }
+
+    /// Job snapshots on every status or progress change.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<DownloadJob> {
+        self.inner.events.subscribe()
+    }
+
+    pub fn list(&self) -> Vec<DownloadJob> {
+        self.lock().jobs.clone()
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<DownloadJob> {
+        self.lock().jobs.iter().find(|j| j.id == job_id).cloned()
+    }
+
+    /// Queues a pull. A reference that is already queued, running or paused
+    /// into the same models root is rejected.
+    pub fn enqueue(
+        &self,
+        model_reference: String,
+        models_root: String,
+    ) -> Result<DownloadJob, String> {
+        let model_reference = model_reference.trim().to_string();
+        if model_reference.is_empty() {
+            return Err("Model reference must not be empty".to_string());
+        }
+        let job = {
+            let mut state = self.lock();
+            if let Some(existing) = state.jobs.iter().find(|j| {
+                j.status.is_active()
+                    && j.model_reference == model_reference
+                    && j.models_root == models_root
+            }) {
+                return Err(format!(
+                    "{} is already being downloaded (job {})",
+                    model_reference, existing.id
+                ));
+            }
+            let job = DownloadJob::new(
+                uuid::Uuid::new_v4().to_string(),
+                model_reference,
+                models_root,
+            );
+            state.jobs.push(job.clone());
+            self.persist(&state);
+            job
+        };
+        self.publish(&job);
+        self.pump();
+        Ok(job)
+    }
+
+    /// Stops a queued or running job, keeping its partial files.
+    pub fn pause(&self, job_id: &str) -> Result<DownloadJob, String> {
+        let job = self.transition(job_id, |job| match job.status {
+            DownloadStatus::Queued | DownloadStatus::Downloading => {
+                job.status = DownloadStatus::Paused;
+                Ok(())
+            }
+            status => Err(format!("Cannot pause a {:?} download", status)),
+        })?;
+        self.pump();
+        Ok(job)
+    }
+
+    /// Requeues a paused or failed job; it continues from its partial files.
+    pub fn resume(&self, job_id: &str) -> Result<DownloadJob, String> {
+        let job = self.transition(job_id, |job| match job.status {
+            DownloadStatus::Paused | DownloadStatus::Failed => {
+                job.status = DownloadStatus::Queued;
+                job.error = None;
+                Ok(())
+            }
+            status => Err(format!("Cannot resume a {:?} download", status)),
+        })?;
+        self.pump();
+        Ok(job)
+    }
+
+    /// Stops a job for good and deletes its partial files.
+    pub fn cancel(&self, job_id: &str) -> Result<DownloadJob, String> {
+        let job = self.transition(job_id, |job| {
+            if job.status.is_active() || job.status == DownloadStatus::Failed {
+                job.status = DownloadStatus::Cancelled;
+                Ok(())
+            } else {
+                Err(format!("Cannot cancel a {:?} download", job.status))
+            }
+        })?;
+        for partial in &job.partial_files {
+            remove_partial_files(Path::new(partial));
+        }
+        self.pump();
+        Ok(job)
+    }
+
+    /// Applies `change` to a job, aborting its task when it leaves `Downloading`.
+    fn transition<F>(&self, job_id: &str, change: F) -> Result<DownloadJob, String>
+    where
+        F: FnOnce(&mut DownloadJob) -> Result<(), String>,
+    {
+        let job = {
+            let mut state = self.lock();
+            let job = state
+                .jobs
+                .iter_mut()
+                .find(|j| j.id == job_id)
+                .ok_or_else(|| format!("Download job {} not found", job_id))?;
+            change(job)?;
+            let job = job.clone();
+            if job.status != DownloadStatus::Downloading {
+                if let Some(task) = state.tasks.remove(job_id) {
+                    task.handle.abort();
+                    state.stopping.insert(job_id.to_string(), task.handle);
+                }
+            }
+            self.persist(&state);
+            job
+        };
+        self.publish(&job);
+        Ok(job)
+    }
+
+    /// Starts queued jobs, oldest first, up to the concurrency limit.
+    fn pump(&self) {
+        let Some(runner) = self
+            .inner
+            .runner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+        else {
+            return;
+        };
+
+        // Tasks are registered under the same lock that marks their job
+        // Downloading, so a pause or cancel always finds the task to abort.
+        let mut state = self.lock();
+        let running = state
+            .jobs
+            .iter()
+            .filter(|j| j.status == DownloadStatus::Downloading)
+            .count();
+        let free = self.inner.max_concurrent.saturating_sub(running);
+        let started: Vec<DownloadJob> = state
+            .jobs
+            .iter_mut()
+            .filter(|j| j.status == DownloadStatus::Queued)
+            .take(free)
+            .map(|job| {
+                job.status = DownloadStatus::Downloading;
+                job.clone()
+            })
+            .collect();
+        if started.is_empty() {
+            return;
+        }
+        self.persist(&state);
+
+        for job in started {
+            self.publish(&job);
+            let job_id = job.id.clone();
+            state.next_run += 1;
+            let run = state.next_run;
+            let previous = state.stopping.remove(&job_id);
+            let handle = self.spawn_job(job, run, previous, runner.clone());
+            state.tasks.insert(job_id, RunningTask { run, handle });
+        }
+    }
+
+    fn spawn_job(
+        &self,
+        job: DownloadJob,
+        run: u64,
+        previous: Option<tauri::async_runtime::JoinHandle<()>>,
+        runner: DownloadRunner,
+    ) -> tauri::async_runtime::JoinHandle<()> {
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            // `abort` only requests the stop; the paused run must be gone
+            // before this one opens the same `.partial` files.
+            if let Some(previous) = previous {
+                let _ = previous.await;
+            }
+            let job_id = job.id.clone();
+            let progress_manager = manager.clone();
+            let progress_id = job_id.clone();
+            let progress: DownloadProgressFn = Arc::new(move |update| {
+                progress_manager.record_progress(&progress_id, run, update);
+            });
+            let result = runner(job, progress).await;
+            manager.finish(&job_id, run, result);
+        })
+    }
+
+    fn record_progress(&self, job_id: &str, run: u64, update: DownloadProgress) {
+        let job = {
+            let mut state = self.lock();
+            if !state.is_current_run(job_id, run) {
+                return;
+            }
+            let Some(job) = state
+                .jobs
+                .iter_mut()
+                .find(|j| j.id == job_id && j.status == DownloadStatus::Downloading)
+            else {
+                return;
+            };
+            job.downloaded_bytes = update.downloaded;
+            job.total_bytes = update.total;
+            job.current_blob = Some(update.blob);
+            let new_partial = update
+                .partial_path
+                .filter(|p| !job.partial_files.contains(p));
+            let added_partial = new_partial.is_some();
+            job.partial_files.extend(new_partial);
+            let job = job.clone();
+            // Progress alone is not worth a write; a new temp file is.
+            if added_partial {
+                self.persist(&state);
+            }
+            job
+        };
+        self.publish(&job);
+    }
+
+    fn finish(&self, job_id: &str, run: u64, result: Result<ModelInfo, String>) {
+        let job = {
+            let mut state = self.lock();
+            if !state.is_current_run(job_id, run) {
+                return;
+            }
+            state.tasks.remove(job_id);
+            let Some(job) = state
+                .jobs
+                .iter_mut()
+                .find(|j| j.id == job_id && j.status == DownloadStatus::Downloading)
+            else {
+                return;
+            };
+            match result {
+                Ok(model) => {
+                    job.status = DownloadStatus::Completed;
+                    job.downloaded_bytes = job.total_bytes.max(job.downloaded_bytes);
+                    job.current_blob = None;
+                    job.partial_files.clear();
+                    job.model = Some(model);
+                }
+                Err(err) => {
+                    eprintln!("[download] Job {} failed: {}", job_id, err);
+                    job.status = DownloadStatus::Failed;
+                    job.error = Some(err);
+                }
+            }
+            let job = job.clone();
+            self.persist(&state);
+            job
+        };
+        self.publish(&job);
+        self.pump();
+    }
+
+    fn publish(&self, job: &DownloadJob) {
+        // No subscribers is fine.
+        let _ = self.inner.events.send(job.clone());
+    }
+
+    /// Writes active and failed jobs; finished ones only live for the session.
+    fn persist(&self, state: &ManagerState) {
+        let Some(path) = &self.inner.store_path else {
+            return;
+        };
+        let jobs: Vec<DownloadJob> = state
+            .jobs
+            .iter()
+            .filter(|j| j.status.is_active() || j.status == DownloadStatus::Failed)
+            .map(|j| DownloadJob {
+                model: None,
+                ..j.clone()
+            })
+            .collect();
+        if let Err(e) = crate::utils::save_json(path, &jobs) {
+            eprintln!("[download] Failed to persist download queue: {}", e);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ManagerState> {
+        self.inner.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ManagerState {
+    fn is_current_run(&self, job_id: &str, run: u64) -> bool {
+        self.tasks.get(job_id).is_some_and(|task| task.run == run)
+    }
+}
+
+fn load_jobs(path: &Path) -> Vec<DownloadJob> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    crate::utils::read_json(path).unwrap_or_else(|e| {
+        eprintln!("[download] Ignoring unreadable download queue: {}", e);
+        Vec::new()
+    })
+}
+
+/// Removes `path` and the `<path>.N` chunk files next to it.
+fn remove_partial_files(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+        return;
+    };
+    let prefix = format!("{}.", name);
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
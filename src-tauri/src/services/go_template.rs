@@ -0,0 +1,1153 @@
+//! Converts Ollama's Go `text/template` chat templates into Jinja templates
+//! that llama-server accepts through `--jinja --chat-template-file`.
+//!
+//! Only the subset Ollama templates use is supported: `.System`, `.Prompt`,
+//! `.Response`, `range .Messages`, `.Tools`/`.ToolCalls`, variables,
+//! `if`/`else if`/`with`/`range` and the comparison, logic, `len`, `slice`,
+//! `index` and `json` functions. Anything else is an error so the caller can
+//! fall back to llama-server's built-in template.
+//!
+//! Templates that never reference `.Messages` are "legacy" templates that
+//! Ollama renders once per user/assistant turn; the generated Jinja loops over
+//! the messages and renders the template body for each turn the same way.
+//!
+//! Every generated tag trims the whitespace before it and all literal text is
+//! emitted as string expressions, so the output does not depend on the
+//! renderer's `trim_blocks`/`lstrip_blocks` settings.
+
+use std::collections::HashMap;
+
+/// Bumped whenever the generated Jinja changes, to invalidate cached files.
+pub const CONVERTER_VERSION: u32 = 1;
+
+const INDENT: &str = "    ";
+
+/// Converts an Ollama Go template to an equivalent Jinja chat template.
+pub fn go_template_to_jinja(source: &str) -> Result<String, String> {
+    let nodes = parse(source)?;
+    let messages_mode = references_root_field(&nodes, "Messages");
+    let mut emitter = Emitter::default();
+    emitter.line(
+        0,
+        "{#- Converted from an Ollama Go template. #}".to_string(),
+    );
+
+    if messages_mode {
+        let root = RootFields {
+            system: "ns.system",
+            prompt: "''",
+            response: "''",
+        };
+        if references_root_field(&nodes, "System") {
+            emitter.line(0, "{%- set ns = namespace(system='') %}".to_string());
+            emitter.line(0, "{%- for message in messages %}".to_string());
+            emitter.line(1, "{%- if message.role == 'system' %}".to_string());
+            emitter.line(2, append_turn("ns.system"));
+            emitter.line(1, "{%- endif %}".to_string());
+            emitter.line(0, "{%- endfor %}".to_string());
+        }
+        emitter.nodes(&nodes, 0, &root)?;
+    } else {
+        // Ollama renders legacy templates per turn: the system prompt and the
+        // user prompt(s) go with the next assistant reply, and the final turn
+        // is cut right after `{{ .Response }}` so it ends on the assistant header.
+        let (prompt_nodes, _) = truncate_after_response(&nodes);
+        emitter.line(
+            0,
+            "{%- set ns = namespace(system='', prompt='') %}".to_string(),
+        );
+        emitter.line(0, "{%- for message in messages %}".to_string());
+        emitter.line(1, "{%- if message.role == 'system' %}".to_string());
+        emitter.line(2, append_turn("ns.system"));
+        emitter.line(1, "{%- elif message.role == 'user' %}".to_string());
+        emitter.line(2, append_turn("ns.prompt"));
+        emitter.line(2, "{%- if loop.last %}".to_string());
+        let last_turn = RootFields {
+            system: "ns.system",
+            prompt: "ns.prompt",
+            response: "''",
+        };
+        emitter.nodes(&prompt_nodes, 3, &last_turn)?;
+        emitter.line(2, "{%- endif %}".to_string());
+        emitter.line(1, "{%- elif message.role == 'assistant' %}".to_string());
+        let full_turn = RootFields {
+            system: "ns.system",
+            prompt: "ns.prompt",
+            response: "message.content",
+        };
+        emitter.nodes(&nodes, 2, &full_turn)?;
+        emitter.line(2, "{%- set ns.system = '' %}".to_string());
+        emitter.line(2, "{%- set ns.prompt = '' %}".to_string());
+        emitter.line(1, "{%- endif %}".to_string());
+        emitter.line(0, "{%- endfor %}".to_string());
+    }
+
+    Ok(emitter.lines.join("\n"))
+}
+
+/// Ollama joins repeated system (or user) messages with a blank line.
+fn append_turn(target: &str) -> String {
+    format!(
+        "{{%- set {0} = {0} + ('\\n\\n' if {0} else '') + message.content %}}",
+        target
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Lexer
+// ---------------------------------------------------------------------------
+
+enum Token {
+    Text(String),
+    Action(String),
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+/// Splits the source into text and `{{ … }}` actions, applying the `{{-` and
+/// `-}}` trim markers and dropping comments.
+fn lex(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut trim_next_text = false;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find("{{") else {
+            push_text(&mut tokens, rest, trim_next_text, false);
+            break;
+        };
+        let after_open = &rest[start + 2..];
+        let trim_left = after_open.starts_with('-') && after_open[1..].starts_with(is_space);
+        push_text(&mut tokens, &rest[..start], trim_next_text, trim_left);
+
+        let body_start = if trim_left { 1 } else { 0 };
+        let end = find_action_end(&after_open[body_start..])
+            .ok_or_else(|| "Unclosed action: missing }}".to_string())?;
+        let mut body = &after_open[body_start..body_start + end];
+        trim_next_text = body.ends_with('-') && body[..body.len() - 1].ends_with(is_space);
+        if trim_next_text {
+            body = &body[..body.len() - 1];
+        }
+        let body = body.trim();
+        if !(body.starts_with("/*") && body.ends_with("*/")) {
+            tokens.push(Token::Action(body.to_string()));
+        }
+        rest = &after_open[body_start + end + 2..];
+    }
+    Ok(tokens)
+}
+
+fn push_text(tokens: &mut Vec<Token>, text: &str, trim_start: bool, trim_end: bool) {
+    let mut text = text;
+    if trim_start {
+        text = text.trim_start_matches(is_space);
+    }
+    if trim_end {
+        text = text.trim_end_matches(is_space);
+    }
+    if !text.is_empty() {
+        tokens.push(Token::Text(text.to_string()));
+    }
+}
+
+/// Byte offset of the `}}` closing an action, skipping quoted strings and comments.
+fn find_action_end(body: &str) -> Option<usize> {
+    let bytes = body.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'\'' => {
+                let quote = bytes[i];
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'`' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'`' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let close = body[i + 2..].find("*/")?;
+                i += close + 3;
+            }
+            b'}' if bytes.get(i + 1) == Some(&b'}') => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Action parser
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    /// `.A.B`; `.` alone is an empty path.
+    Field(Vec<String>),
+    /// `$name.A.B`; `$` alone has an empty name.
+    Var(String, Vec<String>),
+    Ident(String),
+    Str(String),
+    Num(String),
+    LParen,
+    RParen,
+    Pipe,
+    Declare,
+    Assign,
+    Comma,
+}
+
+fn tokenize_action(action: &str) -> Result<Vec<Tok>, String> {
+    let chars: Vec<char> = action.chars().collect();
+    let mut toks = Vec::new();
+    let mut i = 0;
+    let ident_char = |c: char| c.is_alphanumeric() || c == '_';
+    let read_path = |i: &mut usize| {
+        let mut path = Vec::new();
+        while *i < chars.len() && chars[*i] == '.' {
+            let start = *i + 1;
+            let mut end = start;
+            while end < chars.len() && ident_char(chars[end]) {
+                end += 1;
+            }
+            if end > start {
+                path.push(chars[start..end].iter().collect::<String>());
+            }
+            *i = end;
+            if end == start {
+                break;
+            }
+        }
+        path
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if is_space(c) => i += 1,
+            '.' => toks.push(Tok::Field(read_path(&mut i))),
+            '$' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && ident_char(chars[i]) {
+                    i += 1;
+                }
+                let name: String = chars[start..i].iter().collect();
+                toks.push(Tok::Var(name, read_path(&mut i)));
+            }
+            '(' => {
+                toks.push(Tok::LParen);
+                i += 1;
+            }
+            ')' => {
+                toks.push(Tok::RParen);
+                i += 1;
+            }
+            '|' => {
+                toks.push(Tok::Pipe);
+                i += 1;
+            }
+            ',' => {
+                toks.push(Tok::Comma);
+                i += 1;
+            }
+            ':' if chars.get(i + 1) == Some(&'=') => {
+                toks.push(Tok::Declare);
+                i += 2;
+            }
+            '=' => {
+                toks.push(Tok::Assign);
+                i += 1;
+            }
+            '"' => {
+                let mut value = String::new();
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        i += 1;
+                        value.push(match chars[i] {
+                            'n' => '\n',
+                            't' => '\t',
+                            'r' => '\r',
+                            other => other,
+                        });
+                    } else {
+                        value.push(chars[i]);
+                    }
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(format!("Unterminated string in {{{{ {} }}}}", action));
+                }
+                toks.push(Tok::Str(value));
+                i += 1;
+            }
+            '`' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && chars[i] != '`' {
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(format!("Unterminated string in {{{{ {} }}}}", action));
+                }
+                toks.push(Tok::Str(chars[start..i].iter().collect()));
+                i += 1;
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                toks.push(Tok::Num(chars[start..i].iter().collect()));
+            }
+            c if ident_char(c) => {
+                let start = i;
+                while i < chars.len() && ident_char(chars[i]) {
+                    i += 1;
+                }
+                toks.push(Tok::Ident(chars[start..i].iter().collect()));
+            }
+            other => return Err(format!("Unexpected '{}' in {{{{ {} }}}}", other, action)),
+        }
+    }
+    Ok(toks)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    /// A field path on `.` (`root == false`) or on `$` (`root == true`).
+    Field {
+        root: bool,
+        path: Vec<String>,
+    },
+    Var(String, Vec<String>),
+    Str(String),
+    Num(String),
+    Bool(bool),
+    Nil,
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Print(Expr),
+    Assign(String, Expr),
+    If {
+        branches: Vec<(Expr, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    Range {
+        index_var: Option<String>,
+        elem_var: Option<String>,
+        over: Expr,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    With {
+        value: Expr,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+enum Action {
+    Print(Expr),
+    Assign(String, Expr),
+    If(Expr),
+    ElseIf(Expr),
+    Else,
+    End,
+    Range(Option<String>, Option<String>, Expr),
+    With(Expr),
+}
+
+fn parse_action(action: &str) -> Result<Action, String> {
+    let toks = tokenize_action(action)?;
+    let unsupported = |what: &str| Err(format!("Unsupported template action: {}", what));
+    match toks.first() {
+        Some(Tok::Ident(word)) => match word.as_str() {
+            "if" => Ok(Action::If(parse_pipeline(&toks[1..])?)),
+            "else" => match toks.get(1) {
+                Some(Tok::Ident(next)) if next == "if" => {
+                    Ok(Action::ElseIf(parse_pipeline(&toks[2..])?))
+                }
+                None => Ok(Action::Else),
+                _ => unsupported(action),
+            },
+            "end" => Ok(Action::End),
+            "range" => parse_range(&toks[1..]),
+            "with" => Ok(Action::With(parse_pipeline(&toks[1..])?)),
+            "define" | "template" | "block" | "break" | "continue" => unsupported(word),
+            _ => Ok(Action::Print(parse_pipeline(&toks)?)),
+        },
+        Some(Tok::Var(name, path)) if path.is_empty() => match toks.get(1) {
+            Some(Tok::Declare) | Some(Tok::Assign) => {
+                Ok(Action::Assign(name.clone(), parse_pipeline(&toks[2..])?))
+            }
+            _ => Ok(Action::Print(parse_pipeline(&toks)?)),
+        },
+        Some(_) => Ok(Action::Print(parse_pipeline(&toks)?)),
+        None => Err("Empty action".to_string()),
+    }
+}
+
+fn parse_range(toks: &[Tok]) -> Result<Action, String> {
+    let declare = toks.iter().position(|t| *t == Tok::Declare);
+    let Some(declare) = declare else {
+        return Ok(Action::Range(None, None, parse_pipeline(toks)?));
+    };
+    let vars: Vec<String> = toks[..declare]
+        .iter()
+        .filter_map(|t| match t {
+            Tok::Var(name, path) if path.is_empty() => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let over = parse_pipeline(&toks[declare + 1..])?;
+    match vars.as_slice() {
+        [elem] => Ok(Action::Range(None, Some(elem.clone()), over)),
+        [index, elem] => Ok(Action::Range(Some(index.clone()), Some(elem.clone()), over)),
+        _ => Err("Invalid range variables".to_string()),
+    }
+}
+
+/// `a | f b` becomes `f b a`, as in Go.
+fn parse_pipeline(toks: &[Tok]) -> Result<Expr, String> {
+    let mut commands = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, tok) in toks.iter().enumerate() {
+        match tok {
+            Tok::LParen => depth += 1,
+            Tok::RParen => depth -= 1,
+            Tok::Pipe if depth == 0 => {
+                commands.push(&toks[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    commands.push(&toks[start..]);
+
+    let mut expr = parse_command(commands[0])?;
+    for command in &commands[1..] {
+        match parse_command(command)? {
+            Expr::Call(name, mut args) => {
+                args.push(expr);
+                expr = Expr::Call(name, args);
+            }
+            _ => return Err("Only functions can follow | in a pipeline".to_string()),
+        }
+    }
+    Ok(expr)
+}
+
+fn parse_command(toks: &[Tok]) -> Result<Expr, String> {
+    let mut operands = Vec::new();
+    let mut i = 0;
+    while i < toks.len() {
+        let (operand, next) = parse_operand(toks, i)?;
+        operands.push(operand);
+        i = next;
+    }
+    match operands.len() {
+        0 => Err("Empty pipeline".to_string()),
+        1 => Ok(operands.remove(0)),
+        _ => match operands.remove(0) {
+            Expr::Call(name, args) if args.is_empty() => Ok(Expr::Call(name, operands)),
+            _ => Err("Only functions take arguments".to_string()),
+        },
+    }
+}
+
+fn parse_operand(toks: &[Tok], i: usize) -> Result<(Expr, usize), String> {
+    let expr = match &toks[i] {
+        Tok::Field(path) => Expr::Field {
+            root: false,
+            path: path.clone(),
+        },
+        Tok::Var(name, path) if name.is_empty() => Expr::Field {
+            root: true,
+            path: path.clone(),
+        },
+        Tok::Var(name, path) => Expr::Var(name.clone(), path.clone()),
+        Tok::Str(value) => Expr::Str(value.clone()),
+        Tok::Num(value) => Expr::Num(value.clone()),
+        Tok::Ident(word) => match word.as_str() {
+            "true" => Expr::Bool(true),
+            "false" => Expr::Bool(false),
+            "nil" => Expr::Nil,
+            _ => Expr::Call(word.clone(), Vec::new()),
+        },
+        Tok::LParen => {
+            let mut depth = 0;
+            let close = toks[i..]
+                .iter()
+                .position(|t| {
+                    match t {
+                        Tok::LParen => depth += 1,
+                        Tok::RParen => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .ok_or_else(|| "Unbalanced parentheses".to_string())?;
+            return Ok((parse_pipeline(&toks[i + 1..i + close])?, i + close + 1));
+        }
+        other => return Err(format!("Unexpected {:?} in expression", other)),
+    };
+    Ok((expr, i + 1))
+}
+
+/// Builds the node tree from the token stream.
+fn parse(source: &str) -> Result<Vec<Node>, String> {
+    enum Frame {
+        Root,
+        If {
+            branches: Vec<(Expr, Vec<Node>)>,
+            cond: Expr,
+            in_else: bool,
+        },
+        Range(Option<String>, Option<String>, Expr, Option<Vec<Node>>),
+        With(Expr, Option<Vec<Node>>),
+    }
+
+    // Each frame collects the nodes of its current branch.
+    let mut stack: Vec<(Frame, Vec<Node>)> = vec![(Frame::Root, Vec::new())];
+    for token in lex(source)? {
+        let action = match token {
+            Token::Text(text) => {
+                stack.last_mut().unwrap().1.push(Node::Text(text));
+                continue;
+            }
+            Token::Action(action) => parse_action(&action)?,
+        };
+        match action {
+            Action::Print(expr) => stack.last_mut().unwrap().1.push(Node::Print(expr)),
+            Action::Assign(name, expr) => {
+                stack.last_mut().unwrap().1.push(Node::Assign(name, expr))
+            }
+            Action::If(cond) => stack.push((
+                Frame::If {
+                    branches: Vec::new(),
+                    cond,
+                    in_else: false,
+                },
+                Vec::new(),
+            )),
+            Action::Range(index, elem, over) => {
+                stack.push((Frame::Range(index, elem, over, None), Vec::new()))
+            }
+            Action::With(value) => stack.push((Frame::With(value, None), Vec::new())),
+            Action::ElseIf(next_cond) => match stack.last_mut() {
+                Some((
+                    Frame::If {
+                        branches,
+                        cond,
+                        in_else: false,
+                    },
+                    body,
+                )) => {
+                    let done = std::mem::replace(cond, next_cond);
+                    branches.push((done, std::mem::take(body)));
+                }
+                _ => return Err("{{ else if }} outside of {{ if }}".to_string()),
+            },
+            Action::Else => match stack.last_mut() {
+                Some((
+                    Frame::If {
+                        branches,
+                        cond,
+                        in_else,
+                    },
+                    body,
+                )) if !*in_else => {
+                    branches.push((cond.clone(), std::mem::take(body)));
+                    *in_else = true;
+                }
+                Some((Frame::Range(_, _, _, otherwise), body))
+                | Some((Frame::With(_, otherwise), body))
+                    if otherwise.is_none() =>
+                {
+                    *otherwise = Some(std::mem::take(body));
+                }
+                _ => return Err("Unexpected {{ else }}".to_string()),
+            },
+            Action::End => {
+                let (frame, body) = stack.pop().unwrap();
+                let node = match frame {
+                    Frame::Root => return Err("Unexpected {{ end }}".to_string()),
+                    Frame::If {
+                        mut branches,
+                        cond,
+                        in_else,
+                    } => {
+                        if in_else {
+                            Node::If {
+                                branches,
+                                otherwise: body,
+                            }
+                        } else {
+                            branches.push((cond, body));
+                            Node::If {
+                                branches,
+                                otherwise: Vec::new(),
+                            }
+                        }
+                    }
+                    Frame::Range(index_var, elem_var, over, main) => {
+                        let (body, otherwise) = match main {
+                            Some(main) => (main, body),
+                            None => (body, Vec::new()),
+                        };
+                        Node::Range {
+                            index_var,
+                            elem_var,
+                            over,
+                            body,
+                            otherwise,
+                        }
+                    }
+                    Frame::With(value, main) => {
+                        let (body, otherwise) = match main {
+                            Some(main) => (main, body),
+                            None => (body, Vec::new()),
+                        };
+                        Node::With {
+                            value,
+                            body,
+                            otherwise,
+                        }
+                    }
+                };
+                stack.last_mut().unwrap().1.push(node);
+            }
+        }
+    }
+    match stack.pop() {
+        Some((Frame::Root, nodes)) if stack.is_empty() => Ok(nodes),
+        _ => Err("Missing {{ end }}".to_string()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tree helpers
+// ---------------------------------------------------------------------------
+
+fn expr_references(expr: &Expr, field: &str, dot_is_root: bool) -> bool {
+    match expr {
+        Expr::Field { root, path } => {
+            (*root || dot_is_root) && path.first().map(String::as_str) == Some(field)
+        }
+        Expr::Call(_, args) => args.iter().any(|a| expr_references(a, field, dot_is_root)),
+        _ => false,
+    }
+}
+
+/// Whether `.Field` (at the top level) or `$.Field` (anywhere) is used.
+fn references_root_field(nodes: &[Node], field: &str) -> bool {
+    fn walk(nodes: &[Node], field: &str, dot_is_root: bool) -> bool {
+        nodes.iter().any(|node| match node {
+            Node::Text(_) => false,
+            Node::Print(e) | Node::Assign(_, e) => expr_references(e, field, dot_is_root),
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                branches.iter().any(|(c, body)| {
+                    expr_references(c, field, dot_is_root) || walk(body, field, dot_is_root)
+                }) || walk(otherwise, field, dot_is_root)
+            }
+            Node::Range {
+                over,
+                body,
+                otherwise,
+                ..
+            } => {
+                expr_references(over, field, dot_is_root)
+                    || walk(body, field, false)
+                    || walk(otherwise, field, dot_is_root)
+            }
+            Node::With {
+                value,
+                body,
+                otherwise,
+            } => {
+                expr_references(value, field, dot_is_root)
+                    || walk(body, field, false)
+                    || walk(otherwise, field, dot_is_root)
+            }
+        })
+    }
+    walk(nodes, field, true)
+}
+
+/// Drops every node after the first `.Response` reference, descending into
+/// blocks, like Ollama does for the turn being generated.
+fn truncate_after_response(nodes: &[Node]) -> (Vec<Node>, bool) {
+    let mut kept = Vec::new();
+    for node in nodes {
+        match node {
+            Node::Print(e) | Node::Assign(_, e) if expr_references(e, "Response", true) => {
+                return (kept, true);
+            }
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                let mut new_branches = Vec::new();
+                for (cond, body) in branches {
+                    let (body, found) = truncate_after_response(body);
+                    new_branches.push((cond.clone(), body));
+                    if found {
+                        kept.push(Node::If {
+                            branches: new_branches,
+                            otherwise: Vec::new(),
+                        });
+                        return (kept, true);
+                    }
+                }
+                let (otherwise, found) = truncate_after_response(otherwise);
+                kept.push(Node::If {
+                    branches: new_branches,
+                    otherwise,
+                });
+                if found {
+                    return (kept, true);
+                }
+            }
+            other => kept.push(other.clone()),
+        }
+    }
+    (kept, false)
+}
+
+// ---------------------------------------------------------------------------
+// Jinja emitter
+// ---------------------------------------------------------------------------
+
+/// Jinja expressions for the root fields that depend on the render mode.
+struct RootFields {
+    system: &'static str,
+    prompt: &'static str,
+    response: &'static str,
+}
+
+/// A translated expression; `compound` ones need parentheses as operands.
+struct Js {
+    code: String,
+    compound: bool,
+}
+
+impl Js {
+    fn simple(code: String) -> Self {
+        Self {
+            code,
+            compound: false,
+        }
+    }
+
+    fn compound(code: String) -> Self {
+        Self {
+            code,
+            compound: true,
+        }
+    }
+
+    fn operand(&self) -> String {
+        if self.compound {
+            format!("({})", self.code)
+        } else {
+            self.code.clone()
+        }
+    }
+}
+
+#[derive(Default)]
+struct Emitter {
+    lines: Vec<String>,
+    /// What `.` refers to inside nested blocks; empty at the root.
+    dots: Vec<String>,
+    /// Go variable name -> Jinja name, innermost scope last.
+    vars: Vec<HashMap<String, String>>,
+    /// Jinja names already in use, to keep loop variables distinct.
+    taken: Vec<String>,
+}
+
+impl Emitter {
+    fn line(&mut self, depth: usize, code: String) {
+        self.lines.push(format!("{}{}", INDENT.repeat(depth), code));
+    }
+
+    fn nodes(&mut self, nodes: &[Node], depth: usize, root: &RootFields) -> Result<(), String> {
+        self.vars.push(HashMap::new());
+        for node in nodes {
+            self.node(node, depth, root)?;
+        }
+        self.vars.pop();
+        Ok(())
+    }
+
+    fn node(&mut self, node: &Node, depth: usize, root: &RootFields) -> Result<(), String> {
+        match node {
+            Node::Text(text) => self.line(depth, format!("{{{{- {} }}}}", string_literal(text))),
+            Node::Print(expr) => {
+                let value = self.print_expr(expr, root)?;
+                self.line(depth, format!("{{{{- {} }}}}", value));
+            }
+            Node::Assign(name, expr) => {
+                let value = self.expr(expr, root)?.code;
+                let jinja_name = self.declare_var(name);
+                self.line(depth, format!("{{%- set {} = {} %}}", jinja_name, value));
+            }
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                for (i, (cond, body)) in branches.iter().enumerate() {
+                    let keyword = if i == 0 { "if" } else { "elif" };
+                    let cond = self.expr(cond, root)?.code;
+                    self.line(depth, format!("{{%- {} {} %}}", keyword, cond));
+                    self.nodes(body, depth + 1, root)?;
+                }
+                if !otherwise.is_empty() {
+                    self.line(depth, "{%- else %}".to_string());
+                    self.nodes(otherwise, depth + 1, root)?;
+                }
+                self.line(depth, "{%- endif %}".to_string());
+            }
+            Node::Range {
+                index_var,
+                elem_var,
+                over,
+                body,
+                otherwise,
+            } => {
+                let collection = self.expr(over, root)?;
+                let elem = self.fresh_name(element_name(over));
+                self.line(
+                    depth,
+                    format!("{{%- for {} in {} %}}", elem, collection.operand()),
+                );
+                self.vars.push(HashMap::new());
+                if let Some(index_var) = index_var {
+                    if index_var != "_" {
+                        let index = self.declare_var(index_var);
+                        self.line(depth + 1, format!("{{%- set {} = loop.index0 %}}", index));
+                    }
+                }
+                if let Some(elem_var) = elem_var {
+                    self.vars
+                        .last_mut()
+                        .unwrap()
+                        .insert(elem_var.clone(), elem.clone());
+                }
+                self.dots.push(elem.clone());
+                self.nodes(body, depth + 1, root)?;
+                self.dots.pop();
+                self.vars.pop();
+                if !otherwise.is_empty() {
+                    self.line(depth, "{%- else %}".to_string());
+                    self.nodes(otherwise, depth + 1, root)?;
+                }
+                self.line(depth, "{%- endfor %}".to_string());
+                self.release_name(&elem);
+            }
+            Node::With {
+                value,
+                body,
+                otherwise,
+            } => {
+                let value_code = self.expr(value, root)?.code;
+                let name = self.fresh_name(element_name(value));
+                self.line(depth, format!("{{%- if {} %}}", value_code));
+                self.line(depth + 1, format!("{{%- set {} = {} %}}", name, value_code));
+                self.dots.push(name.clone());
+                self.nodes(body, depth + 1, root)?;
+                self.dots.pop();
+                if !otherwise.is_empty() {
+                    self.line(depth, "{%- else %}".to_string());
+                    self.nodes(otherwise, depth + 1, root)?;
+                }
+                self.line(depth, "{%- endif %}".to_string());
+                self.release_name(&name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `expr`, but prints structured values (tools, arguments, …) as JSON
+    /// the way Ollama's Go types stringify them.
+    fn print_expr(&mut self, expr: &Expr, root: &RootFields) -> Result<String, String> {
+        let value = self.expr(expr, root)?;
+        let last = match expr {
+            Expr::Field { path, .. } | Expr::Var(_, path) => path.last().map(String::as_str),
+            _ => return Ok(value.code),
+        };
+        let is_dot_object = last.is_none() && !self.dots.is_empty();
+        Ok(match last {
+            Some("Arguments") => as_json_text(&value),
+            Some("Function" | "Parameters" | "Properties" | "Tools" | "ToolCalls" | "Messages") => {
+                format!("{} | tojson", value.operand())
+            }
+            _ if is_dot_object => format!("{} | tojson", value.operand()),
+            _ => value.code,
+        })
+    }
+
+    fn expr(&mut self, expr: &Expr, root: &RootFields) -> Result<Js, String> {
+        Ok(match expr {
+            Expr::Field { root: true, path } => self.root_field(path, root)?,
+            Expr::Field { root: false, path } => match self.dots.last() {
+                None => self.root_field(path, root)?,
+                Some(dot) => Js::simple(member_path(dot, path)),
+            },
+            Expr::Var(name, path) => {
+                let base = self
+                    .lookup_var(name)
+                    .ok_or_else(|| format!("Undefined variable ${}", name))?;
+                Js::simple(member_path(&base, path))
+            }
+            Expr::Str(value) => Js::simple(string_literal(value)),
+            Expr::Num(value) => Js::simple(value.clone()),
+            Expr::Bool(value) => Js::simple(value.to_string()),
+            Expr::Nil => Js::simple("none".to_string()),
+            Expr::Call(name, args) => self.call(name, args, root)?,
+        })
+    }
+
+    fn root_field(&self, path: &[String], root: &RootFields) -> Result<Js, String> {
+        let Some(first) = path.first() else {
+            return Err("The root context cannot be printed".to_string());
+        };
+        let base = match first.as_str() {
+            "System" => root.system,
+            "Prompt" => root.prompt,
+            "Response" => root.response,
+            "Messages" => "messages",
+            "Tools" => "tools",
+            // Thinking controls have no llama-server equivalent.
+            "Think" | "IsThinkSet" => "false",
+            "ThinkLevel" => "''",
+            other => return Err(format!("Unsupported template field .{}", other)),
+        };
+        Ok(Js::simple(member_path(base, &path[1..])))
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr], root: &RootFields) -> Result<Js, String> {
+        let mut values = Vec::new();
+        for arg in args {
+            values.push(self.expr(arg, root)?);
+        }
+        let arity = |n: usize| {
+            if values.len() < n {
+                Err(format!("{} needs {} arguments", name, n))
+            } else {
+                Ok(())
+            }
+        };
+        let binary = |op: &str, values: &[Js]| {
+            Js::compound(format!(
+                "{} {} {}",
+                values[0].operand(),
+                op,
+                values[1].operand()
+            ))
+        };
+        Ok(match name {
+            "eq" => {
+                arity(2)?;
+                let first = values[0].operand();
+                let tests: Vec<String> = values[1..]
+                    .iter()
+                    .map(|v| format!("{} == {}", first, v.operand()))
+                    .collect();
+                Js::compound(tests.join(" or "))
+            }
+            "ne" => {
+                arity(2)?;
+                binary("!=", &values)
+            }
+            "lt" => {
+                arity(2)?;
+                binary("<", &values)
+            }
+            "le" => {
+                arity(2)?;
+                binary("<=", &values)
+            }
+            "gt" => {
+                arity(2)?;
+                binary(">", &values)
+            }
+            "ge" => {
+                arity(2)?;
+                binary(">=", &values)
+            }
+            "and" | "or" => {
+                arity(1)?;
+                let parts: Vec<String> = values.iter().map(Js::operand).collect();
+                Js::compound(parts.join(&format!(" {} ", name)))
+            }
+            "not" => {
+                arity(1)?;
+                Js::compound(format!("not {}", values[0].operand()))
+            }
+            "len" => {
+                arity(1)?;
+                Js::compound(format!("{} | length", values[0].operand()))
+            }
+            "slice" => {
+                arity(1)?;
+                let base = values[0].operand();
+                match values.len() {
+                    1 => Js::simple(base),
+                    2 => Js::simple(format!("{}[{}:]", base, values[1].code)),
+                    _ => Js::simple(format!("{}[{}:{}]", base, values[1].code, values[2].code)),
+                }
+            }
+            "index" => {
+                arity(2)?;
+                let mut code = values[0].operand();
+                for index in &values[1..] {
+                    code = format!("{}[{}]", code, index.code);
+                }
+                Js::simple(code)
+            }
+            "json" => {
+                arity(1)?;
+                let last = match &args[0] {
+                    Expr::Field { path, .. } | Expr::Var(_, path) => path.last().cloned(),
+                    _ => None,
+                };
+                if last.as_deref() == Some("Arguments") {
+                    Js::compound(as_json_text(&values[0]))
+                } else {
+                    Js::compound(format!("{} | tojson", values[0].operand()))
+                }
+            }
+            "print" => {
+                arity(1)?;
+                let parts: Vec<String> = values.iter().map(Js::operand).collect();
+                Js::compound(parts.join(" ~ "))
+            }
+            other => return Err(format!("Unsupported template function {}", other)),
+        })
+    }
+
+    fn declare_var(&mut self, name: &str) -> String {
+        let mut jinja_name = name.to_string();
+        if JINJA_RESERVED.contains(&jinja_name.as_str()) {
+            jinja_name.push('_');
+        }
+        self.vars
+            .last_mut()
+            .unwrap()
+            .insert(name.to_string(), jinja_name.clone());
+        jinja_name
+    }
+
+    fn lookup_var(&self, name: &str) -> Option<String> {
+        self.vars
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+    }
+
+    fn fresh_name(&mut self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut n = 2;
+        while self.taken.contains(&name) {
+            name = format!("{}{}", base, n);
+            n += 1;
+        }
+        self.taken.push(name.clone());
+        name
+    }
+
+    fn release_name(&mut self, name: &str) {
+        self.taken.retain(|n| n != name);
+    }
+}
+
+const JINJA_RESERVED: &[&str] = &[
+    "loop", "messages", "tools", "ns", "true", "false", "none", "and", "or", "not", "in", "is",
+    "if", "else", "for", "set",
+];
+
+/// Loop variable named after what is iterated.
+fn element_name(collection: &Expr) -> &'static str {
+    let last = match collection {
+        Expr::Field { path, .. } | Expr::Var(_, path) => path.last().map(String::as_str),
+        _ => None,
+    };
+    match last {
+        Some("Messages") => "message",
+        Some("Tools") => "tool",
+        Some("ToolCalls") => "tool_call",
+        _ => "item",
+    }
+}
+
+fn member_path(base: &str, path: &[String]) -> String {
+    let mut code = base.to_string();
+    for field in path {
+        code.push('.');
+        code.push_str(&jinja_field(field));
+    }
+    code
+}
+
+/// Go field names are the CamelCase forms of the OpenAI-style message keys.
+fn jinja_field(field: &str) -> String {
+    match field {
+        "Thinking" => "reasoning_content".to_string(),
+        "ToolName" => "name".to_string(),
+        "ID" => "id".to_string(),
+        _ => {
+            let mut out = String::new();
+            for (i, c) in field.chars().enumerate() {
+                if c.is_uppercase() && i > 0 {
+                    out.push('_');
+                }
+                out.extend(c.to_lowercase());
+            }
+            out
+        }
+    }
+}
+
+/// Tool-call arguments may arrive as a JSON string or as an object.
+fn as_json_text(value: &Js) -> String {
+    let v = value.operand();
+    format!("{0} if {0} is string else {0} | tojson", v)
+}
+
+fn string_literal(text: &str) -> String {
+    let mut out = String::from("'");
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            other => out.push(other),
+        }
+    }
+    out.push('\'');
+    out
+}
@@ -1,19 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot, Mutex as TokioMutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex as TokioMutex};
 
+use crate::infrastructure::llama::logs::ServerLogs;
 use crate::infrastructure::llama::process::ProcessManager;
+use crate::infrastructure::llama::progress::ProgressSender;
 use crate::infrastructure::llama::server::LlamaServer;
 use crate::infrastructure::metrics::MetricsProvider;
+use crate::infrastructure::ports;
 use crate::models::{
-    ActiveModel, ChatRequest, LlamaCppConfig, ModelId, ModelInfo, ModelState, ServerMetrics,
+    ActiveModel, ChatRequest, ChatStreamEvent, LlamaCppConfig, LlamaServerError, ModelEvent, ModelId, ModelInfo,
+    ModelState, PortRange, RunningModel, ServerMetrics,
 };
 
+/// How often the supervisor checks whether a running llama-server is still alive.
+const SUPERVISOR_INTERVAL: Duration = Duration::from_secs(1);
+/// Output lines kept on a crashed model.
+const CRASH_LOG_TAIL_LINES: usize = 20;
+
 pub enum ActorMessage {
     Start {
         model_id: ModelId,
         config: LlamaCppConfig,
-        respond_to: oneshot::Sender<Result<u32, String>>,
+        progress: Option<ProgressSender>,
+        respond_to: oneshot::Sender<Result<u32, LlamaServerError>>,
     },
     Stop {
         model_id: ModelId,
@@ -24,6 +35,11 @@ pub enum ActorMessage {
         request: ChatRequest,
         respond_to: oneshot::Sender<Result<mpsc::Receiver<String>, String>>,
     },
+    StreamChat {
+        model_id: ModelId,
+        request: ChatRequest,
+        respond_to: oneshot::Sender<Result<mpsc::Receiver<ChatStreamEvent>, String>>,
+    },
     CompleteChat {
         model_id: ModelId,
         request: ChatRequest,
@@ -36,14 +52,36 @@ pub enum ActorMessage {
     GetConfig {
         respond_to: oneshot::Sender<Option<LlamaCppConfig>>,
     },
+    GetModelConfig {
+        model_id: ModelId,
+        respond_to: oneshot::Sender<Option<LlamaCppConfig>>,
+    },
+    ListModels {
+        respond_to: oneshot::Sender<Vec<RunningModel>>,
+    },
     GetMetrics {
         respond_to: oneshot::Sender<Option<ServerMetrics>>,
     },
     InternalStartComplete {
         model_id: ModelId,
-        result: Result<(u16, tokio::process::Child), String>,
+        result: Result<(u16, tokio::process::Child), LlamaServerError>,
+        config: LlamaCppConfig,
+        respond_to: oneshot::Sender<Result<u32, LlamaServerError>>,
+    },
+    /// Sent by a model's supervisor; answers whether it should keep watching.
+    InternalCheckExit {
+        model_id: ModelId,
+        pid: u32,
+        respond_to: oneshot::Sender<bool>,
+    },
+    InternalRestart {
+        model_id: ModelId,
         config: LlamaCppConfig,
-        respond_to: oneshot::Sender<Result<u32, String>>,
+    },
+    InternalRestartFailed {
+        model_id: ModelId,
+        config: LlamaCppConfig,
+        error: String,
     },
 }
 
@@ -56,6 +94,12 @@ pub struct LlamaActor {
     self_sender: mpsc::Sender<ActorMessage>,
     process_manager: Arc<dyn ProcessManager>,
     metrics: Arc<dyn MetricsProvider>,
+    logs: Arc<ServerLogs>,
+    events: broadcast::Sender<ModelEvent>,
+    restart_attempts: HashMap<ModelId, u32>,
+    /// Models with a restart scheduled; a user start or stop cancels it.
+    pending_restarts: HashSet<ModelId>,
+    supervisor_interval: Duration,
     model_locks: HashMap<ModelId, Arc<TokioMutex<()>>>,
 }
 
@@ -66,6 +110,8 @@ impl LlamaActor {
         initial_registry: HashMap<ModelId, ModelInfo>,
         process_manager: Arc<dyn ProcessManager>,
         metrics: Arc<dyn MetricsProvider>,
+        logs: Arc<ServerLogs>,
+        events: broadcast::Sender<ModelEvent>,
     ) -> Self {
         Self {
             registry: initial_registry,
@@ -76,6 +122,11 @@ impl LlamaActor {
             self_sender,
             process_manager,
             metrics,
+            logs,
+            events,
+            restart_attempts: HashMap::new(),
+            pending_restarts: HashSet::new(),
+            supervisor_interval: SUPERVISOR_INTERVAL,
             model_locks: HashMap::new(),
         }
     }
@@ -86,9 +137,11 @@ impl LlamaActor {
                 ActorMessage::Start {
                     model_id,
                     config,
+                    progress,
                     respond_to,
                 } => {
-                    self.handle_start_request(model_id, config, respond_to)
+                    self.reset_restarts(&model_id);
+                    self.handle_start_request(model_id, config, progress, respond_to)
                         .await;
                 }
                 ActorMessage::InternalStartComplete {
@@ -103,10 +156,17 @@ impl LlamaActor {
                         Ok((port, child)) => {
                             let pid = child.id().unwrap_or(0);
                             self.process_manager.register(model_id.clone(), child);
+                            let mut config = config;
+                            config.port = port;
                             self.states.insert(
                                 model_id.clone(),
-                                ModelState::Running { port, pid, config },
+                                ModelState::Running {
+                                    port,
+                                    pid,
+                                    config: Box::new(config),
+                                },
                             );
+                            self.spawn_supervisor(model_id.clone(), pid);
                             self.active_model = Some(model_id);
                             Ok(pid)
                         }
@@ -121,12 +181,44 @@ impl LlamaActor {
                     model_id,
                     respond_to,
                 } => {
+                    let model_id = self.resolve_model_id(&model_id);
+                    self.reset_restarts(&model_id);
                     let res = self.handle_stop(&model_id).await;
-                    if res.is_ok() && self.active_model.as_ref() == Some(&model_id) {
-                        self.active_model = None;
+                    if res.is_ok() {
+                        self.release_default(&model_id);
                     }
                     let _ = respond_to.send(res);
                 }
+                ActorMessage::InternalCheckExit {
+                    model_id,
+                    pid,
+                    respond_to,
+                } => {
+                    let keep_watching = self.handle_check_exit(model_id, pid).await;
+                    let _ = respond_to.send(keep_watching);
+                }
+                ActorMessage::InternalRestart { model_id, config } => {
+                    if self.pending_restarts.remove(&model_id) {
+                        self.handle_restart(model_id, config).await;
+                    }
+                }
+                ActorMessage::InternalRestartFailed {
+                    model_id,
+                    config,
+                    error,
+                } => {
+                    let attempt = self.restart_attempts.get(&model_id).copied().unwrap_or(0);
+                    eprintln!(
+                        "[LlamaActor] Restart {} of {} failed: {}",
+                        attempt, model_id, error
+                    );
+                    let _ = self.events.send(ModelEvent::RestartFailed {
+                        model_id: model_id.clone(),
+                        attempt,
+                        error,
+                    });
+                    self.schedule_restart(model_id, config);
+                }
                 ActorMessage::SendChat {
                     model_id,
                     request,
@@ -134,6 +226,13 @@ impl LlamaActor {
                 } => {
                     let _ = respond_to.send(self.handle_chat(&model_id, request).await);
                 }
+                ActorMessage::StreamChat {
+                    model_id,
+                    request,
+                    respond_to,
+                } => {
+                    let _ = respond_to.send(self.handle_chat_events(&model_id, request).await);
+                }
                 ActorMessage::CompleteChat {
                     model_id,
                     request,
@@ -160,7 +259,7 @@ impl LlamaActor {
                         let lock = self.get_model_lock(&id);
                         let _guard = lock.lock().await;
                         self.states.get(&id).and_then(|state| match state {
-                            ModelState::Running { config, .. } => Some(config.clone()),
+                            ModelState::Running { config, .. } => Some((**config).clone()),
                             _ => None,
                         })
                     } else {
@@ -168,6 +267,22 @@ impl LlamaActor {
                     };
                     let _ = respond_to.send(config);
                 }
+                ActorMessage::GetModelConfig {
+                    model_id,
+                    respond_to,
+                } => {
+                    let id = self.resolve_model_id(&model_id);
+                    let lock = self.get_model_lock(&id);
+                    let _guard = lock.lock().await;
+                    let config = self.states.get(&id).and_then(|state| match state {
+                        ModelState::Running { config, .. } => Some((**config).clone()),
+                        _ => None,
+                    });
+                    let _ = respond_to.send(config);
+                }
+                ActorMessage::ListModels { respond_to } => {
+                    let _ = respond_to.send(self.list_running_models());
+                }
                 ActorMessage::GetMetrics { respond_to } => {
                     let metrics = self.handle_get_metrics().await;
                     let _ = respond_to.send(metrics);
@@ -180,7 +295,8 @@ impl LlamaActor {
         &mut self,
         model_id: ModelId,
         config: LlamaCppConfig,
-        respond_to: oneshot::Sender<Result<u32, String>>,
+        progress: Option<ProgressSender>,
+        respond_to: oneshot::Sender<Result<u32, LlamaServerError>>,
     ) {
         let lock = self.get_model_lock(&model_id);
         let _guard = lock.lock().await;
@@ -190,24 +306,47 @@ impl LlamaActor {
                     let _ = respond_to.send(Ok(*pid));
                     return;
                 }
-                ModelState::Starting => {
-                    let _ = respond_to.send(Err(format!("Model {} is already starting", model_id)));
+                ModelState::Starting { .. } => {
+                    let _ = respond_to.send(Err(
+                        format!("Model {} is already starting", model_id).into()
+                    ));
                     return;
                 }
                 _ => {}
             }
         }
 
+        let mut config = config;
+        config.port = match self.allocate_port(&model_id, config.port, config.port_range) {
+            Ok(port) => port,
+            Err(e) => {
+                let _ = respond_to.send(Err(e));
+                return;
+            }
+        };
+
         let model_entry = self.registry.get(&model_id).cloned();
-        self.states.insert(model_id.clone(), ModelState::Starting);
+        self.states.insert(
+            model_id.clone(),
+            ModelState::Starting { port: config.port },
+        );
         drop(_guard);
 
         let self_sender = self.self_sender.clone();
         let client = self.client.clone();
         let config_clone = config.clone();
+        let logs = self.logs.clone();
 
         tauri::async_runtime::spawn(async move {
-            let result = LlamaServer::spawn(model_entry, config_clone.clone(), client).await;
+            let result = LlamaServer::spawn(
+                model_id.clone(),
+                model_entry,
+                config_clone.clone(),
+                client,
+                logs,
+                progress,
+            )
+            .await;
             let _ = self_sender
                 .send(ActorMessage::InternalStartComplete {
                     model_id,
@@ -239,15 +378,25 @@ impl LlamaActor {
         model_id: &ModelId,
         request: ChatRequest,
     ) -> Result<mpsc::Receiver<String>, String> {
+        let model_id = &self.resolve_model_id(model_id);
         let lock = self.get_model_lock(model_id);
         let _guard = lock.lock().await;
-        let port = if let Some(ModelState::Running { port, .. }) = self.states.get(model_id) {
-            *port
-        } else {
-            return Err(format!("Model {} is not running", model_id));
-        };
+        let (port, api_key) = self.running_endpoint(model_id)?;
+
+        LlamaServer::stream_chat(self.client.clone(), port, api_key, request).await
+    }
+
+    async fn handle_chat_events(
+        &mut self,
+        model_id: &ModelId,
+        request: ChatRequest,
+    ) -> Result<mpsc::Receiver<ChatStreamEvent>, String> {
+        let model_id = &self.resolve_model_id(model_id);
+        let lock = self.get_model_lock(model_id);
+        let _guard = lock.lock().await;
+        let (port, api_key) = self.running_endpoint(model_id)?;
 
-        LlamaServer::stream_chat(self.client.clone(), port, request).await
+        LlamaServer::stream_chat_events(self.client.clone(), port, api_key, request).await
     }
 
     async fn handle_complete_chat(
@@ -255,15 +404,163 @@ impl LlamaActor {
         model_id: &ModelId,
         request: ChatRequest,
     ) -> Result<serde_json::Value, String> {
+        let model_id = &self.resolve_model_id(model_id);
         let lock = self.get_model_lock(model_id);
         let _guard = lock.lock().await;
-        let port = if let Some(ModelState::Running { port, .. }) = self.states.get(model_id) {
-            *port
-        } else {
-            return Err(format!("Model {} is not running", model_id));
+        let (port, api_key) = self.running_endpoint(model_id)?;
+
+        LlamaServer::chat_completion(self.client.clone(), port, api_key, request).await
+    }
+
+    /// Port and API key of a running instance.
+    fn running_endpoint(&self, model_id: &ModelId) -> Result<(u16, Option<String>), String> {
+        match self.states.get(model_id) {
+            Some(ModelState::Running { port, config, .. }) => {
+                Ok((*port, config.launch_params.api_key.clone()))
+            }
+            Some(ModelState::Crashed { exit_status, .. }) => {
+                Err(format!("Model {} crashed ({})", model_id, exit_status))
+            }
+            _ => Err(format!("Model {} is not running", model_id)),
+        }
+    }
+
+    /// Polls the child through the process manager until it exits or the
+    /// model is no longer this running instance.
+    fn spawn_supervisor(&self, model_id: ModelId, pid: u32) {
+        let sender = self.self_sender.clone();
+        let interval = self.supervisor_interval;
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let (tx, rx) = oneshot::channel();
+                let check = ActorMessage::InternalCheckExit {
+                    model_id: model_id.clone(),
+                    pid,
+                    respond_to: tx,
+                };
+                if sender.send(check).await.is_err() || !rx.await.unwrap_or(false) {
+                    break;
+                }
+            }
+        });
+    }
+
+    async fn handle_check_exit(&mut self, model_id: ModelId, pid: u32) -> bool {
+        let lock = self.get_model_lock(&model_id);
+        let _guard = lock.lock().await;
+        let config = match self.states.get(&model_id) {
+            Some(ModelState::Running {
+                pid: running_pid,
+                config,
+                ..
+            }) if *running_pid == pid => (**config).clone(),
+            // Stopped or replaced by a newer instance: this supervisor is done.
+            _ => return false,
+        };
+        let Some(status) = self.process_manager.try_wait(&model_id) else {
+            return true;
+        };
+
+        self.process_manager.remove(&model_id);
+        let exit_status = status.to_string();
+        let last_log: Vec<String> = self
+            .logs
+            .tail(&model_id, Some(CRASH_LOG_TAIL_LINES))
+            .into_iter()
+            .map(|entry| entry.line)
+            .collect();
+        eprintln!(
+            "[LlamaActor] Model {} exited unexpectedly: {}",
+            model_id, exit_status
+        );
+        self.states.insert(
+            model_id.clone(),
+            ModelState::Crashed {
+                exit_status: exit_status.clone(),
+                last_log: last_log.clone(),
+            },
+        );
+        drop(_guard);
+
+        self.release_default(&model_id);
+        let _ = self.events.send(ModelEvent::Crashed {
+            model_id: model_id.clone(),
+            exit_status,
+            last_log,
+        });
+        self.schedule_restart(model_id, config);
+        false
+    }
+
+    /// Queues the next restart attempt if the model's policy allows one.
+    fn schedule_restart(&mut self, model_id: ModelId, config: LlamaCppConfig) {
+        let Some(policy) = config.restart_policy else {
+            return;
         };
+        let attempts = self.restart_attempts.entry(model_id.clone()).or_insert(0);
+        if *attempts >= policy.max_restarts {
+            let _ = self.events.send(ModelEvent::RestartsExhausted {
+                model_id,
+                attempts: *attempts,
+            });
+            return;
+        }
+        *attempts += 1;
+        let attempt = *attempts;
+        let delay_ms = policy.backoff_ms(attempt);
+        let _ = self.events.send(ModelEvent::Restarting {
+            model_id: model_id.clone(),
+            attempt,
+            delay_ms,
+        });
+
+        self.pending_restarts.insert(model_id.clone());
+        let sender = self.self_sender.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            let _ = sender
+                .send(ActorMessage::InternalRestart { model_id, config })
+                .await;
+        });
+    }
+
+    async fn handle_restart(&mut self, model_id: ModelId, config: LlamaCppConfig) {
+        let (tx, rx) = oneshot::channel();
+        self.handle_start_request(model_id.clone(), config.clone(), None, tx)
+            .await;
+
+        let sender = self.self_sender.clone();
+        let events = self.events.clone();
+        tauri::async_runtime::spawn(async move {
+            match rx.await {
+                Ok(Ok(pid)) => {
+                    let _ = events.send(ModelEvent::Restarted { model_id, pid });
+                }
+                Ok(Err(error)) => {
+                    let _ = sender
+                        .send(ActorMessage::InternalRestartFailed {
+                            model_id,
+                            config,
+                            error: error.to_string(),
+                        })
+                        .await;
+                }
+                Err(_) => {}
+            }
+        });
+    }
+
+    fn reset_restarts(&mut self, model_id: &ModelId) {
+        self.restart_attempts.remove(model_id);
+        self.pending_restarts.remove(model_id);
+    }
 
-        LlamaServer::chat_completion(self.client.clone(), port, request).await
+    /// Falls back to another running model so chats keep a default target.
+    fn release_default(&mut self, model_id: &ModelId) {
+        if self.active_model.as_ref() == Some(model_id) {
+            self.active_model = self.running_model_ids().into_iter().next();
+        }
     }
 
     async fn handle_get_metrics(&mut self) -> Option<ServerMetrics> {
@@ -281,6 +578,77 @@ impl LlamaActor {
         self.metrics.snapshot_for_pid(pid)
     }
 
+    /// Maps a library identifier (`provider:name:version`) onto the model path
+    /// key used for running instances; unknown ids are returned unchanged.
+    fn resolve_model_id(&self, model_id: &ModelId) -> ModelId {
+        if self.states.contains_key(model_id) {
+            return model_id.clone();
+        }
+        self.registry
+            .get(model_id)
+            .and_then(|entry| entry.model_file_path.clone())
+            .map(ModelId)
+            .filter(|path_id| self.states.contains_key(path_id))
+            .unwrap_or_else(|| model_id.clone())
+    }
+
+    fn running_model_ids(&self) -> Vec<ModelId> {
+        let mut ids: Vec<ModelId> = self
+            .states
+            .iter()
+            .filter(|(_, state)| matches!(state, ModelState::Running { .. }))
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort_by(|a, b| a.0.cmp(&b.0));
+        ids
+    }
+
+    fn list_running_models(&self) -> Vec<RunningModel> {
+        let mut models: Vec<RunningModel> = self
+            .states
+            .iter()
+            .filter_map(|(id, state)| match state {
+                ModelState::Running { port, pid, config } => Some(RunningModel {
+                    model_id: id.clone(),
+                    port: *port,
+                    pid: *pid,
+                    is_default: self.active_model.as_ref() == Some(id),
+                    config: (**config).clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+        models.sort_by_key(|m| m.port);
+        models
+    }
+
+    /// The port a new server will bind: `preferred`, moved past ports other
+    /// starting or running models hold, then probed with `ports::resolve_port`.
+    /// Probing before the spawn reports a clash as such instead of as an
+    /// unexplained early exit or healthcheck timeout.
+    fn allocate_port(
+        &self,
+        model_id: &ModelId,
+        preferred: u16,
+        range: Option<PortRange>,
+    ) -> Result<u16, LlamaServerError> {
+        let taken: Vec<u16> = self
+            .states
+            .iter()
+            .filter(|(id, _)| *id != model_id)
+            .filter_map(|(_, state)| match state {
+                ModelState::Starting { port } | ModelState::Running { port, .. } => Some(*port),
+                ModelState::Stopped | ModelState::Crashed { .. } => None,
+            })
+            .collect();
+
+        let mut port = preferred;
+        while taken.contains(&port) {
+            port = port.wrapping_add(1).max(1024);
+        }
+        ports::resolve_port(port, range, &taken)
+    }
+
     fn get_model_lock(&mut self, model_id: &ModelId) -> Arc<TokioMutex<()>> {
         self.model_locks
             .entry(model_id.clone())
@@ -310,9 +678,10 @@ impl LlamaActor {
         &mut self,
         model_id: ModelId,
         config: LlamaCppConfig,
-        respond_to: oneshot::Sender<Result<u32, String>>,
+        respond_to: oneshot::Sender<Result<u32, LlamaServerError>>,
     ) {
-        self.handle_start_request(model_id, config, respond_to).await;
+        self.handle_start_request(model_id, config, None, respond_to)
+            .await;
     }
 
     pub fn test_set_state(&mut self, model_id: ModelId, state: ModelState) {
@@ -322,4 +691,17 @@ impl LlamaActor {
     pub fn test_set_active_model(&mut self, model_id: Option<ModelId>) {
         self.active_model = model_id;
     }
+
+    pub fn test_set_supervisor_interval(&mut self, interval: Duration) {
+        self.supervisor_interval = interval;
+    }
+
+    pub fn test_allocate_port(
+        &self,
+        model_id: &ModelId,
+        preferred: u16,
+        range: Option<PortRange>,
+    ) -> Result<u16, LlamaServerError> {
+        self.allocate_port(model_id, preferred, range)
+    }
 }
@@ -1,16 +1,29 @@
 use super::actor::{ActorMessage, LlamaActor};
+use crate::infrastructure::llama::logs::ServerLogs;
 use crate::infrastructure::llama::process::LlamaProcessManager;
+use crate::infrastructure::llama::progress::ProgressSender;
 use crate::infrastructure::metrics::SystemMetricsProvider;
 use crate::models::{
-    ChatMessage, ChatRequest, LlamaCppConfig, ModelId, ModelLibrary, ServerMetrics,
+    ChatMessage, ChatRequest, ChatStreamEvent, LlamaCppConfig, LlamaServerError, ModelChatDefaults,
+    ModelEvent, ModelId, ModelLibrary, RunningModel, SamplingParams, ServerLogLine, ServerMetrics,
 };
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+/// Samplers used when neither the chat nor the model sets them.
+const DEFAULT_TEMPERATURE: f32 = 0.7;
+const DEFAULT_TOP_P: f32 = 0.95;
+const DEFAULT_TOP_K: i32 = 40;
 
 #[derive(Clone)]
 pub struct LlamaCppService {
     sender: mpsc::Sender<ActorMessage>,
+    logs: Arc<ServerLogs>,
+    events: broadcast::Sender<ModelEvent>,
+    /// Library defaults (system prompt, stop sequences, ...) keyed by model path.
+    chat_defaults: Arc<Mutex<HashMap<String, ModelChatDefaults>>>,
 }
 
 impl LlamaCppService {
@@ -38,32 +51,104 @@ impl LlamaCppService {
         let tx_clone = tx.clone();
         let process_manager = Arc::new(LlamaProcessManager::new());
         let metrics_provider = Arc::new(SystemMetricsProvider::new());
+        let logs = Arc::new(ServerLogs::default());
+        let (events, _) = broadcast::channel(64);
         let mut actor = LlamaActor::new(
             rx,
             tx_clone,
             initial_registry,
             process_manager,
             metrics_provider,
+            logs.clone(),
+            events.clone(),
         );
 
         tauri::async_runtime::spawn(async move {
             actor.run().await;
         });
 
-        Self { sender: tx }
+        Self {
+            sender: tx,
+            logs,
+            events,
+            chat_defaults: Arc::default(),
+        }
     }
 
     pub fn from_sender(sender: mpsc::Sender<ActorMessage>) -> Self {
-        Self { sender }
+        Self {
+            sender,
+            logs: Arc::new(ServerLogs::default()),
+            events: broadcast::channel(64).0,
+            chat_defaults: Arc::default(),
+        }
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ModelEvent> {
+        self.events.subscribe()
+    }
+
+    /// Captured llama-server output, shared with the actor.
+    pub fn logs(&self) -> Arc<ServerLogs> {
+        self.logs.clone()
     }
 
-    pub async fn start(&self, config: LlamaCppConfig) -> Result<u32, String> {
+    /// Sets (or with `None` clears) the defaults applied to chats with the
+    /// model at `model_path`. Kept across restarts of the same model.
+    pub fn set_chat_defaults(&self, model_path: &str, defaults: Option<ModelChatDefaults>) {
+        let mut map = self.chat_defaults.lock().unwrap();
+        match defaults {
+            Some(defaults) => map.insert(model_path.to_string(), defaults),
+            None => map.remove(model_path),
+        };
+    }
+
+    /// The model's sampling defaults under the chat's own `sampling`.
+    fn resolve_sampling(&self, model_path: &str, sampling: &SamplingParams) -> SamplingParams {
+        match self.chat_defaults.lock().unwrap().get(model_path) {
+            Some(defaults) => sampling.clone().or(&defaults.sampling),
+            None => sampling.clone(),
+        }
+    }
+
+    fn apply_chat_defaults(&self, model_path: &str, request: &mut ChatRequest) {
+        if let Some(defaults) = self.chat_defaults.lock().unwrap().get(model_path) {
+            defaults.apply(request);
+        }
+    }
+
+    /// Fills `request` from `sampling`, then the model's defaults, then the app's.
+    fn apply_sampling(
+        &self,
+        model_path: &str,
+        sampling: &SamplingParams,
+        request: &mut ChatRequest,
+    ) {
+        let sampling = self.resolve_sampling(model_path, sampling);
+        request.temperature = sampling.temperature.unwrap_or(DEFAULT_TEMPERATURE);
+        request.top_p = sampling.top_p.unwrap_or(DEFAULT_TOP_P);
+        request.top_k = sampling.top_k.unwrap_or(DEFAULT_TOP_K);
+        sampling.fill_request(request);
+        self.apply_chat_defaults(model_path, request);
+    }
+
+    pub async fn start(&self, config: LlamaCppConfig) -> Result<u32, LlamaServerError> {
+        self.start_with_progress(config, None).await
+    }
+
+    /// Like `start`, also reporting load progress on `progress` until the server is up.
+    pub async fn start_with_progress(
+        &self,
+        config: LlamaCppConfig,
+        progress: Option<ProgressSender>,
+    ) -> Result<u32, LlamaServerError> {
         let id = ModelId(config.model_path.clone());
         let (tx, rx) = oneshot::channel();
         self.sender
             .send(ActorMessage::Start {
                 model_id: id,
                 config,
+                progress,
                 respond_to: tx,
             })
             .await
@@ -88,6 +173,27 @@ impl LlamaCppService {
         Ok(())
     }
 
+    pub async fn stop_model(&self, model_id: ModelId) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(ActorMessage::Stop {
+                model_id,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+        rx.await.map_err(|_| "Actor dropped".to_string())?
+    }
+
+    pub async fn list_models(&self) -> Vec<RunningModel> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self
+            .sender
+            .send(ActorMessage::ListModels { respond_to: tx })
+            .await;
+        rx.await.unwrap_or_default()
+    }
+
     pub async fn is_running(&self) -> bool {
         let (tx, rx) = oneshot::channel();
         let _ = self
@@ -102,15 +208,61 @@ impl LlamaCppService {
 
     pub async fn send_chat_message(
         &self,
+        model: Option<String>,
         session_id: Option<String>,
         messages: Vec<ChatMessage>,
-        temperature: f32,
-        top_p: f32,
-        top_k: i32,
+        sampling: &SamplingParams,
         max_tokens: i32,
     ) -> Result<mpsc::Receiver<String>, String> {
-        let config = self.get_config().await.ok_or("No model running")?;
-        let id = ModelId(config.model_path);
+        let config = self.resolve_target(model.as_deref()).await?;
+        let request = self.streaming_request(&config, session_id, messages, sampling, max_tokens);
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(ActorMessage::SendChat {
+                model_id: ModelId(config.model_path),
+                request,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+        rx.await.map_err(|_| "Actor dropped".to_string())?
+    }
+
+    /// Like `send_chat_message`, but offers `tools` to the model and also
+    /// yields the `delta.tool_calls` fragments it streams back.
+    pub async fn stream_chat_events(
+        &self,
+        model: Option<String>,
+        session_id: Option<String>,
+        messages: Vec<ChatMessage>,
+        sampling: &SamplingParams,
+        max_tokens: i32,
+        tools: Option<Vec<serde_json::Value>>,
+    ) -> Result<mpsc::Receiver<ChatStreamEvent>, String> {
+        let config = self.resolve_target(model.as_deref()).await?;
+        let mut request =
+            self.streaming_request(&config, session_id, messages, sampling, max_tokens);
+        request.tools = tools;
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(ActorMessage::StreamChat {
+                model_id: ModelId(config.model_path),
+                request,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+        rx.await.map_err(|_| "Actor dropped".to_string())?
+    }
+
+    fn streaming_request(
+        &self,
+        config: &LlamaCppConfig,
+        session_id: Option<String>,
+        messages: Vec<ChatMessage>,
+        sampling: &SamplingParams,
+        max_tokens: i32,
+    ) -> ChatRequest {
         let chat_template_kwargs = if config.chat_template.is_some() || config.chat_template_file.is_some() {
             Some(serde_json::json!({
                 "enable_thinking": true,
@@ -119,33 +271,17 @@ impl LlamaCppService {
         } else {
             None
         };
-        let request = ChatRequest {
-            model: "unknown".to_string(),
+        let mut request = ChatRequest {
+            model: config.model_path.clone(),
             session_id,
             messages,
-            temperature,
-            top_p,
-            top_k,
             max_tokens,
-            reasoning_format: None,
-            reasoning_budget: None,
-            reasoning_budget_message: None,
-            thinking_forced_open: None,
             chat_template_kwargs,
-            tools: None,
-            tool_choice: None,
             stream: true,
+            ..Default::default()
         };
-        let (tx, rx) = oneshot::channel();
-        self.sender
-            .send(ActorMessage::SendChat {
-                model_id: id,
-                request,
-                respond_to: tx,
-            })
-            .await
-            .map_err(|e| e.to_string())?;
-        rx.await.map_err(|_| "Actor dropped".to_string())?
+        self.apply_sampling(&config.model_path, sampling, &mut request);
+        request
     }
 
     pub async fn get_config(&self) -> Option<LlamaCppConfig> {
@@ -157,6 +293,49 @@ impl LlamaCppService {
         rx.await.unwrap_or(None)
     }
 
+    /// Config of the named model, or of the default model when `model` is `None`.
+    pub async fn get_model_config(&self, model: Option<&str>) -> Option<LlamaCppConfig> {
+        let Some(model) = model else {
+            return self.get_config().await;
+        };
+        let (tx, rx) = oneshot::channel();
+        let _ = self
+            .sender
+            .send(ActorMessage::GetModelConfig {
+                model_id: ModelId(model.to_string()),
+                respond_to: tx,
+            })
+            .await;
+        rx.await.unwrap_or(None)
+    }
+
+    async fn resolve_target(&self, model: Option<&str>) -> Result<LlamaCppConfig, String> {
+        match model {
+            Some(id) => self
+                .get_model_config(Some(id))
+                .await
+                .ok_or_else(|| format!("Model {} is not running", id)),
+            None => self
+                .get_config()
+                .await
+                .ok_or_else(|| "No model running".to_string()),
+        }
+    }
+
+    /// Logs survive the process, so a model that just failed to start can still
+    /// be inspected by the id it was launched with.
+    pub async fn server_logs(
+        &self,
+        model: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ServerLogLine>, String> {
+        let model_id = match model {
+            Some(id) if self.logs.contains(&ModelId(id.to_string())) => ModelId(id.to_string()),
+            _ => ModelId(self.resolve_target(model).await?.model_path),
+        };
+        Ok(self.logs.tail(&model_id, limit))
+    }
+
     pub async fn get_metrics(&self) -> Option<ServerMetrics> {
         let (tx, rx) = oneshot::channel();
         let _ = self
@@ -166,49 +345,31 @@ impl LlamaCppService {
         rx.await.unwrap_or(None)
     }
 
+    /// Non-streamed completion on `model` (or the default model). The
+    /// request's `model`, `stream` and sampler fields are set here, the
+    /// samplers resolved from `sampling` as for streamed turns.
     pub async fn complete_chat(
         &self,
-        session_id: Option<String>,
-        messages: Vec<ChatMessage>,
-        temperature: f32,
-        top_p: f32,
-        top_k: i32,
-        max_tokens: i32,
-        reasoning_format: Option<String>,
-        reasoning_budget: Option<i32>,
-        chat_template_kwargs: Option<serde_json::Value>,
-        tools: Option<Vec<serde_json::Value>>,
-        tool_choice: Option<serde_json::Value>,
+        model: Option<String>,
+        request: ChatRequest,
+        sampling: &SamplingParams,
     ) -> Result<serde_json::Value, String> {
-        let config = self.get_config().await.ok_or("No model running")?;
-        let id = ModelId(config.model_path);
-        let template_kwargs = if chat_template_kwargs.is_some() {
-            chat_template_kwargs
-        } else if config.chat_template.is_some() || config.chat_template_file.is_some() {
-            Some(serde_json::json!({
-                "enable_thinking": true,
-                "add_generation_prompt": true
-            }))
-        } else {
-            None
-        };
-        let request = ChatRequest {
-            model: "unknown".to_string(),
-            session_id,
-            messages,
-            temperature,
-            top_p,
-            top_k,
-            max_tokens,
-            reasoning_format,
-            reasoning_budget,
-            reasoning_budget_message: None,
-            thinking_forced_open: None,
-            chat_template_kwargs: template_kwargs,
-            tools,
-            tool_choice,
+        let config = self.resolve_target(model.as_deref()).await?;
+        let id = ModelId(config.model_path.clone());
+        let mut request = ChatRequest {
+            model: id.0.clone(),
             stream: false,
+            ..request
         };
+        if request.chat_template_kwargs.is_none()
+            && (config.chat_template.is_some() || config.chat_template_file.is_some())
+        {
+            request.chat_template_kwargs = Some(serde_json::json!({
+                "enable_thinking": true,
+                "add_generation_prompt": true
+            }));
+        }
+        self.apply_sampling(&config.model_path, sampling, &mut request);
         let (tx, rx) = oneshot::channel();
         self.sender
             .send(ActorMessage::CompleteChat {
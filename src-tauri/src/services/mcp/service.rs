@@ -699,12 +699,16 @@ impl McpService {
             return Err("Tool not allowed".to_string());
         }
 
-        let mut conns = self.connections.lock().await;
-        let conn = conns
-            .get_mut(id)
-            .ok_or_else(|| "Server not connected".to_string())?;
+        // Release the map before the call so other servers can be used meanwhile.
+        let client = {
+            let conns = self.connections.lock().await;
+            let conn = conns
+                .get(id)
+                .ok_or_else(|| "Server not connected".to_string())?;
+            conn.client.clone()
+        };
 
-        match conn.client.call_tool(tool_name, arguments).await {
+        match client.call_tool(tool_name, arguments).await {
             Ok(value) => Ok(value),
             Err(err) => match classify_call_error(err) {
                 McpCallError::Unsupported(err) => {
@@ -0,0 +1,300 @@
+use crate::models::{
+    DeviceMemoryEstimate, FlashAttention, GgufInfo, GgufTensorInfo, KvCacheType, MemoryEstimate,
+    MemoryEstimateOptions, ModelFit,
+};
+
+// Rough llama.cpp memory model. Offloading follows llama.cpp: the last
+// `n_gpu_layers` blocks go to the GPUs, and one layer past the block count
+// also moves the output layer. Layers are spread over GPUs by tensor split.
+// Compute buffers are heuristics sized from the ubatch and the largest
+// intermediate tensors; they aim to be a little pessimistic.
+
+const DEFAULT_UBATCH: u64 = 512;
+const F32_BYTES: u64 = 4;
+// Without metadata, KV cache and compute buffers at a typical context are
+// approximated as a share of the weights.
+const SIZE_ONLY_OVERHEAD_PERCENT: u64 = 20;
+
+/// Estimates memory for `options.n_gpu_layers`, and the best value for the budgets.
+pub fn estimate_memory(
+    info: &GgufInfo,
+    options: &MemoryEstimateOptions,
+) -> Result<MemoryEstimate, String> {
+    let shape = ModelShape::from_info(info)?;
+    let layers_total = shape.n_layer + 1;
+    let n_gpu_layers = if options.n_gpu_layers < 0 {
+        layers_total
+    } else {
+        (options.n_gpu_layers as u32).min(layers_total)
+    };
+
+    let mut estimate = estimate_for_layers(&shape, info, options, n_gpu_layers);
+    if !options.vram_budget_bytes.is_empty() {
+        estimate.recommended_n_gpu_layers = (0..=layers_total)
+            .rev()
+            .find(|&n| gpus_fit(&estimate_for_layers(&shape, info, options, n)));
+        estimate.fits = Some(gpus_fit(&estimate));
+    }
+    Ok(estimate)
+}
+
+/// Coarse verdict for a model known only by its file size, e.g. while browsing
+/// a repository. `vram_bytes` is free memory per GPU.
+pub fn estimate_fit_from_size(file_bytes: u64, vram_bytes: &[u64], ram_bytes: u64) -> ModelFit {
+    let needed = file_bytes + file_bytes * SIZE_ONLY_OVERHEAD_PERCENT / 100;
+    let vram: u64 = vram_bytes.iter().sum();
+    if vram > 0 && needed <= vram {
+        ModelFit::Gpu
+    } else if needed <= vram + ram_bytes {
+        if vram > 0 {
+            ModelFit::Partial
+        } else {
+            ModelFit::Cpu
+        }
+    } else {
+        ModelFit::TooLarge
+    }
+}
+
+fn gpus_fit(estimate: &MemoryEstimate) -> bool {
+    estimate.devices.iter().all(|d| d.fits != Some(false))
+}
+
+/// Dimensions the estimate needs, with the defaults llama.cpp derives.
+struct ModelShape {
+    n_layer: u32,
+    n_embd: u64,
+    n_head: u64,
+    n_embd_k_gqa: u64,
+    n_embd_v_gqa: u64,
+    n_ff: u64,
+    n_vocab: u64,
+}
+
+impl ModelShape {
+    fn from_info(info: &GgufInfo) -> Result<Self, String> {
+        let n_layer = info
+            .block_count
+            .filter(|n| *n > 0)
+            .ok_or_else(|| "GGUF has no block_count".to_string())? as u32;
+        let n_embd = info
+            .embedding_length
+            .ok_or_else(|| "GGUF has no embedding_length".to_string())?;
+        let n_head = info.head_count.unwrap_or(1).max(1);
+        let n_head_kv = info.head_count_kv.unwrap_or(n_head);
+        let head_dim = n_embd / n_head;
+        let n_vocab = info
+            .tensors
+            .iter()
+            .find(|t| t.name == "output.weight" || t.name == "token_embd.weight")
+            .and_then(|t| t.dims.get(1).copied())
+            .unwrap_or(0);
+
+        Ok(Self {
+            n_layer,
+            n_embd,
+            n_head,
+            n_embd_k_gqa: n_head_kv * info.key_length.unwrap_or(head_dim),
+            n_embd_v_gqa: n_head_kv * info.value_length.unwrap_or(head_dim),
+            n_ff: info.feed_forward_length.unwrap_or(4 * n_embd),
+            n_vocab,
+        })
+    }
+}
+
+/// Where a tensor lives when deciding its device.
+enum Placement {
+    Block(u32),
+    Output,
+    Input,
+}
+
+fn placement(tensor: &GgufTensorInfo) -> Placement {
+    if let Some(rest) = tensor.name.strip_prefix("blk.") {
+        if let Some(index) = rest.split('.').next().and_then(|i| i.parse().ok()) {
+            return Placement::Block(index);
+        }
+    }
+    if tensor.name.starts_with("output") {
+        Placement::Output
+    } else {
+        Placement::Input
+    }
+}
+
+fn estimate_for_layers(
+    shape: &ModelShape,
+    info: &GgufInfo,
+    options: &MemoryEstimateOptions,
+    n_gpu_layers: u32,
+) -> MemoryEstimate {
+    let params = &options.launch_params;
+    let split = gpu_split(options);
+    let gpu_count = split.len();
+    let main_gpu = (params.main_gpu.unwrap_or(0) as usize).min(gpu_count - 1);
+
+    // Device index per layer; index n_layer is the output layer. `None` is CPU.
+    let first_gpu_layer = shape.n_layer.saturating_sub(n_gpu_layers);
+    let layer_device = |layer: u32| -> Option<usize> {
+        if n_gpu_layers == 0 || layer < first_gpu_layer {
+            return None;
+        }
+        if layer == shape.n_layer && n_gpu_layers <= shape.n_layer {
+            return None;
+        }
+        let position = (layer - first_gpu_layer) as f64 / n_gpu_layers as f64;
+        Some(
+            split
+                .iter()
+                .position(|&bound| position < bound)
+                .unwrap_or(gpu_count - 1),
+        )
+    };
+
+    let mut cpu = DeviceTotals::default();
+    let mut gpus = vec![DeviceTotals::default(); gpu_count];
+    for layer in 0..=shape.n_layer {
+        match layer_device(layer) {
+            Some(i) => gpus[i].layers += 1,
+            None => cpu.layers += 1,
+        }
+    }
+
+    for tensor in &info.tensors {
+        let size = tensor.size_bytes.unwrap_or(0);
+        let target = match placement(tensor) {
+            Placement::Block(layer) => layer_device(layer.min(shape.n_layer - 1)),
+            Placement::Output => layer_device(shape.n_layer),
+            Placement::Input => None,
+        };
+        match target {
+            Some(i) => gpus[i].weights += size,
+            None => cpu.weights += size,
+        }
+    }
+
+    // KV cache: one row of K and V per context token in every block, kept
+    // with its layer.
+    let ctx = options.ctx_size as u64;
+    let kv_per_layer = kv_bytes(
+        ctx * shape.n_embd_k_gqa,
+        params.cache_type_k.unwrap_or(KvCacheType::F16),
+    ) + kv_bytes(
+        ctx * shape.n_embd_v_gqa,
+        params.cache_type_v.unwrap_or(KvCacheType::F16),
+    );
+    for layer in 0..shape.n_layer {
+        match layer_device(layer) {
+            Some(i) => gpus[i].kv_cache += kv_per_layer,
+            None => cpu.kv_cache += kv_per_layer,
+        }
+    }
+
+    // Compute: activations for one ubatch, the attention score matrix
+    // unless flash attention avoids materialising it, and the logits.
+    let offloading = n_gpu_layers > 0;
+    let flash = match params.flash_attn {
+        Some(FlashAttention::On) => true,
+        Some(FlashAttention::Off) => false,
+        Some(FlashAttention::Auto) | None => offloading,
+    };
+    let ubatch = params
+        .ubatch_size
+        .map(u64::from)
+        .unwrap_or(DEFAULT_UBATCH)
+        .min(ctx.max(1));
+    let activations = ubatch * F32_BYTES * (2 * shape.n_ff + 4 * shape.n_embd);
+    let attention = if flash {
+        0
+    } else {
+        ubatch * ctx * shape.n_head * F32_BYTES
+    };
+    let logits = ubatch * shape.n_vocab * F32_BYTES;
+    if offloading {
+        gpus[main_gpu].compute += activations + attention;
+    } else {
+        cpu.compute += activations + attention;
+    }
+    match layer_device(shape.n_layer) {
+        Some(i) => gpus[i].compute += logits,
+        None => cpu.compute += logits,
+    }
+    // Host copy of the output logits for every slot.
+    cpu.compute += options.parallel.max(1) as u64 * shape.n_vocab * F32_BYTES;
+
+    let mut devices = vec![cpu.into_estimate("CPU".to_string(), None)];
+    for (i, totals) in gpus.into_iter().enumerate() {
+        let budget = options.vram_budget_bytes.get(i).copied();
+        devices.push(totals.into_estimate(format!("GPU{}", i), budget));
+    }
+    MemoryEstimate {
+        layers_total: shape.n_layer + 1,
+        n_gpu_layers,
+        total_bytes: devices.iter().map(|d| d.total_bytes).sum(),
+        devices,
+        recommended_n_gpu_layers: None,
+        fits: None,
+    }
+}
+
+/// Cumulative split bounds in (0, 1], one per GPU.
+fn gpu_split(options: &MemoryEstimateOptions) -> Vec<f64> {
+    let weights: Vec<f64> = if !options.launch_params.tensor_split.is_empty() {
+        options
+            .launch_params
+            .tensor_split
+            .iter()
+            .map(|v| *v as f64)
+            .collect()
+    } else if options.vram_budget_bytes.len() > 1 {
+        // Without an explicit split llama.cpp divides by available memory.
+        options
+            .vram_budget_bytes
+            .iter()
+            .map(|b| *b as f64)
+            .collect()
+    } else {
+        vec![1.0]
+    };
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return vec![1.0];
+    }
+    let mut running = 0.0;
+    weights
+        .iter()
+        .map(|w| {
+            running += w / total;
+            running
+        })
+        .collect()
+}
+
+fn kv_bytes(elements: u64, kind: KvCacheType) -> u64 {
+    let (block, bytes) = kind.block_layout();
+    elements.div_ceil(block) * bytes
+}
+
+#[derive(Default, Clone)]
+struct DeviceTotals {
+    layers: u32,
+    weights: u64,
+    kv_cache: u64,
+    compute: u64,
+}
+
+impl DeviceTotals {
+    fn into_estimate(self, device: String, budget: Option<u64>) -> DeviceMemoryEstimate {
+        let total = self.weights + self.kv_cache + self.compute;
+        DeviceMemoryEstimate {
+            device,
+            layers: self.layers,
+            weights_bytes: self.weights,
+            kv_cache_bytes: self.kv_cache,
+            compute_bytes: self.compute,
+            total_bytes: total,
+            budget_bytes: budget,
+            fits: budget.map(|b| total <= b),
+        }
+    }
+}
@@ -1,63 +1,94 @@
-use crate::models::ChatMessage;
+use crate::infrastructure::session_store::{InMemorySessionStore, SessionStore};
+use crate::models::{
+    ChatMessage, ChatRequest, ChatStreamEvent, ChatTurnOptions, SamplingParams, ToolApproval,
+    ToolApprovalDecision, ToolLoopPolicy,
+};
 use crate::services::capability_registry::{CapabilityRegistry, LlmToolSpecBundle, ResolvedCall};
 use crate::services::llama::service::LlamaCppService;
 use crate::services::mcp::McpService;
 use crate::services::thinking_parser::{ParsedChunk, ThinkingStreamParser};
-use std::collections::{HashMap, HashSet};
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::ipc::Channel;
-use tokio::sync::Mutex;
+use tokio::sync::{oneshot, Mutex};
 
-const MAX_TOOL_ITERATIONS: usize = 3;
-const TOOL_CALL_MAX_TOKENS: i32 = 1024;
-const TOOL_RESULT_TOKEN_BUDGET: usize = 512;
+/// How long a call that needs approval waits for the user before it is dropped.
+const TOOL_APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+const TOOL_CALL_OPEN: &str = "<tool_call>";
+const TOOL_CALL_CLOSE: &str = "</tool_call>";
 
 #[derive(Clone)]
 pub struct ChatOrchestrator {
     sessions: Arc<Mutex<HashMap<String, Vec<ChatMessage>>>>,
+    store: Arc<dyn SessionStore>,
     service: LlamaCppService,
     mcp_service: McpService,
     registry: CapabilityRegistry,
+    /// Tool calls waiting for the user, keyed by approval request id.
+    pending_approvals: Arc<Mutex<HashMap<String, oneshot::Sender<ToolApprovalDecision>>>>,
+    approval_timeout: Duration,
 }
 
 impl ChatOrchestrator {
     pub fn new(service: LlamaCppService, mcp_service: McpService) -> Self {
+        Self::with_session_store(service, mcp_service, Arc::new(InMemorySessionStore::new()))
+    }
+
+    /// Sessions are cached in memory and every mutation is written through to `store`.
+    pub fn with_session_store(
+        service: LlamaCppService,
+        mcp_service: McpService,
+        store: Arc<dyn SessionStore>,
+    ) -> Self {
         let registry = CapabilityRegistry::new();
 
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            store,
             service,
             mcp_service,
             registry,
+            pending_approvals: Arc::new(Mutex::new(HashMap::new())),
+            approval_timeout: TOOL_APPROVAL_TIMEOUT,
         }
     }
 
+    pub fn with_approval_timeout(mut self, timeout: Duration) -> Self {
+        self.approval_timeout = timeout;
+        self
+    }
+
+    /// Delivers the user's answer to a pending `approval_request` event.
+    pub async fn respond_to_approval(
+        &self,
+        request_id: &str,
+        decision: ToolApprovalDecision,
+    ) -> Result<(), String> {
+        let sender = self
+            .pending_approvals
+            .lock()
+            .await
+            .remove(request_id)
+            .ok_or_else(|| {
+                format!(
+                    "No pending approval '{}'; it may have timed out",
+                    request_id
+                )
+            })?;
+        sender
+            .send(decision)
+            .map_err(|_| format!("Approval '{}' is no longer awaited", request_id))
+    }
+
     pub async fn complete_chat_once(
         &self,
-        messages: Vec<ChatMessage>,
-        temperature: f32,
-        top_p: f32,
-        top_k: i32,
-        max_tokens: i32,
-        reasoning_format: Option<String>,
-        reasoning_budget: Option<i32>,
-        chat_template_kwargs: Option<serde_json::Value>,
+        model: Option<String>,
+        request: ChatRequest,
+        sampling: &SamplingParams,
     ) -> Result<serde_json::Value, String> {
-        self.service
-            .complete_chat(
-                None,
-                messages,
-                temperature,
-                top_p,
-                top_k,
-                max_tokens,
-                reasoning_format,
-                reasoning_budget,
-                chat_template_kwargs,
-                None,
-                None,
-            )
-            .await
+        self.service.complete_chat(model, request, sampling).await
     }
 
     fn try_send(on_event: &Channel<serde_json::Value>, payload: serde_json::Value) -> bool {
@@ -77,10 +108,12 @@ impl ChatOrchestrator {
         &self,
         session_id: &str,
         user_input: String,
-        temperature: f32,
-        max_tokens: i32,
+        options: &ChatTurnOptions,
         on_event: Channel<serde_json::Value>,
     ) -> Result<(), String> {
+        let model = options.model.as_deref();
+        let policy = options.tool_loop_policy.clone().unwrap_or_default();
+
         // Guard against race condition: if registry is empty (startup refresh still running),
         // attempt a blocking refresh before processing.
         if self.registry.available_server_ids().await.is_empty() {
@@ -102,14 +135,15 @@ impl ChatOrchestrator {
                 name: None,
                 tool_call_id: None,
                 tool_calls: None,
+                tool_approvals: None,
             },
         )
-        .await;
+        .await?;
 
         if allowed_servers.is_empty() {
             let messages = self.get_history(session_id).await;
             return self
-                .run_streaming(session_id, messages, temperature, max_tokens, on_event)
+                .run_streaming(session_id, messages, options, on_event)
                 .await;
         }
 
@@ -121,80 +155,111 @@ impl ChatOrchestrator {
         if tool_bundle.tools.is_empty() {
             let messages = self.get_history(session_id).await;
             return self
-                .run_streaming(session_id, messages, temperature, max_tokens, on_event)
+                .run_streaming(session_id, messages, options, on_event)
                 .await;
         }
 
+        // Turns that may call tools use the policy's sampling. A turn that
+        // answers without calling one is already streamed, so it stays the
+        // answer. Once a guard below stops the loop, one last turn is streamed
+        // without tools and with the chat's sampling, and it is the answer.
+        let tool_sampling = policy.tool_sampling(&options.sampling);
         let mut iteration = 0usize;
+        let mut final_turn = false;
         let mut seen_calls: HashSet<String> = HashSet::new();
         let mut tool_call_counts: HashMap<String, usize> = HashMap::new();
 
         loop {
             iteration += 1;
-            if iteration > MAX_TOOL_ITERATIONS {
+            if !final_turn && iteration > policy.max_iterations {
                 if !Self::try_send(
                     &on_event,
                     serde_json::json!({
                         "thinking": format!(
                             "Tool loop exceeded max iterations ({}). Streaming final answer.",
-                            MAX_TOOL_ITERATIONS
+                            policy.max_iterations
                         )
                     }),
                 ) {
                     return Ok(());
                 }
-                let messages = self.get_history(session_id).await;
-                return self
-                    .run_streaming(session_id, messages, temperature, max_tokens, on_event)
-                    .await;
+                final_turn = true;
             }
 
-            let ctx_size = self.current_ctx_size().await.unwrap_or(4096) as usize;
-            let tool_max_tokens = clamp_max_tokens(ctx_size, TOOL_CALL_MAX_TOKENS);
-            let prompt_budget = compute_prompt_budget(ctx_size, tool_max_tokens);
+            let ctx_size = self.current_ctx_size(model).await.unwrap_or(4096) as usize;
+            let effective_max_tokens = clamp_max_tokens(ctx_size, options.max_tokens);
+            let prompt_budget = compute_prompt_budget(ctx_size, effective_max_tokens);
             let history = self.get_history(session_id).await;
             let request_messages =
                 sanitize_messages_for_request(trim_messages_to_budget(&history, prompt_budget));
 
-            if !Self::try_send(
-                &on_event,
-                serde_json::json!({
-                    "thinking": format!("Tool loop iteration {}", iteration)
-                }),
-            ) {
-                return Ok(());
-            }
-
-            let response = self
-                .service
-                .complete_chat(
-                    None,
-                    request_messages,
-                    temperature.min(0.5),
-                    0.95,
-                    40,
-                    tool_max_tokens,
-                    None,
-                    None,
-                    None,
-                    Some(tool_bundle.tools.clone()),
-                    None,
-                )
-                .await?;
-
-            let parsed = parse_tool_calls_from_response(&response)?;
-            if parsed.tool_calls.is_empty() {
+            let (sampling, tools) = if final_turn {
+                (&options.sampling, None)
+            } else {
                 if !Self::try_send(
                     &on_event,
                     serde_json::json!({
-                        "thinking": "No tool calls detected. Streaming final answer."
+                        "thinking": format!("Tool loop iteration {}", iteration)
                     }),
                 ) {
                     return Ok(());
                 }
-                let messages = self.get_history(session_id).await;
+                (&tool_sampling, Some(tool_bundle.tools.clone()))
+            };
+
+            // The turn is streamed live; it is the final answer unless it ends
+            // in tool calls. `<tool_call>` markup in the text is held back and
+            // kept only in `full_response`, which is parsed below.
+            let mut rx = self
+                .service
+                .stream_chat_events(
+                    model.map(str::to_string),
+                    Some(session_id.to_string()),
+                    request_messages,
+                    sampling,
+                    effective_max_tokens,
+                    tools,
+                )
+                .await?;
+
+            let mut full_response = String::new();
+            let mut shown = String::new();
+            let mut parser = ThinkingStreamParser::new();
+            let mut markup = ToolMarkupFilter::default();
+            let mut tool_call_deltas = ToolCallAccumulator::default();
+
+            while let Some(event) = rx.recv().await {
+                match event {
+                    ChatStreamEvent::Text(text) => {
+                        let chunks = markup.filter(parser.push(&text), &mut full_response);
+                        if !Self::forward_chunks(&on_event, chunks, &mut shown) {
+                            return Ok(());
+                        }
+                    }
+                    ChatStreamEvent::ToolCallDelta(delta) => tool_call_deltas.push(&delta),
+                    ChatStreamEvent::Error(e) => return Err(e),
+                }
+            }
+            let mut chunks = markup.filter(parser.flush(), &mut full_response);
+            chunks.extend(markup.flush());
+            if !Self::forward_chunks(&on_event, chunks, &mut shown) {
+                return Ok(());
+            }
+            if final_turn {
+                return self.finish_answer(session_id, shown, None, &on_event).await;
+            }
+
+            let mut message = serde_json::json!({ "content": full_response });
+            let streamed_calls = tool_call_deltas.finish();
+            if !streamed_calls.is_empty() {
+                message["tool_calls"] = serde_json::Value::Array(streamed_calls);
+            }
+            let parsed = parse_tool_calls_from_response(
+                &serde_json::json!({ "choices": [{ "message": message }] }),
+            )?;
+            if parsed.tool_calls.is_empty() {
                 return self
-                    .run_streaming(session_id, messages, temperature, max_tokens, on_event)
+                    .finish_answer(session_id, full_response, None, &on_event)
                     .await;
             }
 
@@ -206,9 +271,10 @@ impl ChatOrchestrator {
                     name: None,
                     tool_call_id: None,
                     tool_calls: Some(parsed.raw_tool_calls),
+                    tool_approvals: None,
                 },
             )
-            .await;
+            .await?;
 
             let repeat_detected = self
                 .execute_tool_calls(
@@ -219,6 +285,7 @@ impl ChatOrchestrator {
                     &parsed.tool_calls,
                     &mut seen_calls,
                     &mut tool_call_counts,
+                    &policy,
                     &on_event,
                 )
                 .await?;
@@ -232,22 +299,25 @@ impl ChatOrchestrator {
                 ) {
                     return Ok(());
                 }
-                let messages = self.get_history(session_id).await;
-                return self
-                    .run_streaming(session_id, messages, temperature, max_tokens, on_event)
-                    .await;
+                final_turn = true;
             }
         }
     }
 
-    async fn append_message(&self, session_id: &str, message: ChatMessage) {
+    async fn append_message(&self, session_id: &str, message: ChatMessage) -> Result<(), String> {
         let mut sessions = self.sessions.lock().await;
-        let history = sessions
+        self.hydrate_session(&mut sessions, session_id);
+        self.store.append(session_id, &message)?;
+        sessions
             .entry(session_id.to_string())
-            .or_insert_with(Vec::new);
-        history.push(message);
+            .or_default()
+            .push(message);
+        Ok(())
     }
 
+    /// Runs every call of one model turn. Checks happen in call order, the
+    /// MCP calls themselves run concurrently (up to `max_parallel_tool_calls`)
+    /// and the `tool` messages are appended in call order.
     async fn execute_tool_calls(
         &self,
         session_id: &str,
@@ -257,70 +327,39 @@ impl ChatOrchestrator {
         tool_calls: &[LlmToolCall],
         seen_calls: &mut HashSet<String>,
         tool_call_counts: &mut HashMap<String, usize>,
+        policy: &ToolLoopPolicy,
         on_event: &Channel<serde_json::Value>,
     ) -> Result<bool, String> {
         let mut repeat_detected = false;
+        let mut planned = Vec::with_capacity(tool_calls.len());
+        let mcp_config = self.mcp_service.get_config().await;
 
-        if tool_calls.len() > 1 {
-            if !Self::try_send(
-                &on_event,
-                serde_json::json!({
-                    "thinking": format!(
-                        "Multiple tool calls received ({}). Executing the first only.",
-                        tool_calls.len()
-                    )
-                }),
-            ) {
-                return Ok(repeat_detected);
-            }
-        }
-
-        for (idx, call) in tool_calls.iter().take(1).enumerate() {
+        for call in tool_calls {
             let fingerprint = format!("{}:{}", call.tool_id, hash_args(&call.arguments));
             if !seen_calls.insert(fingerprint) {
                 repeat_detected = true;
-                self.append_tool_error(
-                    session_id,
-                    &call.id,
-                    format!("Repeated tool call for '{}'", call.tool_id),
-                )
-                .await;
+                planned.push(Err(format!("Repeated tool call for '{}'", call.tool_id)));
                 continue;
             }
 
             let (server_id, tool_name) = match resolve_tool_id(&call.tool_id, tool_bundle) {
                 Some(pair) => pair,
                 None => {
-                    self.append_tool_error(
-                        session_id,
-                        &call.id,
-                        format!("Unknown tool id '{}'", call.tool_id),
-                    )
-                    .await;
+                    planned.push(Err(format!("Unknown tool id '{}'", call.tool_id)));
                     continue;
                 }
             };
 
             let tool_count = tool_call_counts.entry(call.tool_id.clone()).or_insert(0);
-            if *tool_count >= 2 {
+            if *tool_count >= policy.max_calls_per_tool {
                 repeat_detected = true;
-                self.append_tool_error(
-                    session_id,
-                    &call.id,
-                    format!("Tool '{}' called too many times", tool_name),
-                )
-                .await;
+                planned.push(Err(format!("Tool '{}' called too many times", tool_name)));
                 continue;
             }
             *tool_count += 1;
 
             if !allowed_servers.contains(&server_id) {
-                self.append_tool_error(
-                    session_id,
-                    &call.id,
-                    format!("Server '{}' not allowed", server_id),
-                )
-                .await;
+                planned.push(Err(format!("Server '{}' not allowed", server_id)));
                 continue;
             }
 
@@ -335,38 +374,95 @@ impl ChatOrchestrator {
                 .await;
 
             let resolved = ResolvedCall {
-                server_id: server_id.clone(),
-                tool_name: tool_name.clone(),
-                arguments: arguments.clone(),
+                server_id,
+                tool_name,
+                arguments,
             };
 
             if let Err(e) = self.registry.validate_call(&resolved).await {
-                self.append_tool_error(session_id, &call.id, e).await;
+                planned.push(Err(e));
                 continue;
             }
 
+            let approval = mcp_config
+                .servers
+                .iter()
+                .find(|server| server.id == resolved.server_id)
+                .map_or(ToolApproval::Allow, |server| {
+                    server.approval_for(&resolved.tool_name)
+                });
+            let resolved = match approval {
+                ToolApproval::Allow => resolved,
+                ToolApproval::Deny => {
+                    planned.push(Err(format!(
+                        "Tool '{}' is denied by the server's approval policy",
+                        resolved.tool_name
+                    )));
+                    continue;
+                }
+                ToolApproval::Ask => {
+                    match self
+                        .await_approval(session_id, &call.id, resolved, on_event)
+                        .await
+                    {
+                        Some(Ok(resolved)) => resolved,
+                        Some(Err(e)) => {
+                            planned.push(Err(e));
+                            continue;
+                        }
+                        None => {
+                            self.append_cancelled_results(session_id, tool_calls)
+                                .await?;
+                            return Ok(repeat_detected);
+                        }
+                    }
+                }
+            };
+
             if !Self::try_send(
                 &on_event,
                 serde_json::json!({
-                    "thinking": format!("Calling MCP tool {}::{}", server_id, tool_name)
+                    "thinking": format!(
+                        "Calling MCP tool {}::{}",
+                        resolved.server_id, resolved.tool_name
+                    )
                 }),
             ) {
+                self.append_cancelled_results(session_id, tool_calls)
+                    .await?;
                 return Ok(repeat_detected);
             }
 
-            let tool_context_args = arguments.clone();
+            planned.push(Ok(resolved));
+        }
 
-            let result = match self.mcp_service.connect(&server_id).await {
-                Ok(()) => {
-                    self.mcp_service
-                        .tools_call(&server_id, &tool_name, arguments)
-                        .await
+        let max_parallel = mcp_config.max_parallel_tool_calls.max(1);
+        // `buffered` keeps the output in input order whatever finishes first.
+        let pending: Vec<_> = planned
+            .iter()
+            .map(|plan| self.call_mcp_tool(plan.as_ref().ok()))
+            .collect();
+        let results: Vec<Option<Result<serde_json::Value, String>>> =
+            stream::iter(pending).buffered(max_parallel).collect().await;
+
+        let mut executed = 0usize;
+        for (index, ((call, plan), result)) in
+            tool_calls.iter().zip(planned).zip(results).enumerate()
+        {
+            let resolved = match plan {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    self.append_tool_error(session_id, &call.id, e).await?;
+                    continue;
                 }
-                Err(e) => Err(e),
             };
+            let Some(result) = result else {
+                continue;
+            };
+            executed += 1;
 
             let (content, raw_result, error_message) = match result {
-                Ok(res) => (format_tool_result(&res), Some(res), None),
+                Ok(res) => (format_tool_result(&res, policy.result_token_budget), Some(res), None),
                 Err(e) => {
                     if !Self::try_send(
                         &on_event,
@@ -374,9 +470,15 @@ impl ChatOrchestrator {
                             "thinking": format!("Tool call failed: {}", e)
                         }),
                     ) {
+                        self.append_cancelled_results(session_id, &tool_calls[index..])
+                            .await?;
                         return Ok(repeat_detected);
                     }
                     if is_rate_limit_error(&e) {
+                        self.append_tool_error(session_id, &call.id, e.clone())
+                            .await?;
+                        self.append_cancelled_results(session_id, &tool_calls[index + 1..])
+                            .await?;
                         return Err(e);
                     }
                     if !is_invalid_input_error(&e) {
@@ -394,14 +496,15 @@ impl ChatOrchestrator {
                     name: None,
                     tool_call_id: Some(call.id.clone()),
                     tool_calls: None,
+                    tool_approvals: None,
                 },
             )
-            .await;
+            .await?;
 
             let tool_context = serde_json::json!({
-                "server_id": server_id,
-                "tool_name": tool_name,
-                "arguments": tool_context_args,
+                "server_id": resolved.server_id,
+                "tool_name": resolved.tool_name,
+                "arguments": resolved.arguments,
                 "result": raw_result.unwrap_or_else(|| {
                     serde_json::json!({ "error": error_message.unwrap_or_else(|| "Tool call failed".to_string()) })
                 }),
@@ -414,22 +517,161 @@ impl ChatOrchestrator {
                     "tool_context": tool_context
                 }),
             ) {
+                self.append_cancelled_results(session_id, &tool_calls[index + 1..])
+                    .await?;
                 return Ok(repeat_detected);
             }
+        }
 
-            if idx + 1 == 1 {
-                if !Self::try_send(
-                    &on_event,
-                    serde_json::json!({
-                        "thinking": "Tool results injected into context."
-                    }),
-                ) {
-                    return Ok(repeat_detected);
+        if executed > 0
+            && !Self::try_send(
+                &on_event,
+                serde_json::json!({
+                    "thinking": "Tool results injected into context."
+                }),
+            )
+        {
+            return Ok(repeat_detected);
+        }
+
+        Ok(repeat_detected)
+    }
+
+    /// Asks the user about `call` and waits for the answer. Returns the call to
+    /// run (with edited arguments, if any), the error to record instead, or
+    /// `None` when the event channel is closed.
+    async fn await_approval(
+        &self,
+        session_id: &str,
+        tool_call_id: &str,
+        call: ResolvedCall,
+        on_event: &Channel<serde_json::Value>,
+    ) -> Option<Result<ResolvedCall, String>> {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.pending_approvals
+            .lock()
+            .await
+            .insert(request_id.clone(), tx);
+
+        let sent = Self::try_send(
+            on_event,
+            serde_json::json!({
+                "approval_request": {
+                    "request_id": request_id,
+                    "tool_call_id": tool_call_id,
+                    "server_id": call.server_id,
+                    "tool_name": call.tool_name,
+                    "arguments": call.arguments,
+                    "timeout_secs": self.approval_timeout.as_secs(),
+                }
+            }),
+        );
+        if !sent {
+            self.pending_approvals.lock().await.remove(&request_id);
+            return None;
+        }
+        let decision = tokio::time::timeout(self.approval_timeout, rx).await;
+        self.pending_approvals.lock().await.remove(&request_id);
+
+        let (label, outcome) = match decision {
+            Ok(Ok(ToolApprovalDecision::Approve)) => ("approved", Ok(call)),
+            Ok(Ok(ToolApprovalDecision::Edit { arguments })) => {
+                let edited = ResolvedCall { arguments, ..call };
+                match self.registry.validate_call(&edited).await {
+                    Ok(()) => ("edited", Ok(edited)),
+                    Err(e) => ("edited", Err(format!("Edited arguments are invalid: {}", e))),
                 }
             }
+            Ok(Ok(ToolApprovalDecision::Reject { reason })) => (
+                "rejected",
+                Err(match reason.filter(|r| !r.trim().is_empty()) {
+                    Some(reason) => format!("The user rejected this tool call: {}", reason),
+                    None => "The user rejected this tool call".to_string(),
+                }),
+            ),
+            Ok(Err(_)) => (
+                "rejected",
+                Err("The approval request was cancelled".to_string()),
+            ),
+            Err(_) => (
+                "timed_out",
+                Err(format!(
+                    "No approval within {}s; the tool call was not run",
+                    self.approval_timeout.as_secs()
+                )),
+            ),
+        };
+        let ran_arguments = outcome.as_ref().ok().map(|call| &call.arguments);
+        self.record_approval(session_id, tool_call_id, label, ran_arguments)
+            .await;
+
+        if !Self::try_send(
+            on_event,
+            serde_json::json!({
+                "approval_resolved": { "request_id": request_id, "decision": label }
+            }),
+        ) {
+            return None;
         }
+        Some(outcome)
+    }
 
-        Ok(repeat_detected)
+    /// Stores the approval decision next to the assistant tool call and, when
+    /// the call runs, the arguments it runs with, so the history shows any edit.
+    async fn record_approval(
+        &self,
+        session_id: &str,
+        tool_call_id: &str,
+        decision: &str,
+        arguments: Option<&serde_json::Value>,
+    ) {
+        let mut sessions = self.sessions.lock().await;
+        let Some(history) = self.hydrate_session(&mut sessions, session_id) else {
+            return;
+        };
+        let is_call = |call: &serde_json::Value| {
+            call.get("id").and_then(|id| id.as_str()) == Some(tool_call_id)
+        };
+        let Some(message) = history
+            .iter_mut()
+            .rev()
+            .find(|message| message.tool_calls.iter().flatten().any(is_call))
+        else {
+            return;
+        };
+        message
+            .tool_approvals
+            .get_or_insert_with(Default::default)
+            .insert(tool_call_id.to_string(), decision.to_string());
+        let call = message
+            .tool_calls
+            .iter_mut()
+            .flatten()
+            .find(|call| is_call(call));
+        if let (Some(call), Some(arguments)) = (call, arguments) {
+            call["function"]["arguments"] = serde_json::Value::String(arguments.to_string());
+        }
+        if let Err(e) = self.store.replace(session_id, history) {
+            eprintln!("[SessionStore] Failed to persist message: {}", e);
+        }
+    }
+
+    /// `None` for calls rejected before execution.
+    async fn call_mcp_tool(
+        &self,
+        call: Option<&ResolvedCall>,
+    ) -> Option<Result<serde_json::Value, String>> {
+        let call = call?;
+        let result = match self.mcp_service.connect(&call.server_id).await {
+            Ok(()) => {
+                self.mcp_service
+                    .tools_call(&call.server_id, &call.tool_name, call.arguments.clone())
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        Some(result)
     }
 
     async fn build_tool_arguments(
@@ -453,7 +695,30 @@ impl ChatOrchestrator {
         serde_json::json!({ "query": original_query })
     }
 
-    async fn append_tool_error(&self, session_id: &str, call_id: &str, error: String) {
+    /// Answers the calls a turn stopped before reaching, so every assistant
+    /// `tool_calls` entry still has its `tool` message.
+    async fn append_cancelled_results(
+        &self,
+        session_id: &str,
+        calls: &[LlmToolCall],
+    ) -> Result<(), String> {
+        for call in calls {
+            self.append_tool_error(
+                session_id,
+                &call.id,
+                "The tool call was cancelled".to_string(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn append_tool_error(
+        &self,
+        session_id: &str,
+        call_id: &str,
+        error: String,
+    ) -> Result<(), String> {
         self.append_message(
             session_id,
             ChatMessage {
@@ -462,9 +727,10 @@ impl ChatOrchestrator {
                 name: None,
                 tool_call_id: Some(call_id.to_string()),
                 tool_calls: None,
+                tool_approvals: None,
             },
         )
-        .await;
+        .await
     }
 
     // ══════════════════════════════════════════════════════════════
@@ -475,12 +741,12 @@ impl ChatOrchestrator {
         &self,
         session_id: &str,
         messages: Vec<ChatMessage>,
-        temperature: f32,
-        max_tokens: i32,
+        options: &ChatTurnOptions,
         on_event: Channel<serde_json::Value>,
     ) -> Result<(), String> {
-        let ctx_size = self.current_ctx_size().await.unwrap_or(4096) as usize;
-        let effective_max_tokens = clamp_max_tokens(ctx_size, max_tokens);
+        let model = options.model.as_deref();
+        let ctx_size = self.current_ctx_size(model).await.unwrap_or(4096) as usize;
+        let effective_max_tokens = clamp_max_tokens(ctx_size, options.max_tokens);
         let prompt_budget = compute_prompt_budget(ctx_size, effective_max_tokens);
         let request_messages =
             sanitize_messages_for_request(trim_messages_to_budget(&messages, prompt_budget));
@@ -488,11 +754,10 @@ impl ChatOrchestrator {
         let mut rx = self
             .service
             .send_chat_message(
+                model.map(str::to_string),
                 Some(session_id.to_string()),
                 request_messages,
-                temperature,
-                0.95,
-                40,
+                &options.sampling,
                 effective_max_tokens,
             )
             .await?;
@@ -501,83 +766,133 @@ impl ChatOrchestrator {
         let mut parser = ThinkingStreamParser::new();
 
         while let Some(chunk) = rx.recv().await {
-            for parsed in parser.push(&chunk) {
-                match parsed {
-                    ParsedChunk::Content(text) => {
-                        full_response.push_str(&text);
-                        if !Self::try_send(&on_event, serde_json::json!({ "chunk": text })) {
-                            return Ok(());
-                        }
-                    }
-                    ParsedChunk::Thinking(text) => {
-                        if !Self::try_send(&on_event, serde_json::json!({ "thinking_chunk": text }))
-                        {
-                            return Ok(());
-                        }
-                    }
-                }
+            if !Self::forward_chunks(&on_event, parser.push(&chunk), &mut full_response) {
+                return Ok(());
             }
         }
 
         // Flush any buffered content at end-of-stream
-        for parsed in parser.flush() {
-            match parsed {
+        if !Self::forward_chunks(&on_event, parser.flush(), &mut full_response) {
+            return Ok(());
+        }
+
+        self.finish_answer(session_id, full_response, None, &on_event)
+            .await
+    }
+
+    /// Sends content and thinking chunks to the UI, collecting the content
+    /// into `full_response`. `false` once the channel is closed.
+    fn forward_chunks(
+        on_event: &Channel<serde_json::Value>,
+        chunks: Vec<ParsedChunk>,
+        full_response: &mut String,
+    ) -> bool {
+        for parsed in chunks {
+            let sent = match parsed {
                 ParsedChunk::Content(text) => {
                     full_response.push_str(&text);
-                    if !Self::try_send(&on_event, serde_json::json!({ "chunk": text })) {
-                        return Ok(());
-                    }
+                    Self::try_send(on_event, serde_json::json!({ "chunk": text }))
                 }
                 ParsedChunk::Thinking(text) => {
-                    if !Self::try_send(&on_event, serde_json::json!({ "thinking_chunk": text })) {
-                        return Ok(());
-                    }
+                    Self::try_send(on_event, serde_json::json!({ "thinking_chunk": text }))
                 }
+            };
+            if !sent {
+                return false;
             }
         }
+        true
+    }
 
-        if !Self::try_send(&on_event, serde_json::json!({ "status": "done" })) {
+    /// Marks the stream done and stores the streamed answer, replacing the
+    /// message at `replace_at` when it regenerates one.
+    async fn finish_answer(
+        &self,
+        session_id: &str,
+        full_response: String,
+        replace_at: Option<usize>,
+        on_event: &Channel<serde_json::Value>,
+    ) -> Result<(), String> {
+        if !Self::try_send(on_event, serde_json::json!({ "status": "done" })) {
             return Ok(());
         }
 
         let mut sessions = self.sessions.lock().await;
-        if let Some(history) = sessions.get_mut(session_id) {
-            history.push(ChatMessage {
+        if let Some(message_index) = replace_at {
+            let history = sessions
+                .get_mut(session_id)
+                .ok_or_else(|| "Session not found".to_string())?;
+            if message_index >= history.len() {
+                return Err("Message removed".to_string());
+            }
+            history[message_index].content = full_response;
+            return self.store.replace(session_id, history);
+        }
+        if let Some(history) = self.hydrate_session(&mut sessions, session_id) {
+            let message = ChatMessage {
                 role: "assistant".to_string(),
                 content: full_response,
                 name: None,
                 tool_call_id: None,
                 tool_calls: None,
-            });
+                tool_approvals: None,
+            };
+            self.store.append(session_id, &message)?;
+            history.push(message);
         }
 
         Ok(())
     }
 
-
     // ══════════════════════════════════════════════════════════════
     //  SESSION MANAGEMENT
     // ══════════════════════════════════════════════════════════════
 
+    /// Returns the cached history, loading it from the store on first access.
+    fn hydrate_session<'a>(
+        &self,
+        sessions: &'a mut HashMap<String, Vec<ChatMessage>>,
+        session_id: &str,
+    ) -> Option<&'a mut Vec<ChatMessage>> {
+        if !sessions.contains_key(session_id) {
+            match self.store.load(session_id) {
+                Ok(Some(history)) => {
+                    sessions.insert(session_id.to_string(), history);
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("[SessionStore] Failed to load session: {}", e),
+            }
+        }
+        sessions.get_mut(session_id)
+    }
+
     async fn get_history(&self, session_id: &str) -> Vec<ChatMessage> {
-        let sessions = self.sessions.lock().await;
-        sessions.get(session_id).cloned().unwrap_or_default()
+        let mut sessions = self.sessions.lock().await;
+        self.hydrate_session(&mut sessions, session_id)
+            .map(|history| history.clone())
+            .unwrap_or_default()
     }
 
     pub async fn clear_session(&self, session_id: &str) {
         let mut sessions = self.sessions.lock().await;
         sessions.remove(session_id);
+        if let Err(e) = self.store.delete(session_id) {
+            eprintln!("[SessionStore] Failed to delete session: {}", e);
+        }
     }
 
     pub async fn get_message(&self, session_id: &str, message_index: usize) -> Option<ChatMessage> {
-        let sessions = self.sessions.lock().await;
-        sessions
-            .get(session_id)
+        let mut sessions = self.sessions.lock().await;
+        self.hydrate_session(&mut sessions, session_id)
             .and_then(|history| history.get(message_index).cloned())
     }
 
+    /// Imports a history from the frontend, replacing whatever was stored for the session.
     pub async fn set_session_history(&self, session_id: &str, history: Vec<ChatMessage>) {
         let mut sessions = self.sessions.lock().await;
+        if let Err(e) = self.store.replace(session_id, &history) {
+            eprintln!("[SessionStore] Failed to persist session: {}", e);
+        }
         sessions.insert(session_id.to_string(), history);
     }
 
@@ -587,8 +902,8 @@ impl ChatOrchestrator {
         message_index: usize,
     ) -> Result<(), String> {
         let mut sessions = self.sessions.lock().await;
-        let history = sessions
-            .get_mut(session_id)
+        let history = self
+            .hydrate_session(&mut sessions, session_id)
             .ok_or_else(|| "Session not found".to_string())?;
 
         if message_index >= history.len() {
@@ -596,7 +911,7 @@ impl ChatOrchestrator {
         }
 
         history.remove(message_index);
-        Ok(())
+        self.store.replace(session_id, history)
     }
 
     pub fn prepare_regenerate_history(
@@ -618,20 +933,20 @@ impl ChatOrchestrator {
         &self,
         session_id: &str,
         message_index: usize,
-        temperature: f32,
-        max_tokens: i32,
+        options: &ChatTurnOptions,
         on_event: Channel<serde_json::Value>,
     ) -> Result<(), String> {
+        let model = options.model.as_deref();
         let history_before = {
-            let sessions = self.sessions.lock().await;
-            let history = sessions
-                .get(session_id)
+            let mut sessions = self.sessions.lock().await;
+            let history = self
+                .hydrate_session(&mut sessions, session_id)
                 .ok_or_else(|| "Session not found".to_string())?;
             Self::prepare_regenerate_history(history, message_index)?
         };
 
-        let ctx_size = self.current_ctx_size().await.unwrap_or(4096) as usize;
-        let effective_max_tokens = clamp_max_tokens(ctx_size, max_tokens);
+        let ctx_size = self.current_ctx_size(model).await.unwrap_or(4096) as usize;
+        let effective_max_tokens = clamp_max_tokens(ctx_size, options.max_tokens);
         let prompt_budget = compute_prompt_budget(ctx_size, effective_max_tokens);
         let request_messages =
             sanitize_messages_for_request(trim_messages_to_budget(&history_before, prompt_budget));
@@ -639,11 +954,10 @@ impl ChatOrchestrator {
         let mut rx = self
             .service
             .send_chat_message(
+                model.map(str::to_string),
                 Some(session_id.to_string()),
                 request_messages,
-                temperature,
-                0.95,
-                40,
+                &options.sampling,
                 effective_max_tokens,
             )
             .await?;
@@ -652,60 +966,23 @@ impl ChatOrchestrator {
         let mut parser = ThinkingStreamParser::new();
 
         while let Some(chunk) = rx.recv().await {
-            for parsed in parser.push(&chunk) {
-                match parsed {
-                    ParsedChunk::Content(text) => {
-                        full_response.push_str(&text);
-                        if !Self::try_send(&on_event, serde_json::json!({ "chunk": text })) {
-                            return Ok(());
-                        }
-                    }
-                    ParsedChunk::Thinking(text) => {
-                        if !Self::try_send(&on_event, serde_json::json!({ "thinking_chunk": text }))
-                        {
-                            return Ok(());
-                        }
-                    }
-                }
-            }
-        }
-
-        for parsed in parser.flush() {
-            match parsed {
-                ParsedChunk::Content(text) => {
-                    full_response.push_str(&text);
-                    if !Self::try_send(&on_event, serde_json::json!({ "chunk": text })) {
-                        return Ok(());
-                    }
-                }
-                ParsedChunk::Thinking(text) => {
-                    if !Self::try_send(&on_event, serde_json::json!({ "thinking_chunk": text })) {
-                        return Ok(());
-                    }
-                }
+            if !Self::forward_chunks(&on_event, parser.push(&chunk), &mut full_response) {
+                return Ok(());
             }
         }
-
-        if !Self::try_send(&on_event, serde_json::json!({ "status": "done" })) {
+        if !Self::forward_chunks(&on_event, parser.flush(), &mut full_response) {
             return Ok(());
         }
 
-        let mut sessions = self.sessions.lock().await;
-        let history = sessions
-            .get_mut(session_id)
-            .ok_or_else(|| "Session not found".to_string())?;
-
-        if message_index >= history.len() {
-            return Err("Message removed".to_string());
-        }
-
-        history[message_index].content = full_response;
-
-        Ok(())
+        self.finish_answer(session_id, full_response, Some(message_index), &on_event)
+            .await
     }
 
-    async fn current_ctx_size(&self) -> Option<u32> {
-        self.service.get_config().await.map(|cfg| cfg.ctx_size)
+    async fn current_ctx_size(&self, model: Option<&str>) -> Option<u32> {
+        self.service
+            .get_model_config(model)
+            .await
+            .map(|cfg| cfg.ctx_size)
     }
 }
 
@@ -872,6 +1149,135 @@ fn parse_function_call(function: &serde_json::Value, idx: usize) -> Option<LlmTo
     })
 }
 
+/// Merges streamed `delta.tool_calls` fragments into whole calls. The first
+/// fragment of a call carries `id` and the function name; `arguments` arrives
+/// in pieces that are concatenated.
+#[derive(Debug, Default)]
+struct ToolCallAccumulator {
+    calls: BTreeMap<u64, StreamedToolCall>,
+}
+
+#[derive(Debug, Default)]
+struct StreamedToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl ToolCallAccumulator {
+    fn push(&mut self, delta: &serde_json::Value) {
+        let function = delta.get("function");
+        let field = |key: &str| function.and_then(|f| f.get(key));
+        let index = match delta.get("index").and_then(|v| v.as_u64()) {
+            Some(index) => index,
+            // Without an index, a fragment naming a call starts a new one.
+            None => {
+                let next = self.calls.keys().next_back().map_or(0, |last| last + 1);
+                if delta.get("id").is_some() || field("name").is_some() {
+                    next
+                } else {
+                    next.saturating_sub(1)
+                }
+            }
+        };
+        let call = self.calls.entry(index).or_default();
+        if let Some(id) = delta.get("id").and_then(|v| v.as_str()) {
+            if !id.is_empty() {
+                call.id = id.to_string();
+            }
+        }
+        if let Some(name) = field("name").and_then(|v| v.as_str()) {
+            call.name.push_str(name);
+        }
+        match field("arguments") {
+            Some(serde_json::Value::String(part)) => call.arguments.push_str(part),
+            Some(serde_json::Value::Null) | None => {}
+            Some(whole) => call.arguments = whole.to_string(),
+        }
+    }
+
+    /// Calls in the shape of a non-streamed `message.tool_calls`.
+    fn finish(self) -> Vec<serde_json::Value> {
+        self.calls
+            .into_values()
+            .enumerate()
+            .map(|(idx, call)| {
+                let id = if call.id.is_empty() {
+                    default_tool_call_id(idx)
+                } else {
+                    call.id
+                };
+                serde_json::json!({
+                    "id": id,
+                    "type": "function",
+                    "function": { "name": call.name, "arguments": call.arguments }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Keeps `<tool_call>…</tool_call>` blocks out of the streamed content. Text
+/// that may start a block is held until the block either closes, and is
+/// dropped if it parses as a call, or turns out to be plain text.
+#[derive(Debug, Default)]
+struct ToolMarkupFilter {
+    held: String,
+}
+
+impl ToolMarkupFilter {
+    /// Appends the raw content of `chunks` to `raw` and returns the chunks
+    /// with tool markup removed.
+    fn filter(&mut self, chunks: Vec<ParsedChunk>, raw: &mut String) -> Vec<ParsedChunk> {
+        chunks
+            .into_iter()
+            .filter_map(|chunk| match chunk {
+                ParsedChunk::Content(text) => {
+                    raw.push_str(&text);
+                    let visible = self.push(&text);
+                    (!visible.is_empty()).then_some(ParsedChunk::Content(visible))
+                }
+                thinking => Some(thinking),
+            })
+            .collect()
+    }
+
+    fn push(&mut self, text: &str) -> String {
+        self.held.push_str(text);
+        let mut visible = String::new();
+        while let Some(start) = self.held.find(TOOL_CALL_OPEN) {
+            visible.extend(self.held.drain(..start));
+            let block_start = TOOL_CALL_OPEN.len();
+            let Some(end_rel) = self.held[block_start..].find(TOOL_CALL_CLOSE) else {
+                return visible;
+            };
+            let block_end = block_start + end_rel;
+            let is_call = parse_tool_call_block(&self.held[block_start..block_end], 0).is_some();
+            let block: String = self
+                .held
+                .drain(..block_end + TOOL_CALL_CLOSE.len())
+                .collect();
+            if !is_call {
+                visible.push_str(&block);
+            }
+        }
+        // A tail like `<tool_ca` could still become a block.
+        let partial = (1..TOOL_CALL_OPEN.len())
+            .rev()
+            .find(|&len| self.held.ends_with(&TOOL_CALL_OPEN[..len]))
+            .unwrap_or(0);
+        visible.extend(self.held.drain(..self.held.len() - partial));
+        visible
+    }
+
+    /// Releases what is still held once the stream ends: an unclosed block or
+    /// a partial tag is plain text after all.
+    fn flush(&mut self) -> Option<ParsedChunk> {
+        let held = std::mem::take(&mut self.held);
+        (!held.is_empty()).then_some(ParsedChunk::Content(held))
+    }
+}
+
 fn default_tool_call_id(idx: usize) -> String {
     format!("call-{}", idx)
 }
@@ -904,21 +1310,18 @@ struct ParsedToolCallsFromContent {
 }
 
 fn parse_tool_calls_from_content(content: &str) -> Option<ParsedToolCallsFromContent> {
-    const OPEN_TAG: &str = "<tool_call>";
-    const CLOSE_TAG: &str = "</tool_call>";
-
     let mut cleaned = String::new();
     let mut tool_calls = Vec::new();
     let mut raw_tool_calls = Vec::new();
     let mut cursor = 0;
     let mut idx = 0;
 
-    while let Some(start_rel) = content[cursor..].find(OPEN_TAG) {
+    while let Some(start_rel) = content[cursor..].find(TOOL_CALL_OPEN) {
         let start = cursor + start_rel;
         cleaned.push_str(&content[cursor..start]);
 
-        let block_start = start + OPEN_TAG.len();
-        let Some(end_rel) = content[block_start..].find(CLOSE_TAG) else {
+        let block_start = start + TOOL_CALL_OPEN.len();
+        let Some(end_rel) = content[block_start..].find(TOOL_CALL_CLOSE) else {
             cleaned.push_str(&content[start..]);
             return None;
         };
@@ -931,12 +1334,12 @@ fn parse_tool_calls_from_content(content: &str) -> Option<ParsedToolCallsFromCon
             raw_tool_calls.push(raw_call);
             idx += 1;
         } else {
-            cleaned.push_str(OPEN_TAG);
+            cleaned.push_str(TOOL_CALL_OPEN);
             cleaned.push_str(block);
-            cleaned.push_str(CLOSE_TAG);
+            cleaned.push_str(TOOL_CALL_CLOSE);
         }
 
-        cursor = end + CLOSE_TAG.len();
+        cursor = end + TOOL_CALL_CLOSE.len();
     }
 
     if cursor < content.len() {
@@ -1164,6 +1567,9 @@ fn truncate_to_token_budget(text: &str, token_budget: usize) -> String {
 }
 
 fn sanitize_messages_for_request(mut messages: Vec<ChatMessage>) -> Vec<ChatMessage> {
+    for message in &mut messages {
+        message.tool_approvals = None;
+    }
     // llama.cpp rejects payloads that end with multiple assistant messages.
     while messages.len() >= 2 {
         let len = messages.len();
@@ -1176,14 +1582,17 @@ fn sanitize_messages_for_request(mut messages: Vec<ChatMessage>) -> Vec<ChatMess
     messages
 }
 
-fn format_tool_result(result: &serde_json::Value) -> String {
+fn format_tool_result(result: &serde_json::Value, token_budget: usize) -> String {
     let json_str = serde_json::to_string_pretty(result).unwrap_or_else(|_| "{}".to_string());
-    truncate_to_token_budget(&json_str, TOOL_RESULT_TOKEN_BUDGET)
+    truncate_to_token_budget(&json_str, token_budget)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_tool_arguments, parse_tool_calls_from_response};
+    use super::{
+        parse_tool_arguments, parse_tool_calls_from_response, ToolCallAccumulator, ToolMarkupFilter,
+    };
+    use crate::services::thinking_parser::ParsedChunk;
 
     #[test]
     fn parse_tool_arguments_accepts_json_string() {
@@ -1237,4 +1646,78 @@ mod tests {
         assert_eq!(parsed.tool_calls[0].arguments["max_results"], 10);
         assert!(parsed.content.trim().is_empty());
     }
+
+    #[test]
+    fn tool_call_accumulator_joins_fragments_by_index() {
+        let mut acc = ToolCallAccumulator::default();
+        acc.push(&serde_json::json!({ "index": 1, "id": "b", "function": { "name": "mcp__s__two", "arguments": "{}" } }));
+        acc.push(&serde_json::json!({ "index": 0, "id": "a", "function": { "name": "mcp__s__one", "arguments": "{\"q\":" } }));
+        acc.push(&serde_json::json!({ "index": 0, "function": { "arguments": "\"hi\"}" } }));
+
+        let calls = acc.finish();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0]["id"], "a");
+        assert_eq!(calls[0]["function"]["arguments"], "{\"q\":\"hi\"}");
+        assert_eq!(calls[1]["function"]["name"], "mcp__s__two");
+
+        let parsed = parse_tool_calls_from_response(&serde_json::json!({
+            "choices": [{ "message": { "content": "", "tool_calls": calls } }]
+        }))
+        .expect("parsed");
+        assert_eq!(parsed.tool_calls[0].arguments["q"], "hi");
+    }
+
+    #[test]
+    fn tool_call_accumulator_handles_missing_index_and_id() {
+        let mut acc = ToolCallAccumulator::default();
+        acc.push(&serde_json::json!({ "function": { "name": "mcp__s__one", "arguments": "{\"q\"" } }));
+        acc.push(&serde_json::json!({ "function": { "arguments": ":1}" } }));
+        acc.push(&serde_json::json!({ "function": { "name": "mcp__s__two", "arguments": { "x": 2 } } }));
+
+        let calls = acc.finish();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0]["id"], "call-0");
+        assert_eq!(calls[0]["function"]["arguments"], "{\"q\":1}");
+        assert_eq!(calls[1]["id"], "call-1");
+        assert_eq!(calls[1]["function"]["arguments"], "{\"x\":2}");
+    }
+
+    /// Streams `pieces` through a filter and returns what the UI would show.
+    fn filter_stream(pieces: &[&str]) -> (String, String) {
+        let mut filter = ToolMarkupFilter::default();
+        let mut raw = String::new();
+        let mut chunks = Vec::new();
+        for piece in pieces {
+            chunks.extend(filter.filter(vec![ParsedChunk::Content(piece.to_string())], &mut raw));
+        }
+        chunks.extend(filter.flush());
+        let shown = chunks
+            .into_iter()
+            .map(|chunk| match chunk {
+                ParsedChunk::Content(text) | ParsedChunk::Thinking(text) => text,
+            })
+            .collect();
+        (shown, raw)
+    }
+
+    #[test]
+    fn tool_markup_filter_holds_back_a_split_tool_call() {
+        let (shown, raw) = filter_stream(&[
+            "Let me look. <tool",
+            "_call>\n<function=mcp__s__search>\n<parameter=q>\nhi\n</parameter>\n",
+            "</function>\n</tool_",
+            "call> ",
+        ]);
+        assert_eq!(shown, "Let me look.  ");
+        assert!(raw.contains("<function=mcp__s__search>"));
+    }
+
+    #[test]
+    fn tool_markup_filter_releases_text_that_is_not_a_call() {
+        let (shown, _) = filter_stream(&["a <tool", "box> b <tool_call>no function</tool_call>"]);
+        assert_eq!(shown, "a <toolbox> b <tool_call>no function</tool_call>");
+
+        let (shown, _) = filter_stream(&["unclosed <tool_call>", "<function=x>"]);
+        assert_eq!(shown, "unclosed <tool_call><function=x>");
+    }
 }
@@ -1,5 +1,5 @@
 // Legacy subagent implementation (kept for reference).
-use crate::models::ChatMessage;
+use crate::models::{ChatMessage, ChatRequest, SamplingParams};
 use crate::services::capability_registry::{CapabilityRegistry, ResolvedCall};
 use crate::services::llama::service::LlamaCppService;
 use crate::services::mcp::McpService;
@@ -77,6 +77,7 @@ impl Subagent {
             name: None,
             tool_call_id: None,
             tool_calls: None,
+            tool_approvals: None,
         });
 
         // Initial user message
@@ -86,6 +87,7 @@ impl Subagent {
             name: None,
             tool_call_id: None,
             tool_calls: None,
+            tool_approvals: None,
         });
 
         // Agentic loop
@@ -138,6 +140,7 @@ impl Subagent {
                         name: None,
                         tool_call_id: None,
                         tool_calls: None,
+                        tool_approvals: None,
                     });
 
                     conversation_history.push(ChatMessage {
@@ -146,6 +149,7 @@ impl Subagent {
                         name: None,
                         tool_call_id: None,
                         tool_calls: None,
+                        tool_approvals: None,
                     });
 
                     tool_results.push(tool_result);
@@ -166,17 +170,17 @@ impl Subagent {
         let response = self
             .service
             .complete_chat(
-                None, // Ephemeral session
-                conversation_history.to_vec(),
-                temperature.min(0.5),
-                0.95,
-                40,
-                SUBAGENT_MAX_TOKENS,
-                None,
-                None,
-                None,
-                None,
                 None,
+                ChatRequest {
+                    // No session_id: the subagent's turns are ephemeral.
+                    messages: conversation_history.to_vec(),
+                    max_tokens: SUBAGENT_MAX_TOKENS,
+                    ..Default::default()
+                },
+                &SamplingParams {
+                    temperature: Some(temperature.min(0.5)),
+                    ..Default::default()
+                },
             )
             .await?;
 
@@ -1,6 +1,7 @@
 use reqwest::Client;
 use serde_json::Value;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
 use tauri::AppHandle;
 use tauri::Manager;
 
@@ -48,6 +49,43 @@ pub async fn ensure_hf_chat_template(
     Ok(cache_path)
 }
 
+/// Converte o template Go de um modelo Ollama para Jinja e o guarda no cache
+/// `<app_data>/chat_templates/ollama_<sha256>.jinja`.
+pub fn ensure_converted_go_template(app: &AppHandle, go_template: &str) -> Result<PathBuf, String> {
+    let cache_dir: PathBuf = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Erro ao obter diretório de dados: {}", e))?
+        .join("chat_templates");
+    ensure_converted_go_template_at_dir(&cache_dir, go_template)
+}
+
+pub fn ensure_converted_go_template_at_dir(
+    cache_dir: &Path,
+    go_template: &str,
+) -> Result<PathBuf, String> {
+    // A versão do conversor entra no hash para invalidar conversões antigas
+    let mut hasher = Sha256::new();
+    hasher.update(crate::services::go_template::CONVERTER_VERSION.to_le_bytes());
+    hasher.update(go_template.as_bytes());
+    let hash_hex = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    let cache_path = cache_dir.join(format!("ollama_{}.jinja", hash_hex));
+    if cache_path.exists() {
+        return Ok(cache_path);
+    }
+
+    let jinja = crate::services::go_template::go_template_to_jinja(go_template)?;
+    std::fs::create_dir_all(cache_dir)
+        .map_err(|e| format!("Erro ao criar diretório de cache: {}", e))?;
+    std::fs::write(&cache_path, jinja)
+        .map_err(|e| format!("Erro ao salvar template em cache: {}", e))?;
+    Ok(cache_path)
+}
+
 /// Tenta baixar o chat template em ordem: jinja file -> tokenizer_config.json
 async fn download_chat_template(
     client: &Client,
@@ -1,11 +1,15 @@
+use crate::infrastructure::session_store::{FileSessionStore, InMemorySessionStore, SessionStore};
+use crate::services::downloads::{DownloadManager, DEFAULT_MAX_CONCURRENT_DOWNLOADS};
 use crate::services::llama::LlamaCppService;
 use crate::services::mcp::McpService;
 use crate::services::orchestrator::ChatOrchestrator;
+use std::sync::Arc;
 
 pub struct AppState {
     pub llama_service: LlamaCppService,
     pub mcp_service: McpService,
     pub orchestrator: ChatOrchestrator,
+    pub downloads: DownloadManager,
 }
 
 impl AppState {
@@ -13,14 +17,25 @@ impl AppState {
         models_path: std::path::PathBuf,
         mcp_config: crate::models::McpConfig,
         resource_dir: Option<std::path::PathBuf>,
+        sessions_dir: Option<std::path::PathBuf>,
+        downloads_path: Option<std::path::PathBuf>,
     ) -> Self {
         let llama_service = LlamaCppService::new(models_path);
         let mcp_service = McpService::new(mcp_config, resource_dir);
-        let orchestrator = ChatOrchestrator::new(llama_service.clone(), mcp_service.clone());
+        let session_store: Arc<dyn SessionStore> = match sessions_dir {
+            Some(dir) => Arc::new(FileSessionStore::new(dir)),
+            None => Arc::new(InMemorySessionStore::new()),
+        };
+        let orchestrator = ChatOrchestrator::with_session_store(
+            llama_service.clone(),
+            mcp_service.clone(),
+            session_store,
+        );
         Self {
             llama_service,
             mcp_service,
             orchestrator,
+            downloads: DownloadManager::new(downloads_path, DEFAULT_MAX_CONCURRENT_DOWNLOADS),
         }
     }
 }
@@ -55,3 +55,33 @@ pub fn append_jsonl<T: Serialize>(path: &Path, data: &T) -> Result<(), String> {
     writeln!(file, "{}", json)
         .map_err(|e| format!("Failed to append to file {}: {}", path.display(), e))
 }
+
+/// Serialize a slice as JSONL, replacing the file atomically via a temp file
+pub fn save_jsonl<T: Serialize>(path: &Path, items: &[T]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            format!(
+                "Failed to create parent directory {}: {}",
+                parent.display(),
+                e
+            )
+        })?;
+    }
+
+    let mut content = String::new();
+    for item in items {
+        let line = serde_json::to_string(item)
+            .map_err(|e| format!("Failed to serialize data: {}", e))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+
+    // `<name>.tmp`, not `with_extension`, so `a.jsonl` never lands on a sibling `a.tmp`.
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    fs::write(&tmp_path, content)
+        .map_err(|e| format!("Failed to write to file {}: {}", tmp_path.display(), e))?;
+    fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace file {}: {}", path.display(), e))
+}
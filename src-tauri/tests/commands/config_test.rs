@@ -2,9 +2,10 @@ use crate::common;
 
 use llama_desktop_lib::commands::config::{
     build_config_file_path_from_dir, get_config_from_path, reset_config_at_path,
-    save_config_to_path,
+    save_config_keeping_secrets, save_config_to_path, set_hf_token_at_path,
+    set_registry_credential_at_path,
 };
-use llama_desktop_lib::models::AppConfig;
+use llama_desktop_lib::models::{AppConfig, RegistryCredential};
 
 #[test]
 fn test_build_config_file_path_from_dir() {
@@ -71,3 +72,40 @@ fn test_save_config_creates_parent_dirs() {
 
     assert!(path.exists());
 }
+
+#[test]
+fn test_secrets_are_kept_out_of_the_frontend_config() {
+    let dir = common::temp_dir();
+    let path = dir.path().join("config.json");
+    let credential = RegistryCredential::Basic {
+        username: "alice".to_string(),
+        password: "s3cret".to_string(),
+    };
+    set_registry_credential_at_path(&path, "ghcr.io", Some(credential.clone())).unwrap();
+    set_hf_token_at_path(&path, Some(" hf_abc ".to_string())).unwrap();
+
+    let frontend = get_config_from_path(&path).unwrap().without_secrets();
+    assert!(frontend.registry_credentials.is_empty());
+    assert!(frontend.hf_token.is_none());
+
+    // Saving what the frontend holds must not wipe the stored secrets.
+    save_config_keeping_secrets(
+        &path,
+        AppConfig {
+            theme: "light".to_string(),
+            ..frontend
+        },
+    )
+    .unwrap();
+    let stored = get_config_from_path(&path).unwrap();
+    assert_eq!(stored.theme, "light");
+    assert_eq!(stored.registry_credentials["ghcr.io"], credential);
+    assert_eq!(stored.hf_token.as_deref(), Some("hf_abc"));
+
+    set_registry_credential_at_path(&path, "ghcr.io", None).unwrap();
+    set_hf_token_at_path(&path, Some("  ".to_string())).unwrap();
+    let stored = get_config_from_path(&path).unwrap();
+    assert!(stored.registry_credentials.is_empty());
+    assert!(stored.hf_token.is_none());
+    assert!(set_registry_credential_at_path(&path, " ", None).is_err());
+}
@@ -0,0 +1,123 @@
+use llama_desktop_lib::commands::hf_files::{
+    group_gguf_files, hf_list_gguf_files_with_config, quant_label_from_filename,
+};
+use llama_desktop_lib::models::{AppConfig, ModelFit};
+use serde_json::json;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+#[test]
+fn test_quant_label_from_filename() {
+    let cases = [
+        ("Llama-3.2-3B-Instruct-Q4_K_M.gguf", Some("Q4_K_M")),
+        ("qwen2.5-7b-instruct-iq3_xs.gguf", Some("IQ3_XS")),
+        ("gemma-2-9b-it.Q8_0.gguf", Some("Q8_0")),
+        ("Mistral-7B-Instruct-v0.3-IQ4_NL.gguf", Some("IQ4_NL")),
+        ("phi-4-BF16.gguf", Some("BF16")),
+        (
+            "UD-Q4_K_XL/Model-UD-Q4_K_XL-00001-of-00002.gguf",
+            Some("Q4_K_XL"),
+        ),
+        ("Qwen2-0.5B.gguf", None),
+        ("x-abéé12345678.gguf", None),
+        ("modèle-Q4_K_M-00001-of-00002.gguf", Some("Q4_K_M")),
+    ];
+    for (name, expected) in cases {
+        assert_eq!(
+            quant_label_from_filename(name).as_deref(),
+            expected,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_split_parts_are_grouped_and_projectors_skipped() {
+    let files = vec![
+        ("README.md".to_string(), Some(10)),
+        ("big-Q8_0-00002-of-00002.gguf".to_string(), Some(3 * GIB)),
+        ("big-Q8_0-00001-of-00002.gguf".to_string(), Some(5 * GIB)),
+        ("big-Q4_K_M.gguf".to_string(), Some(4 * GIB)),
+        ("mmproj-big-f16.gguf".to_string(), Some(GIB)),
+    ];
+
+    let listed = group_gguf_files(&files);
+
+    assert_eq!(listed.len(), 2);
+    let split = listed
+        .iter()
+        .find(|f| f.quantization.as_deref() == Some("Q8_0"))
+        .unwrap();
+    assert_eq!(split.filename, "big-Q8_0-00001-of-00002.gguf");
+    assert_eq!(
+        split.parts,
+        vec![
+            "big-Q8_0-00001-of-00002.gguf",
+            "big-Q8_0-00002-of-00002.gguf"
+        ]
+    );
+    assert_eq!(split.size_bytes, 8 * GIB);
+}
+
+#[tokio::test]
+async fn test_lists_files_from_mirror_with_token() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/models/org/gated/revision/main"))
+        .and(query_param("blobs", "true"))
+        .and(header("Authorization", "Bearer hf_secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "siblings": [
+                {"rfilename": "gated-Q4_K_M.gguf", "size": 4 * GIB},
+                {"rfilename": "gated-F16.gguf", "size": 16 * GIB},
+                {"rfilename": "config.json", "size": 100}
+            ]
+        })))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401))
+        .with_priority(5)
+        .mount(&server)
+        .await;
+
+    let mut app_config = AppConfig {
+        hf_endpoint: Some(format!("{}/", server.uri())),
+        ..Default::default()
+    };
+    let err = hf_list_gguf_files_with_config(&app_config, "org/gated", None, &[], 0)
+        .await
+        .unwrap_err();
+    assert!(err.contains("gated or private"), "{}", err);
+
+    app_config.hf_token = Some("hf_secret".to_string());
+    let listed = hf_list_gguf_files_with_config(&app_config, "org/gated", None, &[8 * GIB], 0)
+        .await
+        .unwrap();
+
+    assert_eq!(listed.len(), 2);
+    let q4 = listed
+        .iter()
+        .find(|f| f.filename == "gated-Q4_K_M.gguf")
+        .unwrap();
+    assert_eq!(q4.size_bytes, 4 * GIB);
+    assert_eq!(q4.fit, Some(ModelFit::Gpu));
+    let f16 = listed
+        .iter()
+        .find(|f| f.filename == "gated-F16.gguf")
+        .unwrap();
+    assert_eq!(f16.quantization.as_deref(), Some("F16"));
+    assert_eq!(f16.fit, Some(ModelFit::TooLarge));
+}
+
+#[tokio::test]
+async fn test_rejects_malformed_repo_id() {
+    let err = hf_list_gguf_files_with_config(&AppConfig::default(), "just-a-name", None, &[], 0)
+        .await
+        .unwrap_err();
+    assert!(err.contains("<org>/<repo>"), "{}", err);
+}
@@ -1,3 +1,8 @@
 mod config_test;
 mod mcp_config_test;
 mod models_test;
+mod presets_test;
+mod model_maintenance_test;
+mod model_import_test;
+mod model_bundle_test;
+mod hf_files_test;
@@ -0,0 +1,241 @@
+use crate::common;
+
+use llama_desktop_lib::commands::model_bundle::{
+    export_model_at_root, import_model_bundle_at_root,
+};
+use llama_desktop_lib::models::{BundleBlob, ManifestConfig, ManifestLayer, ModelManifest};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const MODEL_ID: &str = "registry.ollama.ai:llama3:latest";
+
+fn digest_of(bytes: &[u8]) -> String {
+    let hex: String = Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256:{}", hex)
+}
+
+fn manifest_path(root: &Path) -> PathBuf {
+    root.join("manifests/registry.ollama.ai/library/llama3/latest/manifest.json")
+}
+
+/// Installs llama3:latest with real digests so imports can verify them.
+fn install_model(root: &Path, weights: &[u8]) -> ModelManifest {
+    let config = br#"{"model_format":"gguf"}"#;
+    let blobs = root.join("blobs");
+    std::fs::create_dir_all(&blobs).unwrap();
+    for bytes in [&config[..], weights] {
+        let name = digest_of(bytes).replace(':', "-");
+        std::fs::write(blobs.join(name), bytes).unwrap();
+    }
+    let manifest = ModelManifest {
+        schema_version: 2,
+        media_type: "application/vnd.docker.distribution.manifest.v2+json".to_string(),
+        config: ManifestConfig {
+            media_type: "application/vnd.docker.container.image.v1+json".to_string(),
+            digest: digest_of(config),
+            size: config.len() as u64,
+        },
+        layers: vec![ManifestLayer {
+            media_type: "application/vnd.ollama.image.model".to_string(),
+            digest: digest_of(weights),
+            size: weights.len() as u64,
+        }],
+    };
+    let path = manifest_path(root);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, serde_json::to_vec_pretty(&manifest).unwrap()).unwrap();
+    manifest
+}
+
+fn import(root: &TempDir, bundle: &Path) -> Result<String, String> {
+    let metadata = common::temp_dir();
+    import_model_bundle_at_root(root.path(), metadata.path().to_str().unwrap(), bundle)
+        .map(|model| model.full_identifier)
+}
+
+fn append(builder: &mut tar::Builder<std::fs::File>, name: &str, bytes: &[u8]) {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, name, bytes).unwrap();
+}
+
+#[test]
+fn test_export_then_import_reproduces_the_model() {
+    let source = common::temp_dir();
+    let target = common::temp_dir();
+    let manifest = install_model(source.path(), b"weights");
+    let bundle = source.path().join("llama3.tar");
+
+    let index = export_model_at_root(source.path(), MODEL_ID, &bundle).unwrap();
+
+    assert_eq!(index.name, "llama3");
+    assert_eq!(index.blobs.len(), 2);
+    assert_eq!(index.blobs[0].digest, manifest.config.digest);
+    assert_eq!(index.total_bytes, manifest.config.size + 7);
+    assert!(!source.path().join("llama3.tar.partial").exists());
+
+    assert_eq!(import(&target, &bundle).unwrap(), MODEL_ID);
+    assert_eq!(
+        std::fs::read(manifest_path(target.path())).unwrap(),
+        std::fs::read(manifest_path(source.path())).unwrap()
+    );
+    let weights = manifest.layers[0].digest.replace(':', "-");
+    assert_eq!(
+        std::fs::read(target.path().join("blobs").join(weights)).unwrap(),
+        b"weights"
+    );
+}
+
+#[test]
+fn test_reimport_is_idempotent_and_restores_missing_blobs() {
+    let source = common::temp_dir();
+    let target = common::temp_dir();
+    let manifest = install_model(source.path(), b"weights");
+    let bundle = source.path().join("llama3.tar");
+    export_model_at_root(source.path(), MODEL_ID, &bundle).unwrap();
+    import(&target, &bundle).unwrap();
+
+    let weights = target
+        .path()
+        .join("blobs")
+        .join(manifest.layers[0].digest.replace(':', "-"));
+    std::fs::remove_file(&weights).unwrap();
+
+    assert_eq!(import(&target, &bundle).unwrap(), MODEL_ID);
+    assert!(weights.exists());
+}
+
+#[test]
+fn test_import_rejects_a_different_installed_manifest() {
+    let source = common::temp_dir();
+    let target = common::temp_dir();
+    install_model(source.path(), b"weights");
+    install_model(target.path(), b"other weights");
+    let bundle = source.path().join("llama3.tar");
+    export_model_at_root(source.path(), MODEL_ID, &bundle).unwrap();
+
+    let err = import(&target, &bundle).unwrap_err();
+
+    assert!(err.contains("already installed"), "{}", err);
+}
+
+#[test]
+fn test_import_rejects_tampered_blob_without_installing() {
+    let source = common::temp_dir();
+    let target = common::temp_dir();
+    let manifest = install_model(source.path(), b"weights");
+    let bundle = source.path().join("llama3.tar");
+    let index = export_model_at_root(source.path(), MODEL_ID, &bundle).unwrap();
+
+    // Rebuild the bundle with the weights swapped for same-sized bytes.
+    let tampered = source.path().join("tampered.tar");
+    let mut builder = tar::Builder::new(std::fs::File::create(&tampered).unwrap());
+    append(
+        &mut builder,
+        "index.json",
+        &serde_json::to_vec(&index).unwrap(),
+    );
+    append(
+        &mut builder,
+        "manifest.json",
+        &std::fs::read(manifest_path(source.path())).unwrap(),
+    );
+    let weights = manifest.layers[0].digest.replace(':', "-");
+    append(&mut builder, &format!("blobs/{}", weights), b"WEIGHTS");
+    builder.finish().unwrap();
+    drop(builder);
+
+    let err = import(&target, &tampered).unwrap_err();
+
+    assert!(err.contains("digest mismatch"), "{}", err);
+    assert!(!manifest_path(target.path()).exists());
+    let leftovers: Vec<_> = std::fs::read_dir(target.path().join("blobs"))
+        .unwrap()
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
+
+#[test]
+fn test_import_rejects_bundle_without_all_blobs() {
+    let source = common::temp_dir();
+    let target = common::temp_dir();
+    install_model(source.path(), b"weights");
+    let bundle = source.path().join("llama3.tar");
+    let index = export_model_at_root(source.path(), MODEL_ID, &bundle).unwrap();
+
+    let truncated = source.path().join("truncated.tar");
+    let mut builder = tar::Builder::new(std::fs::File::create(&truncated).unwrap());
+    append(
+        &mut builder,
+        "index.json",
+        &serde_json::to_vec(&index).unwrap(),
+    );
+    append(
+        &mut builder,
+        "manifest.json",
+        &std::fs::read(manifest_path(source.path())).unwrap(),
+    );
+    builder.finish().unwrap();
+    drop(builder);
+
+    let err = import(&target, &truncated).unwrap_err();
+
+    assert!(err.contains("missing blob"), "{}", err);
+    assert!(!manifest_path(target.path()).exists());
+}
+
+#[test]
+fn test_import_rejects_a_digest_that_is_not_a_sha256() {
+    let source = common::temp_dir();
+    let target = common::temp_dir();
+    install_model(source.path(), b"weights");
+    let bundle = source.path().join("llama3.tar");
+    let mut index = export_model_at_root(source.path(), MODEL_ID, &bundle).unwrap();
+    index.blobs.push(BundleBlob {
+        digest: "sha256:../../../escape".to_string(),
+        size: 1,
+        media_type: "application/vnd.ollama.image.model".to_string(),
+    });
+
+    let forged = source.path().join("forged.tar");
+    let mut builder = tar::Builder::new(std::fs::File::create(&forged).unwrap());
+    append(
+        &mut builder,
+        "index.json",
+        &serde_json::to_vec(&index).unwrap(),
+    );
+    builder.finish().unwrap();
+    drop(builder);
+
+    let err = import(&target, &forged).unwrap_err();
+
+    assert!(err.contains("Invalid blob digest"), "{}", err);
+    assert!(!target.path().join("blobs").exists());
+}
+
+#[test]
+fn test_export_fails_on_missing_blob() {
+    let source = common::temp_dir();
+    let manifest = install_model(source.path(), b"weights");
+    std::fs::remove_file(
+        source
+            .path()
+            .join("blobs")
+            .join(manifest.layers[0].digest.replace(':', "-")),
+    )
+    .unwrap();
+    let bundle = source.path().join("llama3.tar");
+
+    let err = export_model_at_root(source.path(), MODEL_ID, &bundle).unwrap_err();
+
+    assert!(err.contains("missing"), "{}", err);
+    assert!(!bundle.exists());
+}
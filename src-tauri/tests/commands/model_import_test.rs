@@ -0,0 +1,188 @@
+use crate::common::{self, GgufBuilder};
+
+use llama_desktop_lib::commands::model_import::import_gguf_at_root;
+use llama_desktop_lib::models::{ImportMode, ModelManifest};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+struct Fixture {
+    root: TempDir,
+    metadata: TempDir,
+    source_dir: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self {
+            root: common::temp_dir(),
+            metadata: common::temp_dir(),
+            source_dir: common::temp_dir(),
+        }
+    }
+
+    fn write_gguf(&self, filename: &str, name: Option<&str>) -> PathBuf {
+        let mut builder = GgufBuilder::new("llama").kv_u32("general.file_type", 15);
+        if let Some(name) = name {
+            builder = builder.kv_str("general.name", name);
+        }
+        let path = self.source_dir.path().join(filename);
+        std::fs::write(
+            &path,
+            builder.tensor("token_embd.weight", &[8, 4], 0).build(),
+        )
+        .unwrap();
+        path
+    }
+
+    async fn import(
+        &self,
+        source: &Path,
+        name: Option<&str>,
+        version: Option<&str>,
+        mode: ImportMode,
+    ) -> Result<llama_desktop_lib::models::ModelInfo, String> {
+        import_gguf_at_root(
+            self.root.path(),
+            self.metadata.path().to_str().unwrap(),
+            source,
+            name.map(str::to_string),
+            version.map(str::to_string),
+            mode,
+        )
+        .await
+    }
+
+    fn blob_count(&self) -> usize {
+        std::fs::read_dir(self.root.path().join("blobs"))
+            .unwrap()
+            .count()
+    }
+}
+
+#[tokio::test]
+async fn test_import_defaults_name_and_version_from_gguf_metadata() {
+    let fixture = Fixture::new();
+    let source = fixture.write_gguf("tiny.gguf", Some("Tiny Llama 3B"));
+
+    let model = fixture
+        .import(&source, None, None, ImportMode::Copy)
+        .await
+        .unwrap();
+
+    assert_eq!(model.provider, "local");
+    assert_eq!(model.name, "tiny-llama-3b");
+    assert_eq!(model.version, "q4_k_m");
+    assert!(source.exists());
+    let blob = PathBuf::from(model.model_file_path.unwrap());
+    assert_eq!(
+        std::fs::read(&blob).unwrap(),
+        std::fs::read(&source).unwrap()
+    );
+    assert!(blob
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with("sha256-"));
+
+    let manifest: ModelManifest =
+        serde_json::from_str(&std::fs::read_to_string(model.manifest_path.unwrap()).unwrap())
+            .unwrap();
+    assert_eq!(manifest.layers.len(), 1);
+    assert_eq!(
+        manifest.layers[0].media_type,
+        "application/vnd.ollama.image.model"
+    );
+}
+
+#[tokio::test]
+async fn test_import_falls_back_to_file_stem() {
+    let fixture = Fixture::new();
+    let source = fixture.write_gguf("My Model.gguf", None);
+
+    let model = fixture
+        .import(&source, None, Some("v1"), ImportMode::HardLink)
+        .await
+        .unwrap();
+
+    assert_eq!(model.name, "my-model");
+    assert_eq!(model.version, "v1");
+    assert!(source.exists());
+}
+
+#[tokio::test]
+async fn test_move_import_removes_source() {
+    let fixture = Fixture::new();
+    let source = fixture.write_gguf("tiny.gguf", None);
+    let bytes = std::fs::read(&source).unwrap();
+
+    let model = fixture
+        .import(&source, Some("tiny"), None, ImportMode::Move)
+        .await
+        .unwrap();
+
+    assert!(!source.exists());
+    assert_eq!(
+        std::fs::read(model.model_file_path.unwrap()).unwrap(),
+        bytes
+    );
+}
+
+#[tokio::test]
+async fn test_reimport_is_idempotent_and_dedupes_blobs() {
+    let fixture = Fixture::new();
+    let source = fixture.write_gguf("tiny.gguf", None);
+
+    let first = fixture
+        .import(&source, Some("tiny"), None, ImportMode::Copy)
+        .await
+        .unwrap();
+    let blobs = fixture.blob_count();
+    let again = fixture
+        .import(&source, Some("tiny"), None, ImportMode::Copy)
+        .await
+        .unwrap();
+    let other_tag = fixture
+        .import(&source, Some("tiny"), Some("copy"), ImportMode::Copy)
+        .await
+        .unwrap();
+
+    assert_eq!(first.model_file_path, again.model_file_path);
+    assert_eq!(first.model_file_path, other_tag.model_file_path);
+    assert_eq!(fixture.blob_count(), blobs);
+}
+
+#[tokio::test]
+async fn test_import_rejects_conflicting_tag() {
+    let fixture = Fixture::new();
+    let first = fixture.write_gguf("a.gguf", Some("first"));
+    let second = fixture.write_gguf("b.gguf", Some("second"));
+
+    fixture
+        .import(&first, Some("tiny"), Some("latest"), ImportMode::Copy)
+        .await
+        .unwrap();
+    let err = fixture
+        .import(&second, Some("tiny"), Some("latest"), ImportMode::Copy)
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("already exists"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_import_rejects_non_gguf_and_bad_names() {
+    let fixture = Fixture::new();
+    let text = fixture.source_dir.path().join("notes.gguf");
+    std::fs::write(&text, b"not a model").unwrap();
+    let source = fixture.write_gguf("tiny.gguf", None);
+
+    assert!(fixture
+        .import(&text, None, None, ImportMode::Copy)
+        .await
+        .is_err());
+    assert!(fixture
+        .import(&source, Some("../escape"), None, ImportMode::Copy)
+        .await
+        .is_err());
+    assert!(!fixture.root.path().join("blobs").exists());
+}
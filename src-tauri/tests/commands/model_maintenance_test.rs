@@ -0,0 +1,479 @@
+use crate::common;
+
+use llama_desktop_lib::commands::model_maintenance::{
+    delete_model_at_root, list_manifest_files, verify_models_directory_at_root,
+};
+use llama_desktop_lib::commands::presets::{
+    build_presets_path, load_presets_from_path, save_launch_preset_at_path,
+};
+use llama_desktop_lib::models::{
+    BlobStatus, LaunchParams, LaunchPreset, ManifestConfig, ManifestLayer, ModelInfo, ModelLibrary,
+    ModelManifest,
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const LLAMA: &str = "registry.ollama.ai:llama3:latest";
+const LLAMA_TEXT: &str = "registry.ollama.ai:llama3:text";
+
+/// Two tags of llama3 sharing the config blob, plus an orphan blob.
+struct Fixture {
+    root: TempDir,
+    metadata: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let fixture = Self {
+            root: common::temp_dir(),
+            metadata: common::temp_dir(),
+        };
+        fixture.write_blob("sha256-config", 10);
+        fixture.write_blob("sha256-weights", 1000);
+        fixture.write_blob("sha256-textweights", 500);
+        fixture.write_blob("sha256-orphan", 7);
+        let latest = fixture.write_manifest("latest", "sha256:weights", 1000);
+        let text = fixture.write_manifest("text", "sha256:textweights", 500);
+
+        let library = ModelLibrary {
+            models: vec![
+                model_info(LLAMA, "latest", &latest),
+                model_info(LLAMA_TEXT, "text", &text),
+            ],
+        };
+        llama_desktop_lib::utils::save_json(
+            &fixture.root.path().join("modelLibrary.json"),
+            &library,
+        )
+        .unwrap();
+        llama_desktop_lib::utils::save_json(
+            &fixture.metadata.path().join("models.json"),
+            &serde_json::json!({
+                "models": { LLAMA: { "tokenizer.ggml.model": "gpt2" }, LLAMA_TEXT: {} },
+                "gguf": {}
+            }),
+        )
+        .unwrap();
+        fixture
+    }
+
+    fn blobs(&self) -> PathBuf {
+        self.root.path().join("blobs")
+    }
+
+    fn write_blob(&self, name: &str, len: usize) {
+        std::fs::create_dir_all(self.blobs()).unwrap();
+        std::fs::write(self.blobs().join(name), vec![0u8; len]).unwrap();
+    }
+
+    fn write_manifest(&self, version: &str, weights: &str, size: u64) -> PathBuf {
+        let manifest = ModelManifest {
+            schema_version: 2,
+            media_type: "application/vnd.ollama.manifest.v1+json".to_string(),
+            config: ManifestConfig {
+                media_type: "application/vnd.ollama.image.config".to_string(),
+                digest: "sha256:config".to_string(),
+                size: 10,
+            },
+            layers: vec![ManifestLayer {
+                media_type: "application/vnd.ollama.image.model".to_string(),
+                digest: weights.to_string(),
+                size,
+            }],
+        };
+        let path = self
+            .root
+            .path()
+            .join("manifests/registry.ollama.ai/library/llama3")
+            .join(version)
+            .join("manifest.json");
+        llama_desktop_lib::utils::save_json(&path, &manifest).unwrap();
+        path
+    }
+
+    fn delete(
+        &self,
+        model_id: &str,
+        dry_run: bool,
+    ) -> Result<llama_desktop_lib::models::DeleteModelReport, String> {
+        self.delete_with(model_id, dry_run, &[], &[])
+    }
+
+    fn delete_with(
+        &self,
+        model_id: &str,
+        dry_run: bool,
+        running: &[PathBuf],
+        partials: &[PathBuf],
+    ) -> Result<llama_desktop_lib::models::DeleteModelReport, String> {
+        delete_model_at_root(
+            self.root.path(),
+            &self.metadata.path().to_string_lossy(),
+            model_id,
+            dry_run,
+            running,
+            partials,
+        )
+    }
+
+    fn library_ids(&self) -> Vec<String> {
+        let library: ModelLibrary =
+            llama_desktop_lib::utils::read_json(&self.root.path().join("modelLibrary.json"))
+                .unwrap();
+        library
+            .models
+            .into_iter()
+            .map(|m| m.full_identifier)
+            .collect()
+    }
+}
+
+fn model_info(id: &str, version: &str, manifest: &Path) -> ModelInfo {
+    ModelInfo {
+        provider: "registry.ollama.ai".to_string(),
+        library: "library".to_string(),
+        name: "llama3".to_string(),
+        version: version.to_string(),
+        full_identifier: id.to_string(),
+        manifest_path: Some(manifest.to_string_lossy().to_string()),
+        model_file_path: None,
+        ..common::create_test_model_info()
+    }
+}
+
+fn names(files: &[llama_desktop_lib::models::BlobFile]) -> Vec<String> {
+    files
+        .iter()
+        .map(|f| {
+            Path::new(&f.path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_delete_model_removes_unreferenced_blobs_and_keeps_shared_ones() {
+    let fixture = Fixture::new();
+    fixture.write_blob("sha256-weights.partial", 3);
+
+    let report = fixture.delete(LLAMA, false).unwrap();
+
+    assert_eq!(names(&report.removed_blobs), vec!["sha256-weights"]);
+    assert_eq!(
+        names(&report.removed_partials),
+        vec!["sha256-weights.partial"]
+    );
+    assert_eq!(report.shared_blobs, vec!["sha256:config".to_string()]);
+    assert_eq!(report.bytes_reclaimed, 1000 + 3);
+    assert!(!report.dry_run);
+
+    assert!(!fixture.blobs().join("sha256-weights").exists());
+    // Not referenced by the deleted manifest, e.g. a layer of a running pull.
+    assert!(fixture.blobs().join("sha256-orphan").exists());
+    assert!(fixture.blobs().join("sha256-config").exists());
+    assert!(fixture.blobs().join("sha256-textweights").exists());
+    assert_eq!(list_manifest_files(fixture.root.path()).len(), 1);
+    assert!(!fixture
+        .root
+        .path()
+        .join("manifests/registry.ollama.ai/library/llama3/latest")
+        .exists());
+    assert_eq!(fixture.library_ids(), vec![LLAMA_TEXT.to_string()]);
+
+    let cache: serde_json::Value =
+        llama_desktop_lib::utils::read_json(&fixture.metadata.path().join("models.json")).unwrap();
+    assert!(cache["models"].get(LLAMA).is_none());
+    assert!(cache["models"].get(LLAMA_TEXT).is_some());
+}
+
+#[test]
+fn test_delete_last_model_removes_shared_blob_and_empty_directories() {
+    let fixture = Fixture::new();
+    fixture.delete(LLAMA, false).unwrap();
+
+    let report = fixture.delete(LLAMA_TEXT, false).unwrap();
+
+    assert_eq!(
+        names(&report.removed_blobs),
+        vec!["sha256-config", "sha256-textweights"]
+    );
+    assert!(report.shared_blobs.is_empty());
+    assert!(fixture.root.path().join("manifests").exists());
+    assert!(!fixture
+        .root
+        .path()
+        .join("manifests/registry.ollama.ai")
+        .exists());
+    assert!(fixture.library_ids().is_empty());
+}
+
+#[test]
+fn test_delete_model_dry_run_changes_nothing() {
+    let fixture = Fixture::new();
+
+    let report = fixture.delete(LLAMA, true).unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.bytes_reclaimed, 1000);
+    assert!(fixture.blobs().join("sha256-weights").exists());
+    assert!(fixture.blobs().join("sha256-orphan").exists());
+    assert_eq!(list_manifest_files(fixture.root.path()).len(), 2);
+    assert_eq!(fixture.library_ids().len(), 2);
+}
+
+#[test]
+fn test_delete_model_refuses_while_blob_is_running() {
+    let fixture = Fixture::new();
+    let running = vec![fixture.blobs().join("sha256-weights")];
+
+    let err = fixture
+        .delete_with(LLAMA, false, &running, &[])
+        .unwrap_err();
+
+    assert!(err.contains("running"), "{}", err);
+    assert_eq!(list_manifest_files(fixture.root.path()).len(), 2);
+    assert!(fixture.blobs().join("sha256-weights").exists());
+}
+
+#[test]
+fn test_delete_model_keeps_partials_of_active_downloads() {
+    let fixture = Fixture::new();
+    fixture.write_blob("sha256-next.partial", 4);
+    fixture.write_blob("sha256-next.partial.0", 4);
+    fixture.write_blob("sha256-stale.partial", 5);
+    let active = vec![fixture.blobs().join("sha256-next.partial")];
+
+    let report = fixture.delete_with(LLAMA, false, &[], &active).unwrap();
+
+    assert_eq!(
+        names(&report.removed_partials),
+        vec!["sha256-stale.partial"]
+    );
+    assert!(fixture.blobs().join("sha256-next.partial").exists());
+    assert!(fixture.blobs().join("sha256-next.partial.0").exists());
+}
+
+#[test]
+fn test_delete_model_keeps_blobs_when_a_manifest_is_unreadable() {
+    let fixture = Fixture::new();
+    let broken = fixture
+        .root
+        .path()
+        .join("manifests/registry.ollama.ai/library/other/latest");
+    std::fs::create_dir_all(broken.parent().unwrap()).unwrap();
+    std::fs::write(&broken, "not json").unwrap();
+
+    let report = fixture.delete(LLAMA, false).unwrap();
+
+    assert!(report.removed_blobs.is_empty());
+    assert_eq!(report.warnings.len(), 1);
+    assert!(fixture.blobs().join("sha256-weights").exists());
+    assert_eq!(fixture.library_ids(), vec![LLAMA_TEXT.to_string()]);
+}
+
+#[test]
+fn test_delete_model_drops_its_launch_presets() {
+    let fixture = Fixture::new();
+    let presets_path = build_presets_path(fixture.root.path());
+    let preset = LaunchPreset {
+        name: "fast".to_string(),
+        binary_path: None,
+        port: None,
+        ctx_size: Some(4096),
+        n_gpu_layers: None,
+        parallel: None,
+        chat_template: None,
+        chat_template_file: None,
+        restart_policy: None,
+        launch_params: LaunchParams::default(),
+    };
+    save_launch_preset_at_path(&presets_path, LLAMA, preset.clone(), false).unwrap();
+    save_launch_preset_at_path(&presets_path, LLAMA_TEXT, preset, false).unwrap();
+
+    fixture.delete(LLAMA, false).unwrap();
+
+    let presets = load_presets_from_path(&presets_path).unwrap();
+    assert!(!presets.models.contains_key(LLAMA));
+    assert!(presets.models.contains_key(LLAMA_TEXT));
+}
+
+#[test]
+fn test_delete_model_finds_manifest_without_library_entry() {
+    let fixture = Fixture::new();
+    std::fs::remove_file(fixture.root.path().join("modelLibrary.json")).unwrap();
+
+    let report = fixture.delete(LLAMA_TEXT, false).unwrap();
+
+    assert!(report.manifest_path.contains("text"));
+    assert!(fixture
+        .delete("registry.ollama.ai:missing:latest", false)
+        .is_err());
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_verify_reports_healthy_models_and_orphans() {
+    let fixture = Fixture::new();
+    fixture.write_blob("sha256-next.partial", 3);
+
+    let report = verify_models_directory_at_root(fixture.root.path(), false)
+        .await
+        .unwrap();
+
+    assert!(!report.rehashed);
+    assert_eq!(report.manifests.len(), 2);
+    assert!(report.manifests.iter().all(|m| m.healthy));
+    assert_eq!(report.manifests[0].model_id, LLAMA);
+    assert_eq!(report.manifests[0].blobs.len(), 2);
+    assert_eq!(names(&report.orphan_blobs), vec!["sha256-orphan"]);
+    assert_eq!(names(&report.partial_files), vec!["sha256-next.partial"]);
+    assert_eq!(report.total_bytes, 10 + 1000 + 500 + 7 + 3);
+    assert_eq!(report.reclaimable_bytes, 10);
+}
+
+#[tokio::test]
+async fn test_verify_flags_missing_and_truncated_blobs() {
+    let fixture = Fixture::new();
+    std::fs::remove_file(fixture.blobs().join("sha256-weights")).unwrap();
+    fixture.write_blob("sha256-textweights", 499);
+
+    let report = verify_models_directory_at_root(fixture.root.path(), false)
+        .await
+        .unwrap();
+
+    let latest = &report.manifests[0];
+    assert!(!latest.healthy);
+    assert_eq!(latest.blobs[0].status, BlobStatus::Ok);
+    assert_eq!(latest.blobs[1].status, BlobStatus::Missing);
+    assert_eq!(latest.blobs[1].path, None);
+    let text = &report.manifests[1];
+    assert_eq!(text.blobs[1].status, BlobStatus::SizeMismatch);
+    assert_eq!(text.blobs[1].actual_size, Some(499));
+}
+
+#[tokio::test]
+async fn test_verify_rehash_detects_corrupted_content() {
+    let fixture = common::temp_dir();
+    let good = b"good weights".to_vec();
+    let digest = format!("sha256:{}", sha256_hex(&good));
+    let blobs = fixture.path().join("blobs");
+    std::fs::create_dir_all(&blobs).unwrap();
+    std::fs::write(blobs.join(digest.replace(':', "-")), b"evil weights").unwrap();
+    let config = b"{}".to_vec();
+    let config_digest = format!("sha256:{}", sha256_hex(&config));
+    std::fs::write(blobs.join(config_digest.replace(':', "-")), &config).unwrap();
+    let manifest = ModelManifest {
+        schema_version: 2,
+        media_type: "application/vnd.ollama.manifest.v1+json".to_string(),
+        config: ManifestConfig {
+            media_type: "application/vnd.ollama.image.config".to_string(),
+            digest: config_digest,
+            size: config.len() as u64,
+        },
+        layers: vec![ManifestLayer {
+            media_type: "application/vnd.ollama.image.model".to_string(),
+            digest,
+            size: good.len() as u64,
+        }],
+    };
+    llama_desktop_lib::utils::save_json(
+        &fixture
+            .path()
+            .join("manifests/hf.co/org/repo/Q4_K_M/manifest.json"),
+        &manifest,
+    )
+    .unwrap();
+
+    let quick = verify_models_directory_at_root(fixture.path(), false)
+        .await
+        .unwrap();
+    let full = verify_models_directory_at_root(fixture.path(), true)
+        .await
+        .unwrap();
+
+    assert!(quick.manifests[0].healthy);
+    assert!(full.rehashed);
+    assert_eq!(full.manifests[0].blobs[0].status, BlobStatus::Ok);
+    assert_eq!(
+        full.manifests[0].blobs[1].status,
+        BlobStatus::DigestMismatch
+    );
+    assert!(!full.manifests[0].healthy);
+}
+
+#[tokio::test]
+async fn test_verify_reports_unreadable_blob_and_continues() {
+    let fixture = common::temp_dir();
+    let blobs = fixture.path().join("blobs");
+    std::fs::create_dir_all(&blobs).unwrap();
+    let config = b"{}".to_vec();
+    let config_digest = format!("sha256:{}", sha256_hex(&config));
+    std::fs::write(blobs.join(config_digest.replace(':', "-")), &config).unwrap();
+    // A directory where the weights should be cannot be hashed.
+    let digest = format!("sha256:{}", sha256_hex(b"weights"));
+    std::fs::create_dir_all(blobs.join(digest.replace(':', "-"))).unwrap();
+    let manifest = ModelManifest {
+        schema_version: 2,
+        media_type: "application/vnd.ollama.manifest.v1+json".to_string(),
+        config: ManifestConfig {
+            media_type: "application/vnd.ollama.image.config".to_string(),
+            digest: config_digest,
+            size: config.len() as u64,
+        },
+        layers: vec![ManifestLayer {
+            media_type: "application/vnd.ollama.image.model".to_string(),
+            digest,
+            size: 0,
+        }],
+    };
+    llama_desktop_lib::utils::save_json(
+        &fixture
+            .path()
+            .join("manifests/hf.co/org/repo/Q4_K_M/manifest.json"),
+        &manifest,
+    )
+    .unwrap();
+
+    let report = verify_models_directory_at_root(fixture.path(), true)
+        .await
+        .unwrap();
+
+    let blobs = &report.manifests[0].blobs;
+    assert_eq!(blobs[0].status, BlobStatus::Ok);
+    assert_eq!(blobs[1].status, BlobStatus::Unreadable);
+    assert!(blobs[1].error.is_some());
+    assert!(!report.manifests[0].healthy);
+}
+
+#[tokio::test]
+async fn test_verify_reports_unreadable_manifest() {
+    let fixture = Fixture::new();
+    let broken = fixture
+        .root
+        .path()
+        .join("manifests/registry.ollama.ai/library/other/latest");
+    std::fs::create_dir_all(broken.parent().unwrap()).unwrap();
+    std::fs::write(&broken, "not json").unwrap();
+
+    let report = verify_models_directory_at_root(fixture.root.path(), false)
+        .await
+        .unwrap();
+
+    let other = report
+        .manifests
+        .iter()
+        .find(|m| m.model_id == "registry.ollama.ai:other:latest")
+        .unwrap();
+    assert!(other.error.is_some());
+    assert!(!other.healthy);
+}
@@ -1,6 +1,14 @@
 use crate::common;
 
-use llama_desktop_lib::commands::models::{save_model_library, test_utils};
+use llama_desktop_lib::commands::models::{
+    download_hf_file, hf_partial_path, pull_registry_model_at_root, save_model_library, test_utils,
+};
+use llama_desktop_lib::models::{AppConfig, RegistryCredential};
+use llama_desktop_lib::services::downloads::DownloadProgressFn;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[tokio::test]
 async fn test_scan_models_directory_empty() {
@@ -72,3 +80,311 @@ async fn test_save_model_library_creates_dirs() {
 
     assert!(path.exists());
 }
+
+#[tokio::test]
+async fn test_scan_caches_gguf_info_in_models_json() {
+    let dir = common::temp_dir();
+    let metadata_dir = common::temp_dir();
+    let metadata_root = metadata_dir.path().to_str().unwrap();
+    let manifests_dir = dir.path().join("manifests/test/lib/model/v1");
+    std::fs::create_dir_all(&manifests_dir).unwrap();
+    llama_desktop_lib::utils::save_json(
+        &manifests_dir.join("manifest.json"),
+        &common::create_test_model_manifest(),
+    )
+    .unwrap();
+    let blobs_dir = dir.path().join("blobs");
+    std::fs::create_dir_all(&blobs_dir).unwrap();
+    common::GgufBuilder::new("llama")
+        .kv_u32("llama.context_length", 2048)
+        .kv_str("tokenizer.chat_template", "{{ messages }}")
+        .tensor("blk.0.attn_q.weight", &[32, 32], 1)
+        .tensor("output.weight", &[32, 32], 1)
+        .write(&blobs_dir.join("sha256-def456"));
+
+    let models = test_utils::scan_models_directory_for_test(
+        dir.path().to_str().unwrap().to_string(),
+        metadata_root,
+    )
+    .unwrap();
+    let gguf = models[0].gguf.as_ref().unwrap();
+    assert_eq!(gguf.context_length, Some(2048));
+    // Only the tensor the vocabulary size comes from is kept.
+    assert_eq!(gguf.tensors.len(), 1);
+    assert_eq!(gguf.tensors[0].name, "output.weight");
+    assert!(models[0].tokenizer_metadata.is_some());
+
+    let cache: serde_json::Value =
+        llama_desktop_lib::utils::read_json(&metadata_dir.path().join("models.json")).unwrap();
+    let cached = &cache["gguf"][&models[0].full_identifier];
+    assert_eq!(cached["architecture"], "llama");
+    assert_eq!(cached["tensors"].as_array().unwrap().len(), 1);
+
+    // A library saved without the header data gets it back from the cache.
+    let library_path = dir.path().join("modelLibrary.json");
+    let mut stripped = models[0].clone();
+    stripped.gguf = None;
+    save_model_library(library_path.to_str().unwrap().to_string(), vec![stripped])
+        .await
+        .unwrap();
+    let loaded = test_utils::load_model_library_for_test(
+        library_path.to_str().unwrap().to_string(),
+        metadata_root,
+    )
+    .await
+    .unwrap();
+    assert_eq!(loaded[0].gguf.as_ref().unwrap().context_length, Some(2048));
+}
+
+#[tokio::test]
+async fn test_scan_reads_ollama_modelfile_layers() {
+    let dir = common::temp_dir();
+    let manifests_dir = dir.path().join("manifests/test/lib/model/v1");
+    std::fs::create_dir_all(&manifests_dir).unwrap();
+    let blobs_dir = dir.path().join("blobs");
+    std::fs::create_dir_all(&blobs_dir).unwrap();
+
+    let mut manifest = common::create_test_model_manifest();
+    let layers = [
+        ("template", "sha256:tmpl", "{{ .Prompt }}"),
+        ("system", "sha256:sys", "Answer briefly."),
+        (
+            "params",
+            "sha256:params",
+            r#"{"stop":["<|end|>"],"num_ctx":4096}"#,
+        ),
+        ("license", "sha256:lic", "MIT"),
+    ];
+    for (kind, digest, content) in layers {
+        std::fs::write(blobs_dir.join(digest.replace(':', "-")), content).unwrap();
+        manifest
+            .layers
+            .push(llama_desktop_lib::models::ManifestLayer {
+                media_type: format!("application/vnd.ollama.image.{}", kind),
+                digest: digest.to_string(),
+                size: content.len() as u64,
+            });
+    }
+    llama_desktop_lib::utils::save_json(&manifests_dir.join("manifest.json"), &manifest).unwrap();
+
+    let models =
+        test_utils::scan_models_directory_for_test(dir.path().to_str().unwrap().to_string(), "")
+            .unwrap();
+
+    let model = &models[0];
+    assert_eq!(model.template.as_deref(), Some("{{ .Prompt }}"));
+    assert_eq!(model.system_prompt.as_deref(), Some("Answer briefly."));
+    assert_eq!(model.stop_sequences, vec!["<|end|>"]);
+    assert_eq!(model.params.as_ref().unwrap().num_ctx, Some(4096));
+    assert_eq!(model.license.as_deref(), Some("MIT"));
+}
+
+fn sha256_digest(bytes: &[u8]) -> String {
+    let hash: String = Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256:{}", hash)
+}
+
+#[tokio::test]
+async fn test_pull_from_private_registry_over_http() {
+    let server = MockServer::start().await;
+    let host = format!("127.0.0.1:{}", server.address().port());
+    let config_blob = br#"{"model_family":"llama"}"#.to_vec();
+    let model_blob = common::GgufBuilder::new("llama")
+        .kv_str("general.name", "Private")
+        .build();
+    let manifest = serde_json::json!({
+        "schemaVersion": 2,
+        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+        "config": {
+            "mediaType": "application/vnd.ollama.image.config",
+            "digest": sha256_digest(&config_blob),
+            "size": config_blob.len()
+        },
+        "layers": [{
+            "mediaType": "application/vnd.ollama.image.model",
+            "digest": sha256_digest(&model_blob),
+            "size": model_blob.len()
+        }]
+    });
+
+    // Everything under /v2/ needs the token the /token endpoint hands to alice.
+    let routes = [
+        (
+            "/v2/team/private/manifests/q4".to_string(),
+            serde_json::to_vec(&manifest).unwrap(),
+        ),
+        (
+            format!("/v2/team/private/blobs/{}", sha256_digest(&config_blob)),
+            config_blob,
+        ),
+        (
+            format!("/v2/team/private/blobs/{}", sha256_digest(&model_blob)),
+            model_blob,
+        ),
+    ];
+    for (route, body) in routes {
+        Mock::given(method("GET"))
+            .and(path(route.as_str()))
+            .and(header("Authorization", "Bearer team-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(body))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+    }
+    let challenge = format!(
+        "Bearer realm=\"{}/token\",service=\"stand-in\"",
+        server.uri()
+    );
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(401).insert_header("WWW-Authenticate", challenge.as_str()),
+        )
+        .with_priority(5)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/token"))
+        .and(header("Authorization", "Basic YWxpY2U6czNjcmV0"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"token": "team-token"})),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    let mut app_config = AppConfig::default();
+    app_config.insecure_registries.push(host.clone());
+    app_config.registry_credentials.insert(
+        host.clone(),
+        RegistryCredential::Basic {
+            username: "alice".to_string(),
+            password: "s3cret".to_string(),
+        },
+    );
+    let dir = common::temp_dir();
+    let models_root = dir.path().to_str().unwrap().to_string();
+
+    let model = pull_registry_model_at_root(
+        &format!("{}/team/private:q4", host),
+        models_root.clone(),
+        &models_root,
+        &app_config,
+        Arc::new(|_| {}),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(model.provider, host);
+    assert_eq!(model.name, "private");
+    assert_eq!(model.version, "q4");
+    assert!(dir
+        .path()
+        .join("manifests")
+        .join(&host)
+        .join("team/private/q4/manifest.json")
+        .exists());
+
+    // Without the insecure flag the client insists on TLS and cannot connect.
+    let err = pull_registry_model_at_root(
+        &format!("{}/team/private:q4", host),
+        models_root.clone(),
+        &models_root,
+        &AppConfig::default(),
+        Arc::new(|_| {}),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.contains("Failed to reach registry"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_hf_download_resumes_stable_partial() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/org/repo/resolve/main/model.gguf"))
+        .and(header("Range", "bytes=6-"))
+        .respond_with(
+            ResponseTemplate::new(206)
+                .insert_header("Content-Range", "bytes 6-10/11")
+                .set_body_bytes(b"world".to_vec()),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let temp = tempfile::TempDir::new().unwrap();
+    let blobs_dir = temp.path().join("blobs");
+    std::fs::create_dir_all(&blobs_dir).unwrap();
+    let partial = hf_partial_path(&blobs_dir, "org/repo", "main", "model.gguf");
+    assert_eq!(
+        partial,
+        hf_partial_path(&blobs_dir, "org/repo", "main", "model.gguf")
+    );
+    assert_ne!(
+        partial,
+        hf_partial_path(&blobs_dir, "org/repo", "v2", "model.gguf")
+    );
+    std::fs::write(&partial, b"hello ").unwrap();
+
+    let progress: DownloadProgressFn = Arc::new(|_| {});
+    let url = format!("{}/org/repo/resolve/main/model.gguf", server.uri());
+    let (digest, size) = download_hf_file(
+        &reqwest::Client::new(),
+        &url,
+        "model.gguf",
+        &blobs_dir,
+        &partial,
+        &progress,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(digest, sha256_digest(b"hello world"));
+    assert_eq!(size, 11);
+    assert!(!partial.exists());
+}
+
+#[tokio::test]
+async fn test_scan_remembers_a_gguf_without_tokenizer() {
+    let dir = common::temp_dir();
+    let metadata_dir = common::temp_dir();
+    let metadata_root = metadata_dir.path().to_str().unwrap();
+    let manifests_dir = dir.path().join("manifests/test/lib/model/v1");
+    std::fs::create_dir_all(&manifests_dir).unwrap();
+    llama_desktop_lib::utils::save_json(
+        &manifests_dir.join("manifest.json"),
+        &common::create_test_model_manifest(),
+    )
+    .unwrap();
+    let blobs_dir = dir.path().join("blobs");
+    std::fs::create_dir_all(&blobs_dir).unwrap();
+    let blob = blobs_dir.join("sha256-def456");
+    common::GgufBuilder::new("llama")
+        .kv_u32("llama.context_length", 2048)
+        .write(&blob);
+    let scan = || {
+        test_utils::scan_models_directory_for_test(
+            dir.path().to_str().unwrap().to_string(),
+            metadata_root,
+        )
+        .unwrap()
+    };
+
+    let models = scan();
+    assert!(models[0].tokenizer_metadata.is_none());
+    let cache: serde_json::Value =
+        llama_desktop_lib::utils::read_json(&metadata_dir.path().join("models.json")).unwrap();
+    assert_eq!(
+        cache["models"].get(&models[0].full_identifier),
+        Some(&serde_json::Value::Null)
+    );
+
+    // The second scan answers from the cache instead of reading the header.
+    std::fs::write(&blob, b"not a gguf").unwrap();
+    let models = scan();
+    assert!(models[0].tokenizer_metadata.is_none());
+    assert_eq!(models[0].gguf.as_ref().unwrap().context_length, Some(2048));
+}
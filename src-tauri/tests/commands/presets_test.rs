@@ -0,0 +1,239 @@
+use crate::common;
+
+use llama_desktop_lib::commands::presets::{
+    build_presets_path, config_from_preset, delete_launch_preset_at_path,
+    get_model_presets_at_path, load_presets_from_path, resolve_model_launch_at_root,
+    save_launch_preset_at_path, set_default_launch_preset_at_path, set_model_sampling_at_path,
+    DEFAULT_N_GPU_LAYERS,
+};
+use llama_desktop_lib::models::{
+    AppConfig, LaunchOverrides, LaunchParams, LaunchPreset, ModelLibrary, ModelParams,
+    SamplingParams,
+};
+
+const MODEL: &str = "test:model:v1";
+
+fn preset(name: &str, ctx_size: u32, n_gpu_layers: i32) -> LaunchPreset {
+    LaunchPreset {
+        name: name.to_string(),
+        binary_path: None,
+        port: None,
+        ctx_size: Some(ctx_size),
+        n_gpu_layers: Some(n_gpu_layers),
+        parallel: None,
+        chat_template: None,
+        chat_template_file: None,
+        restart_policy: None,
+        launch_params: LaunchParams::default(),
+    }
+}
+
+fn app_config() -> AppConfig {
+    AppConfig {
+        llama_directory: Some("/bin/llama-server".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_presets_path_sits_next_to_model_library() {
+    let dir = common::temp_dir();
+    assert_eq!(
+        build_presets_path(dir.path()),
+        dir.path().join("launchPresets.json")
+    );
+}
+
+#[test]
+fn test_missing_presets_file_is_empty() {
+    let dir = common::temp_dir();
+    let path = build_presets_path(dir.path());
+
+    assert!(get_model_presets_at_path(&path, MODEL)
+        .unwrap()
+        .presets
+        .is_empty());
+}
+
+#[test]
+fn test_save_replaces_preset_with_same_name() {
+    let dir = common::temp_dir();
+    let path = build_presets_path(dir.path());
+
+    save_launch_preset_at_path(&path, MODEL, preset("fast", 4096, 20), false).unwrap();
+    save_launch_preset_at_path(&path, MODEL, preset("long", 16384, 35), true).unwrap();
+    let presets =
+        save_launch_preset_at_path(&path, MODEL, preset("fast", 8192, 20), false).unwrap();
+
+    assert_eq!(presets.presets.len(), 2);
+    assert_eq!(presets.get("fast").unwrap().ctx_size, Some(8192));
+    assert_eq!(presets.default_preset.as_deref(), Some("long"));
+    assert_eq!(get_model_presets_at_path(&path, MODEL).unwrap(), presets);
+}
+
+#[test]
+fn test_save_rejects_invalid_preset() {
+    let dir = common::temp_dir();
+    let path = build_presets_path(dir.path());
+
+    let mut invalid = preset("bad", 0, 0);
+    assert!(save_launch_preset_at_path(&path, MODEL, invalid.clone(), false).is_err());
+
+    invalid.ctx_size = Some(2048);
+    invalid.launch_params.threads = Some(0);
+    let err = save_launch_preset_at_path(&path, MODEL, invalid, false).unwrap_err();
+    assert!(err.contains("threads"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_delete_clears_default_and_drops_empty_models() {
+    let dir = common::temp_dir();
+    let path = build_presets_path(dir.path());
+    save_launch_preset_at_path(&path, MODEL, preset("fast", 4096, 20), true).unwrap();
+
+    let presets = delete_launch_preset_at_path(&path, MODEL, "fast").unwrap();
+    assert!(presets.default_preset.is_none());
+    assert!(load_presets_from_path(&path).unwrap().models.is_empty());
+
+    assert!(delete_launch_preset_at_path(&path, MODEL, "fast").is_err());
+}
+
+#[test]
+fn test_set_default_requires_existing_preset() {
+    let dir = common::temp_dir();
+    let path = build_presets_path(dir.path());
+    save_launch_preset_at_path(&path, MODEL, preset("fast", 4096, 20), false).unwrap();
+
+    assert!(set_default_launch_preset_at_path(&path, MODEL, Some("missing".to_string())).is_err());
+    let presets =
+        set_default_launch_preset_at_path(&path, MODEL, Some("fast".to_string())).unwrap();
+    assert_eq!(presets.default_preset.as_deref(), Some("fast"));
+
+    let presets = set_default_launch_preset_at_path(&path, MODEL, None).unwrap();
+    assert!(presets.default_preset.is_none());
+}
+
+#[test]
+fn test_resolve_model_launch_uses_default_or_named_preset() {
+    let dir = common::temp_dir();
+    llama_desktop_lib::utils::save_json(
+        &dir.path().join("modelLibrary.json"),
+        &ModelLibrary {
+            models: vec![common::create_test_model_info()],
+        },
+    )
+    .unwrap();
+    let path = build_presets_path(dir.path());
+    save_launch_preset_at_path(&path, MODEL, preset("fast", 4096, 20), false).unwrap();
+    save_launch_preset_at_path(&path, MODEL, preset("long", 16384, 35), true).unwrap();
+
+    let (model, default) = resolve_model_launch_at_root(dir.path(), MODEL, None).unwrap();
+    assert_eq!(model.full_identifier, MODEL);
+    assert_eq!(default.unwrap().name, "long");
+
+    let (_, named) = resolve_model_launch_at_root(dir.path(), MODEL, Some("fast")).unwrap();
+    assert_eq!(named.unwrap().name, "fast");
+
+    assert!(resolve_model_launch_at_root(dir.path(), MODEL, Some("missing")).is_err());
+    assert!(resolve_model_launch_at_root(dir.path(), "other:model:v1", None).is_err());
+}
+
+#[test]
+fn test_config_from_preset_overrides_app_settings() {
+    let model = common::create_test_model_info();
+    let mut fast = preset("fast", 16384, 35);
+    fast.port = Some(9001);
+    fast.launch_params.threads = Some(8);
+
+    let config = config_from_preset(&model, Some(&fast), &app_config()).unwrap();
+    assert_eq!(config.llama_cpp_path, "/bin/llama-server");
+    assert_eq!(config.model_path, "/models/test.gguf");
+    assert_eq!(config.port, 9001);
+    assert_eq!(config.ctx_size, 16384);
+    assert_eq!(config.n_gpu_layers, 35);
+    assert_eq!(config.launch_params.threads, Some(8));
+}
+
+#[test]
+fn test_launch_overrides_replace_only_the_fields_they_set() {
+    let mut model = common::create_test_model_info();
+    model.tokenizer_metadata = Some(serde_json::json!({
+        "tokenizer.chat_template": "{{ messages }}"
+    }));
+    let mut config =
+        config_from_preset(&model, Some(&preset("fast", 16384, 35)), &app_config()).unwrap();
+
+    LaunchOverrides {
+        port: Some(9002),
+        parallel: Some(4),
+        chat_template_file: Some("/tmp/custom.jinja".to_string()),
+        ..LaunchOverrides::default()
+    }
+    .apply_to(&mut config);
+    assert_eq!(config.port, 9002);
+    assert_eq!(config.parallel, 4);
+    assert_eq!(config.ctx_size, 16384);
+    assert_eq!(config.n_gpu_layers, 35);
+    // A template file replaces the embedded inline template instead of sitting beside it.
+    assert_eq!(config.chat_template, None);
+    assert_eq!(config.chat_template_file.as_deref(), Some("/tmp/custom.jinja"));
+}
+
+#[test]
+fn test_model_num_ctx_sits_between_preset_and_app_settings() {
+    let mut model = common::create_test_model_info();
+    model.params = Some(ModelParams {
+        num_ctx: Some(8192),
+        ..ModelParams::default()
+    });
+
+    let config = config_from_preset(&model, None, &app_config()).unwrap();
+    assert_eq!(config.ctx_size, 8192);
+
+    let fast = preset("fast", 16384, 35);
+    let config = config_from_preset(&model, Some(&fast), &app_config()).unwrap();
+    assert_eq!(config.ctx_size, 16384);
+}
+
+#[test]
+fn test_config_without_preset_uses_app_settings_and_embedded_template() {
+    let mut model = common::create_test_model_info();
+    model.tokenizer_metadata = Some(serde_json::json!({
+        "tokenizer.chat_template": "{{ messages }}"
+    }));
+    let settings = app_config();
+
+    let config = config_from_preset(&model, None, &settings).unwrap();
+    assert_eq!(config.port, settings.server_port);
+    assert_eq!(config.ctx_size, settings.context_size);
+    assert_eq!(config.n_gpu_layers, DEFAULT_N_GPU_LAYERS);
+    assert_eq!(config.parallel, 1);
+    assert_eq!(config.chat_template.as_deref(), Some("{{ messages }}"));
+
+    assert!(config_from_preset(&model, None, &AppConfig::default()).is_err());
+}
+
+#[test]
+fn test_model_sampling_is_kept_without_presets() {
+    let dir = common::temp_dir();
+    let path = build_presets_path(dir.path());
+    let sampling = SamplingParams {
+        min_p: Some(0.05),
+        ..Default::default()
+    };
+
+    set_model_sampling_at_path(&path, MODEL, Some(sampling.clone())).unwrap();
+    let stored = get_model_presets_at_path(&path, MODEL).unwrap();
+    assert!(stored.presets.is_empty());
+    assert_eq!(stored.sampling, Some(sampling));
+
+    let invalid = SamplingParams {
+        min_p: Some(2.0),
+        ..Default::default()
+    };
+    assert!(set_model_sampling_at_path(&path, MODEL, Some(invalid)).is_err());
+
+    set_model_sampling_at_path(&path, MODEL, None).unwrap();
+    assert!(load_presets_from_path(&path).unwrap().models.is_empty());
+}
@@ -12,6 +12,9 @@ pub fn sample_llama_config() -> LlamaCppConfig {
         n_gpu_layers: 0,
         chat_template: None,
         chat_template_file: None,
+        port_range: None,
+        restart_policy: None,
+        launch_params: LaunchParams::default(),
     }
 }
 
@@ -29,6 +32,8 @@ pub fn sample_mcp_server(id: &str) -> McpServerConfig {
         headers: None,
         tool_allowlist: None,
         resource_allowlist: None,
+        tool_approval: None,
+        tool_approval_overrides: None,
     }
 }
 
@@ -39,6 +44,7 @@ pub fn sample_chat_message(role: &str, content: &str) -> ChatMessage {
         name: None,
         tool_call_id: None,
         tool_calls: None,
+        tool_approvals: None,
     }
 }
 
@@ -85,8 +91,131 @@ pub fn create_test_model_info() -> ModelInfo {
         version: "v1".to_string(),
         manifest_data: create_test_model_manifest(),
         tokenizer_metadata: None,
+        gguf: None,
+        system_prompt: None,
+        template: None,
+        params: None,
+        stop_sequences: Vec::new(),
+        license: None,
         model_file_path: Some("/models/test.gguf".to_string()),
         manifest_path: Some("manifests/test/library/model/v1/manifest.json".to_string()),
         full_identifier: "test:model:v1".to_string(),
     }
 }
+
+/// Writes an executable `llama-server` shell script and a dummy model into
+/// `dir`, returning a config that points at both and a free port.
+#[cfg(unix)]
+pub fn fake_llama_server_config(dir: &std::path::Path, script: &str) -> LlamaCppConfig {
+    use std::os::unix::fs::PermissionsExt;
+
+    let binary = dir.join("llama-server");
+    std::fs::write(&binary, format!("#!/bin/sh\n{}", script)).unwrap();
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let model_path = dir.join("model.gguf");
+    std::fs::write(&model_path, b"GGUF").unwrap();
+
+    let port = std::net::TcpListener::bind(("127.0.0.1", 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    LlamaCppConfig {
+        llama_cpp_path: binary.to_string_lossy().to_string(),
+        model_path: model_path.to_string_lossy().to_string(),
+        port,
+        ..sample_llama_config()
+    }
+}
+
+/// Assembles a minimal GGUF v3 file: metadata plus a tensor table, no data.
+#[derive(Default)]
+pub struct GgufBuilder {
+    kvs: Vec<(String, u32, Vec<u8>)>,
+    tensors: Vec<(String, Vec<u64>, u32)>,
+}
+
+impl GgufBuilder {
+    pub fn new(arch: &str) -> Self {
+        Self::default().kv_str("general.architecture", arch)
+    }
+
+    fn kv(mut self, key: &str, value_type: u32, value: Vec<u8>) -> Self {
+        self.kvs.push((key.to_string(), value_type, value));
+        self
+    }
+
+    pub fn kv_u32(self, key: &str, value: u32) -> Self {
+        self.kv(key, 4, value.to_le_bytes().to_vec())
+    }
+
+    pub fn kv_u64(self, key: &str, value: u64) -> Self {
+        self.kv(key, 10, value.to_le_bytes().to_vec())
+    }
+
+    pub fn kv_f32(self, key: &str, value: f32) -> Self {
+        self.kv(key, 6, value.to_le_bytes().to_vec())
+    }
+
+    pub fn kv_str(self, key: &str, value: &str) -> Self {
+        self.kv(key, 8, gguf_string(value))
+    }
+
+    pub fn kv_str_array(self, key: &str, values: &[&str]) -> Self {
+        let mut bytes = 8u32.to_le_bytes().to_vec();
+        bytes.extend((values.len() as u64).to_le_bytes());
+        for value in values {
+            bytes.extend(gguf_string(value));
+        }
+        self.kv(key, 9, bytes)
+    }
+
+    pub fn kv_u32_array(self, key: &str, values: &[u32]) -> Self {
+        let mut bytes = 4u32.to_le_bytes().to_vec();
+        bytes.extend((values.len() as u64).to_le_bytes());
+        for value in values {
+            bytes.extend(value.to_le_bytes());
+        }
+        self.kv(key, 9, bytes)
+    }
+
+    /// `type_id` is the ggml type (0 = F32, 1 = F16, 12 = Q4_K, ...).
+    pub fn tensor(mut self, name: &str, dims: &[u64], type_id: u32) -> Self {
+        self.tensors.push((name.to_string(), dims.to_vec(), type_id));
+        self
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut out = b"GGUF".to_vec();
+        out.extend(3u32.to_le_bytes());
+        out.extend((self.tensors.len() as u64).to_le_bytes());
+        out.extend((self.kvs.len() as u64).to_le_bytes());
+        for (key, value_type, value) in &self.kvs {
+            out.extend(gguf_string(key));
+            out.extend(value_type.to_le_bytes());
+            out.extend(value);
+        }
+        let mut offset = 0u64;
+        for (name, dims, type_id) in &self.tensors {
+            out.extend(gguf_string(name));
+            out.extend((dims.len() as u32).to_le_bytes());
+            for dim in dims {
+                out.extend(dim.to_le_bytes());
+            }
+            out.extend(type_id.to_le_bytes());
+            out.extend(offset.to_le_bytes());
+            offset += 32;
+        }
+        out
+    }
+
+    pub fn write(&self, path: &std::path::Path) {
+        std::fs::write(path, self.build()).unwrap();
+    }
+}
+
+fn gguf_string(value: &str) -> Vec<u8> {
+    let mut bytes = (value.len() as u64).to_le_bytes().to_vec();
+    bytes.extend(value.as_bytes());
+    bytes
+}
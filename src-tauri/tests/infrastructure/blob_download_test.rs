@@ -0,0 +1,425 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use llama_desktop_lib::infrastructure::blob_download::{
+    blob_filename, download_blob, partial_path, split_ranges, BlobClient, DownloadOptions,
+    ProgressFn,
+};
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+const BLOB_PATH: &str = "/v2/library/test/blobs/blob";
+
+fn body(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn digest_of(bytes: &[u8]) -> String {
+    let hash: String = Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256:{}", hash)
+}
+
+fn options(connections: usize, parallel_threshold: u64) -> DownloadOptions {
+    DownloadOptions {
+        connections,
+        parallel_threshold,
+        max_attempts: 1,
+        retry_delay: Duration::ZERO,
+    }
+}
+
+fn progress_sink() -> (ProgressFn, Arc<AtomicU64>) {
+    let last = Arc::new(AtomicU64::new(0));
+    let sink = last.clone();
+    let progress: ProgressFn = Arc::new(move |downloaded, _total| {
+        sink.store(downloaded, Ordering::SeqCst);
+    });
+    (progress, last)
+}
+
+/// Serves `body`, honouring `Range: bytes=a-b` / `bytes=a-` with a 206.
+struct RangeResponder {
+    body: Vec<u8>,
+}
+
+impl Respond for RangeResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let range = request
+            .headers
+            .get("range")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes="));
+        let Some(range) = range else {
+            return ResponseTemplate::new(200).set_body_bytes(self.body.clone());
+        };
+        let (start, end) = range.split_once('-').unwrap();
+        let start: usize = start.parse().unwrap();
+        let end: usize = if end.is_empty() {
+            self.body.len() - 1
+        } else {
+            end.parse().unwrap()
+        };
+        if start >= self.body.len() {
+            return ResponseTemplate::new(416);
+        }
+        ResponseTemplate::new(206)
+            .insert_header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end, self.body.len()).as_str(),
+            )
+            .set_body_bytes(self.body[start..=end].to_vec())
+    }
+}
+
+async fn range_server(bytes: &[u8]) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(BLOB_PATH))
+        .respond_with(RangeResponder {
+            body: bytes.to_vec(),
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn test_download_blob_writes_verified_blob() {
+    let bytes = body(4096);
+    let digest = digest_of(&bytes);
+    let server = range_server(&bytes).await;
+    let dir = TempDir::new().unwrap();
+    let (progress, last) = progress_sink();
+
+    let path = download_blob(
+        &BlobClient::new(reqwest::Client::new()),
+        &format!("{}{}", server.uri(), BLOB_PATH),
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options(1, u64::MAX),
+        progress,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(path, dir.path().join(blob_filename(&digest)));
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    assert!(!partial_path(&path).exists());
+    assert_eq!(last.load(Ordering::SeqCst), bytes.len() as u64);
+}
+
+#[tokio::test]
+async fn test_download_blob_resumes_partial_with_range() {
+    let bytes = body(4096);
+    let digest = digest_of(&bytes);
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(BLOB_PATH))
+        .and(header("Range", "bytes=1000-"))
+        .respond_with(RangeResponder {
+            body: bytes.clone(),
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().unwrap();
+    let blob_path = dir.path().join(blob_filename(&digest));
+    std::fs::write(partial_path(&blob_path), &bytes[..1000]).unwrap();
+    let (progress, _) = progress_sink();
+
+    download_blob(
+        &BlobClient::new(reqwest::Client::new()),
+        &format!("{}{}", server.uri(), BLOB_PATH),
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options(1, u64::MAX),
+        progress,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(std::fs::read(&blob_path).unwrap(), bytes);
+}
+
+#[tokio::test]
+async fn test_download_blob_restarts_when_range_is_ignored() {
+    let bytes = body(2048);
+    let digest = digest_of(&bytes);
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(BLOB_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(bytes.clone()))
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().unwrap();
+    let blob_path = dir.path().join(blob_filename(&digest));
+    std::fs::write(partial_path(&blob_path), vec![0u8; 500]).unwrap();
+    let (progress, _) = progress_sink();
+
+    download_blob(
+        &BlobClient::new(reqwest::Client::new()),
+        &format!("{}{}", server.uri(), BLOB_PATH),
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options(1, u64::MAX),
+        progress,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(std::fs::read(&blob_path).unwrap(), bytes);
+}
+
+#[tokio::test]
+async fn test_download_blob_falls_back_when_chunk_range_is_ignored() {
+    let bytes = body(10_000);
+    let digest = digest_of(&bytes);
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(BLOB_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(bytes.clone()))
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().unwrap();
+    let (progress, last) = progress_sink();
+
+    let path = download_blob(
+        &BlobClient::new(reqwest::Client::new()),
+        &format!("{}{}", server.uri(), BLOB_PATH),
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options(4, 1),
+        progress,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    assert_eq!(last.load(Ordering::SeqCst), bytes.len() as u64);
+    let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .filter(|n| n.to_string_lossy().contains("partial"))
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
+
+#[tokio::test]
+async fn test_download_blob_rejects_digest_mismatch() {
+    let bytes = body(1024);
+    let digest = digest_of(b"something else");
+    let server = range_server(&bytes).await;
+    let dir = TempDir::new().unwrap();
+    let (progress, _) = progress_sink();
+
+    let err = download_blob(
+        &BlobClient::new(reqwest::Client::new()),
+        &format!("{}{}", server.uri(), BLOB_PATH),
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options(1, u64::MAX),
+        progress,
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.contains("digest mismatch"), "{}", err);
+    let blob_path = dir.path().join(blob_filename(&digest));
+    assert!(!blob_path.exists());
+    assert!(!partial_path(&blob_path).exists());
+}
+
+#[tokio::test]
+async fn test_download_blob_fetches_chunks_in_parallel() {
+    let bytes = body(10_000);
+    let digest = digest_of(&bytes);
+    let server = range_server(&bytes).await;
+    let dir = TempDir::new().unwrap();
+    let (progress, last) = progress_sink();
+
+    let path = download_blob(
+        &BlobClient::new(reqwest::Client::new()),
+        &format!("{}{}", server.uri(), BLOB_PATH),
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options(4, 1),
+        progress,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 4);
+    assert!(requests.iter().all(|r| r.headers.contains_key("range")));
+    assert_eq!(last.load(Ordering::SeqCst), bytes.len() as u64);
+    let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .filter(|n| n.to_string_lossy().contains("partial"))
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
+
+#[tokio::test]
+async fn test_download_blob_resumes_individual_chunks() {
+    let bytes = body(8000);
+    let digest = digest_of(&bytes);
+    let server = range_server(&bytes).await;
+    let dir = TempDir::new().unwrap();
+    let blob_path = dir.path().join(blob_filename(&digest));
+    // Chunk 0 is complete, chunk 1 is half done.
+    std::fs::write(blob_path.with_extension("partial.0"), &bytes[..2000]).unwrap();
+    std::fs::write(blob_path.with_extension("partial.1"), &bytes[2000..3000]).unwrap();
+    let (progress, _) = progress_sink();
+
+    download_blob(
+        &BlobClient::new(reqwest::Client::new()),
+        &format!("{}{}", server.uri(), BLOB_PATH),
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options(4, 1),
+        progress,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(std::fs::read(&blob_path).unwrap(), bytes);
+    let ranges: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.headers["range"].to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(ranges.len(), 3);
+    assert!(ranges.contains(&"bytes=3000-3999".to_string()));
+}
+
+#[tokio::test]
+async fn test_download_blob_skips_existing_blob() {
+    let bytes = body(512);
+    let digest = digest_of(&bytes);
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join(blob_filename(&digest)), &bytes).unwrap();
+    let (progress, _) = progress_sink();
+
+    download_blob(
+        &BlobClient::new(reqwest::Client::new()),
+        &format!("{}{}", server.uri(), BLOB_PATH),
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options(1, u64::MAX),
+        progress,
+    )
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test_split_ranges_covers_whole_blob() {
+    assert_eq!(split_ranges(10, 3), vec![(0, 3), (4, 7), (8, 9)]);
+    assert_eq!(split_ranges(2, 4), vec![(0, 0), (1, 1)]);
+    assert!(split_ranges(0, 4).is_empty());
+}
+
+#[tokio::test]
+async fn test_download_blob_rejects_digest_without_sha256_prefix() {
+    let bytes = body(1024);
+    let server = range_server(&bytes).await;
+    let dir = TempDir::new().unwrap();
+
+    for digest in ["0123abcd", "md5:0123abcd"] {
+        let (progress, _) = progress_sink();
+        let err = download_blob(
+            &BlobClient::new(reqwest::Client::new()),
+            &format!("{}{}", server.uri(), BLOB_PATH),
+            digest,
+            bytes.len() as u64,
+            dir.path(),
+            &options(1, u64::MAX),
+            progress,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.contains("digest"), "{}", err);
+    }
+    assert!(server.received_requests().await.unwrap().is_empty());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+/// Sends the whole blob for a range starting at 0, and stalls every other
+/// range far longer than the test is willing to wait.
+struct FirstChunkIgnoresRange {
+    body: Vec<u8>,
+}
+
+impl Respond for FirstChunkIgnoresRange {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let from_start = request
+            .headers
+            .get("range")
+            .and_then(|v| v.to_str().ok())
+            .is_none_or(|v| v.starts_with("bytes=0-"));
+        if from_start {
+            return ResponseTemplate::new(200).set_body_bytes(self.body.clone());
+        }
+        ResponseTemplate::new(206).set_delay(Duration::from_secs(60))
+    }
+}
+
+#[tokio::test]
+async fn test_download_blob_cancels_chunks_once_range_is_ignored() {
+    let bytes = body(10_000);
+    let digest = digest_of(&bytes);
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(BLOB_PATH))
+        .respond_with(FirstChunkIgnoresRange {
+            body: bytes.clone(),
+        })
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().unwrap();
+    let (progress, _) = progress_sink();
+
+    let client = BlobClient::new(reqwest::Client::new());
+    let url = format!("{}{}", server.uri(), BLOB_PATH);
+    let options = options(4, 1);
+    let download = download_blob(
+        &client,
+        &url,
+        &digest,
+        bytes.len() as u64,
+        dir.path(),
+        &options,
+        progress,
+    );
+    let path = tokio::time::timeout(Duration::from_secs(10), download)
+        .await
+        .expect("stalled chunks were not cancelled")
+        .unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+}
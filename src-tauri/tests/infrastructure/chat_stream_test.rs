@@ -0,0 +1,120 @@
+use llama_desktop_lib::infrastructure::llama::server::LlamaServer;
+use llama_desktop_lib::models::{ChatRequest, ChatStreamEvent};
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn stream_request() -> ChatRequest {
+    ChatRequest {
+        model: "test".to_string(),
+        session_id: None,
+        messages: vec![],
+        temperature: 0.7,
+        top_p: 0.95,
+        top_k: 40,
+        max_tokens: 64,
+        reasoning_format: None,
+        reasoning_budget: None,
+        reasoning_budget_message: None,
+        thinking_forced_open: None,
+        chat_template_kwargs: None,
+        tools: Some(vec![
+            json!({"type": "function", "function": {"name": "search"}}),
+        ]),
+        tool_choice: None,
+        stop: None,
+        min_p: None,
+        repeat_penalty: None,
+        seed: None,
+        stream: true,
+        ..Default::default()
+    }
+}
+
+fn sse(chunks: &[serde_json::Value]) -> String {
+    let mut body: String = chunks
+        .iter()
+        .map(|chunk| format!("data: {}\n\n", chunk))
+        .collect();
+    body.push_str("data: [DONE]\n\n");
+    body
+}
+
+#[tokio::test]
+async fn test_stream_chat_events_yields_text_and_tool_call_deltas() {
+    let server = MockServer::start().await;
+    let body = sse(&[
+        json!({"choices": [{"delta": {"reasoning_content": "hmm"}}]}),
+        json!({"choices": [{"delta": {"content": "Checking"}}]}),
+        json!({"choices": [{"delta": {"tool_calls": [
+            {"index": 0, "id": "call-1", "type": "function", "function": {"name": "search", "arguments": "{\"q\""}}
+        ]}}]}),
+        json!({"choices": [{"delta": {"tool_calls": [
+            {"index": 0, "function": {"arguments": ":\"x\"}"}}
+        ]}, "finish_reason": "tool_calls"}]}),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_partial_json(
+            json!({"stream": true, "tools": [{"function": {"name": "search"}}]}),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+
+    let mut rx = LlamaServer::stream_chat_events(
+        reqwest::Client::new(),
+        server.address().port(),
+        None,
+        stream_request(),
+    )
+    .await
+    .unwrap();
+    let mut events = Vec::new();
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+
+    assert_eq!(
+        events,
+        vec![
+            ChatStreamEvent::Text("<think>hmm</think>".to_string()),
+            ChatStreamEvent::Text("Checking".to_string()),
+            ChatStreamEvent::ToolCallDelta(json!({
+                "index": 0, "id": "call-1", "type": "function",
+                "function": {"name": "search", "arguments": "{\"q\""}
+            })),
+            ChatStreamEvent::ToolCallDelta(json!({
+                "index": 0, "function": {"arguments": ":\"x\"}"}
+            })),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_text_stream_drops_tool_call_deltas() {
+    let server = MockServer::start().await;
+    let body = sse(&[
+        json!({"choices": [{"delta": {"content": "Hi"}}]}),
+        json!({"choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"name": "search"}}]}}]}),
+    ]);
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+
+    let mut rx = LlamaServer::stream_chat(
+        reqwest::Client::new(),
+        server.address().port(),
+        None,
+        stream_request(),
+    )
+    .await
+    .unwrap();
+    let mut chunks = Vec::new();
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+
+    assert_eq!(chunks, vec!["Hi".to_string()]);
+}
@@ -0,0 +1,143 @@
+use crate::common::GgufBuilder;
+
+use llama_desktop_lib::infrastructure::gguf::{
+    file_type_label, ggml_tensor_size, read_gguf, read_gguf_file,
+};
+use std::io::Cursor;
+
+fn llama_like() -> GgufBuilder {
+    GgufBuilder::new("llama")
+        .kv_str("general.name", "Tiny Llama")
+        .kv_u32("general.file_type", 15)
+        .kv_u32("llama.block_count", 2)
+        .kv_u32("llama.context_length", 4096)
+        .kv_u32("llama.embedding_length", 256)
+        .kv_u32("llama.feed_forward_length", 512)
+        .kv_u32("llama.attention.head_count", 8)
+        .kv_u32("llama.attention.head_count_kv", 2)
+        .kv_f32("llama.rope.freq_base", 10000.0)
+        .kv_u32("llama.rope.dimension_count", 32)
+        .kv_str("tokenizer.ggml.model", "llama")
+        .kv_str_array("tokenizer.ggml.tokens", &["<s>", "</s>", "hello"])
+        .kv_str("tokenizer.chat_template", "{{ messages }}")
+        .tensor("token_embd.weight", &[256, 100], 12)
+        .tensor("blk.0.attn_q.weight", &[256, 256], 12)
+        .tensor("output_norm.weight", &[256], 0)
+}
+
+#[test]
+fn test_reads_general_and_arch_metadata() {
+    let info = read_gguf(Cursor::new(llama_like().build())).unwrap().info();
+
+    assert_eq!(info.version, 3);
+    assert_eq!(info.architecture.as_deref(), Some("llama"));
+    assert_eq!(info.name.as_deref(), Some("Tiny Llama"));
+    assert_eq!(info.block_count, Some(2));
+    assert_eq!(info.context_length, Some(4096));
+    assert_eq!(info.embedding_length, Some(256));
+    assert_eq!(info.feed_forward_length, Some(512));
+    assert_eq!(info.head_count, Some(8));
+    assert_eq!(info.head_count_kv, Some(2));
+    assert_eq!(info.rope_freq_base, Some(10000.0));
+    assert_eq!(info.rope_dimension_count, Some(32));
+    assert_eq!(info.file_type, Some(15));
+    assert_eq!(info.quantization.as_deref(), Some("Q4_K_M"));
+
+    // Only general.* and <arch>.* keys are kept on the summary.
+    assert!(info.metadata.contains_key("llama.block_count"));
+    assert!(info.metadata.contains_key("general.name"));
+    assert!(!info.metadata.contains_key("tokenizer.ggml.model"));
+}
+
+#[test]
+fn test_reads_tensor_table_with_sizes() {
+    let info = read_gguf(Cursor::new(llama_like().build())).unwrap().info();
+
+    assert_eq!(info.tensors.len(), 3);
+    let embd = &info.tensors[0];
+    assert_eq!(embd.name, "token_embd.weight");
+    assert_eq!(embd.dims, vec![256, 100]);
+    assert_eq!(embd.dtype, "Q4_K");
+    assert_eq!(embd.size_bytes, Some(100 * 144));
+    assert_eq!(info.tensors[2].dtype, "F32");
+    assert_eq!(info.tensors[2].size_bytes, Some(256 * 4));
+    assert_eq!(info.tensor_bytes, 100 * 144 + 256 * 144 + 256 * 4);
+
+    // No general.parameter_count: fall back to counting tensor elements.
+    assert_eq!(info.parameter_count, Some(256 * 100 + 256 * 256 + 256));
+    assert_eq!(info.data_offset % 32, 0);
+}
+
+#[test]
+fn test_tokenizer_metadata_skips_vocabulary() {
+    let header = read_gguf(Cursor::new(llama_like().build())).unwrap();
+    let tokenizer = header.tokenizer_metadata();
+
+    assert_eq!(tokenizer["tokenizer.chat_template"], "{{ messages }}");
+    assert_eq!(tokenizer["tokenizer.ggml.model"], "llama");
+    assert!(!tokenizer.contains_key("tokenizer.ggml.tokens"));
+}
+
+#[test]
+fn test_per_layer_head_counts_and_dominant_dtype() {
+    let bytes = GgufBuilder::new("qwen3")
+        .kv_u32_array("qwen3.attention.head_count_kv", &[0, 4, 4])
+        .kv_u64("general.parameter_count", 1_000)
+        .tensor("a", &[64, 64], 8)
+        .tensor("b", &[64], 0)
+        .build();
+    let info = read_gguf(Cursor::new(bytes)).unwrap().info();
+
+    assert_eq!(info.head_count_kv, Some(4));
+    assert_eq!(info.parameter_count, Some(1_000));
+    assert_eq!(info.quantization.as_deref(), Some("Q8_0"));
+}
+
+#[test]
+fn test_rejects_non_gguf_and_truncated_files() {
+    assert_eq!(
+        read_gguf(Cursor::new(b"GGML\0\0\0\0".to_vec())).unwrap_err(),
+        "Not a GGUF file"
+    );
+
+    let bytes = llama_like().build();
+    assert!(read_gguf(Cursor::new(bytes[..bytes.len() - 4].to_vec())).is_err());
+}
+
+#[test]
+fn test_rejects_overflowing_tensor_sizes() {
+    let bytes = GgufBuilder::new("llama")
+        .tensor("huge", &[u64::MAX, 2], 0)
+        .build();
+    let err = read_gguf(Cursor::new(bytes)).unwrap_err();
+    assert!(err.contains("too many elements"), "{}", err);
+
+    let bytes = GgufBuilder::new("llama")
+        .tensor("a", &[u64::MAX / 2 + 1], 24)
+        .tensor("b", &[u64::MAX / 2 + 1], 24)
+        .build();
+    let err = read_gguf(Cursor::new(bytes)).unwrap_err();
+    assert!(err.contains("overflows"), "{}", err);
+
+    assert_eq!(ggml_tensor_size(0, u64::MAX), None);
+}
+
+#[test]
+fn test_read_gguf_file_from_disk() {
+    let dir = crate::common::temp_dir();
+    let path = dir.path().join("model.gguf");
+    llama_like().write(&path);
+
+    let header = read_gguf_file(&path).unwrap();
+    assert_eq!(header.architecture(), Some("llama"));
+    assert!(read_gguf_file(&dir.path().join("missing.gguf")).is_err());
+}
+
+#[test]
+fn test_type_tables() {
+    assert_eq!(ggml_tensor_size(8, 64), Some(68));
+    assert_eq!(ggml_tensor_size(12, 100), None);
+    assert_eq!(ggml_tensor_size(1000, 1), None);
+    assert_eq!(file_type_label(7), Some("Q8_0"));
+    assert_eq!(file_type_label(4), None);
+}
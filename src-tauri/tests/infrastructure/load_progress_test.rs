@@ -0,0 +1,157 @@
+use crate::common;
+
+use llama_desktop_lib::infrastructure::llama::logs::ServerLogs;
+use llama_desktop_lib::infrastructure::llama::progress::{
+    is_loading_health_body, parse_load_line, LoadProgressTracker, LoadSignal,
+};
+use llama_desktop_lib::infrastructure::llama::server::LlamaServer;
+use llama_desktop_lib::models::{LoadPhase, ModelId};
+use std::sync::Arc;
+
+fn model() -> ModelId {
+    ModelId("/models/test.gguf".to_string())
+}
+
+#[test]
+fn test_parse_load_line_phases() {
+    let cases = [
+        (
+            "llama_model_loader: loaded meta data with 30 key-value pairs and 291 tensors",
+            LoadPhase::LoadingMetadata,
+        ),
+        (
+            "load_tensors: loading model tensors, this can take a while... (mmap = true)",
+            LoadPhase::LoadingTensors,
+        ),
+        (
+            "llm_load_tensors: CPU_Mapped model buffer size = 4165.37 MiB",
+            LoadPhase::LoadingTensors,
+        ),
+        (
+            "llama_kv_cache_unified: size = 512.00 MiB (4096 cells, 32 layers)",
+            LoadPhase::AllocatingContext,
+        ),
+        (
+            "common_init_from_params: warming up the model with an empty run",
+            LoadPhase::WarmingUp,
+        ),
+        (
+            "main: server is listening on http://127.0.0.1:8080 - starting the main loop",
+            LoadPhase::Listening,
+        ),
+    ];
+
+    for (line, phase) in cases {
+        assert_eq!(
+            parse_load_line(line),
+            Some(LoadSignal::Phase(phase)),
+            "{}",
+            line
+        );
+    }
+    assert_eq!(
+        parse_load_line("srv  log_server_r: request: GET /health"),
+        None
+    );
+}
+
+#[test]
+fn test_parse_load_line_offload_and_context() {
+    assert_eq!(
+        parse_load_line("load_tensors: offloaded 33/33 layers to GPU"),
+        Some(LoadSignal::Offloaded {
+            layers: 33,
+            total: 33
+        })
+    );
+    assert_eq!(
+        parse_load_line("llama_context: n_ctx         = 4096"),
+        Some(LoadSignal::ContextSize(4096))
+    );
+    assert_eq!(
+        parse_load_line("llama_new_context_with_model: n_ctx      = 8192"),
+        Some(LoadSignal::ContextSize(8192))
+    );
+    assert_eq!(
+        parse_load_line("llama_context: n_ctx_per_seq = 4096"),
+        Some(LoadSignal::Phase(LoadPhase::AllocatingContext))
+    );
+}
+
+#[test]
+fn test_health_body_loading_detection() {
+    assert!(is_loading_health_body(
+        r#"{"error":{"code":503,"message":"Loading model","type":"unavailable_error"}}"#
+    ));
+    assert!(!is_loading_health_body(r#"{"status":"ok"}"#));
+}
+
+#[test]
+fn test_tracker_is_monotonic_and_keeps_details() {
+    let mut tracker = LoadProgressTracker::new(model());
+
+    let event = tracker
+        .observe_line("load_tensors: offloaded 20/33 layers to GPU")
+        .unwrap();
+    assert_eq!(event.phase, LoadPhase::LoadingTensors);
+    assert_eq!(event.layers_offloaded, Some(20));
+    assert_eq!(event.layers_total, Some(33));
+
+    // A late 503 or metadata line must not move progress backwards.
+    assert!(tracker.observe_health_loading().is_none());
+    assert!(tracker
+        .observe_line("llama_model_loader: - kv   0: general.architecture str = llama")
+        .is_none());
+
+    let event = tracker.observe_line("llama_context: n_ctx = 4096").unwrap();
+    assert_eq!(event.phase, LoadPhase::AllocatingContext);
+    assert_eq!(event.n_ctx, Some(4096));
+    assert_eq!(event.layers_offloaded, Some(20));
+
+    let ready = tracker.ready();
+    assert_eq!(ready.phase, LoadPhase::Ready);
+    assert_eq!(ready.percent, 100.0);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_spawn_streams_progress_and_closes_channel() {
+    let dir = common::temp_dir();
+    let config = common::fake_llama_server_config(
+        dir.path(),
+        "echo 'llama_model_loader: loaded meta data with 30 key-value pairs' >&2\n\
+         echo 'load_tensors: offloaded 12/33 layers to GPU' >&2\n\
+         echo 'llama_context: n_ctx = 2048' >&2\n\
+         exit 1\n",
+    );
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let result = LlamaServer::spawn(
+        model(),
+        None,
+        config,
+        reqwest::Client::new(),
+        Arc::new(ServerLogs::default()),
+        Some(tx),
+    )
+    .await;
+    assert!(result.is_err());
+
+    let mut events = Vec::new();
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+    let phases: Vec<LoadPhase> = events.iter().map(|e| e.phase).collect();
+    assert_eq!(
+        phases,
+        vec![
+            LoadPhase::Spawning,
+            LoadPhase::LoadingMetadata,
+            LoadPhase::LoadingTensors,
+            LoadPhase::AllocatingContext,
+        ]
+    );
+    let last = events.last().unwrap();
+    assert_eq!(last.n_ctx, Some(2048));
+    assert_eq!(last.layers_offloaded, Some(12));
+}
@@ -1,3 +1,13 @@
 mod process_manager_test;
 mod metrics_test;
 mod nvidia_smi_test;
+mod ports_test;
+mod session_store_test;
+mod server_logs_test;
+mod load_progress_test;
+mod server_args_test;
+mod gguf_test;
+mod blob_download_test;
+mod modelfile_test;
+mod registry_client_test;
+mod chat_stream_test;
@@ -0,0 +1,61 @@
+use llama_desktop_lib::infrastructure::modelfile::{
+    parse_params, ModelfileLayers, LICENSE_MEDIA_TYPE, PARAMS_MEDIA_TYPE, SYSTEM_MEDIA_TYPE,
+    TEMPLATE_MEDIA_TYPE,
+};
+
+#[test]
+fn test_parse_params_reads_typed_fields_and_stop_sequences() {
+    let (params, stop) = parse_params(
+        br#"{"stop":["<|im_start|>","<|im_end|>"],"temperature":0.6,"top_k":[20],"num_ctx":8192,"mirostat":1}"#,
+    )
+    .unwrap();
+
+    assert_eq!(stop, vec!["<|im_start|>", "<|im_end|>"]);
+    assert_eq!(params.temperature, Some(0.6));
+    assert_eq!(params.top_k, Some(20));
+    assert_eq!(params.num_ctx, Some(8192));
+    assert_eq!(params.other["mirostat"], 1);
+    assert!(!params.other.contains_key("stop"));
+}
+
+#[test]
+fn test_parse_params_accepts_single_stop_string() {
+    let (params, stop) = parse_params(br#"{"stop":"</s>"}"#).unwrap();
+
+    assert_eq!(stop, vec!["</s>"]);
+    assert_eq!(params.temperature, None);
+}
+
+#[test]
+fn test_parse_params_rejects_invalid_json_and_types() {
+    assert!(parse_params(b"temperature 0.7").is_err());
+    assert!(parse_params(br#"{"temperature":"hot"}"#).is_err());
+}
+
+#[test]
+fn test_layers_collect_text_and_join_licenses() {
+    let mut layers = ModelfileLayers::default();
+    layers
+        .add_layer(TEMPLATE_MEDIA_TYPE, b"{{ .System }} {{ .Prompt }}")
+        .unwrap();
+    layers
+        .add_layer(SYSTEM_MEDIA_TYPE, b"You are a pirate.")
+        .unwrap();
+    layers.add_layer(LICENSE_MEDIA_TYPE, b"MIT").unwrap();
+    layers.add_layer(LICENSE_MEDIA_TYPE, b"Apache-2.0").unwrap();
+    layers
+        .add_layer(PARAMS_MEDIA_TYPE, br#"{"stop":["<eot>"]}"#)
+        .unwrap();
+    layers
+        .add_layer("application/vnd.ollama.image.model", b"GGUF")
+        .unwrap();
+
+    assert_eq!(
+        layers.template.as_deref(),
+        Some("{{ .System }} {{ .Prompt }}")
+    );
+    assert_eq!(layers.system.as_deref(), Some("You are a pirate."));
+    assert_eq!(layers.license.as_deref(), Some("MIT\n\nApache-2.0"));
+    assert_eq!(layers.stop, vec!["<eot>"]);
+    assert!(layers.params.is_some());
+}
@@ -1,5 +1,6 @@
 use llama_desktop_lib::infrastructure::nvidia_smi::{
-    test_parse_gpu_query, test_parse_process_list, test_set_nvidia_smi_outputs, NvidiaSmi,
+    test_parse_free_memory, test_parse_gpu_query, test_parse_process_list,
+    test_set_nvidia_smi_outputs, NvidiaSmi,
 };
 
 #[test]
@@ -102,3 +103,11 @@ fn test_get_gpu_metrics_with_stub_failure() {
     assert_eq!(gpu, None);
     assert_eq!(vram, None);
 }
+
+#[test]
+fn test_parse_free_memory_per_gpu() {
+    assert_eq!(
+        test_parse_free_memory("8000\n 1024 \nN/A\n"),
+        vec![8000 * 1024 * 1024, 1024 * 1024 * 1024]
+    );
+}
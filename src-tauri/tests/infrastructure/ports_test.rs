@@ -0,0 +1,138 @@
+use std::net::TcpListener;
+
+use llama_desktop_lib::infrastructure::ports::{
+    find_free_port, is_port_free, resolve_port, test_parse_lsof_output, test_parse_netstat_output,
+    test_parse_proc_net_tcp,
+};
+use llama_desktop_lib::models::{LlamaServerError, PortRange};
+
+fn occupy_port() -> (TcpListener, u16) {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    (listener, port)
+}
+
+#[test]
+fn test_is_port_free_detects_listener() {
+    let (listener, port) = occupy_port();
+    assert!(!is_port_free(port));
+
+    drop(listener);
+    assert!(is_port_free(port));
+}
+
+#[test]
+fn test_resolve_port_keeps_free_preferred_port() {
+    let (listener, port) = occupy_port();
+    drop(listener);
+
+    assert_eq!(resolve_port(port, None, &[]).unwrap(), port);
+}
+
+#[test]
+fn test_resolve_port_busy_without_range_is_port_in_use() {
+    let (_listener, port) = occupy_port();
+
+    let err = resolve_port(port, None, &[]).unwrap_err();
+    match err {
+        LlamaServerError::PortInUse {
+            port: busy, pid, ..
+        } => {
+            assert_eq!(busy, port);
+            if cfg!(target_os = "linux") {
+                assert_eq!(pid, Some(std::process::id()));
+            }
+        }
+        other => panic!("expected PortInUse, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_resolve_port_falls_back_to_range() {
+    let (_busy, port) = occupy_port();
+    let (free, free_port) = occupy_port();
+    drop(free);
+
+    let range = PortRange {
+        start: free_port,
+        end: free_port,
+    };
+    assert_eq!(resolve_port(port, Some(range), &[]).unwrap(), free_port);
+}
+
+#[test]
+fn test_resolve_port_skips_reserved_ports() {
+    let (_busy, port) = occupy_port();
+    let (free, free_port) = occupy_port();
+    drop(free);
+    let range = PortRange {
+        start: free_port,
+        end: free_port,
+    };
+
+    assert_eq!(
+        resolve_port(port, Some(range), &[free_port]).unwrap_err(),
+        LlamaServerError::NoFreePort {
+            start: free_port,
+            end: free_port
+        }
+    );
+    assert!(resolve_port(free_port, None, &[free_port]).is_err());
+}
+
+#[test]
+fn test_resolve_port_exhausted_range() {
+    let (_busy, port) = occupy_port();
+    let range = PortRange {
+        start: port,
+        end: port,
+    };
+
+    assert_eq!(find_free_port(range), None);
+    assert_eq!(
+        resolve_port(port, Some(range), &[]).unwrap_err(),
+        LlamaServerError::NoFreePort {
+            start: port,
+            end: port
+        }
+    );
+}
+
+#[test]
+fn test_llama_server_error_serializes_with_kind() {
+    let err = LlamaServerError::PortInUse {
+        port: 8080,
+        pid: Some(42),
+        process_name: Some("python".to_string()),
+    };
+    let json = serde_json::to_value(&err).unwrap();
+
+    assert_eq!(json["kind"], "port_in_use");
+    assert_eq!(json["pid"], 42);
+    assert_eq!(
+        err.to_string(),
+        "Port 8080 is already in use by python (pid 42)"
+    );
+}
+
+#[test]
+fn test_parse_proc_net_tcp_matches_listening_port() {
+    let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 12345 1 0000000000000000 100 0 0 10 0\n   1: 0100007F:1F90 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 67890 1 0000000000000000 20 4 30 10 -1\n";
+
+    assert_eq!(test_parse_proc_net_tcp(content, 8080), vec![12345]);
+    assert!(test_parse_proc_net_tcp(content, 8081).is_empty());
+}
+
+#[test]
+fn test_parse_netstat_output_finds_listener_pid() {
+    let stdout = "\nActive Connections\n\n  Proto  Local Address          Foreign Address        State           PID\n  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       1000\n  TCP    127.0.0.1:8080         0.0.0.0:0              LISTENING       4242\n  TCP    127.0.0.1:8080         127.0.0.1:50000        ESTABLISHED     4242\n";
+
+    assert_eq!(test_parse_netstat_output(stdout, 8080), Some(4242));
+    assert_eq!(test_parse_netstat_output(stdout, 80), None);
+}
+
+#[test]
+fn test_parse_lsof_output_first_pid() {
+    assert_eq!(test_parse_lsof_output("4242\n"), Some(4242));
+    assert_eq!(test_parse_lsof_output(""), None);
+}
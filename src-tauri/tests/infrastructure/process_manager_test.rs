@@ -85,3 +85,24 @@ fn test_process_manager_poisoned_mutex() {
     let pid = manager.get_pid(&model_id);
     assert_eq!(pid, None);
 }
+
+#[tokio::test]
+async fn test_process_manager_try_wait_reports_exit() {
+    let manager = Arc::new(LlamaProcessManager::new());
+    let model_id = ModelId("test:model:v1".to_string());
+
+    let child = common::spawn_sleep_process().unwrap();
+    manager.register(model_id.clone(), child);
+    assert!(manager.try_wait(&model_id).is_none());
+
+    // Kill through the map entry, then the exit must be observable while still registered.
+    let mut child = manager.remove(&model_id).unwrap();
+    child.start_kill().unwrap();
+    child.wait().await.unwrap();
+    manager.register(model_id.clone(), child);
+
+    let status = manager.try_wait(&model_id);
+    assert!(status.is_some());
+    assert!(!status.unwrap().success());
+    assert!(manager.get_pid(&model_id).is_none());
+}
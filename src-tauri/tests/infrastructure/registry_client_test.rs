@@ -0,0 +1,320 @@
+use llama_desktop_lib::infrastructure::blob_download::{download_blob, DownloadOptions};
+use llama_desktop_lib::infrastructure::registry_client::{parse_challenge, RegistryClient};
+use llama_desktop_lib::models::RegistryCredential;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const MANIFEST_PATH: &str = "/v2/acme/model/manifests/latest";
+
+fn host(server: &MockServer) -> String {
+    format!("127.0.0.1:{}", server.address().port())
+}
+
+fn manifest_body() -> serde_json::Value {
+    json!({
+        "schemaVersion": 2,
+        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+        "config": {"mediaType": "application/vnd.ollama.image.config", "digest": "sha256:c0", "size": 2},
+        "layers": [{"mediaType": "application/vnd.ollama.image.model", "digest": "sha256:m0", "size": 4}]
+    })
+}
+
+/// Answers unauthenticated requests to `route` with a bearer challenge
+/// pointing at this server's `/token` endpoint.
+async fn require_bearer(server: &MockServer, route: &str, token: &str) {
+    Mock::given(method("GET"))
+        .and(path(route))
+        .and(header(
+            "Authorization",
+            format!("Bearer {}", token).as_str(),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(manifest_body()))
+        .with_priority(1)
+        .mount(server)
+        .await;
+    let challenge = format!(
+        "Bearer realm=\"{}/token\",service=\"stand-in\",scope=\"repository:acme/model:pull\"",
+        server.uri()
+    );
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(
+            ResponseTemplate::new(401).insert_header("WWW-Authenticate", challenge.as_str()),
+        )
+        .with_priority(2)
+        .mount(server)
+        .await;
+}
+
+#[test]
+fn test_parse_challenge_keeps_commas_inside_quotes() {
+    let challenge = parse_challenge(
+        "Bearer realm=\"https://auth.example/token\",service=\"registry.example\",scope=\"repository:a/b:pull,push\"",
+    )
+    .unwrap();
+
+    assert_eq!(challenge.scheme, "Bearer");
+    assert_eq!(challenge.params["realm"], "https://auth.example/token");
+    assert_eq!(challenge.params["service"], "registry.example");
+    assert_eq!(challenge.params["scope"], "repository:a/b:pull,push");
+
+    let basic = parse_challenge("Basic realm=zot").unwrap();
+    assert_eq!(basic.scheme, "Basic");
+    assert_eq!(basic.params["realm"], "zot");
+}
+
+#[tokio::test]
+async fn test_anonymous_bearer_token_is_negotiated() {
+    let server = MockServer::start().await;
+    require_bearer(&server, MANIFEST_PATH, "anon-token").await;
+    Mock::given(method("GET"))
+        .and(path("/token"))
+        .and(query_param("service", "stand-in"))
+        .and(query_param("scope", "repository:acme/model:pull"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"token": "anon-token"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut client = RegistryClient::new(&host(&server), None, true).unwrap();
+    let manifest = client.fetch_manifest("acme/model", "latest").await.unwrap();
+
+    assert_eq!(manifest.layers[0].digest, "sha256:m0");
+}
+
+#[tokio::test]
+async fn test_basic_credentials_are_traded_for_a_token() {
+    let server = MockServer::start().await;
+    require_bearer(&server, MANIFEST_PATH, "private-token").await;
+    // "alice:s3cret" in base64.
+    Mock::given(method("GET"))
+        .and(path("/token"))
+        .and(header("Authorization", "Basic YWxpY2U6czNjcmV0"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"access_token": "private-token"})),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(401))
+        .with_priority(10)
+        .mount(&server)
+        .await;
+
+    let credential = RegistryCredential::Basic {
+        username: "alice".to_string(),
+        password: "s3cret".to_string(),
+    };
+    let mut client = RegistryClient::new(&host(&server), Some(credential), true).unwrap();
+    assert!(client.fetch_manifest("acme/model", "latest").await.is_ok());
+
+    let wrong = RegistryCredential::Basic {
+        username: "alice".to_string(),
+        password: "nope".to_string(),
+    };
+    let mut client = RegistryClient::new(&host(&server), Some(wrong), true).unwrap();
+    let err = client
+        .fetch_manifest("acme/model", "latest")
+        .await
+        .unwrap_err();
+    assert!(err.contains("refused a token"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_password_is_not_sent_to_a_plain_http_realm_elsewhere() {
+    let server = MockServer::start().await;
+    let token_server = MockServer::start().await;
+    let challenge = format!(
+        "Bearer realm=\"{}/token\",service=\"stand-in\"",
+        token_server.uri()
+    );
+    Mock::given(method("GET"))
+        .and(path(MANIFEST_PATH))
+        .respond_with(
+            ResponseTemplate::new(401).insert_header("WWW-Authenticate", challenge.as_str()),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"token": "t"})))
+        .expect(0)
+        .mount(&token_server)
+        .await;
+
+    let credential = RegistryCredential::Basic {
+        username: "alice".to_string(),
+        password: "s3cret".to_string(),
+    };
+    let mut client = RegistryClient::new(&host(&server), Some(credential), true).unwrap();
+    let err = client
+        .fetch_manifest("acme/model", "latest")
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("plain HTTP"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_basic_challenge_uses_stored_password() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(MANIFEST_PATH))
+        .and(header("Authorization", "Basic YWxpY2U6czNjcmV0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(manifest_body()))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(MANIFEST_PATH))
+        .respond_with(
+            ResponseTemplate::new(401).insert_header("WWW-Authenticate", "Basic realm=\"zot\""),
+        )
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    let mut anonymous = RegistryClient::new(&host(&server), None, true).unwrap();
+    let err = anonymous
+        .fetch_manifest("acme/model", "latest")
+        .await
+        .unwrap_err();
+    assert!(err.contains("username and password"), "{}", err);
+
+    let credential = RegistryCredential::Basic {
+        username: "alice".to_string(),
+        password: "s3cret".to_string(),
+    };
+    let mut client = RegistryClient::new(&host(&server), Some(credential), true).unwrap();
+    assert!(client.fetch_manifest("acme/model", "latest").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_static_token_is_sent_without_negotiation() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(MANIFEST_PATH))
+        .and(header("Authorization", "Bearer robot-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(manifest_body()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let credential = RegistryCredential::Token {
+        token: "robot-token".to_string(),
+    };
+    let mut client = RegistryClient::new(&host(&server), Some(credential), true).unwrap();
+    assert!(client.fetch_manifest("acme/model", "latest").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_oci_index_resolves_to_model_manifest() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(MANIFEST_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "schemaVersion": 2,
+            "mediaType": "application/vnd.oci.image.index.v1+json",
+            "manifests": [
+                {
+                    "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                    "digest": "sha256:attestation",
+                    "size": 10,
+                    "platform": {"os": "unknown", "architecture": "unknown"}
+                },
+                {
+                    "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                    "digest": "sha256:model",
+                    "size": 10
+                }
+            ]
+        })))
+        .mount(&server)
+        .await;
+    // OCI manifests may leave out their own mediaType.
+    let mut manifest = manifest_body();
+    manifest.as_object_mut().unwrap().remove("mediaType");
+    Mock::given(method("GET"))
+        .and(path("/v2/acme/model/manifests/sha256:model"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(manifest))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut client = RegistryClient::new(&host(&server), None, true).unwrap();
+    let manifest = client.fetch_manifest("acme/model", "latest").await.unwrap();
+
+    assert_eq!(manifest.config.digest, "sha256:c0");
+    assert_eq!(manifest.media_type, "");
+}
+
+#[tokio::test]
+async fn test_blob_download_renews_an_expired_token() {
+    let server = MockServer::start().await;
+    require_bearer(&server, MANIFEST_PATH, "first-token").await;
+    let blob = b"model bytes".to_vec();
+    let hash: String = Sha256::digest(&blob)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let digest = format!("sha256:{}", hash);
+    let blob_path = format!("/v2/acme/model/blobs/{}", digest);
+    // By the time the blob is fetched the manifest's token has expired.
+    Mock::given(method("GET"))
+        .and(path(blob_path.as_str()))
+        .and(header("Authorization", "Bearer second-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(blob.clone()))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    let challenge = format!(
+        "Bearer realm=\"{}/token\",service=\"stand-in\"",
+        server.uri()
+    );
+    Mock::given(method("GET"))
+        .and(path(blob_path.as_str()))
+        .respond_with(
+            ResponseTemplate::new(401).insert_header("WWW-Authenticate", challenge.as_str()),
+        )
+        .with_priority(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"token": "first-token"})))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"token": "second-token"})))
+        .expect(1)
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    let mut client = RegistryClient::new(&host(&server), None, true).unwrap();
+    client.fetch_manifest("acme/model", "latest").await.unwrap();
+    let blobs = tempfile::TempDir::new().unwrap();
+    let path = download_blob(
+        &client.blob_client("acme/model").unwrap(),
+        &client.blob_url("acme/model", &digest),
+        &digest,
+        blob.len() as u64,
+        blobs.path(),
+        &DownloadOptions {
+            max_attempts: 1,
+            ..Default::default()
+        },
+        Arc::new(|_, _| {}),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(std::fs::read(path).unwrap(), blob);
+}
@@ -0,0 +1,153 @@
+use crate::common;
+
+use llama_desktop_lib::infrastructure::llama::server::LlamaServer;
+use llama_desktop_lib::models::{
+    FlashAttention, KvCacheType, LaunchParams, LlamaCppConfig, LoraAdapter, RopeScaling,
+};
+
+fn args_for(config: &LlamaCppConfig) -> Vec<String> {
+    LlamaServer::build_args("/models/test.gguf", 8085, config)
+}
+
+#[test]
+fn test_default_args_match_previous_fixed_flags() {
+    let config = common::sample_llama_config();
+
+    assert_eq!(
+        args_for(&config),
+        vec![
+            "-m",
+            "/models/test.gguf",
+            "--port",
+            "8085",
+            "-c",
+            "2048",
+            "-np",
+            "1",
+            "-ngl",
+            "0",
+            "--jinja"
+        ]
+    );
+}
+
+#[test]
+fn test_chat_template_and_jinja_toggle() {
+    let config = LlamaCppConfig {
+        chat_template: Some("chatml".to_string()),
+        launch_params: LaunchParams {
+            jinja: false,
+            ..Default::default()
+        },
+        ..common::sample_llama_config()
+    };
+
+    let args = args_for(&config);
+    assert!(!args.contains(&"--jinja".to_string()));
+    assert_eq!(&args[10..], ["--chat-template", "chatml"]);
+}
+
+#[test]
+fn test_full_launch_params_argv() {
+    let config = LlamaCppConfig {
+        ctx_size: 8192,
+        n_gpu_layers: 99,
+        launch_params: LaunchParams {
+            flash_attn: Some(FlashAttention::On),
+            cache_type_k: Some(KvCacheType::Q8_0),
+            cache_type_v: Some(KvCacheType::Q4_0),
+            threads: Some(8),
+            threads_batch: Some(16),
+            batch_size: Some(2048),
+            ubatch_size: Some(512),
+            mlock: true,
+            no_mmap: true,
+            rope_scaling: Some(RopeScaling::Yarn),
+            rope_scale: Some(4.0),
+            rope_freq_base: Some(1000000.0),
+            yarn_orig_ctx: Some(32768),
+            tensor_split: vec![3.0, 1.0],
+            main_gpu: Some(1),
+            draft_model: Some("/models/draft.gguf".to_string()),
+            draft_n_gpu_layers: Some(99),
+            draft_max: Some(16),
+            draft_min: Some(4),
+            lora: vec![
+                LoraAdapter {
+                    path: "/loras/style.gguf".to_string(),
+                    scale: None,
+                },
+                LoraAdapter {
+                    path: "/loras/tone.gguf".to_string(),
+                    scale: Some(0.5),
+                },
+            ],
+            api_key: Some("secret".to_string()),
+            extra_args: vec!["--no-webui".to_string()],
+            ..Default::default()
+        },
+        ..common::sample_llama_config()
+    };
+
+    assert_eq!(
+        args_for(&config),
+        vec![
+            "-m",
+            "/models/test.gguf",
+            "--port",
+            "8085",
+            "-c",
+            "8192",
+            "-np",
+            "1",
+            "-ngl",
+            "99",
+            "--jinja",
+            "--flash-attn",
+            "on",
+            "--cache-type-k",
+            "q8_0",
+            "--cache-type-v",
+            "q4_0",
+            "--threads",
+            "8",
+            "--threads-batch",
+            "16",
+            "--batch-size",
+            "2048",
+            "--ubatch-size",
+            "512",
+            "--rope-scaling",
+            "yarn",
+            "--rope-scale",
+            "4",
+            "--rope-freq-base",
+            "1000000",
+            "--yarn-orig-ctx",
+            "32768",
+            "--tensor-split",
+            "3,1",
+            "--main-gpu",
+            "1",
+            "--model-draft",
+            "/models/draft.gguf",
+            "--gpu-layers-draft",
+            "99",
+            "--draft-max",
+            "16",
+            "--draft-min",
+            "4",
+            "--api-key",
+            "secret",
+            "--mlock",
+            "--no-mmap",
+            "--lora",
+            "/loras/style.gguf",
+            "--lora-scaled",
+            "/loras/tone.gguf",
+            "0.5",
+            "--no-webui"
+        ]
+    );
+    assert!(config.launch_params.validate().is_ok());
+}
@@ -0,0 +1,116 @@
+use crate::common;
+
+use llama_desktop_lib::infrastructure::llama::logs::ServerLogs;
+use llama_desktop_lib::infrastructure::llama::server::LlamaServer;
+use llama_desktop_lib::models::{LlamaServerError, LogStream, ModelId};
+use std::sync::Arc;
+
+fn model() -> ModelId {
+    ModelId("/models/test.gguf".to_string())
+}
+
+#[test]
+fn test_logs_keep_only_last_lines() {
+    let logs = ServerLogs::new(3);
+    for i in 0..5 {
+        logs.push(&model(), LogStream::Stdout, &format!("line {}\n", i));
+    }
+
+    let lines: Vec<String> = logs
+        .tail(&model(), None)
+        .into_iter()
+        .map(|l| l.line)
+        .collect();
+    assert_eq!(lines, vec!["line 2", "line 3", "line 4"]);
+}
+
+#[test]
+fn test_logs_tail_limit_and_isolation() {
+    let logs = ServerLogs::new(10);
+    let other = ModelId("/models/other.gguf".to_string());
+    logs.push(&model(), LogStream::Stdout, "a");
+    logs.push(&model(), LogStream::Stderr, "b");
+    logs.push(&other, LogStream::Stdout, "c");
+
+    let tail = logs.tail(&model(), Some(1));
+    assert_eq!(tail.len(), 1);
+    assert_eq!(tail[0].line, "b");
+    assert_eq!(tail[0].stream, LogStream::Stderr);
+    assert_eq!(logs.tail(&other, None).len(), 1);
+
+    logs.clear(&model());
+    assert!(!logs.contains(&model()));
+    assert!(logs.tail(&model(), None).is_empty());
+}
+
+#[tokio::test]
+async fn test_logs_broadcast_to_subscribers() {
+    let logs = ServerLogs::default();
+    let mut receiver = logs.subscribe();
+
+    logs.push(&model(), LogStream::Stdout, "loading model\n");
+
+    let line = receiver.recv().await.unwrap();
+    assert_eq!(line.model_id, model());
+    assert_eq!(line.line, "loading model");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_pipe_output_captures_both_streams() {
+    let logs = Arc::new(ServerLogs::default());
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg("echo out; echo err >&2")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let readers = LlamaServer::test_pipe_output(&mut child, &model(), &logs);
+    for reader in readers {
+        reader.await.unwrap();
+    }
+    child.wait().await.unwrap();
+
+    let tail = logs.tail(&model(), None);
+    assert!(tail
+        .iter()
+        .any(|l| l.line == "out" && l.stream == LogStream::Stdout));
+    assert!(tail
+        .iter()
+        .any(|l| l.line == "err" && l.stream == LogStream::Stderr));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_spawn_early_exit_attaches_log_tail() {
+    let dir = common::temp_dir();
+    let config = common::fake_llama_server_config(
+        dir.path(),
+        "echo 'llama_model_load: error loading model: unknown architecture' >&2\nexit 1\n",
+    );
+    let logs = Arc::new(ServerLogs::default());
+
+    let err = LlamaServer::spawn(
+        model(),
+        None,
+        config,
+        reqwest::Client::new(),
+        logs.clone(),
+        None,
+    )
+    .await
+    .unwrap_err();
+
+    match err {
+        LlamaServerError::ExitedEarly { log_tail, .. } => {
+            assert_eq!(
+                log_tail,
+                vec!["llama_model_load: error loading model: unknown architecture"]
+            );
+        }
+        other => panic!("expected ExitedEarly, got {:?}", other),
+    }
+    assert_eq!(logs.tail(&model(), None).len(), 1);
+}
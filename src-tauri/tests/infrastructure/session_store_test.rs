@@ -0,0 +1,132 @@
+use crate::common;
+
+use llama_desktop_lib::infrastructure::session_store::{
+    session_file_path, FileSessionStore, InMemorySessionStore, SessionStore,
+};
+
+#[test]
+fn test_file_store_load_missing_session() {
+    let dir = common::temp_dir();
+    let store = FileSessionStore::new(dir.path().to_path_buf());
+
+    assert!(store.load("missing").unwrap().is_none());
+}
+
+#[test]
+fn test_file_store_append_and_load() {
+    let dir = common::temp_dir();
+    let store = FileSessionStore::new(dir.path().to_path_buf());
+
+    store
+        .append("s1", &common::sample_chat_message("user", "Hello"))
+        .unwrap();
+    store
+        .append("s1", &common::sample_chat_message("assistant", "Hi"))
+        .unwrap();
+
+    let history = store.load("s1").unwrap().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].content, "Hello");
+    assert_eq!(history[1].role, "assistant");
+}
+
+#[test]
+fn test_file_store_replace_overwrites_history() {
+    let dir = common::temp_dir();
+    let store = FileSessionStore::new(dir.path().to_path_buf());
+
+    store
+        .append("s1", &common::sample_chat_message("user", "Old"))
+        .unwrap();
+    store
+        .replace("s1", &[common::sample_chat_message("user", "New")])
+        .unwrap();
+
+    let history = store.load("s1").unwrap().unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].content, "New");
+}
+
+#[test]
+fn test_file_store_delete() {
+    let dir = common::temp_dir();
+    let store = FileSessionStore::new(dir.path().to_path_buf());
+
+    store
+        .append("s1", &common::sample_chat_message("user", "Hello"))
+        .unwrap();
+    store.delete("s1").unwrap();
+
+    assert!(store.load("s1").unwrap().is_none());
+    assert!(store.delete("s1").is_ok());
+}
+
+#[test]
+fn test_file_store_skips_torn_line() {
+    let dir = common::temp_dir();
+    let store = FileSessionStore::new(dir.path().to_path_buf());
+
+    store
+        .append("s1", &common::sample_chat_message("user", "Hello"))
+        .unwrap();
+    let path = store.session_path("s1");
+    let mut content = std::fs::read_to_string(&path).unwrap();
+    content.push_str("{\"role\":\"assis");
+    std::fs::write(&path, content).unwrap();
+
+    let history = store.load("s1").unwrap().unwrap();
+    assert_eq!(history.len(), 1);
+}
+
+#[test]
+fn test_session_file_path_is_sanitized() {
+    let dir = common::temp_dir();
+    let path = session_file_path(dir.path(), "../../etc/passwd");
+
+    assert_eq!(path.parent().unwrap(), dir.path());
+    assert_eq!(
+        path.file_name().unwrap(),
+        "%2E%2E%2F%2E%2E%2Fetc%2Fpasswd.jsonl"
+    );
+}
+
+#[test]
+fn test_session_file_path_ignores_filesystem_case() {
+    let dir = common::temp_dir();
+    let upper = session_file_path(dir.path(), "Abc");
+    let lower = session_file_path(dir.path(), "abc");
+
+    let upper_name = upper.file_name().unwrap().to_string_lossy().to_lowercase();
+    let lower_name = lower.file_name().unwrap().to_string_lossy().to_lowercase();
+    assert_ne!(upper_name, lower_name);
+    assert_eq!(upper.file_name().unwrap(), "%41bc.jsonl");
+}
+
+#[test]
+fn test_session_file_path_keeps_distinct_ids_apart() {
+    let dir = common::temp_dir();
+    let ids = ["a.b", "a_b", "a%2Eb", "", "%", "sessão"];
+    let paths: std::collections::HashSet<_> = ids
+        .iter()
+        .map(|id| session_file_path(dir.path(), id))
+        .collect();
+
+    assert_eq!(paths.len(), ids.len());
+    assert_eq!(
+        session_file_path(dir.path(), "chat-42_x"),
+        dir.path().join("chat-42_x.jsonl")
+    );
+}
+
+#[test]
+fn test_in_memory_store_roundtrip() {
+    let store = InMemorySessionStore::new();
+
+    store
+        .append("s1", &common::sample_chat_message("user", "Hello"))
+        .unwrap();
+    assert_eq!(store.load("s1").unwrap().unwrap().len(), 1);
+
+    store.delete("s1").unwrap();
+    assert!(store.load("s1").unwrap().is_none());
+}
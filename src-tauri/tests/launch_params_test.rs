@@ -0,0 +1,185 @@
+use llama_desktop_lib::models::*;
+
+fn errors_for(params: LaunchParams) -> Vec<String> {
+    params.validate().unwrap_err()
+}
+
+#[test]
+fn test_default_launch_params_are_valid() {
+    assert!(LaunchParams::default().validate().is_ok());
+}
+
+#[test]
+fn test_missing_fields_and_version_use_defaults() {
+    let params: LaunchParams = serde_json::from_str(r#"{"threads": 4}"#).unwrap();
+
+    assert_eq!(params.version, LAUNCH_PARAMS_VERSION);
+    assert!(params.jinja);
+    assert_eq!(params.threads, Some(4));
+}
+
+#[test]
+fn test_config_without_launch_params_still_deserializes() {
+    let json = r#"{
+        "llama_cpp_path": "llama-server",
+        "model_path": "/models/test.gguf",
+        "port": 8080,
+        "ctx_size": 2048,
+        "parallel": 1,
+        "n_gpu_layers": 0
+    }"#;
+    let config: LlamaCppConfig = serde_json::from_str(json).unwrap();
+
+    assert_eq!(config.launch_params, LaunchParams::default());
+}
+
+#[test]
+fn test_launch_params_round_trip_uses_snake_case_values() {
+    let params = LaunchParams {
+        flash_attn: Some(FlashAttention::Auto),
+        cache_type_k: Some(KvCacheType::Iq4Nl),
+        rope_scaling: Some(RopeScaling::Linear),
+        ..Default::default()
+    };
+    let json = serde_json::to_value(&params).unwrap();
+
+    assert_eq!(json["flash_attn"], "auto");
+    assert_eq!(json["cache_type_k"], "iq4_nl");
+    assert_eq!(json["rope_scaling"], "linear");
+    assert_eq!(
+        serde_json::from_value::<LaunchParams>(json).unwrap(),
+        params
+    );
+}
+
+#[test]
+fn test_newer_version_is_rejected() {
+    let errors = errors_for(LaunchParams {
+        version: LAUNCH_PARAMS_VERSION + 1,
+        ..Default::default()
+    });
+    assert!(errors[0].contains("newer than supported"));
+}
+
+#[test]
+fn test_quantized_v_cache_requires_flash_attention() {
+    let params = LaunchParams {
+        cache_type_v: Some(KvCacheType::Q8_0),
+        ..Default::default()
+    };
+    assert_eq!(
+        errors_for(params.clone()),
+        vec!["A quantized V cache requires flash_attn to be on"]
+    );
+
+    let params = LaunchParams {
+        flash_attn: Some(FlashAttention::On),
+        ..params
+    };
+    assert!(params.validate().is_ok());
+}
+
+#[test]
+fn test_batch_limits() {
+    let errors = errors_for(LaunchParams {
+        threads: Some(0),
+        batch_size: Some(256),
+        ubatch_size: Some(512),
+        ..Default::default()
+    });
+    assert_eq!(
+        errors,
+        vec![
+            "threads must be greater than 0",
+            "ubatch_size (512) cannot exceed batch_size (256)"
+        ]
+    );
+}
+
+#[test]
+fn test_rope_options_require_scaling_mode() {
+    let errors = errors_for(LaunchParams {
+        rope_scale: Some(2.0),
+        yarn_orig_ctx: Some(4096),
+        ..Default::default()
+    });
+    assert_eq!(
+        errors,
+        vec![
+            "rope_scale requires rope_scaling",
+            "yarn_orig_ctx requires rope_scaling = yarn"
+        ]
+    );
+}
+
+#[test]
+fn test_tensor_split_and_main_gpu() {
+    let errors = errors_for(LaunchParams {
+        tensor_split: vec![0.0, 0.0],
+        main_gpu: Some(2),
+        ..Default::default()
+    });
+    assert_eq!(errors.len(), 2);
+    assert!(errors[1].contains("main_gpu 2"));
+}
+
+#[test]
+fn test_draft_options_require_draft_model() {
+    let errors = errors_for(LaunchParams {
+        draft_max: Some(4),
+        draft_min: Some(8),
+        ..Default::default()
+    });
+    assert_eq!(
+        errors,
+        vec![
+            "Draft options require draft_model",
+            "draft_min (8) cannot exceed draft_max (4)"
+        ]
+    );
+}
+
+#[test]
+fn test_empty_api_key_and_lora_path_rejected() {
+    let errors = errors_for(LaunchParams {
+        api_key: Some(" ".to_string()),
+        lora: vec![LoraAdapter {
+            path: String::new(),
+            scale: None,
+        }],
+        ..Default::default()
+    });
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn test_extra_args_cannot_override_managed_flags() {
+    let errors = errors_for(LaunchParams {
+        threads: Some(4),
+        extra_args: vec![
+            "--port=9000".to_string(),
+            "--threads".to_string(),
+            "8".to_string(),
+            "--no-webui".to_string(),
+        ],
+        ..Default::default()
+    });
+    assert_eq!(
+        errors,
+        vec![
+            "extra_args cannot set --port; it is managed by the launcher",
+            "extra_args cannot set --threads; it is managed by the launcher"
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_launch_params_error_serializes_errors() {
+    let err = LlamaServerError::InvalidLaunchParams {
+        errors: vec!["threads must be greater than 0".to_string()],
+    };
+    let json = serde_json::to_value(&err).unwrap();
+
+    assert_eq!(json["kind"], "invalid_launch_params");
+    assert_eq!(json["errors"][0], "threads must be greater than 0");
+}
@@ -1,6 +1,7 @@
 // Test modules organized by layer
 mod common;
 mod models_test;
+mod launch_params_test;
 mod utils_test;
 mod parsing_test;
 
@@ -58,6 +58,140 @@ fn test_mcp_server_config_serialization() {
     assert_eq!(server.enabled, deserialized.enabled);
 }
 
+#[test]
+fn test_tool_approval_override_beats_server_default() {
+    let json = r#"{
+        "id": "files", "name": "Files", "enabled": true, "transport": "stdio",
+        "toolApproval": "ask",
+        "toolApprovalOverrides": { "read_file": "allow", "delete_file": "deny" }
+    }"#;
+    let server: McpServerConfig = serde_json::from_str(json).unwrap();
+
+    assert_eq!(server.approval_for("read_file"), ToolApproval::Allow);
+    assert_eq!(server.approval_for("delete_file"), ToolApproval::Deny);
+    assert_eq!(server.approval_for("write_file"), ToolApproval::Ask);
+    assert_eq!(
+        common::sample_mcp_server("legacy").approval_for("anything"),
+        ToolApproval::Allow
+    );
+}
+
+#[test]
+fn test_tool_approval_decision_deserialization() {
+    let edit: ToolApprovalDecision =
+        serde_json::from_str(r#"{"action": "edit", "arguments": {"q": "x"}}"#).unwrap();
+    assert_eq!(
+        edit,
+        ToolApprovalDecision::Edit { arguments: serde_json::json!({"q": "x"}) }
+    );
+    let reject: ToolApprovalDecision = serde_json::from_str(r#"{"action": "reject"}"#).unwrap();
+    assert_eq!(reject, ToolApprovalDecision::Reject { reason: None });
+}
+
+#[test]
+fn test_tool_loop_policy_defaults_and_validation() {
+    let config: AppConfig = serde_json::from_str(r#"{"toolLoopPolicy": {"maxIterations": 10}}"#).unwrap();
+    assert_eq!(config.tool_loop_policy.max_iterations, 10);
+    assert_eq!(config.tool_loop_policy.max_calls_per_tool, 2);
+    assert_eq!(config.tool_loop_policy.result_token_budget, 512);
+    assert_eq!(AppConfig::default().tool_loop_policy, ToolLoopPolicy::default());
+    assert!(config.tool_loop_policy.validate().is_ok());
+
+    let policy = ToolLoopPolicy { max_temperature: Some(0.5), ..Default::default() };
+    assert_eq!(policy.tool_temperature(0.9), 0.5);
+    assert_eq!(policy.tool_temperature(0.2), 0.2);
+    let uncapped = ToolLoopPolicy { max_temperature: None, ..Default::default() };
+    assert_eq!(uncapped.tool_temperature(0.9), 0.9);
+
+    for invalid in [
+        ToolLoopPolicy { max_calls_per_tool: 0, ..Default::default() },
+        ToolLoopPolicy { result_token_budget: 0, ..Default::default() },
+        ToolLoopPolicy { max_temperature: Some(-1.0), ..Default::default() },
+        ToolLoopPolicy { top_p: 0.0, ..Default::default() },
+        ToolLoopPolicy { top_k: -1, ..Default::default() },
+    ] {
+        assert!(invalid.validate().is_err(), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn test_user_sampling_overrides_modelfile_params() {
+    let mut model = common::create_test_model_info();
+    model.stop_sequences = vec!["<|end|>".to_string()];
+    let mut other = serde_json::Map::new();
+    other.insert("mirostat".to_string(), serde_json::json!(2));
+    other.insert("presence_penalty".to_string(), serde_json::json!(0.5));
+    model.params = Some(ModelParams {
+        temperature: Some(0.6),
+        top_k: Some(20),
+        other,
+        ..ModelParams::default()
+    });
+    let saved = SamplingParams {
+        temperature: Some(1.0),
+        stop: Some(vec!["###".to_string()]),
+        ..Default::default()
+    };
+
+    let defaults = ModelChatDefaults::from_model(&model, Some(&saved)).unwrap();
+
+    assert_eq!(defaults.sampling.temperature, Some(1.0));
+    assert_eq!(defaults.sampling.top_k, Some(20));
+    assert_eq!(defaults.sampling.mirostat, Some(2));
+    assert_eq!(defaults.sampling.presence_penalty, Some(0.5));
+    assert_eq!(defaults.sampling.stop, Some(vec!["###".to_string()]));
+}
+
+#[test]
+fn test_sampling_params_validation_reports_every_problem() {
+    let params: SamplingParams = serde_json::from_str(
+        r#"{"top_p": 1.5, "mirostat": 3, "dry_base": 0.5, "logit_bias": {"15043": -1.0}}"#,
+    )
+    .unwrap();
+
+    let err = params.validate().unwrap_err();
+    assert!(err.contains("top_p"), "{}", err);
+    assert!(err.contains("mirostat"), "{}", err);
+    assert!(err.contains("dry_base"), "{}", err);
+    assert!(!err.contains("logit_bias"), "{}", err);
+    assert!(SamplingParams::default().validate().is_ok());
+    assert!(SamplingParams { logit_bias: Some(serde_json::json!(1)), ..Default::default() }
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn test_default_tool_loop_policy_matches_previous_limits() {
+    // The limits the tool loop hard-coded before they became configurable.
+    let policy = ToolLoopPolicy::default();
+    assert_eq!(policy.max_iterations, 3);
+    assert_eq!(policy.result_token_budget, 512);
+    assert_eq!(policy.max_temperature, Some(0.5));
+    assert_eq!(policy.tool_temperature(0.9), 0.5);
+
+    let tool = policy.tool_sampling(&SamplingParams { temperature: Some(0.9), ..Default::default() });
+    assert_eq!(tool.temperature, Some(0.5));
+    assert_eq!(tool.top_p, Some(0.95));
+    assert_eq!(tool.top_k, Some(40));
+}
+
+#[test]
+fn test_tool_sampling_keeps_chat_samplers() {
+    let policy = ToolLoopPolicy { max_temperature: Some(0.5), ..Default::default() };
+    let chat = SamplingParams {
+        temperature: Some(0.9),
+        top_p: Some(0.5),
+        seed: Some(42),
+        ..Default::default()
+    };
+
+    let tool = policy.tool_sampling(&chat);
+    assert_eq!(tool.temperature, Some(0.5));
+    assert_eq!(tool.top_p, Some(0.95));
+    assert_eq!(tool.top_k, Some(40));
+    assert_eq!(tool.seed, Some(42));
+}
+
 #[test]
 fn test_mcp_config_default() {
     let config = McpConfig::default();
@@ -128,3 +262,55 @@ fn test_app_config_serialization() {
     assert_eq!(config.models_directory, deserialized.models_directory);
     assert_eq!(config.llama_directory, deserialized.llama_directory);
 }
+
+#[test]
+fn test_model_chat_defaults_fill_unset_request_fields() {
+    let mut model = common::create_test_model_info();
+    assert!(ModelChatDefaults::from_model(&model, None).is_none());
+    model.system_prompt = Some("Be terse.".to_string());
+    model.stop_sequences = vec!["<|end|>".to_string()];
+    model.params = Some(ModelParams {
+        repeat_penalty: Some(1.1),
+        ..ModelParams::default()
+    });
+    let defaults = ModelChatDefaults::from_model(&model, None).unwrap();
+
+    let mut request = ChatRequest {
+        model: "m".to_string(),
+        session_id: None,
+        messages: vec![common::sample_chat_message("user", "Hi")],
+        temperature: 0.7,
+        top_p: 0.9,
+        top_k: 40,
+        max_tokens: 128,
+        reasoning_format: None,
+        reasoning_budget: None,
+        reasoning_budget_message: None,
+        thinking_forced_open: None,
+        chat_template_kwargs: None,
+        tools: None,
+        tool_choice: None,
+        stop: None,
+        min_p: None,
+        repeat_penalty: None,
+        seed: Some(7),
+        stream: false,
+        ..Default::default()
+    };
+    defaults.apply(&mut request);
+
+    assert_eq!(request.messages[0].role, "system");
+    assert_eq!(request.messages[0].content, "Be terse.");
+    assert_eq!(request.stop, Some(vec!["<|end|>".to_string()]));
+    assert_eq!(request.repeat_penalty, Some(1.1));
+    assert_eq!(request.seed, Some(7));
+
+    // A conversation that already has a system message keeps it.
+    request.messages = vec![
+        common::sample_chat_message("system", "Custom"),
+        common::sample_chat_message("user", "Hi"),
+    ];
+    defaults.apply(&mut request);
+    assert_eq!(request.messages.len(), 2);
+    assert_eq!(request.messages[0].content, "Custom");
+}
@@ -0,0 +1,300 @@
+use crate::common::{create_test_model_info, temp_dir};
+
+use futures::FutureExt;
+use llama_desktop_lib::models::{DownloadJob, DownloadProgress, DownloadStatus};
+use llama_desktop_lib::services::downloads::{DownloadManager, DownloadRunner};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Runner that reports `partial` (when set), then waits for `gate` when the
+/// reference starts with "slow", and fails for references starting with "bad".
+fn fake_runner(
+    gate: Arc<Notify>,
+    calls: Arc<AtomicUsize>,
+    partial: Option<PathBuf>,
+) -> DownloadRunner {
+    Arc::new(move |job: DownloadJob, progress| {
+        let gate = gate.clone();
+        let calls = calls.clone();
+        let partial = partial.clone();
+        async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            progress(DownloadProgress {
+                blob: "sha256:abc".to_string(),
+                blob_downloaded: 10,
+                blob_total: 100,
+                downloaded: 10,
+                total: 100,
+                partial_path: partial.map(|p| p.to_string_lossy().to_string()),
+            });
+            if job.model_reference.starts_with("slow") {
+                gate.notified().await;
+            }
+            if job.model_reference.starts_with("bad") {
+                return Err("registry unreachable".to_string());
+            }
+            Ok(create_test_model_info())
+        }
+        .boxed()
+    })
+}
+
+async fn wait_for_status(
+    manager: &DownloadManager,
+    job_id: &str,
+    status: DownloadStatus,
+) -> DownloadJob {
+    for _ in 0..200 {
+        if let Some(job) = manager.get(job_id) {
+            if job.status == status {
+                return job;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!(
+        "job {} never reached {:?}: {:?}",
+        job_id,
+        status,
+        manager.get(job_id)
+    );
+}
+
+async fn wait_for_calls(calls: &AtomicUsize, expected: usize) {
+    for _ in 0..200 {
+        if calls.load(Ordering::SeqCst) >= expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!(
+        "runner was called {} times, expected {}",
+        calls.load(Ordering::SeqCst),
+        expected
+    );
+}
+
+#[tokio::test]
+async fn test_download_job_completes_and_reports_progress() {
+    let manager = DownloadManager::new(None, 1);
+    let mut events = manager.subscribe_events();
+    manager.start(fake_runner(
+        Arc::new(Notify::new()),
+        Arc::new(AtomicUsize::new(0)),
+        None,
+    ));
+
+    let job = manager
+        .enqueue("llama3".to_string(), "/models".to_string())
+        .unwrap();
+    let done = wait_for_status(&manager, &job.id, DownloadStatus::Completed).await;
+
+    assert!(done.model.is_some());
+    assert_eq!(done.total_bytes, 100);
+    assert_eq!(done.downloaded_bytes, 100);
+    let mut seen = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        seen.push((event.status, event.downloaded_bytes));
+    }
+    assert!(
+        seen.contains(&(DownloadStatus::Downloading, 10)),
+        "{:?}",
+        seen
+    );
+    assert_eq!(seen.last().unwrap().0, DownloadStatus::Completed);
+}
+
+#[tokio::test]
+async fn test_download_jobs_run_one_at_a_time() {
+    let gate = Arc::new(Notify::new());
+    let manager = DownloadManager::new(None, 1);
+    manager.start(fake_runner(
+        gate.clone(),
+        Arc::new(AtomicUsize::new(0)),
+        None,
+    ));
+
+    let first = manager
+        .enqueue("slow-model".to_string(), "/models".to_string())
+        .unwrap();
+    let second = manager
+        .enqueue("fast-model".to_string(), "/models".to_string())
+        .unwrap();
+    wait_for_status(&manager, &first.id, DownloadStatus::Downloading).await;
+    assert_eq!(
+        manager.get(&second.id).unwrap().status,
+        DownloadStatus::Queued
+    );
+
+    gate.notify_one();
+    wait_for_status(&manager, &first.id, DownloadStatus::Completed).await;
+    wait_for_status(&manager, &second.id, DownloadStatus::Completed).await;
+}
+
+#[tokio::test]
+async fn test_pause_keeps_partial_and_resume_restarts() {
+    let dir = temp_dir();
+    let partial = dir.path().join("sha256-abc.partial");
+    std::fs::write(&partial, b"half").unwrap();
+    let gate = Arc::new(Notify::new());
+    let calls = Arc::new(AtomicUsize::new(0));
+    let manager = DownloadManager::new(None, 1);
+    manager.start(fake_runner(
+        gate.clone(),
+        calls.clone(),
+        Some(partial.clone()),
+    ));
+
+    let job = manager
+        .enqueue("slow-model".to_string(), "/models".to_string())
+        .unwrap();
+    wait_for_calls(&calls, 1).await;
+    let paused = manager.pause(&job.id).unwrap();
+
+    assert_eq!(paused.status, DownloadStatus::Paused);
+    assert!(partial.exists());
+    assert!(manager.pause(&job.id).is_err());
+
+    manager.resume(&job.id).unwrap();
+    // The resumed run starts only once the paused one is gone, so the gate
+    // can only wake the resumed run.
+    wait_for_calls(&calls, 2).await;
+    gate.notify_one();
+    wait_for_status(&manager, &job.id, DownloadStatus::Completed).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_cancel_removes_partial_and_chunk_files() {
+    let dir = temp_dir();
+    let partial = dir.path().join("sha256-abc.partial");
+    std::fs::write(&partial, b"half").unwrap();
+    std::fs::write(dir.path().join("sha256-abc.partial.0"), b"chunk").unwrap();
+    std::fs::write(dir.path().join("sha256-other"), b"keep").unwrap();
+    let manager = DownloadManager::new(None, 1);
+    manager.start(fake_runner(
+        Arc::new(Notify::new()),
+        Arc::new(AtomicUsize::new(0)),
+        Some(partial.clone()),
+    ));
+
+    let job = manager
+        .enqueue("slow-model".to_string(), "/models".to_string())
+        .unwrap();
+    wait_for_status(&manager, &job.id, DownloadStatus::Downloading).await;
+    // Give the runner time to report its partial file.
+    for _ in 0..100 {
+        if !manager.get(&job.id).unwrap().partial_files.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let cancelled = manager.cancel(&job.id).unwrap();
+
+    assert_eq!(cancelled.status, DownloadStatus::Cancelled);
+    assert!(!partial.exists());
+    assert!(!dir.path().join("sha256-abc.partial.0").exists());
+    assert!(dir.path().join("sha256-other").exists());
+    assert!(manager.resume(&job.id).is_err());
+}
+
+#[tokio::test]
+async fn test_failed_job_records_error_and_can_be_retried() {
+    let manager = DownloadManager::new(None, 1);
+    let calls = Arc::new(AtomicUsize::new(0));
+    manager.start(fake_runner(Arc::new(Notify::new()), calls.clone(), None));
+
+    let job = manager
+        .enqueue("bad-model".to_string(), "/models".to_string())
+        .unwrap();
+    let failed = wait_for_status(&manager, &job.id, DownloadStatus::Failed).await;
+    assert_eq!(failed.error.as_deref(), Some("registry unreachable"));
+
+    let retried = manager.resume(&job.id).unwrap();
+    assert_eq!(retried.error, None);
+    wait_for_status(&manager, &job.id, DownloadStatus::Failed).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_enqueue_rejects_duplicate_active_reference() {
+    let manager = DownloadManager::new(None, 1);
+
+    manager
+        .enqueue("llama3".to_string(), "/models".to_string())
+        .unwrap();
+    let err = manager
+        .enqueue(" llama3 ".to_string(), "/models".to_string())
+        .unwrap_err();
+
+    assert!(err.contains("already being downloaded"), "{}", err);
+    assert!(manager
+        .enqueue("llama3".to_string(), "/other".to_string())
+        .is_ok());
+    assert!(manager
+        .enqueue("  ".to_string(), "/models".to_string())
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_queue_is_persisted_and_resumed_on_next_launch() {
+    let dir = temp_dir();
+    let store = dir.path().join("downloads.json");
+    {
+        let manager = DownloadManager::new(Some(store.clone()), 1);
+        let queued = manager
+            .enqueue("llama3".to_string(), "/models".to_string())
+            .unwrap();
+        let paused = manager
+            .enqueue("qwen".to_string(), "/models".to_string())
+            .unwrap();
+        manager.pause(&paused.id).unwrap();
+        assert_eq!(
+            manager.get(&queued.id).unwrap().status,
+            DownloadStatus::Queued
+        );
+    }
+
+    let manager = DownloadManager::new(Some(store.clone()), 1);
+    let jobs = manager.list();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0].status, DownloadStatus::Queued);
+    assert_eq!(jobs[1].status, DownloadStatus::Paused);
+
+    manager.start(fake_runner(
+        Arc::new(Notify::new()),
+        Arc::new(AtomicUsize::new(0)),
+        None,
+    ));
+    wait_for_status(&manager, &jobs[0].id, DownloadStatus::Completed).await;
+    assert_eq!(
+        manager.get(&jobs[1].id).unwrap().status,
+        DownloadStatus::Paused
+    );
+
+    // Completed jobs drop out of the persisted queue.
+    let persisted: Vec<DownloadJob> =
+        serde_json::from_str(&std::fs::read_to_string(&store).unwrap()).unwrap();
+    assert_eq!(persisted.len(), 1);
+    assert_eq!(persisted[0].id, jobs[1].id);
+}
+
+#[tokio::test]
+async fn test_interrupted_download_is_requeued_on_load() {
+    let dir = temp_dir();
+    let store = dir.path().join("downloads.json");
+    let mut job = DownloadJob::new(
+        "job-1".to_string(),
+        "llama3".to_string(),
+        "/models".to_string(),
+    );
+    job.status = DownloadStatus::Downloading;
+    std::fs::write(&store, serde_json::to_string(&vec![job]).unwrap()).unwrap();
+
+    let manager = DownloadManager::new(Some(store), 1);
+
+    assert_eq!(manager.get("job-1").unwrap().status, DownloadStatus::Queued);
+}
@@ -0,0 +1,159 @@
+use crate::common::temp_dir;
+
+use llama_desktop_lib::services::go_template::go_template_to_jinja;
+use llama_desktop_lib::services::templates::ensure_converted_go_template_at_dir;
+use minijinja::{context, Environment, Value};
+use serde_json::json;
+use std::path::PathBuf;
+
+const FAMILIES: &[&str] = &["llama3", "qwen2.5", "mistral", "gemma2", "phi3"];
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/go_templates")
+        .join(name)
+}
+
+fn convert(family: &str) -> String {
+    let source = std::fs::read_to_string(fixture(&format!("{}.gotmpl", family))).unwrap();
+    go_template_to_jinja(&source).unwrap_or_else(|e| panic!("{}: {}", family, e))
+}
+
+/// Renders with the whitespace options llama.cpp and transformers use.
+fn render(jinja: &str, messages: serde_json::Value, tools: Option<serde_json::Value>) -> String {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.add_template("chat", jinja).unwrap();
+    let tools = tools.map(Value::from_serialize);
+    env.get_template("chat")
+        .unwrap()
+        .render(context! { messages => messages, tools => tools, add_generation_prompt => true })
+        .unwrap()
+}
+
+/// Set `UPDATE_GOLDEN=1` to rewrite the expected `.jinja` files.
+#[test]
+fn test_converted_templates_match_golden_files() {
+    for family in FAMILIES {
+        let jinja = convert(family);
+        let golden = fixture(&format!("{}.jinja", family));
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&golden, &jinja).unwrap();
+        }
+        let expected = std::fs::read_to_string(&golden).unwrap();
+        assert_eq!(jinja, expected, "golden mismatch for {}", family);
+    }
+}
+
+#[test]
+fn test_legacy_template_renders_one_turn_per_reply() {
+    let prompt = render(
+        &convert("llama3"),
+        json!([
+            {"role": "system", "content": "Be brief."},
+            {"role": "user", "content": "Hi"},
+            {"role": "assistant", "content": "Hello"},
+            {"role": "user", "content": "Bye"}
+        ]),
+        None,
+    );
+
+    assert_eq!(
+        prompt,
+        "<|start_header_id|>system<|end_header_id|>\n\nBe brief.<|eot_id|>\
+         <|start_header_id|>user<|end_header_id|>\n\nHi<|eot_id|>\
+         <|start_header_id|>assistant<|end_header_id|>\n\nHello<|eot_id|>\
+         <|start_header_id|>user<|end_header_id|>\n\nBye<|eot_id|>\
+         <|start_header_id|>assistant<|end_header_id|>\n\n"
+    );
+}
+
+#[test]
+fn test_messages_templates_render_like_ollama() {
+    let conversation = json!([
+        {"role": "system", "content": "Be brief."},
+        {"role": "user", "content": "Hi"}
+    ]);
+
+    assert_eq!(
+        render(&convert("qwen2.5"), conversation.clone(), None),
+        "<|im_start|>system\nBe brief.<|im_end|>\n<|im_start|>user\nHi<|im_end|>\n<|im_start|>assistant\n"
+    );
+    assert_eq!(
+        render(&convert("phi3"), conversation.clone(), None),
+        "<|system|>\nBe brief.<|end|>\n<|user|>\nHi<|end|>\n<|assistant|>\n"
+    );
+    assert_eq!(
+        render(&convert("mistral"), conversation, None),
+        "[INST] Be brief.\n\nHi[/INST]"
+    );
+    assert_eq!(
+        render(
+            &convert("gemma2"),
+            json!([
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": "Hello"},
+                {"role": "user", "content": "Bye"}
+            ]),
+            None,
+        ),
+        "<start_of_turn>user\nHi<end_of_turn>\n<start_of_turn>model\nHello<end_of_turn>\n\
+         <start_of_turn>user\nBye<end_of_turn>\n<start_of_turn>model\n"
+    );
+}
+
+#[test]
+fn test_tool_calls_and_results_are_rendered() {
+    let tools = json!([{
+        "type": "function",
+        "function": {"name": "get_weather", "parameters": {"type": "object"}}
+    }]);
+    let prompt = render(
+        &convert("qwen2.5"),
+        json!([
+            {"role": "user", "content": "Weather in Paris?"},
+            {"role": "assistant", "content": "", "tool_calls": [
+                {"type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}}
+            ]},
+            {"role": "tool", "content": "sunny"}
+        ]),
+        Some(tools),
+    );
+
+    assert!(prompt.starts_with("<|im_start|>system\n\n# Tools\n"));
+    assert!(prompt.contains("<tools>\n{\"type\": \"function\", \"function\": {"));
+    assert!(prompt.contains("\"name\":\"get_weather\""));
+    assert!(prompt.ends_with(
+        "<|im_start|>assistant\n<tool_call>\n{\"name\": \"get_weather\", \"arguments\": {\"city\":\"Paris\"}}\n\
+         </tool_call><|im_end|>\n<|im_start|>user\n<tool_response>\nsunny\n</tool_response><|im_end|>\n\
+         <|im_start|>assistant\n"
+    ));
+}
+
+#[test]
+fn test_unsupported_constructs_are_rejected() {
+    assert!(go_template_to_jinja("{{ template \"x\" . }}").is_err());
+    assert!(go_template_to_jinja("{{ if .System }}unterminated").is_err());
+    assert!(go_template_to_jinja("{{ .Unknown }}").is_err());
+    assert!(go_template_to_jinja("{{ printf \"%s\" .Prompt }}").is_err());
+}
+
+#[test]
+fn test_converted_template_is_cached_by_source_hash() {
+    let dir = temp_dir();
+    let source = std::fs::read_to_string(fixture("phi3.gotmpl")).unwrap();
+
+    let path = ensure_converted_go_template_at_dir(dir.path(), &source).unwrap();
+    assert!(path
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with("ollama_"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), convert("phi3"));
+    assert_eq!(
+        ensure_converted_go_template_at_dir(dir.path(), &source).unwrap(),
+        path
+    );
+    assert!(ensure_converted_go_template_at_dir(dir.path(), "{{ .Nope }}").is_err());
+}
@@ -0,0 +1,371 @@
+use crate::common;
+
+use llama_desktop_lib::infrastructure::llama::logs::ServerLogs;
+use llama_desktop_lib::infrastructure::llama::process::LlamaProcessManager;
+use llama_desktop_lib::infrastructure::metrics::MetricsProvider;
+use llama_desktop_lib::models::{
+    LlamaCppConfig, LlamaServerError, ModelEvent, ModelId, ModelInfo, ModelState, PortRange,
+    RestartPolicy, ServerMetrics,
+};
+use llama_desktop_lib::services::llama::actor::{ActorMessage, LlamaActor};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+struct NoMetrics;
+
+impl MetricsProvider for NoMetrics {
+    fn snapshot_for_pid(&self, _pid: u32) -> Option<ServerMetrics> {
+        None
+    }
+}
+
+fn new_actor(
+    registry: HashMap<ModelId, ModelInfo>,
+) -> (LlamaActor, mpsc::Sender<ActorMessage>) {
+    let (actor, tx, _events) = new_actor_with_events(registry);
+    (actor, tx)
+}
+
+fn new_actor_with_events(
+    registry: HashMap<ModelId, ModelInfo>,
+) -> (
+    LlamaActor,
+    mpsc::Sender<ActorMessage>,
+    broadcast::Receiver<ModelEvent>,
+) {
+    let (tx, rx) = mpsc::channel(16);
+    let (events, events_rx) = broadcast::channel(16);
+    let mut actor = LlamaActor::new(
+        rx,
+        tx.clone(),
+        registry,
+        Arc::new(LlamaProcessManager::new()),
+        Arc::new(NoMetrics),
+        Arc::new(ServerLogs::default()),
+        events,
+    );
+    actor.test_set_supervisor_interval(Duration::from_millis(20));
+    (actor, tx, events_rx)
+}
+
+fn config_for(model_path: &str, port: u16) -> LlamaCppConfig {
+    LlamaCppConfig {
+        model_path: model_path.to_string(),
+        port,
+        ..common::sample_llama_config()
+    }
+}
+
+async fn complete_start(tx: &mpsc::Sender<ActorMessage>, model_path: &str, port: u16) {
+    let (resp_tx, resp_rx) = oneshot::channel();
+    let child = common::spawn_sleep_process().unwrap();
+    tx.send(ActorMessage::InternalStartComplete {
+        model_id: ModelId(model_path.to_string()),
+        result: Ok((port, child)),
+        config: config_for(model_path, port),
+        respond_to: resp_tx,
+    })
+    .await
+    .unwrap();
+    resp_rx.await.unwrap().unwrap();
+}
+
+async fn stop(tx: &mpsc::Sender<ActorMessage>, model_id: &str) {
+    let (resp_tx, resp_rx) = oneshot::channel();
+    tx.send(ActorMessage::Stop {
+        model_id: ModelId(model_id.to_string()),
+        respond_to: resp_tx,
+    })
+    .await
+    .unwrap();
+    resp_rx.await.unwrap().unwrap();
+}
+
+#[test]
+fn test_allocate_port_skips_ports_of_other_models() {
+    let (mut actor, _tx) = new_actor(HashMap::new());
+    actor.test_set_state(
+        ModelId("/models/large.gguf".to_string()),
+        ModelState::Running {
+            port: 8080,
+            pid: 1,
+            config: Box::new(config_for("/models/large.gguf", 8080)),
+        },
+    );
+    actor.test_set_state(
+        ModelId("/models/medium.gguf".to_string()),
+        ModelState::Starting { port: 8081 },
+    );
+
+    let small = ModelId("/models/small.gguf".to_string());
+    assert_eq!(actor.test_allocate_port(&small, 8080, None).unwrap(), 8082);
+    assert_eq!(actor.test_allocate_port(&small, 9000, None).unwrap(), 9000);
+}
+
+#[test]
+fn test_allocate_port_probes_and_excludes_ports_of_other_models() {
+    let (mut actor, _tx) = new_actor(HashMap::new());
+    let busy = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let busy_port = busy.local_addr().unwrap().port();
+    let free_port = {
+        let free = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        free.local_addr().unwrap().port()
+    };
+    let range = PortRange {
+        start: free_port,
+        end: free_port,
+    };
+    let small = ModelId("/models/small.gguf".to_string());
+
+    assert_eq!(
+        actor
+            .test_allocate_port(&small, busy_port, Some(range))
+            .unwrap(),
+        free_port
+    );
+
+    // A starting model has not bound its port yet but still owns it.
+    actor.test_set_state(
+        ModelId("/models/medium.gguf".to_string()),
+        ModelState::Starting { port: free_port },
+    );
+    assert!(matches!(
+        actor.test_allocate_port(&small, busy_port, Some(range)),
+        Err(LlamaServerError::NoFreePort { .. })
+    ));
+}
+
+#[test]
+fn test_allocate_port_keeps_own_port() {
+    let (mut actor, _tx) = new_actor(HashMap::new());
+    let model = ModelId("/models/large.gguf".to_string());
+    actor.test_set_state(model.clone(), ModelState::Starting { port: 8080 });
+
+    assert_eq!(actor.test_allocate_port(&model, 8080, None).unwrap(), 8080);
+}
+
+#[tokio::test]
+async fn test_list_models_and_route_by_id() {
+    let mut info = common::create_test_model_info();
+    info.model_file_path = Some("/models/small.gguf".to_string());
+    let registry = HashMap::from([(ModelId(info.full_identifier.clone()), info)]);
+    let (mut actor, tx) = new_actor(registry);
+    tokio::spawn(async move { actor.run().await });
+
+    complete_start(&tx, "/models/large.gguf", 8080).await;
+    complete_start(&tx, "/models/small.gguf", 8081).await;
+
+    let (list_tx, list_rx) = oneshot::channel();
+    tx.send(ActorMessage::ListModels { respond_to: list_tx })
+        .await
+        .unwrap();
+    let models = list_rx.await.unwrap();
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0].port, 8080);
+    assert!(!models[0].is_default);
+    assert!(models[1].is_default);
+
+    // Library identifiers resolve to the running instance of that model file.
+    let (cfg_tx, cfg_rx) = oneshot::channel();
+    tx.send(ActorMessage::GetModelConfig {
+        model_id: ModelId("test:model:v1".to_string()),
+        respond_to: cfg_tx,
+    })
+    .await
+    .unwrap();
+    assert_eq!(cfg_rx.await.unwrap().unwrap().port, 8081);
+
+    stop(&tx, "/models/large.gguf").await;
+    stop(&tx, "/models/small.gguf").await;
+}
+
+#[tokio::test]
+async fn test_stopping_default_model_promotes_another() {
+    let (mut actor, tx) = new_actor(HashMap::new());
+    tokio::spawn(async move { actor.run().await });
+
+    complete_start(&tx, "/models/large.gguf", 8080).await;
+    complete_start(&tx, "/models/small.gguf", 8081).await;
+    stop(&tx, "/models/small.gguf").await;
+
+    let (cfg_tx, cfg_rx) = oneshot::channel();
+    tx.send(ActorMessage::GetConfig { respond_to: cfg_tx })
+        .await
+        .unwrap();
+    let config = cfg_rx.await.unwrap().unwrap();
+    assert_eq!(config.model_path, "/models/large.gguf");
+
+    stop(&tx, "/models/large.gguf").await;
+}
+
+/// Registers a process that exits on its own shortly after starting.
+#[cfg(unix)]
+async fn start_short_lived(tx: &mpsc::Sender<ActorMessage>, config: LlamaCppConfig) {
+    let (resp_tx, resp_rx) = oneshot::channel();
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg("sleep 0.1; exit 3")
+        .spawn()
+        .unwrap();
+    tx.send(ActorMessage::InternalStartComplete {
+        model_id: ModelId(config.model_path.clone()),
+        result: Ok((config.port, child)),
+        config,
+        respond_to: resp_tx,
+    })
+    .await
+    .unwrap();
+    resp_rx.await.unwrap().unwrap();
+}
+
+async fn next_event(events: &mut broadcast::Receiver<ModelEvent>) -> ModelEvent {
+    tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timed out waiting for model event")
+        .unwrap()
+}
+
+#[test]
+fn test_restart_policy_backoff_doubles_up_to_cap() {
+    let policy = RestartPolicy {
+        max_restarts: 5,
+        initial_backoff_ms: 500,
+        max_backoff_ms: 3_000,
+    };
+    assert_eq!(policy.backoff_ms(1), 500);
+    assert_eq!(policy.backoff_ms(2), 1_000);
+    assert_eq!(policy.backoff_ms(3), 2_000);
+    assert_eq!(policy.backoff_ms(4), 3_000);
+    assert_eq!(policy.backoff_ms(80), 3_000);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_supervisor_marks_exited_model_crashed() {
+    let (mut actor, tx, mut events) = new_actor_with_events(HashMap::new());
+    tokio::spawn(async move { actor.run().await });
+
+    start_short_lived(&tx, config_for("/models/small.gguf", 8080)).await;
+
+    match next_event(&mut events).await {
+        ModelEvent::Crashed {
+            model_id,
+            exit_status,
+            ..
+        } => {
+            assert_eq!(model_id.0, "/models/small.gguf");
+            assert!(exit_status.contains('3'), "{}", exit_status);
+        }
+        other => panic!("expected Crashed, got {:?}", other),
+    }
+
+    let (run_tx, run_rx) = oneshot::channel();
+    tx.send(ActorMessage::IsRunning {
+        model_id: Some(ModelId("/models/small.gguf".to_string())),
+        respond_to: run_tx,
+    })
+    .await
+    .unwrap();
+    assert!(!run_rx.await.unwrap());
+
+    let (chat_tx, chat_rx) = oneshot::channel();
+    tx.send(ActorMessage::CompleteChat {
+        model_id: ModelId("/models/small.gguf".to_string()),
+        request: serde_json::from_value(serde_json::json!({
+            "model": "/models/small.gguf",
+            "session_id": null,
+            "messages": [],
+            "temperature": 0.7,
+            "top_p": 0.95,
+            "top_k": 40,
+            "max_tokens": 16,
+            "stream": false
+        }))
+        .unwrap(),
+        respond_to: chat_tx,
+    })
+    .await
+    .unwrap();
+    let err = chat_rx.await.unwrap().unwrap_err();
+    assert!(err.contains("crashed"), "{}", err);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_restart_policy_retries_with_backoff_then_gives_up() {
+    let (mut actor, tx, mut events) = new_actor_with_events(HashMap::new());
+    tokio::spawn(async move { actor.run().await });
+
+    // The restart itself fails: the model file does not exist.
+    let config = LlamaCppConfig {
+        restart_policy: Some(RestartPolicy {
+            max_restarts: 2,
+            initial_backoff_ms: 10,
+            max_backoff_ms: 15,
+        }),
+        ..config_for("/models/missing.gguf", 8080)
+    };
+    start_short_lived(&tx, config).await;
+
+    assert!(matches!(next_event(&mut events).await, ModelEvent::Crashed { .. }));
+    assert!(matches!(
+        next_event(&mut events).await,
+        ModelEvent::Restarting {
+            attempt: 1,
+            delay_ms: 10,
+            ..
+        }
+    ));
+    assert!(matches!(
+        next_event(&mut events).await,
+        ModelEvent::RestartFailed { attempt: 1, .. }
+    ));
+    assert!(matches!(
+        next_event(&mut events).await,
+        ModelEvent::Restarting {
+            attempt: 2,
+            delay_ms: 15,
+            ..
+        }
+    ));
+    assert!(matches!(
+        next_event(&mut events).await,
+        ModelEvent::RestartFailed { attempt: 2, .. }
+    ));
+    assert!(matches!(
+        next_event(&mut events).await,
+        ModelEvent::RestartsExhausted { attempts: 2, .. }
+    ));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stop_cancels_pending_restart() {
+    let (mut actor, tx, mut events) = new_actor_with_events(HashMap::new());
+    tokio::spawn(async move { actor.run().await });
+
+    let config = LlamaCppConfig {
+        restart_policy: Some(RestartPolicy {
+            max_restarts: 3,
+            initial_backoff_ms: 200,
+            max_backoff_ms: 200,
+        }),
+        ..config_for("/models/missing.gguf", 8080)
+    };
+    start_short_lived(&tx, config).await;
+
+    assert!(matches!(next_event(&mut events).await, ModelEvent::Crashed { .. }));
+    assert!(matches!(
+        next_event(&mut events).await,
+        ModelEvent::Restarting { attempt: 1, .. }
+    ));
+    stop(&tx, "/models/missing.gguf").await;
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert!(matches!(
+        events.try_recv(),
+        Err(broadcast::error::TryRecvError::Empty)
+    ));
+}
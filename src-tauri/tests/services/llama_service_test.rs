@@ -2,6 +2,7 @@ use crate::common;
 
 use llama_desktop_lib::services::llama::service::LlamaCppService;
 use llama_desktop_lib::services::llama::actor::ActorMessage;
+use llama_desktop_lib::models::{ChatRequest, LogStream, ModelChatDefaults, ModelId, SamplingParams};
 use tokio::sync::mpsc;
 
 #[tokio::test]
@@ -118,7 +119,7 @@ async fn test_service_send_chat_no_model() {
         }
     });
 
-    let result = service.send_chat_message(None, vec![], 0.7, 1.0, 40, 512).await;
+    let result = service.send_chat_message(None, None, vec![], &SamplingParams::default(), 512).await;
     assert!(result.is_err());
     assert!(result.unwrap_err().contains("No model running"));
 }
@@ -142,7 +143,7 @@ async fn test_service_send_chat_success() {
         }
     });
 
-    let mut receiver = service.send_chat_message(None, vec![], 0.7, 1.0, 40, 512).await.unwrap();
+    let mut receiver = service.send_chat_message(None, None, vec![], &SamplingParams::default(), 512).await.unwrap();
     let chunk = receiver.recv().await;
     assert_eq!(chunk, Some("Hello".to_string()));
 }
@@ -158,7 +159,9 @@ async fn test_service_complete_chat_no_model() {
         }
     });
 
-    let result = service.complete_chat(None, vec![], 0.7, 1.0, 40, 512, None, None, None, None, None).await;
+    let result = service
+        .complete_chat(None, ChatRequest::default(), &SamplingParams::default())
+        .await;
     assert!(result.is_err());
 }
 
@@ -177,6 +180,193 @@ async fn test_service_complete_chat_success() {
         }
     });
 
-    let result = service.complete_chat(None, vec![], 0.7, 1.0, 40, 512, None, None, None, None, None).await;
+    let result = service
+        .complete_chat(None, ChatRequest::default(), &SamplingParams::default())
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_service_list_models() {
+    let (tx, mut rx) = mpsc::channel(4);
+    let service = LlamaCppService::from_sender(tx);
+    let config = common::sample_llama_config();
+
+    tokio::spawn(async move {
+        if let Some(ActorMessage::ListModels { respond_to }) = rx.recv().await {
+            let _ = respond_to.send(vec![llama_desktop_lib::models::RunningModel {
+                model_id: llama_desktop_lib::models::ModelId(config.model_path.clone()),
+                port: config.port,
+                pid: 42,
+                is_default: true,
+                config,
+            }]);
+        }
+    });
+
+    let models = service.list_models().await;
+    assert_eq!(models.len(), 1);
+    assert_eq!(models[0].pid, 42);
+}
+
+#[tokio::test]
+async fn test_service_send_chat_routes_to_named_model() {
+    let (tx, mut rx) = mpsc::channel(4);
+    let service = LlamaCppService::from_sender(tx);
+    let config = common::sample_llama_config();
+
+    tokio::spawn(async move {
+        if let Some(ActorMessage::GetModelConfig { model_id, respond_to }) = rx.recv().await {
+            assert_eq!(model_id.0, "small");
+            let _ = respond_to.send(Some(config));
+        }
+        if let Some(ActorMessage::SendChat { model_id, request, respond_to }) = rx.recv().await {
+            assert_eq!(model_id.0, "/models/test.gguf");
+            assert_eq!(request.model, "/models/test.gguf");
+            let (_tx, rx) = mpsc::channel(1);
+            let _ = respond_to.send(Ok(rx));
+        }
+    });
+
+    let result = service
+        .send_chat_message(Some("small".to_string()), None, vec![], &SamplingParams::default(), 512)
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_service_send_chat_named_model_not_running() {
+    let (tx, mut rx) = mpsc::channel(4);
+    let service = LlamaCppService::from_sender(tx);
+
+    tokio::spawn(async move {
+        if let Some(ActorMessage::GetModelConfig { respond_to, .. }) = rx.recv().await {
+            let _ = respond_to.send(None);
+        }
+    });
+
+    let result = service
+        .send_chat_message(Some("small".to_string()), None, vec![], &SamplingParams::default(), 512)
+        .await;
+    assert_eq!(result.unwrap_err(), "Model small is not running");
+}
+
+#[tokio::test]
+async fn test_service_server_logs_for_default_model() {
+    let (tx, mut rx) = mpsc::channel(4);
+    let service = LlamaCppService::from_sender(tx);
+    let id = ModelId("/models/test.gguf".to_string());
+    service.logs().push(&id, LogStream::Stderr, "first");
+    service.logs().push(&id, LogStream::Stderr, "second");
+
+    tokio::spawn(async move {
+        if let Some(ActorMessage::GetConfig { respond_to }) = rx.recv().await {
+            let _ = respond_to.send(Some(common::sample_llama_config()));
+        }
+    });
+
+    let lines = service.server_logs(None, Some(1)).await.unwrap();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].line, "second");
+}
+
+#[tokio::test]
+async fn test_service_server_logs_after_failed_start() {
+    // No actor round-trip: logs of a model that never came up are still readable.
+    let (tx, _rx) = mpsc::channel(4);
+    let service = LlamaCppService::from_sender(tx);
+    let id = ModelId("/models/broken.gguf".to_string());
+    service.logs().push(&id, LogStream::Stderr, "error loading model");
+
+    let lines = service
+        .server_logs(Some("/models/broken.gguf"), None)
+        .await
+        .unwrap();
+    assert_eq!(lines[0].line, "error loading model");
+}
+
+#[tokio::test]
+async fn test_service_chat_sampling_over_model_defaults() {
+    let (tx, mut rx) = mpsc::channel(4);
+    let service = LlamaCppService::from_sender(tx);
+    let config = common::sample_llama_config();
+    service.set_chat_defaults(
+        &config.model_path,
+        Some(ModelChatDefaults {
+            system_prompt: None,
+            sampling: SamplingParams {
+                temperature: Some(0.2),
+                top_k: Some(20),
+                dry_multiplier: Some(0.8),
+                ..Default::default()
+            },
+        }),
+    );
+
+    tokio::spawn(async move {
+        if let Some(ActorMessage::GetConfig { respond_to }) = rx.recv().await {
+            let _ = respond_to.send(Some(config));
+        }
+        if let Some(ActorMessage::SendChat { request, respond_to, .. }) = rx.recv().await {
+            assert_eq!(request.temperature, 1.1);
+            assert_eq!(request.top_k, 20);
+            assert_eq!(request.top_p, 0.95);
+            let body = serde_json::to_value(&request).unwrap();
+            assert_eq!(body["dry_multiplier"], serde_json::json!(0.8f32));
+            assert_eq!(body["presence_penalty"], serde_json::json!(0.25f32));
+            assert_eq!(body["logit_bias"], serde_json::json!([[15043, false]]));
+            assert!(body.get("mirostat").is_none());
+            let (_tx, rx) = mpsc::channel(1);
+            let _ = respond_to.send(Ok(rx));
+        }
+    });
+
+    let sampling = SamplingParams {
+        temperature: Some(1.1),
+        presence_penalty: Some(0.25),
+        logit_bias: Some(serde_json::json!([[15043, false]])),
+        ..Default::default()
+    };
+    let result = service.send_chat_message(None, None, vec![], &sampling, 512).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_service_complete_chat_uses_model_sampling_defaults() {
+    let (tx, mut rx) = mpsc::channel(4);
+    let service = LlamaCppService::from_sender(tx);
+    let config = common::sample_llama_config();
+    service.set_chat_defaults(
+        &config.model_path,
+        Some(ModelChatDefaults {
+            system_prompt: None,
+            sampling: SamplingParams {
+                temperature: Some(0.2),
+                top_k: Some(20),
+                min_p: Some(0.1),
+                ..Default::default()
+            },
+        }),
+    );
+
+    tokio::spawn(async move {
+        if let Some(ActorMessage::GetConfig { respond_to }) = rx.recv().await {
+            let _ = respond_to.send(Some(config));
+        }
+        if let Some(ActorMessage::CompleteChat { request, respond_to, .. }) = rx.recv().await {
+            assert_eq!(request.temperature, 0.3);
+            assert_eq!(request.top_k, 20);
+            assert_eq!(request.top_p, 0.95);
+            assert_eq!(request.min_p, Some(0.1));
+            assert!(!request.stream);
+            let _ = respond_to.send(Ok(serde_json::json!({"response": "test"})));
+        }
+    });
+
+    let sampling = SamplingParams {
+        temperature: Some(0.3),
+        ..Default::default()
+    };
+    let result = service.complete_chat(None, ChatRequest::default(), &sampling).await;
     assert!(result.is_ok());
 }
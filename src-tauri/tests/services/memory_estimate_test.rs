@@ -0,0 +1,197 @@
+use crate::common::GgufBuilder;
+
+use llama_desktop_lib::infrastructure::gguf::read_gguf;
+use llama_desktop_lib::models::{
+    FlashAttention, GgufInfo, KvCacheType, LaunchParams, MemoryEstimate, MemoryEstimateOptions,
+    ModelFit,
+};
+use llama_desktop_lib::services::memory_estimate::{estimate_fit_from_size, estimate_memory};
+use std::io::Cursor;
+
+const F16: u32 = 1;
+const F32: u32 = 0;
+// One block: attn_q (256x256) + ffn_up (256x512), both F16.
+const BLOCK_BYTES: u64 = 256 * 256 * 2 + 256 * 512 * 2;
+const EMBD_BYTES: u64 = 256 * 1000 * 2;
+const OUTPUT_BYTES: u64 = 256 * 1000 * 2 + 256 * 4;
+// ctx 1024 x (2 kv heads x 32 dims) for K and V, F16.
+const KV_LAYER_BYTES: u64 = 2 * 1024 * 64 * 2;
+
+fn tiny_model() -> GgufInfo {
+    let mut builder = GgufBuilder::new("llama")
+        .kv_u32("llama.block_count", 4)
+        .kv_u32("llama.embedding_length", 256)
+        .kv_u32("llama.feed_forward_length", 512)
+        .kv_u32("llama.attention.head_count", 8)
+        .kv_u32("llama.attention.head_count_kv", 2)
+        .tensor("token_embd.weight", &[256, 1000], F16);
+    for i in 0..4 {
+        builder = builder
+            .tensor(&format!("blk.{}.attn_q.weight", i), &[256, 256], F16)
+            .tensor(&format!("blk.{}.ffn_up.weight", i), &[256, 512], F16);
+    }
+    let builder = builder.tensor("output.weight", &[256, 1000], F16).tensor(
+        "output_norm.weight",
+        &[256],
+        F32,
+    );
+    read_gguf(Cursor::new(builder.build())).unwrap().info()
+}
+
+fn options(n_gpu_layers: i32) -> MemoryEstimateOptions {
+    MemoryEstimateOptions {
+        ctx_size: 1024,
+        parallel: 1,
+        n_gpu_layers,
+        launch_params: LaunchParams::default(),
+        vram_budget_bytes: Vec::new(),
+    }
+}
+
+fn gpu_total(estimate: &MemoryEstimate) -> u64 {
+    estimate.devices[1..].iter().map(|d| d.total_bytes).sum()
+}
+
+#[test]
+fn test_cpu_only_keeps_everything_on_host() {
+    let estimate = estimate_memory(&tiny_model(), &options(0)).unwrap();
+
+    assert_eq!(estimate.layers_total, 5);
+    assert_eq!(estimate.n_gpu_layers, 0);
+    let cpu = &estimate.devices[0];
+    assert_eq!(cpu.device, "CPU");
+    assert_eq!(cpu.layers, 5);
+    assert_eq!(
+        cpu.weights_bytes,
+        EMBD_BYTES + 4 * BLOCK_BYTES + OUTPUT_BYTES
+    );
+    assert_eq!(cpu.kv_cache_bytes, 4 * KV_LAYER_BYTES);
+    assert_eq!(gpu_total(&estimate), 0);
+    assert!(estimate.recommended_n_gpu_layers.is_none());
+    assert!(estimate.fits.is_none());
+}
+
+#[test]
+fn test_partial_offload_moves_last_blocks() {
+    let estimate = estimate_memory(&tiny_model(), &options(2)).unwrap();
+
+    let (cpu, gpu) = (&estimate.devices[0], &estimate.devices[1]);
+    assert_eq!((cpu.layers, gpu.layers), (3, 2));
+    assert_eq!(gpu.weights_bytes, 2 * BLOCK_BYTES);
+    assert_eq!(gpu.kv_cache_bytes, 2 * KV_LAYER_BYTES);
+    assert_eq!(
+        cpu.weights_bytes,
+        EMBD_BYTES + 2 * BLOCK_BYTES + OUTPUT_BYTES
+    );
+    assert!(gpu.compute_bytes > 0);
+}
+
+#[test]
+fn test_full_offload_includes_output_layer() {
+    let all = estimate_memory(&tiny_model(), &options(-1)).unwrap();
+    let explicit = estimate_memory(&tiny_model(), &options(99)).unwrap();
+
+    assert_eq!(all, explicit);
+    assert_eq!(all.n_gpu_layers, 5);
+    let gpu = &all.devices[1];
+    assert_eq!(gpu.weights_bytes, 4 * BLOCK_BYTES + OUTPUT_BYTES);
+    assert_eq!(all.devices[0].weights_bytes, EMBD_BYTES);
+}
+
+#[test]
+fn test_quantized_kv_cache_shrinks_cache() {
+    let mut opts = options(-1);
+    opts.launch_params.flash_attn = Some(FlashAttention::On);
+    opts.launch_params.cache_type_k = Some(KvCacheType::Q8_0);
+    opts.launch_params.cache_type_v = Some(KvCacheType::Q8_0);
+    let estimate = estimate_memory(&tiny_model(), &opts).unwrap();
+
+    // 65536 elements per layer and tensor, 34 bytes per 32-element block.
+    assert_eq!(
+        estimate.devices[1].kv_cache_bytes,
+        4 * 2 * (65536 / 32 * 34)
+    );
+}
+
+#[test]
+fn test_flash_attention_off_adds_attention_scores() {
+    let mut on = options(-1);
+    on.launch_params.flash_attn = Some(FlashAttention::On);
+    let mut off = options(-1);
+    off.launch_params.flash_attn = Some(FlashAttention::Off);
+
+    let on = estimate_memory(&tiny_model(), &on).unwrap();
+    let off = estimate_memory(&tiny_model(), &off).unwrap();
+    // ubatch 512 x ctx 1024 x 8 heads x f32.
+    assert_eq!(
+        off.devices[1].compute_bytes - on.devices[1].compute_bytes,
+        512 * 1024 * 8 * 4
+    );
+}
+
+#[test]
+fn test_recommends_largest_layer_count_within_budget() {
+    let model = tiny_model();
+    let three = gpu_total(&estimate_memory(&model, &options(3)).unwrap());
+    let four = gpu_total(&estimate_memory(&model, &options(4)).unwrap());
+    assert!(three < four);
+
+    let mut opts = options(-1);
+    opts.vram_budget_bytes = vec![three + (four - three) / 2];
+    let estimate = estimate_memory(&model, &opts).unwrap();
+
+    assert_eq!(estimate.recommended_n_gpu_layers, Some(3));
+    assert_eq!(estimate.fits, Some(false));
+    assert_eq!(estimate.devices[1].fits, Some(false));
+
+    opts.vram_budget_bytes = vec![1];
+    let estimate = estimate_memory(&model, &opts).unwrap();
+    assert_eq!(estimate.recommended_n_gpu_layers, Some(0));
+}
+
+#[test]
+fn test_tensor_split_spreads_layers_over_gpus() {
+    let mut opts = options(-1);
+    opts.launch_params.tensor_split = vec![1.0, 1.0];
+    let estimate = estimate_memory(&tiny_model(), &opts).unwrap();
+
+    assert_eq!(estimate.devices.len(), 3);
+    let (gpu0, gpu1) = (&estimate.devices[1], &estimate.devices[2]);
+    assert_eq!(
+        (gpu0.device.as_str(), gpu1.device.as_str()),
+        ("GPU0", "GPU1")
+    );
+    assert_eq!((gpu0.layers, gpu1.layers), (3, 2));
+    assert_eq!(gpu0.weights_bytes, 3 * BLOCK_BYTES);
+    assert_eq!(gpu1.weights_bytes, BLOCK_BYTES + OUTPUT_BYTES);
+}
+
+#[test]
+fn test_requires_layer_metadata() {
+    let info = read_gguf(Cursor::new(GgufBuilder::new("llama").build()))
+        .unwrap()
+        .info();
+    assert!(estimate_memory(&info, &options(0)).is_err());
+}
+
+#[test]
+fn test_fit_from_size_adds_headroom_for_cache() {
+    const GIB: u64 = 1024 * 1024 * 1024;
+    // 10 GiB of weights need about 12 GiB with cache and buffers.
+    assert_eq!(
+        estimate_fit_from_size(10 * GIB, &[8 * GIB, 8 * GIB], 0),
+        ModelFit::Gpu
+    );
+    assert_eq!(
+        estimate_fit_from_size(10 * GIB, &[11 * GIB], 32 * GIB),
+        ModelFit::Partial
+    );
+    assert_eq!(
+        estimate_fit_from_size(10 * GIB, &[], 32 * GIB),
+        ModelFit::Cpu
+    );
+    assert_eq!(
+        estimate_fit_from_size(10 * GIB, &[4 * GIB], 4 * GIB),
+        ModelFit::TooLarge
+    );
+}
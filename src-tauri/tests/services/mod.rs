@@ -1,5 +1,9 @@
+mod llama_actor_test;
 mod llama_service_test;
 mod mcp_service_test;
 mod orchestrator_test;
 mod capability_registry_test;
 mod thinking_parser_test;
+mod memory_estimate_test;
+mod downloads_test;
+mod go_template_test;
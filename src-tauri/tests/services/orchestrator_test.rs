@@ -10,7 +10,7 @@ fn create_test_orchestrator() -> ChatOrchestrator {
 use llama_desktop_lib::services::orchestrator::ChatOrchestrator;
 use llama_desktop_lib::services::llama::service::LlamaCppService;
 use llama_desktop_lib::services::mcp::service::McpService;
-use llama_desktop_lib::models::McpConfig;
+use llama_desktop_lib::models::{McpConfig, McpServerConfig};
 
 
 #[tokio::test]
@@ -114,8 +114,892 @@ async fn test_orchestrator_complete_chat_once_no_model() {
     let orchestrator = create_test_orchestrator();
     
     let messages = vec![common::sample_chat_message("user", "Hello")];
-    let result = orchestrator.complete_chat_once(messages, 0.7, 1.0, 40, 512, None, None, None).await;
+    let request = ChatRequest {
+        messages,
+        max_tokens: 512,
+        ..Default::default()
+    };
+    let result = orchestrator
+        .complete_chat_once(None, request, &SamplingParams::default())
+        .await;
     
     assert!(result.is_err());
     assert!(result.unwrap_err().contains("No model running"));
-}
\ No newline at end of file
+}
+
+fn create_persistent_orchestrator(sessions_dir: &std::path::Path) -> ChatOrchestrator {
+    let temp_dir = common::temp_dir();
+    let llama_service = LlamaCppService::new(temp_dir.path().to_path_buf());
+    let mcp_service = McpService::new(McpConfig::default(), None);
+    let store = std::sync::Arc::new(FileSessionStore::new(sessions_dir.to_path_buf()));
+    ChatOrchestrator::with_session_store(llama_service, mcp_service, store)
+}
+
+use llama_desktop_lib::infrastructure::session_store::{
+    FileSessionStore, InMemorySessionStore, SessionStore,
+};
+
+#[tokio::test]
+async fn test_orchestrator_reports_a_message_it_could_not_store() {
+    let dir = common::temp_dir();
+    // A file where the sessions directory should be makes every append fail.
+    let blocked = dir.path().join("sessions");
+    std::fs::write(&blocked, "").unwrap();
+    let orchestrator = create_persistent_orchestrator(&blocked);
+
+    let result = orchestrator
+        .process(
+            "unstored",
+            "Hello".to_string(),
+            &options(0.7, ToolLoopPolicy::default()),
+            Channel::new(|_| Ok(())),
+        )
+        .await;
+
+    assert!(result.is_err());
+    assert!(orchestrator.get_message("unstored", 0).await.is_none());
+}
+
+#[tokio::test]
+async fn test_orchestrator_history_survives_restart() {
+    let dir = common::temp_dir();
+    let session_id = "persisted";
+
+    let orchestrator = create_persistent_orchestrator(dir.path());
+    orchestrator
+        .set_session_history(
+            session_id,
+            vec![
+                common::sample_chat_message("user", "Hello"),
+                common::sample_chat_message("assistant", "Hi there"),
+            ],
+        )
+        .await;
+    drop(orchestrator);
+
+    let restarted = create_persistent_orchestrator(dir.path());
+    let msg = restarted.get_message(session_id, 1).await;
+    assert_eq!(msg.unwrap().content, "Hi there");
+}
+
+#[tokio::test]
+async fn test_orchestrator_remove_message_writes_through() {
+    let dir = common::temp_dir();
+    let session_id = "persisted";
+
+    let orchestrator = create_persistent_orchestrator(dir.path());
+    orchestrator
+        .set_session_history(
+            session_id,
+            vec![
+                common::sample_chat_message("user", "First"),
+                common::sample_chat_message("assistant", "Second"),
+            ],
+        )
+        .await;
+    orchestrator.remove_message(session_id, 0).await.unwrap();
+
+    let stored = FileSessionStore::new(dir.path().to_path_buf())
+        .load(session_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].content, "Second");
+}
+
+#[tokio::test]
+async fn test_orchestrator_clear_session_deletes_file() {
+    let dir = common::temp_dir();
+    let session_id = "persisted";
+
+    let orchestrator = create_persistent_orchestrator(dir.path());
+    orchestrator
+        .set_session_history(session_id, vec![common::sample_chat_message("user", "Hello")])
+        .await;
+    orchestrator.clear_session(session_id).await;
+
+    let store = FileSessionStore::new(dir.path().to_path_buf());
+    assert!(store.load(session_id).unwrap().is_none());
+}
+
+use llama_desktop_lib::models::{
+    ChatRequest, ChatStreamEvent, ChatTurnOptions, McpTransport, SamplingParams, ToolApproval,
+    ToolApprovalDecision, ToolLoopPolicy,
+};
+use llama_desktop_lib::services::llama::ActorMessage;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::{Channel, InvokeResponseBody};
+use tokio::sync::mpsc;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// Streamable-HTTP MCP server with one `search` tool whose calls take `delay`.
+struct McpResponder {
+    name: &'static str,
+    delay: Duration,
+}
+
+impl Respond for McpResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+        let Some(id) = body.get("id").cloned() else {
+            return ResponseTemplate::new(202);
+        };
+        let mut delay = Duration::ZERO;
+        let result = match body["method"].as_str().unwrap_or("") {
+            "initialize" => json!({
+                "protocolVersion": "2025-03-26",
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": self.name, "version": "1.0" }
+            }),
+            "tools/list" => json!({ "tools": [{
+                "name": "search",
+                "description": "Search",
+                "inputSchema": { "type": "object", "properties": { "q": { "type": "string" } } }
+            }] }),
+            "tools/call" => {
+                delay = self.delay;
+                let q = body["params"]["arguments"]["q"].as_str().unwrap_or("");
+                json!({ "content": [{ "type": "text", "text": format!("{}:{}", self.name, q) }] })
+            }
+            "resources/list" => json!({ "resources": [] }),
+            _ => json!({}),
+        };
+        ResponseTemplate::new(200)
+            .set_body_json(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+            .set_delay(delay)
+    }
+}
+
+async fn start_mcp_server(name: &'static str, delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(McpResponder { name, delay })
+        .mount(&server)
+        .await;
+    server
+}
+
+fn http_server_config(id: &str, server: &MockServer) -> McpServerConfig {
+    McpServerConfig {
+        transport: McpTransport::HttpSse,
+        command: None,
+        args: None,
+        url: Some(server.uri()),
+        ..common::sample_mcp_server(id)
+    }
+}
+
+/// Streams `turns` in order, one per streamed request, and counts the
+/// requests that are not streamed tool turns.
+fn streaming_service(turns: Vec<Vec<ChatStreamEvent>>) -> (LlamaCppService, Arc<AtomicUsize>) {
+    let (service, other_requests, _) = recording_streaming_service(turns);
+    (service, other_requests)
+}
+
+/// `streaming_service` that also keeps every `ChatRequest`, streamed or not.
+fn recording_streaming_service(
+    turns: Vec<Vec<ChatStreamEvent>>,
+) -> (LlamaCppService, Arc<AtomicUsize>, Arc<Mutex<Vec<ChatRequest>>>) {
+    let (tx, mut rx) = mpsc::channel(8);
+    let other_requests = Arc::new(AtomicUsize::new(0));
+    let counter = other_requests.clone();
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let recorder = streamed.clone();
+    tokio::spawn(async move {
+        let mut turns = turns.into_iter();
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                ActorMessage::GetConfig { respond_to } => {
+                    let _ = respond_to.send(Some(common::sample_llama_config()));
+                }
+                ActorMessage::GetModelConfig { respond_to, .. } => {
+                    let _ = respond_to.send(Some(common::sample_llama_config()));
+                }
+                ActorMessage::StreamChat {
+                    request,
+                    respond_to,
+                    ..
+                } => {
+                    recorder.lock().unwrap().push(request);
+                    let events = turns.next().unwrap_or_default();
+                    let (out_tx, out_rx) = mpsc::channel(events.len().max(1));
+                    for event in events {
+                        let _ = out_tx.send(event).await;
+                    }
+                    let _ = respond_to.send(Ok(out_rx));
+                }
+                ActorMessage::SendChat {
+                    request,
+                    respond_to,
+                    ..
+                } => {
+                    recorder.lock().unwrap().push(request);
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let (out_tx, out_rx) = mpsc::channel(1);
+                    let _ = out_tx.send("fallback".to_string()).await;
+                    let _ = respond_to.send(Ok(out_rx));
+                }
+                ActorMessage::CompleteChat { respond_to, .. } => {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let _ = respond_to.send(Err("not streamed".to_string()));
+                }
+                _ => {}
+            }
+        }
+    });
+    (LlamaCppService::from_sender(tx), other_requests, streamed)
+}
+
+/// First turn streams `tool_calls` as deltas, the second answers "done".
+fn tool_calling_service(tool_calls: serde_json::Value) -> LlamaCppService {
+    let first_turn = tool_calls
+        .as_array()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .map(|(index, call)| {
+            let mut delta = call.clone();
+            delta["index"] = json!(index);
+            ChatStreamEvent::ToolCallDelta(delta)
+        })
+        .collect();
+    let (service, _) = streaming_service(vec![
+        first_turn,
+        vec![ChatStreamEvent::Text("done".to_string())],
+    ]);
+    service
+}
+
+fn options(temperature: f32, policy: ToolLoopPolicy) -> ChatTurnOptions {
+    ChatTurnOptions {
+        max_tokens: 64,
+        sampling: SamplingParams {
+            temperature: Some(temperature),
+            ..Default::default()
+        },
+        tool_loop_policy: Some(policy),
+        ..Default::default()
+    }
+}
+
+fn collecting_channel() -> (Channel<serde_json::Value>, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let channel = Channel::new(move |body| {
+        if let InvokeResponseBody::Json(text) = body {
+            sink.lock().unwrap().push(serde_json::from_str(&text).unwrap());
+        }
+        Ok(())
+    });
+    (channel, events)
+}
+
+fn search_call(id: &str, server_id: &str, q: &str) -> serde_json::Value {
+    json!({
+        "id": id,
+        "type": "function",
+        "function": {
+            "name": format!("mcp__{}__search", server_id),
+            "arguments": json!({ "q": q }).to_string()
+        }
+    })
+}
+
+/// Runs one turn where the model calls `slow` then `fast` and returns the
+/// time spent in `process`.
+async fn run_parallel_turn(max_parallel_tool_calls: usize) -> (ChatOrchestrator, Duration) {
+    let slow = start_mcp_server("slow", Duration::from_millis(600)).await;
+    let fast = start_mcp_server("fast", Duration::from_millis(300)).await;
+    let config = McpConfig {
+        servers: vec![http_server_config("slow", &slow), http_server_config("fast", &fast)],
+        max_parallel_tool_calls,
+    };
+    let service = tool_calling_service(json!([
+        search_call("call-slow", "slow", "first"),
+        search_call("call-fast", "fast", "second"),
+    ]));
+    let orchestrator = ChatOrchestrator::new(service, McpService::new(config, None));
+    orchestrator.refresh_capabilities().await.unwrap();
+
+    let (channel, _events) = collecting_channel();
+    let started = Instant::now();
+    orchestrator
+        .process("tools", "look both up".to_string(), &options(0.2, ToolLoopPolicy::default()), channel)
+        .await
+        .unwrap();
+    let elapsed = started.elapsed();
+    // Keep the mock servers alive until the turn is over.
+    drop((slow, fast));
+    (orchestrator, elapsed)
+}
+
+#[tokio::test]
+async fn test_parallel_tool_calls_run_concurrently_in_call_order() {
+    let (orchestrator, elapsed) = run_parallel_turn(4).await;
+
+    assert!(elapsed < Duration::from_millis(850), "took {:?}", elapsed);
+    let first = orchestrator.get_message("tools", 2).await.unwrap();
+    let second = orchestrator.get_message("tools", 3).await.unwrap();
+    assert_eq!(first.role, "tool");
+    assert_eq!(first.tool_call_id.as_deref(), Some("call-slow"));
+    assert!(first.content.contains("slow:first"), "{}", first.content);
+    assert_eq!(second.tool_call_id.as_deref(), Some("call-fast"));
+    assert!(second.content.contains("fast:second"), "{}", second.content);
+    let answer = orchestrator.get_message("tools", 4).await.unwrap();
+    assert_eq!(answer.content, "done");
+}
+
+#[tokio::test]
+async fn test_parallel_tool_calls_respect_concurrency_cap() {
+    let (orchestrator, elapsed) = run_parallel_turn(1).await;
+
+    assert!(elapsed >= Duration::from_millis(900), "took {:?}", elapsed);
+    let first = orchestrator.get_message("tools", 2).await.unwrap();
+    assert_eq!(first.tool_call_id.as_deref(), Some("call-slow"));
+}
+
+#[tokio::test]
+async fn test_streamed_answer_without_tool_calls_is_final() {
+    let server = start_mcp_server("web", Duration::ZERO).await;
+    let config = McpConfig {
+        servers: vec![http_server_config("web", &server)],
+        ..Default::default()
+    };
+    let (service, other_requests) = streaming_service(vec![vec![
+        ChatStreamEvent::Text("<think>no tools needed</think>".to_string()),
+        ChatStreamEvent::Text("Hello ".to_string()),
+        ChatStreamEvent::Text("there".to_string()),
+    ]]);
+    let orchestrator = ChatOrchestrator::new(service, McpService::new(config, None));
+    orchestrator.refresh_capabilities().await.unwrap();
+
+    let (channel, events) = collecting_channel();
+    orchestrator
+        .process("plain", "hi".to_string(), &options(0.7, ToolLoopPolicy::default()), channel)
+        .await
+        .unwrap();
+
+    assert_eq!(other_requests.load(Ordering::SeqCst), 0);
+    let answer = orchestrator.get_message("plain", 1).await.unwrap();
+    assert_eq!(answer.role, "assistant");
+    assert_eq!(answer.content, "Hello there");
+    let events = events.lock().unwrap();
+    let chunks: String = events
+        .iter()
+        .filter_map(|e| e["chunk"].as_str())
+        .collect();
+    assert_eq!(chunks, "Hello there");
+    assert!(events.iter().any(|e| e["thinking_chunk"] == "no tools needed"));
+    assert!(events.iter().any(|e| e["status"] == "done"));
+}
+
+#[tokio::test]
+async fn test_tool_call_fragments_are_joined_before_execution() {
+    let server = start_mcp_server("web", Duration::ZERO).await;
+    let config = McpConfig {
+        servers: vec![http_server_config("web", &server)],
+        ..Default::default()
+    };
+    let fragments = vec![
+        ChatStreamEvent::Text("Let me check.".to_string()),
+        ChatStreamEvent::ToolCallDelta(json!({
+            "index": 0, "id": "call-x", "type": "function",
+            "function": { "name": "mcp__web__search", "arguments": "" }
+        })),
+        ChatStreamEvent::ToolCallDelta(json!({ "index": 0, "function": { "arguments": "{\"q\":" } })),
+        ChatStreamEvent::ToolCallDelta(json!({ "index": 0, "function": { "arguments": "\"rust\"}" } })),
+    ];
+    let (service, other_requests) = streaming_service(vec![
+        fragments,
+        vec![ChatStreamEvent::Text("Found it.".to_string())],
+    ]);
+    let orchestrator = ChatOrchestrator::new(service, McpService::new(config, None));
+    orchestrator.refresh_capabilities().await.unwrap();
+
+    let (channel, _events) = collecting_channel();
+    orchestrator
+        .process("frag", "search rust".to_string(), &options(0.7, ToolLoopPolicy::default()), channel)
+        .await
+        .unwrap();
+
+    assert_eq!(other_requests.load(Ordering::SeqCst), 0);
+    let call = orchestrator.get_message("frag", 1).await.unwrap();
+    assert_eq!(call.content, "Let me check.");
+    let tool_calls = call.tool_calls.unwrap();
+    assert_eq!(tool_calls[0]["function"]["arguments"], "{\"q\":\"rust\"}");
+    let result = orchestrator.get_message("frag", 2).await.unwrap();
+    assert_eq!(result.tool_call_id.as_deref(), Some("call-x"));
+    assert!(result.content.contains("web:rust"), "{}", result.content);
+    let answer = orchestrator.get_message("frag", 3).await.unwrap();
+    assert_eq!(answer.content, "Found it.");
+}
+
+/// Event channel that can be awaited, for answering approval requests.
+fn awaitable_channel() -> (Channel<serde_json::Value>, mpsc::UnboundedReceiver<serde_json::Value>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let channel = Channel::new(move |body| {
+        if let InvokeResponseBody::Json(text) = body {
+            let _ = tx.send(serde_json::from_str(&text).unwrap());
+        }
+        Ok(())
+    });
+    (channel, rx)
+}
+
+async fn approval_orchestrator(
+    server: &MockServer,
+    approval: ToolApproval,
+    overrides: Option<HashMap<String, ToolApproval>>,
+) -> ChatOrchestrator {
+    let store = Arc::new(InMemorySessionStore::new());
+    approval_orchestrator_with_store(server, approval, overrides, store).await
+}
+
+async fn approval_orchestrator_with_store(
+    server: &MockServer,
+    approval: ToolApproval,
+    overrides: Option<HashMap<String, ToolApproval>>,
+    store: Arc<dyn SessionStore>,
+) -> ChatOrchestrator {
+    let config = McpConfig {
+        servers: vec![McpServerConfig {
+            tool_approval: Some(approval),
+            tool_approval_overrides: overrides,
+            ..http_server_config("web", server)
+        }],
+        ..Default::default()
+    };
+    let service = tool_calling_service(json!([search_call("call-1", "web", "rust")]));
+    let orchestrator =
+        ChatOrchestrator::with_session_store(service, McpService::new(config, None), store)
+            .with_approval_timeout(Duration::from_millis(200));
+    orchestrator.refresh_capabilities().await.unwrap();
+    orchestrator
+}
+
+/// Runs one turn, answering the first approval request with `decision`.
+async fn run_with_decision(
+    orchestrator: &ChatOrchestrator,
+    decision: Option<ToolApprovalDecision>,
+) -> Vec<serde_json::Value> {
+    let (channel, mut rx) = awaitable_channel();
+    let turn = orchestrator.clone();
+    let handle = tokio::spawn(async move {
+        turn.process(
+            "approve",
+            "search rust".to_string(),
+            &options(0.2, ToolLoopPolicy::default()),
+            channel,
+        )
+        .await
+    });
+
+    let mut events = Vec::new();
+    while let Some(event) = rx.recv().await {
+        if let (Some(request), Some(decision)) = (event.get("approval_request"), &decision) {
+            let request_id = request["request_id"].as_str().unwrap();
+            orchestrator
+                .respond_to_approval(request_id, decision.clone())
+                .await
+                .unwrap();
+        }
+        events.push(event);
+    }
+    handle.await.unwrap().unwrap();
+    events
+}
+
+#[tokio::test]
+async fn test_approval_request_runs_call_with_edited_arguments() {
+    let server = start_mcp_server("web", Duration::ZERO).await;
+    let orchestrator = approval_orchestrator(&server, ToolApproval::Ask, None).await;
+
+    let events = run_with_decision(
+        &orchestrator,
+        Some(ToolApprovalDecision::Edit {
+            arguments: json!({ "q": "rust lang" }),
+        }),
+    )
+    .await;
+
+    let request = events
+        .iter()
+        .find_map(|e| e.get("approval_request"))
+        .unwrap();
+    assert_eq!(request["tool_name"], "search");
+    assert_eq!(request["tool_call_id"], "call-1");
+    assert_eq!(request["arguments"], json!({ "q": "rust" }));
+    assert!(events
+        .iter()
+        .any(|e| e["approval_resolved"]["decision"] == "edited"));
+
+    let call = orchestrator.get_message("approve", 1).await.unwrap();
+    assert_eq!(
+        call.tool_calls.unwrap()[0]["function"]["arguments"],
+        "{\"q\":\"rust lang\"}"
+    );
+    let result = orchestrator.get_message("approve", 2).await.unwrap();
+    assert!(result.content.contains("web:rust lang"), "{}", result.content);
+}
+
+#[tokio::test]
+async fn test_rejected_and_unanswered_calls_are_not_run() {
+    let server = start_mcp_server("web", Duration::ZERO).await;
+
+    let orchestrator = approval_orchestrator(&server, ToolApproval::Ask, None).await;
+    run_with_decision(
+        &orchestrator,
+        Some(ToolApprovalDecision::Reject {
+            reason: Some("costs money".to_string()),
+        }),
+    )
+    .await;
+    let result = orchestrator.get_message("approve", 2).await.unwrap();
+    assert_eq!(result.tool_call_id.as_deref(), Some("call-1"));
+    assert!(result.content.contains("rejected this tool call: costs money"), "{}", result.content);
+
+    let orchestrator = approval_orchestrator(&server, ToolApproval::Ask, None).await;
+    let events = run_with_decision(&orchestrator, None).await;
+    assert!(events
+        .iter()
+        .any(|e| e["approval_resolved"]["decision"] == "timed_out"));
+    let result = orchestrator.get_message("approve", 2).await.unwrap();
+    assert!(result.content.contains("No approval within"), "{}", result.content);
+
+    let calls = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| String::from_utf8_lossy(&r.body).contains("tools/call"))
+        .count();
+    assert_eq!(calls, 0);
+}
+
+#[tokio::test]
+async fn test_approval_decision_is_stored_on_the_tool_call() {
+    let server = start_mcp_server("web", Duration::ZERO).await;
+    let cases = [
+        (Some(ToolApprovalDecision::Approve), "approved"),
+        (
+            Some(ToolApprovalDecision::Edit {
+                arguments: json!({ "q": "rust lang" }),
+            }),
+            "edited",
+        ),
+        (Some(ToolApprovalDecision::Reject { reason: None }), "rejected"),
+        (None, "timed_out"),
+    ];
+
+    for (decision, label) in cases {
+        let dir = common::temp_dir();
+        let store = Arc::new(FileSessionStore::new(dir.path().to_path_buf()));
+        let orchestrator =
+            approval_orchestrator_with_store(&server, ToolApproval::Ask, None, store).await;
+        run_with_decision(&orchestrator, decision).await;
+
+        let stored = FileSessionStore::new(dir.path().to_path_buf())
+            .load("approve")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored[1].tool_approvals.as_ref().unwrap()["call-1"], label);
+        // The raw call goes back to llama-server, so it stays untouched.
+        let tool_calls = stored[1].tool_calls.as_ref().unwrap();
+        assert!(tool_calls[0].get("approval").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_closed_channel_during_approval_still_answers_the_call() {
+    let server = start_mcp_server("web", Duration::ZERO).await;
+    let orchestrator = approval_orchestrator(&server, ToolApproval::Ask, None).await;
+    // The window goes away while the approval prompt is being shown.
+    let channel = Channel::new(|body| match body {
+        InvokeResponseBody::Json(text) if text.contains("approval_request") => Err(
+            tauri::Error::Io(std::io::Error::from(std::io::ErrorKind::BrokenPipe)),
+        ),
+        _ => Ok(()),
+    });
+
+    orchestrator
+        .process(
+            "approve",
+            "search rust".to_string(),
+            &options(0.2, ToolLoopPolicy::default()),
+            channel,
+        )
+        .await
+        .unwrap();
+
+    let reply = orchestrator.get_message("approve", 2).await.unwrap();
+    assert_eq!(reply.role, "tool");
+    assert_eq!(reply.tool_call_id.as_deref(), Some("call-1"));
+    assert!(reply.content.contains("cancelled"), "{}", reply.content);
+}
+
+#[tokio::test]
+async fn test_denied_tool_is_refused_without_asking() {
+    let server = start_mcp_server("web", Duration::ZERO).await;
+    let overrides = HashMap::from([("search".to_string(), ToolApproval::Deny)]);
+    let orchestrator = approval_orchestrator(&server, ToolApproval::Ask, Some(overrides)).await;
+
+    let events = run_with_decision(&orchestrator, None).await;
+
+    assert!(!events.iter().any(|e| e.get("approval_request").is_some()));
+    let result = orchestrator.get_message("approve", 2).await.unwrap();
+    assert!(result.content.contains("denied"), "{}", result.content);
+    assert!(orchestrator
+        .respond_to_approval("unknown", ToolApprovalDecision::Approve)
+        .await
+        .is_err());
+}
+
+fn tool_call_turn(calls: &[serde_json::Value]) -> Vec<ChatStreamEvent> {
+    calls
+        .iter()
+        .enumerate()
+        .map(|(index, call)| {
+            let mut delta = call.clone();
+            delta["index"] = json!(index);
+            ChatStreamEvent::ToolCallDelta(delta)
+        })
+        .collect()
+}
+
+/// Runs one user turn against a `web` MCP server with `policy`.
+async fn run_policy_turn(
+    turns: Vec<Vec<ChatStreamEvent>>,
+    policy: ToolLoopPolicy,
+) -> (
+    ChatOrchestrator,
+    Vec<serde_json::Value>,
+    usize,
+    Vec<ChatRequest>,
+) {
+    let server = start_mcp_server("web", Duration::ZERO).await;
+    let config = McpConfig {
+        servers: vec![http_server_config("web", &server)],
+        ..Default::default()
+    };
+    let (service, other_requests, streamed) = recording_streaming_service(turns);
+    let orchestrator = ChatOrchestrator::new(service, McpService::new(config, None));
+    orchestrator.refresh_capabilities().await.unwrap();
+
+    let (channel, events) = collecting_channel();
+    orchestrator
+        .process("policy", "search".to_string(), &options(0.9, policy), channel)
+        .await
+        .unwrap();
+
+    let events = events.lock().unwrap().clone();
+    let streamed = streamed.lock().unwrap().clone();
+    (
+        orchestrator,
+        events,
+        other_requests.load(Ordering::SeqCst),
+        streamed,
+    )
+}
+
+#[tokio::test]
+async fn test_policy_max_iterations_forces_the_answer() {
+    let turns = || {
+        vec![
+            tool_call_turn(&[search_call("call-1", "web", "a")]),
+            tool_call_turn(&[search_call("call-2", "web", "b")]),
+            vec![ChatStreamEvent::Text("done".to_string())],
+        ]
+    };
+
+    let (orchestrator, _, forced, streamed) =
+        run_policy_turn(turns(), ToolLoopPolicy::default()).await;
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(forced, 0);
+    assert_eq!(orchestrator.get_message("policy", 5).await.unwrap().content, "done");
+
+    let policy = ToolLoopPolicy {
+        max_iterations: 1,
+        ..Default::default()
+    };
+    let turns = vec![
+        tool_call_turn(&[search_call("call-1", "web", "a")]),
+        vec![ChatStreamEvent::Text("done".to_string())],
+    ];
+    let (orchestrator, events, forced, requests) = run_policy_turn(turns, policy).await;
+    // The answer is the loop's next streamed turn, offered no tools.
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].tools.is_none());
+    assert_eq!(forced, 0);
+    assert!(events.iter().any(|e| e["thinking"]
+        .as_str()
+        .is_some_and(|t| t.contains("max iterations (1)"))));
+    assert_eq!(orchestrator.get_message("policy", 3).await.unwrap().content, "done");
+}
+
+#[tokio::test]
+async fn test_xml_tool_call_markup_is_not_streamed_to_the_ui() {
+    let turns = vec![
+        vec![
+            ChatStreamEvent::Text("Searching. <tool_".to_string()),
+            ChatStreamEvent::Text(
+                "call>\n<function=mcp__web__search>\n<parameter=q>\nrust\n</parameter>\n\
+                 </function>\n</tool_call>"
+                    .to_string(),
+            ),
+        ],
+        vec![ChatStreamEvent::Text("done".to_string())],
+    ];
+
+    let (orchestrator, events, _, _) = run_policy_turn(turns, ToolLoopPolicy::default()).await;
+
+    let shown: String = events.iter().filter_map(|e| e["chunk"].as_str()).collect();
+    assert_eq!(shown, "Searching. done");
+    let result = orchestrator.get_message("policy", 2).await.unwrap();
+    assert_eq!(result.role, "tool");
+}
+
+#[tokio::test]
+async fn test_policy_max_calls_per_tool() {
+    let turn = || {
+        vec![
+            tool_call_turn(&[
+                search_call("call-1", "web", "a"),
+                search_call("call-2", "web", "b"),
+            ]),
+            vec![ChatStreamEvent::Text("done".to_string())],
+        ]
+    };
+
+    let (orchestrator, _, _, _) = run_policy_turn(turn(), ToolLoopPolicy::default()).await;
+    let second = orchestrator.get_message("policy", 3).await.unwrap();
+    assert!(second.content.contains("web:b"), "{}", second.content);
+
+    let policy = ToolLoopPolicy {
+        max_calls_per_tool: 1,
+        ..Default::default()
+    };
+    let (orchestrator, _, forced, _) = run_policy_turn(turn(), policy).await;
+    let first = orchestrator.get_message("policy", 2).await.unwrap();
+    assert!(first.content.contains("web:a"), "{}", first.content);
+    let second = orchestrator.get_message("policy", 3).await.unwrap();
+    assert!(second.content.contains("called too many times"), "{}", second.content);
+    assert_eq!(forced, 0);
+    assert_eq!(orchestrator.get_message("policy", 4).await.unwrap().content, "done");
+}
+
+#[tokio::test]
+async fn test_policy_result_token_budget() {
+    let long_query = "x".repeat(1000);
+    let turn = || {
+        vec![
+            tool_call_turn(&[search_call("call-1", "web", &long_query)]),
+            vec![ChatStreamEvent::Text("done".to_string())],
+        ]
+    };
+
+    let (orchestrator, _, _, _) = run_policy_turn(turn(), ToolLoopPolicy::default()).await;
+    let full = orchestrator.get_message("policy", 2).await.unwrap().content;
+    assert!(!full.contains("truncated"));
+
+    let policy = ToolLoopPolicy {
+        result_token_budget: 64,
+        ..Default::default()
+    };
+    let (orchestrator, _, _, _) = run_policy_turn(turn(), policy).await;
+    let cut = orchestrator.get_message("policy", 2).await.unwrap().content;
+    assert!(cut.contains("truncated"), "{}", cut);
+    assert!(cut.len() < full.len());
+}
+
+#[tokio::test]
+async fn test_policy_sampling_for_tool_turns() {
+    // A tool turn without tool calls is the answer, so it keeps the tool sampling.
+    let turn = vec![vec![ChatStreamEvent::Text("done".to_string())]];
+
+    let (_, _, _, streamed) = run_policy_turn(turn.clone(), ToolLoopPolicy::default()).await;
+    assert_eq!(streamed[0].temperature, 0.5);
+    assert_eq!(streamed[0].top_p, 0.95);
+    assert_eq!(streamed[0].top_k, 40);
+
+    let uncapped = ToolLoopPolicy {
+        max_temperature: None,
+        ..Default::default()
+    };
+    let (_, _, _, streamed) = run_policy_turn(turn.clone(), uncapped).await;
+    assert_eq!(streamed[0].temperature, 0.9);
+
+    let policy = ToolLoopPolicy {
+        max_temperature: Some(0.4),
+        top_p: 0.8,
+        top_k: 20,
+        ..Default::default()
+    };
+    let (_, _, _, streamed) = run_policy_turn(turn, policy).await;
+    assert_eq!(streamed[0].temperature, 0.4);
+    assert_eq!(streamed[0].top_p, 0.8);
+    assert_eq!(streamed[0].top_k, 20);
+}
+
+#[tokio::test]
+async fn test_forced_answer_uses_chat_sampling() {
+    let turns = vec![tool_call_turn(&[search_call("call-1", "web", "a")])];
+    let policy = ToolLoopPolicy {
+        max_iterations: 1,
+        max_temperature: Some(0.4),
+        top_p: 0.8,
+        top_k: 20,
+        ..Default::default()
+    };
+
+    let (_, _, forced, requests) = run_policy_turn(turns, policy).await;
+    assert_eq!(forced, 0);
+    let (tool_turn, answer) = (&requests[0], &requests[1]);
+    assert!(answer.tools.is_none());
+    assert_eq!(
+        (tool_turn.temperature, tool_turn.top_p, tool_turn.top_k),
+        (0.4, 0.8, 20)
+    );
+    assert_eq!((answer.temperature, answer.top_p, answer.top_k), (0.9, 0.95, 40));
+}
+
+#[tokio::test]
+async fn test_regenerate_replaces_and_persists_the_answer() {
+    let dir = common::temp_dir();
+    let (service, _) = streaming_service(Vec::new());
+    let store = Arc::new(FileSessionStore::new(dir.path().to_path_buf()));
+    let orchestrator = ChatOrchestrator::with_session_store(
+        service,
+        McpService::new(McpConfig::default(), None),
+        store,
+    );
+    orchestrator
+        .set_session_history(
+            "regen",
+            vec![
+                common::sample_chat_message("user", "Hello"),
+                common::sample_chat_message("assistant", "old answer"),
+            ],
+        )
+        .await;
+
+    let (channel, events) = collecting_channel();
+    orchestrator
+        .regenerate_at("regen", 1, &options(0.7, ToolLoopPolicy::default()), channel)
+        .await
+        .unwrap();
+
+    let events = events.lock().unwrap().clone();
+    assert_eq!(events.first(), Some(&json!({ "chunk": "fallback" })));
+    assert_eq!(events.last(), Some(&json!({ "status": "done" })));
+    let stored = FileSessionStore::new(dir.path().to_path_buf())
+        .load("regen")
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.len(), 2);
+    assert_eq!(stored[1].content, "fallback");
+}
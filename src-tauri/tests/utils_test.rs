@@ -91,3 +91,20 @@ fn test_append_jsonl_creates_parent_dirs() {
     
     assert!(path.exists());
 }
+
+#[test]
+fn test_save_jsonl_leaves_sibling_tmp_file_alone() {
+    let dir = common::temp_dir();
+    let path = dir.path().join("data.jsonl");
+    let sibling = dir.path().join("data.tmp");
+    std::fs::write(&sibling, "keep me").unwrap();
+
+    let items = vec![TestData { name: "a".to_string(), value: 1 }];
+    utils::save_jsonl(&path, &items).unwrap();
+
+    assert_eq!(std::fs::read_to_string(&sibling).unwrap(), "keep me");
+    assert!(!dir.path().join("data.jsonl.tmp").exists());
+    let loaded: TestData =
+        serde_json::from_str(std::fs::read_to_string(&path).unwrap().trim()).unwrap();
+    assert_eq!(loaded, items[0]);
+}
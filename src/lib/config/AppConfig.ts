@@ -1,3 +1,18 @@
+export type RegistryCredential =
+  | { type: "basic"; username: string; password: string }
+  | { type: "token"; token: string };
+
+/** Limits of the agentic tool loop; also accepted per message by send_message. */
+export interface ToolLoopPolicy {
+  maxIterations: number;
+  maxCallsPerTool: number;
+  resultTokenBudget: number;
+  /** Caps the temperature of tool turns; null keeps the chat temperature. */
+  maxTemperature: number | null;
+  topP: number;
+  topK: number;
+}
+
 export interface AppConfig {
   modelsDirectory: string | null;
   llamaDirectory: string | null;
@@ -6,10 +21,18 @@ export interface AppConfig {
   maxTokens: number;
   contextSize: number;
   temperature: number;
+  /** Samplers sent with every message besides `temperature`; unset ones use the model's defaults. */
+  sampling: import("../types/backend").SamplingParams;
   autoSaveChat: boolean;
   chatHistoryLimit: number;
   serverPort: number;
+  serverPortRange: { start: number; end: number } | null;
   webSearchProvider: "tavily" | "custom";
   webSearchMcpId: string | null;
   chatHeaderStyle: "default" | "capsule";
+  /** Registry hosts pulled over plain HTTP. */
+  insecureRegistries: string[];
+  /** Hugging Face mirror base URL; null uses https://huggingface.co. */
+  hfEndpoint: string | null;
+  toolLoopPolicy: ToolLoopPolicy;
 }
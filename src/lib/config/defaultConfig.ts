@@ -11,10 +11,22 @@ export const DEFAULT_CONFIG: AppConfig = {
   maxTokens: 2048,
   contextSize: 8192,
   temperature: 0.7,
+  sampling: {},
   autoSaveChat: true,
   chatHistoryLimit: 50,
   serverPort: 8080,
+  serverPortRange: null,
   webSearchProvider: "tavily",
   webSearchMcpId: null,
   chatHeaderStyle: "default",
+  insecureRegistries: [],
+  hfEndpoint: null,
+  toolLoopPolicy: {
+    maxIterations: 3,
+    maxCallsPerTool: 2,
+    resultTokenBudget: 512,
+    maxTemperature: 0.5,
+    topP: 0.95,
+    topK: 40,
+  },
 };
@@ -1,5 +1,5 @@
 import { invokeCommand } from "../infrastructure/ipc";
-import type { AppConfig } from "./AppConfig";
+import type { AppConfig, RegistryCredential } from "./AppConfig";
 import { DEFAULT_CONFIG } from "./defaultConfig";
 
 export type { AppConfig };
@@ -22,6 +22,28 @@ export async function saveConfig(config: AppConfig): Promise<void> {
   await invokeCommand("save_config", { config });
 }
 
+/**
+ * Store the credentials for a registry host; they are not part of AppConfig
+ * @param {string} host - Registry host, e.g. "ghcr.io" or "localhost:5000"
+ * @param {RegistryCredential | null} credential - null removes them
+ * @returns {Promise<void>}
+ */
+export async function setRegistryCredential(
+  host: string,
+  credential: RegistryCredential | null
+): Promise<void> {
+  await invokeCommand("set_registry_credential", { host, credential });
+}
+
+/**
+ * Store the token for gated or private Hugging Face repositories
+ * @param {string | null} token - null removes it
+ * @returns {Promise<void>}
+ */
+export async function setHfToken(token: string | null): Promise<void> {
+  await invokeCommand("set_hf_token", { token });
+}
+
 /**
  * Reset configuration to defaults
  * @returns {Promise<AppConfig>} Default configuration object
@@ -1,4 +1,6 @@
+import { Channel } from '@tauri-apps/api/core';
 import { invokeCommand } from '../infrastructure/ipc';
+import type { LoadProgress } from '../types/backend';
 
 // ─── Tipos ────────────────────────────────────────────────────────────────────
 
@@ -13,6 +15,8 @@ export interface StartServerOptions {
   chatTemplate?: string;
   /** Caminho para um arquivo .jinja local (passado ao --chat-template-file) */
   chatTemplatePath?: string;
+  /** Recebe os eventos de carregamento enquanto o modelo sobe */
+  onProgress?: (progress: LoadProgress) => void;
 }
 
 // ─── ensure_chat_template ─────────────────────────────────────────────────────
@@ -51,15 +55,23 @@ export async function ensureChatTemplate(hfRepo: string): Promise<string> {
 export async function startLlamaServer(
   options: StartServerOptions,
 ): Promise<string> {
+  // O backend exige o canal mesmo quando ninguém acompanha o progresso.
+  const onProgress = new Channel<LoadProgress>();
+  if (options.onProgress) {
+    onProgress.onmessage = options.onProgress;
+  }
   return (await invokeCommand('start_llama_server', {
-    binaryPath: options.binaryPath,
-    modelPath: options.modelPath,
-    port: options.port,
-    ctxSize: options.ctxSize,
-    nGpuLayers: options.nGpuLayers,
-    parallel: options.parallel ?? null,
-    chatTemplate: options.chatTemplate ?? null,
-    chatTemplateFile: options.chatTemplatePath ?? null,
+    overrides: {
+      binary_path: options.binaryPath,
+      model_path: options.modelPath,
+      port: options.port,
+      ctx_size: options.ctxSize,
+      n_gpu_layers: options.nGpuLayers,
+      parallel: options.parallel ?? null,
+      chat_template: options.chatTemplate ?? null,
+      chat_template_file: options.chatTemplatePath ?? null,
+    },
+    onProgress,
   })) as string;
 }
 
@@ -1,6 +1,38 @@
+import { Channel } from '@tauri-apps/api/core';
 import { invokeCommand } from '../infrastructure/ipc';
 import type { Model } from '../types/models';
+import type { DownloadJob, HfGgufFile } from '../types/backend';
 
 export async function downloadModelFromRegistry(modelsRoot: string, modelReference: string): Promise<Model> {
   return await invokeCommand('download_model_from_registry', { modelsRoot, modelReference }) as Promise<Model>;
 }
+
+export async function startDownload(modelsRoot: string, modelReference: string): Promise<DownloadJob> {
+  return await invokeCommand('start_download', { modelsRoot, modelReference }) as Promise<DownloadJob>;
+}
+
+export async function pauseDownload(jobId: string): Promise<DownloadJob> {
+  return await invokeCommand('pause_download', { jobId }) as Promise<DownloadJob>;
+}
+
+export async function resumeDownload(jobId: string): Promise<DownloadJob> {
+  return await invokeCommand('resume_download', { jobId }) as Promise<DownloadJob>;
+}
+
+export async function cancelDownload(jobId: string): Promise<DownloadJob> {
+  return await invokeCommand('cancel_download', { jobId }) as Promise<DownloadJob>;
+}
+
+export async function listDownloads(): Promise<DownloadJob[]> {
+  return await invokeCommand('list_downloads') as Promise<DownloadJob[]>;
+}
+
+export async function subscribeDownloadEvents(onJob: (job: DownloadJob) => void): Promise<void> {
+  const onEvent = new Channel<DownloadJob>();
+  onEvent.onmessage = onJob;
+  await invokeCommand('subscribe_download_events', { onEvent });
+}
+
+export async function hfListGgufFiles(repoId: string, revision?: string): Promise<HfGgufFile[]> {
+  return await invokeCommand('hf_list_gguf_files', { repoId, revision }) as Promise<HfGgufFile[]>;
+}
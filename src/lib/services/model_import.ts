@@ -0,0 +1,28 @@
+import { invokeCommand } from '../infrastructure/ipc';
+import type { BundleIndex, ImportMode } from '../types/backend';
+import type { Model } from '../types/models';
+
+/**
+ * Add a GGUF file on disk to the library as `local:<name>:<version>`.
+ * Name and version default to the GGUF metadata; hard links fall back to a copy.
+ */
+export async function importGguf(
+  modelsRoot: string,
+  sourcePath: string,
+  options: { name?: string; version?: string; mode?: ImportMode } = {},
+): Promise<Model> {
+  return await invokeCommand('import_gguf', { modelsRoot, sourcePath, ...options }) as Promise<Model>;
+}
+
+/**
+ * Write a model's manifest and blobs to a single tar bundle at `destination`
+ * for copying to machines without registry access.
+ */
+export async function exportModel(modelsRoot: string, modelId: string, destination: string): Promise<BundleIndex> {
+  return await invokeCommand('export_model', { modelsRoot, modelId, destination }) as Promise<BundleIndex>;
+}
+
+/** Install a bundle from `exportModel`, verifying every blob's digest. */
+export async function importModelBundle(modelsRoot: string, bundlePath: string): Promise<Model> {
+  return await invokeCommand('import_model_bundle', { modelsRoot, bundlePath }) as Promise<Model>;
+}
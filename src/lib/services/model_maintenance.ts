@@ -0,0 +1,18 @@
+import { invokeCommand } from '../infrastructure/ipc';
+import type { DeleteModelReport, ModelsDirectoryReport } from '../types/backend';
+
+/**
+ * Delete a model and garbage-collect blobs no other manifest uses.
+ * With `dryRun` nothing is removed; the report lists what would be.
+ */
+export async function deleteModel(modelsRoot: string, modelId: string, dryRun = false): Promise<DeleteModelReport> {
+  return await invokeCommand('delete_model', { modelsRoot, modelId, dryRun }) as Promise<DeleteModelReport>;
+}
+
+/**
+ * Audit every manifest's blobs (presence, size and, with `rehash`, SHA-256)
+ * and list orphan blobs and partial downloads. Re-hashing reads every blob.
+ */
+export async function verifyModelsDirectory(modelsRoot: string, rehash = false): Promise<ModelsDirectoryReport> {
+  return await invokeCommand('verify_models_directory', { modelsRoot, rehash }) as Promise<ModelsDirectoryReport>;
+}
@@ -13,6 +13,11 @@ import {
   deleteConversation,
   type Conversation
 } from '$lib/services/history';
+import type {
+  ChatTurnOptions,
+  ToolApprovalDecision,
+  ToolApprovalRequest
+} from '$lib/types/backend';
 
 export interface Message {
   role: 'user' | 'assistant' | 'system';
@@ -39,6 +44,7 @@ class ChatStore {
   thinkingLabel = $state('Thinking');
   thinkingTags = $state<string[]>([]);
   toolContext = $state<ToolContext[]>([]);
+  pendingApprovals = $state<ToolApprovalRequest[]>([]);
   isLoading = $state(false);
   error = $state<string | null>(null);
   modelLoaded = $state(true);
@@ -194,6 +200,8 @@ class ChatStore {
         this.appendThinkingChunk(String(payload.thinking_chunk));
       }
 
+      this.trackApproval(payload);
+
       if (payload.tool_context) {
         const ctx = payload.tool_context;
         this.toolContext = [
@@ -285,8 +293,7 @@ class ChatStore {
       await invokeCommand('send_message', {
         message: content,
         sessionId: this.sessionId,
-        temperature: settingsStore.settings.temperature,
-        maxTokens: settingsStore.settings.maxTokens,
+        options: this.turnOptions(),
         onEvent
       });
     } catch (err) {
@@ -355,6 +362,8 @@ class ChatStore {
         this.appendThinkingChunk(String(payload.thinking_chunk));
       }
 
+      this.trackApproval(payload);
+
       if (payload.tool_context) {
         const ctx = payload.tool_context;
         this.toolContext = [
@@ -408,8 +417,7 @@ class ChatStore {
       await invokeCommand('chat_action_regenerate', {
         sessionId: this.sessionId,
         messageIndex,
-        temperature: settingsStore.settings.temperature,
-        maxTokens: settingsStore.settings.maxTokens,
+        options: this.turnOptions(),
         onEvent
       });
     } catch (err) {
@@ -480,6 +488,37 @@ class ChatStore {
       }
     }
   }
+
+  private turnOptions(): ChatTurnOptions {
+    return {
+      max_tokens: settingsStore.settings.maxTokens,
+      sampling: {
+        ...settingsStore.settings.sampling,
+        temperature: settingsStore.settings.temperature
+      }
+    };
+  }
+
+  private trackApproval(payload: any) {
+    if (payload.approval_request) {
+      this.pendingApprovals = [...this.pendingApprovals, payload.approval_request];
+    }
+    if (payload.approval_resolved) {
+      const { request_id } = payload.approval_resolved;
+      this.pendingApprovals = this.pendingApprovals.filter((p) => p.request_id !== request_id);
+    }
+  }
+
+  async respondToApproval(requestId: string, decision: ToolApprovalDecision) {
+    try {
+      await invokeCommand('respond_tool_approval', { requestId, decision });
+    } catch (e) {
+      this.error = String(e);
+    } finally {
+      this.pendingApprovals = this.pendingApprovals.filter((p) => p.request_id !== requestId);
+    }
+  }
+
   async generateTitle(conversationId: number, userFirstMsg: string, assistantFirstMsg: string) {
     console.log('=== Generating title for conversation', conversationId);
 
@@ -1,5 +1,6 @@
+import { Channel } from '@tauri-apps/api/core';
 import { invokeCommand } from '$infrastructure/ipc';
-import type { LlamaCppConfig } from '$lib/types/backend';
+import type { LlamaCppConfig, LoadProgress } from '$lib/types/backend';
 
 export interface ServerStatus {
     isRunning: boolean;
@@ -8,6 +9,31 @@ export interface ServerStatus {
     currentConfig: LlamaCppConfig | null;
 }
 
+/**
+ * start_llama_server rejects with a tagged error ({ kind, ... }) so a busy
+ * port can be told apart from a model that failed to load.
+ */
+function formatStartError(err: unknown): string {
+    if (err && typeof err === 'object' && 'kind' in err) {
+        const e = err as { kind: string; port?: number; pid?: number | null; process_name?: string | null; start?: number; end?: number; message?: string };
+        switch (e.kind) {
+            case 'port_in_use': {
+                const owner = e.process_name ? `${e.process_name} (pid ${e.pid})` : e.pid ? `pid ${e.pid}` : null;
+                return owner ? `Port ${e.port} is already in use by ${owner}` : `Port ${e.port} is already in use`;
+            }
+            case 'no_free_port':
+                return `No free port in range ${e.start}-${e.end}`;
+            case 'exited_early': {
+                const { status, log_tail } = err as { status: string; log_tail: string[] };
+                return [`llama-server exited early with status: ${status}`, ...log_tail].join('\n');
+            }
+            default:
+                return e.message ?? String(err);
+        }
+    }
+    return err instanceof Error ? err.message : String(err);
+}
+
 class ServerStore {
     isRunning = $state(false);
     isHealthy = $state(false);
@@ -15,6 +41,7 @@ class ServerStore {
     isChecking = $state(false);
     isStarting = $state(false);
     currentConfig = $state<LlamaCppConfig | null>(null);
+    loadProgress = $state<LoadProgress | null>(null);
     serverMetrics = $state<{ 
         cpu_usage: number; 
         mem_usage: number; 
@@ -62,15 +89,23 @@ class ServerStore {
         try {
             this.error = null;
             this.isStarting = true;
+            this.loadProgress = null;
+            const onProgress = new Channel<LoadProgress>();
+            onProgress.onmessage = (progress) => {
+                this.loadProgress = progress;
+            };
             const pid = await invokeCommand('start_llama_server', {
-                binaryPath: binaryPath,
-                modelPath: modelPath,
-                port,
-                ctxSize,
-                nGpuLayers,
-                parallel,
-                chatTemplate,
-                chatTemplateFile,
+                overrides: {
+                    binary_path: binaryPath,
+                    model_path: modelPath,
+                    port,
+                    ctx_size: ctxSize,
+                    n_gpu_layers: nGpuLayers,
+                    parallel,
+                    chat_template: chatTemplate ?? null,
+                    chat_template_file: chatTemplateFile ?? null,
+                },
+                onProgress,
             });
             this.isRunning = true;
             this.currentConfig = {
@@ -88,7 +123,7 @@ class ServerStore {
             // Start health monitoring
             this.startHealthMonitoring();
         } catch (err) {
-            this.error = err instanceof Error ? err.message : String(err);
+            this.error = formatStartError(err);
             this.isRunning = false;
             console.error('Failed to start server:', err);
         } finally {
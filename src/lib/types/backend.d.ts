@@ -73,6 +73,146 @@ export interface LlamaCppConfig {
     n_gpu_layers: number;
     chat_template?: string | null;
     chat_template_file?: string | null;
+    port_range?: { start: number; end: number } | null;
+    restart_policy?: RestartPolicy | null;
+    launch_params?: Partial<LaunchParams>;
+}
+
+/** `start_llama_server` overrides; each field set replaces the preset's value. */
+export interface LaunchOverrides {
+    binary_path?: string | null;
+    model_path?: string | null;
+    port?: number | null;
+    ctx_size?: number | null;
+    n_gpu_layers?: number | null;
+    parallel?: number | null;
+    chat_template?: string | null;
+    chat_template_file?: string | null;
+    port_range?: { start: number; end: number } | null;
+    restart_policy?: RestartPolicy | null;
+    launch_params?: Partial<LaunchParams> | null;
+}
+
+export type KvCacheType =
+    | 'f32' | 'f16' | 'bf16' | 'q8_0' | 'q4_0' | 'q4_1' | 'iq4_nl' | 'q5_0' | 'q5_1';
+
+export interface LoraAdapter {
+    path: string;
+    scale?: number | null;
+}
+
+export interface LaunchParams {
+    version: number;
+    jinja: boolean;
+    flash_attn: 'on' | 'off' | 'auto' | null;
+    cache_type_k: KvCacheType | null;
+    cache_type_v: KvCacheType | null;
+    threads: number | null;
+    threads_batch: number | null;
+    batch_size: number | null;
+    ubatch_size: number | null;
+    mlock: boolean;
+    no_mmap: boolean;
+    rope_scaling: 'none' | 'linear' | 'yarn' | null;
+    rope_scale: number | null;
+    rope_freq_base: number | null;
+    yarn_orig_ctx: number | null;
+    tensor_split: number[];
+    main_gpu: number | null;
+    draft_model: string | null;
+    draft_n_gpu_layers: number | null;
+    draft_max: number | null;
+    draft_min: number | null;
+    lora: LoraAdapter[];
+    api_key: string | null;
+    extra_args: string[];
+}
+
+export interface LaunchPreset {
+    name: string;
+    binary_path?: string | null;
+    port?: number | null;
+    ctx_size?: number | null;
+    n_gpu_layers?: number | null;
+    parallel?: number | null;
+    chat_template?: string | null;
+    chat_template_file?: string | null;
+    restart_policy?: RestartPolicy | null;
+    launch_params?: Partial<LaunchParams>;
+}
+
+export interface ModelPresets {
+    default_preset: string | null;
+    presets: LaunchPreset[];
+    /** Chat sampling defaults for the model (set_model_sampling). */
+    sampling?: SamplingParams | null;
+}
+
+/**
+ * Sampler settings accepted by send_message and chat_action_regenerate.
+ * Unset fields fall back to the model's defaults.
+ */
+export interface SamplingParams {
+    temperature?: number;
+    top_p?: number;
+    top_k?: number;
+    min_p?: number;
+    repeat_penalty?: number;
+    repeat_last_n?: number;
+    presence_penalty?: number;
+    frequency_penalty?: number;
+    seed?: number;
+    stop?: string[];
+    mirostat?: 0 | 1 | 2;
+    mirostat_tau?: number;
+    mirostat_eta?: number;
+    dry_multiplier?: number;
+    dry_base?: number;
+    dry_allowed_length?: number;
+    dry_penalty_last_n?: number;
+    logit_bias?: Record<string, number | false> | Array<[number | string, number | false]>;
+}
+
+/** The `options` argument of send_message and chat_action_regenerate. */
+export interface ChatTurnOptions {
+    /** A loaded model to route to; the default model when omitted. */
+    model?: string | null;
+    max_tokens: number;
+    sampling?: SamplingParams;
+    /** send_message only; the settings' policy when omitted. */
+    tool_loop_policy?: import('../config/AppConfig').ToolLoopPolicy | null;
+}
+
+export interface RestartPolicy {
+    max_restarts: number;
+    initial_backoff_ms: number;
+    max_backoff_ms: number;
+}
+
+export type ModelEvent =
+    | { type: 'crashed'; model_id: string; exit_status: string; last_log: string[] }
+    | { type: 'restarting'; model_id: string; attempt: number; delay_ms: number }
+    | { type: 'restarted'; model_id: string; pid: number }
+    | { type: 'restart_failed'; model_id: string; attempt: number; error: string }
+    | { type: 'restarts_exhausted'; model_id: string; attempts: number };
+
+export type LoadPhase =
+    | 'spawning'
+    | 'loading_model'
+    | 'loading_metadata'
+    | 'loading_tensors'
+    | 'allocating_context'
+    | 'warming_up'
+    | 'listening'
+    | 'ready';
+
+export interface LoadProgress {
+    model_id: string;
+    phase: LoadPhase;
+    percent: number;
+    n_ctx: number | null;
+    layers_offloaded: number | null;
+    layers_total: number | null;
 }
 
 export type McpTransport = 'stdio' | 'http_sse';
@@ -90,10 +230,32 @@ export interface McpServerConfig {
     headers?: Record<string, string> | null;
     tool_allowlist?: string[] | null;
     resource_allowlist?: string[] | null;
+    /** Approval policy for this server's tools (default "allow"). */
+    toolApproval?: ToolApproval | null;
+    /** Per-tool policies that take precedence over `toolApproval`. */
+    toolApprovalOverrides?: Record<string, ToolApproval> | null;
+}
+
+export type ToolApproval = 'allow' | 'ask' | 'deny';
+
+export type ToolApprovalDecision =
+    | { action: 'approve' }
+    | { action: 'edit'; arguments: unknown }
+    | { action: 'reject'; reason?: string | null };
+
+export interface ToolApprovalRequest {
+    request_id: string;
+    tool_call_id: string;
+    server_id: string;
+    tool_name: string;
+    arguments: unknown;
+    timeout_secs: number;
 }
 
 export interface McpConfig {
     servers: McpServerConfig[];
+    /** Tool calls from one model turn that may run at once (default 4). */
+    maxParallelToolCalls?: number;
 }
 
 export interface McpServerStatus {
@@ -121,3 +283,121 @@ export interface McpCapabilities {
 
 export type ToolDefinition = Record<string, any>;
 export type ResourceDefinition = Record<string, any>;
+
+export interface MemoryEstimateOptions {
+    ctx_size: number;
+    parallel?: number;
+    n_gpu_layers: number;
+    launch_params?: Partial<LaunchParams>;
+    vram_budget_bytes?: number[];
+}
+
+export interface DeviceMemoryEstimate {
+    device: string;
+    layers: number;
+    weights_bytes: number;
+    kv_cache_bytes: number;
+    compute_bytes: number;
+    total_bytes: number;
+    budget_bytes: number | null;
+    fits: boolean | null;
+}
+
+export interface MemoryEstimate {
+    layers_total: number;
+    n_gpu_layers: number;
+    devices: DeviceMemoryEstimate[];
+    total_bytes: number;
+    recommended_n_gpu_layers: number | null;
+    fits: boolean | null;
+}
+
+export type ModelFit = 'gpu' | 'partial' | 'cpu' | 'too_large';
+
+export interface HfGgufFile {
+    filename: string;
+    parts: string[];
+    size_bytes: number;
+    quantization: string | null;
+    fit: ModelFit | null;
+}
+
+export type DownloadStatus = 'queued' | 'downloading' | 'paused' | 'completed' | 'failed' | 'cancelled';
+
+export interface DownloadJob {
+    id: string;
+    model_reference: string;
+    models_root: string;
+    status: DownloadStatus;
+    downloaded_bytes: number;
+    total_bytes: number;
+    current_blob: string | null;
+    partial_files: string[];
+    error: string | null;
+    model?: import('./models').Model;
+}
+
+export interface BlobFile {
+    path: string;
+    bytes: number;
+}
+
+export interface DeleteModelReport {
+    model_id: string;
+    dry_run: boolean;
+    manifest_path: string;
+    removed_blobs: BlobFile[];
+    removed_partials: BlobFile[];
+    shared_blobs: string[];
+    bytes_reclaimed: number;
+    warnings: string[];
+}
+
+export type BlobStatus = 'ok' | 'missing' | 'size_mismatch' | 'digest_mismatch' | 'unreadable';
+
+export interface BlobCheck {
+    digest: string;
+    path: string | null;
+    expected_size: number;
+    actual_size: number | null;
+    status: BlobStatus;
+    error: string | null;
+}
+
+export interface ManifestCheck {
+    model_id: string;
+    manifest_path: string;
+    blobs: BlobCheck[];
+    error: string | null;
+    healthy: boolean;
+}
+
+export interface ModelsDirectoryReport {
+    models_root: string;
+    rehashed: boolean;
+    manifests: ManifestCheck[];
+    orphan_blobs: BlobFile[];
+    partial_files: BlobFile[];
+    total_bytes: number;
+    reclaimable_bytes: number;
+}
+
+/** How `import_gguf` places the source file under `blobs/`. */
+export type ImportMode = 'hard_link' | 'copy' | 'move';
+
+export interface BundleBlob {
+    digest: string;
+    size: number;
+    media_type: string;
+}
+
+/** `index.json` of a bundle written by `export_model`. */
+export interface BundleIndex {
+    format_version: number;
+    provider: string;
+    library: string;
+    name: string;
+    version: string;
+    blobs: BundleBlob[];
+    total_bytes: number;
+}
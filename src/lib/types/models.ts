@@ -17,5 +17,59 @@ export interface Model {
         }>;
     };
     tokenizer_metadata?: Record<string, unknown>;
+    gguf?: GgufInfo | null;
+    /** From the Ollama `system`, `template`, `params` and `license` layers. */
+    system_prompt?: string | null;
+    template?: string | null;
+    params?: ModelParams | null;
+    stop_sequences?: string[];
+    license?: string | null;
     model_file_path?: string;
 }
+
+/** Ollama `PARAMETER` values; unrecognised keys are passed through as-is. */
+export interface ModelParams {
+    temperature?: number;
+    top_p?: number;
+    top_k?: number;
+    min_p?: number;
+    repeat_penalty?: number;
+    seed?: number;
+    num_ctx?: number;
+    num_predict?: number;
+    [key: string]: unknown;
+}
+
+export interface GgufTensorInfo {
+    name: string;
+    dims: number[];
+    dtype: string;
+    offset: number;
+    size_bytes: number | null;
+}
+
+export interface GgufInfo {
+    version: number;
+    architecture: string | null;
+    name: string | null;
+    parameter_count: number | null;
+    block_count: number | null;
+    embedding_length: number | null;
+    feed_forward_length: number | null;
+    head_count: number | null;
+    head_count_kv: number | null;
+    key_length: number | null;
+    value_length: number | null;
+    context_length: number | null;
+    rope_freq_base: number | null;
+    rope_dimension_count: number | null;
+    rope_scaling_type: string | null;
+    rope_scaling_factor: number | null;
+    file_type: number | null;
+    quantization: string | null;
+    data_offset: number;
+    tensor_bytes: number;
+    /** Full from read_gguf_info; on a library model only output/token_embd. */
+    tensors: GgufTensorInfo[];
+    metadata: Record<string, unknown>;
+}